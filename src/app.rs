@@ -3,14 +3,16 @@ use crate::cheats::CheatEngine;
 use crate::config::AppConfig;
 use crate::document::ProjectDocument;
 use crate::document::actions::DocumentAction;
+use crate::error::ProjectError;
 use crate::io;
 use crate::state::PreviewState;
 use crate::ui;
 use crate::ui::font_wizard::FontWizardState;
 use crate::ui::messages::{self, EditorEvent};
+use crate::ui::texture_composer::TextureComposerState;
 use crate::ui::viewport_controller::ViewportController;
 use eframe::egui;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
 const MAX_RECENT_FILES: usize = 5;
 
@@ -45,9 +47,7 @@ pub enum CreationModal {
     LumpSelector,
     SBarDef,
     SkyDefs,
-    #[allow(dead_code)]
     Interlevel,
-    #[allow(dead_code)]
     Finale,
     UmapInfo,
     LayoutTemplate,
@@ -95,6 +95,8 @@ pub struct CacocoApp {
     pub settings_open: bool,
     /// State for the font auto-detection wizard.
     pub font_wizard: Option<FontWizardState>,
+    /// State for the composite texture editor.
+    pub texture_composer: Option<TextureComposerState>,
     /// State for any active confirmation dialog.
     pub confirmation_modal: Option<ConfirmationRequest>,
     /// Registry for global keyboard shortcuts.
@@ -105,6 +107,10 @@ pub struct CacocoApp {
     pub active_mode: ProjectMode,
     /// Modal to select which Lump to create.
     pub creation_modal: CreationModal,
+    /// Project currently loading in the background, if any.
+    pub project_load: Option<io::ProjectLoad>,
+    /// Load, save and launch failures not yet dismissed, oldest first.
+    pub error_reports: VecDeque<ProjectError>,
 }
 
 impl Default for CacocoApp {
@@ -120,11 +126,14 @@ impl Default for CacocoApp {
             last_selection: HashSet::new(),
             settings_open: false,
             font_wizard: None,
+            texture_composer: None,
             confirmation_modal: None,
             hotkeys: crate::hotkeys::HotkeyRegistry::default(),
             iwad_verified: false,
             active_mode: ProjectMode::SBarDef,
             creation_modal: CreationModal::default(),
+            project_load: None,
+            error_reports: VecDeque::new(),
         }
     }
 }
@@ -135,7 +144,7 @@ impl CacocoApp {
         cc.egui_ctx.set_visuals(egui::Visuals::dark());
 
         let mut app = Self::default();
-        app.load_system_assets(&cc.egui_ctx);
+        app.assets.load_system_assets();
 
         if app.config.base_wad_path.is_none() {
             if let Some(auto_path) = crate::discovery::find_iwad() {
@@ -146,51 +155,15 @@ impl CacocoApp {
             }
         }
 
-        if let Some(path) = &app.config.base_wad_path {
-            app.iwad_verified = io::load_wad_from_path(&cc.egui_ctx, path, &mut app.assets);
-        }
-
         if let Some(file_path) = open_file_path {
-            if let Some(loaded) = io::load_project_from_path(&cc.egui_ctx, &file_path) {
-                app.load_project(&cc.egui_ctx, loaded, &file_path);
-                println!("Loaded project from command line: {}", file_path);
-            } else {
-                eprintln!("Failed to load project from command line: {}", file_path);
-            }
+            app.begin_load(&file_path);
+        } else if let Some(path) = app.config.base_wad_path.clone() {
+            app.begin_base_load(&path);
         }
 
         app
     }
 
-    /// Loads built-in branding, badges, and template assets into the store.
-    pub fn load_system_assets(&mut self, ctx: &egui::Context) {
-        self.assets.load_smooth_image(
-            ctx,
-            "_BG_MASTER",
-            include_bytes!("../assets/background.png"),
-        );
-        self.assets
-            .load_reference_image(ctx, "HICACOCO", include_bytes!("../assets/HICACOCO.png"));
-        self.assets.load_system_assets(ctx);
-
-        self.assets.load_smooth_image(
-            ctx,
-            "_MINIMAP_PLACEHOLDER",
-            include_bytes!("../assets/automap.png"),
-        );
-
-        self.assets.load_smooth_image(
-            ctx,
-            "_MINIMAP_THUMB",
-            include_bytes!("../assets/tinyautomap.png"),
-        );
-
-        for asset in crate::library::ASSETS {
-            let key = AssetStore::stem(asset.name);
-            self.assets.load_reference_image(ctx, &key, asset.bytes);
-        }
-    }
-
     /// Updates the recent files list and saves the configuration.
     pub fn add_to_recent(&mut self, path: &str) {
         self.config.recent_files.retain(|p| p != path);
@@ -199,8 +172,85 @@ impl CacocoApp {
         self.config.save();
     }
 
+    /// Starts loading a project on a worker thread. The result is picked up
+    /// by [`Self::poll_project_load`]. A load already running is cancelled.
+    pub fn begin_load(&mut self, path_str: &str) {
+        let base_wad = self.config.base_wad_path.clone();
+        self.start_load(io::ProjectLoad::spawn(path_str, base_wad));
+    }
+
+    /// Reads a base IWAD on a worker thread and lays it under the current
+    /// assets once done. It becomes the configured IWAD only if it loads.
+    pub fn begin_base_load(&mut self, path_str: &str) {
+        self.start_load(io::ProjectLoad::spawn_base_wad(path_str));
+    }
+
+    fn start_load(&mut self, load: io::ProjectLoad) {
+        if let Some(previous) = &self.project_load {
+            previous.progress.cancel();
+        }
+        self.project_load = Some(load);
+    }
+
+    /// Abandons the background load. The worker stops at its next check and
+    /// its result is dropped.
+    pub fn cancel_project_load(&mut self) {
+        if let Some(load) = self.project_load.take() {
+            load.progress.cancel();
+            messages::log_event(&mut self.preview_state, EditorEvent::ProjectLoadCancelled);
+        }
+    }
+
+    /// Installs the background load's project once its worker is done, or
+    /// reports why it failed.
+    pub fn poll_project_load(&mut self) {
+        let Some(result) = self.project_load.as_ref().and_then(|l| l.poll()) else {
+            return;
+        };
+        let Some(load) = self.project_load.take() else {
+            return;
+        };
+        if let Ok(loaded) = &result {
+            self.iwad_verified |= loaded.has_base;
+        }
+        match (result, load.source) {
+            (Ok(loaded), io::LoadSource::File(path)) => self.load_project(loaded, &path),
+            (Ok(loaded), io::LoadSource::New(template)) => {
+                self.install_new_project(loaded, template)
+            }
+            (Ok(loaded), io::LoadSource::BaseWad(path)) => self.install_base_wad(loaded, path),
+            (Err(e), _) => self.report_error(e),
+        }
+    }
+
+    /// Lays a base IWAD read by [`Self::begin_base_load`] under the current
+    /// assets and rebakes the open project's textures against it.
+    fn install_base_wad(&mut self, loaded: io::LoadedProject, path: String) {
+        self.assets.underlay(loaded.assets);
+        if let Some(doc) = &mut self.doc {
+            doc.composites =
+                crate::wad::legacy::project_composites(&doc.passthrough_lumps, &self.assets);
+            self.assets.sync_composites(&doc.composites);
+            let names: Vec<String> = doc.lumps.iter().flat_map(|l| l.tranmap_names()).collect();
+            self.assets.load_referenced_tranmaps(&names);
+        }
+        self.config.base_wad_path = Some(path);
+        self.config.save();
+    }
+
+    /// Logs a failure and queues it for the error panel, which shows the
+    /// details of one at a time.
+    pub fn report_error(&mut self, error: ProjectError) {
+        messages::log_event(
+            &mut self.preview_state,
+            EditorEvent::Failed(error.title().to_string()),
+        );
+        self.error_reports.push_back(error);
+    }
+
     /// Loads a project from a file and resets the application state.
-    pub fn load_project(&mut self, ctx: &egui::Context, loaded: io::LoadedProject, path_str: &str) {
+    pub fn load_project(&mut self, mut loaded: io::LoadedProject, path_str: &str) {
+        let warnings = std::mem::take(&mut loaded.warnings);
         if let Some(first) = loaded.lumps.first() {
             self.active_mode = ProjectMode::from_data(first);
         }
@@ -208,6 +258,7 @@ impl CacocoApp {
         self.doc = Some(ProjectDocument {
             lumps: loaded.lumps,
             passthrough_lumps: loaded.passthrough_lumps,
+            composites: loaded.composites,
             path: Some(path_str.to_string()),
             selection: HashSet::new(),
             selection_pivot: None,
@@ -218,11 +269,6 @@ impl CacocoApp {
         self.assets = loaded.assets;
         self.preview_state = PreviewState::default();
 
-        self.load_system_assets(ctx);
-        if let Some(path) = &self.config.base_wad_path {
-            io::load_wad_from_path(ctx, path, &mut self.assets);
-        }
-
         self.last_selection.clear();
         self.current_statusbar_idx = 0;
         self.add_to_recent(path_str);
@@ -231,24 +277,52 @@ impl CacocoApp {
             &mut self.preview_state,
             EditorEvent::ProjectLoaded(path_str.to_string()),
         );
+        for warning in warnings {
+            self.report_error(warning);
+        }
+    }
+
+    /// Initializes a new empty project. The base IWAD is read underneath it
+    /// in the background, like a loaded project's.
+    pub fn new_project(&mut self, data: crate::models::ProjectData) {
+        let seed = io::LoadedProject {
+            lumps: vec![data],
+            ..Default::default()
+        };
+        let base_wad = self.config.base_wad_path.clone();
+        self.start_load(io::ProjectLoad::spawn_new(seed, None, base_wad));
     }
 
-    /// Initializes a new empty project.
-    pub fn new_project(&mut self, ctx: &egui::Context, data: crate::models::ProjectData) {
+    /// Installs a project started by [`Self::new_project`] or
+    /// [`Self::apply_template`] once its assets are ready.
+    fn install_new_project(
+        &mut self,
+        mut loaded: io::LoadedProject,
+        template: Option<&'static crate::library::Template>,
+    ) {
+        if loaded.lumps.is_empty() {
+            return;
+        }
+        let data = loaded.lumps.remove(0);
         self.active_mode = ProjectMode::from_data(&data);
         self.doc = Some(ProjectDocument::new(data, Vec::new(), None));
-        self.assets = AssetStore::default();
+        self.assets = loaded.assets;
         self.preview_state = PreviewState::default();
 
-        self.load_system_assets(ctx);
-        if let Some(path) = &self.config.base_wad_path {
-            io::load_wad_from_path(ctx, path, &mut self.assets);
-        }
-
         self.last_selection.clear();
         self.current_statusbar_idx = 0;
 
-        messages::log_event(&mut self.preview_state, EditorEvent::ProjectNew);
+        let event = match template {
+            Some(template) => {
+                self.load_template_assets(template);
+                EditorEvent::TemplateApplied(template.name.to_string())
+            }
+            None => EditorEvent::ProjectNew,
+        };
+        messages::log_event(&mut self.preview_state, event);
+        for warning in loaded.warnings {
+            self.report_error(warning);
+        }
     }
 
     /// Appends a new lump to the current project or switches to it if it exists.
@@ -264,8 +338,9 @@ impl CacocoApp {
         }
     }
 
-    /// Applies a library template as the current project.
-    pub fn apply_template(&mut self, ctx: &egui::Context, template: &crate::library::Template) {
+    /// Applies a library template as the current project. Without an open
+    /// project, one is started from it in the background.
+    pub fn apply_template(&mut self, template: &'static crate::library::Template) {
         match serde_json::from_str::<crate::models::ProjectData>(template.json_content) {
             Ok(mut data) => {
                 match &mut data {
@@ -277,34 +352,25 @@ impl CacocoApp {
                     _ => {}
                 }
 
+                let Some(doc) = &mut self.doc else {
+                    let seed = io::LoadedProject {
+                        lumps: vec![data],
+                        ..Default::default()
+                    };
+                    let base_wad = self.config.base_wad_path.clone();
+                    self.start_load(io::ProjectLoad::spawn_new(seed, Some(template), base_wad));
+                    return;
+                };
+
                 let mode = ProjectMode::from_data(&data);
                 self.active_mode = mode;
-
-                if let Some(doc) = &mut self.doc {
-                    doc.lumps.retain(|l| ProjectMode::from_data(l) != mode);
-                    doc.lumps.push(data);
-                    doc.dirty = true;
-                } else {
-                    self.doc = Some(ProjectDocument::new(data, Vec::new(), None));
-                    self.assets = AssetStore::default();
-                    self.preview_state = PreviewState::default();
-                    self.load_system_assets(ctx);
-                    if let Some(path) = &self.config.base_wad_path {
-                        io::load_wad_from_path(ctx, path, &mut self.assets);
-                    }
-                }
+                doc.lumps.retain(|l| ProjectMode::from_data(l) != mode);
+                doc.lumps.push(data);
+                doc.dirty = true;
 
                 self.last_selection.clear();
                 self.current_statusbar_idx = 0;
-
-                for prefix in template.required_prefixes {
-                    for lib_asset in crate::library::ASSETS {
-                        if lib_asset.name.to_lowercase().starts_with(prefix) {
-                            let key = AssetStore::stem(lib_asset.name);
-                            self.assets.load_image(ctx, &key, lib_asset.bytes);
-                        }
-                    }
-                }
+                self.load_template_assets(template);
 
                 messages::log_event(
                     &mut self.preview_state,
@@ -315,6 +381,18 @@ impl CacocoApp {
         }
     }
 
+    /// Imports the library graphics a template draws with.
+    fn load_template_assets(&mut self, template: &crate::library::Template) {
+        for prefix in template.required_prefixes {
+            for lib_asset in crate::library::ASSETS {
+                if lib_asset.name.to_lowercase().starts_with(prefix) {
+                    let key = AssetStore::stem(lib_asset.name);
+                    self.assets.load_image(&key, lib_asset.bytes);
+                }
+            }
+        }
+    }
+
     /// Delegation helper to execute actions on the current document.
     pub fn execute_actions(&mut self, actions: Vec<DocumentAction>) {
         if let Some(doc) = &mut self.doc {
@@ -335,11 +413,9 @@ impl CacocoApp {
     }
 
     /// Opens the system dialog to pick a project and loads it if successful.
-    pub fn open_project_ui(&mut self, ctx: &egui::Context) {
+    pub fn open_project_ui(&mut self) {
         if let Some(path) = io::open_project_dialog() {
-            if let Some(loaded) = io::load_project_from_path(ctx, &path) {
-                self.load_project(ctx, loaded, &path);
-            }
+            self.begin_load(&path);
         }
     }
 }
@@ -360,6 +436,8 @@ impl eframe::App for CacocoApp {
             }
         }
 
+        self.poll_project_load();
+
         ctx.set_visuals(egui::Visuals::dark());
         ui::draw_root_ui(ctx, self);
 
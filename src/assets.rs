@@ -1,8 +1,26 @@
 use eframe::egui;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
+use std::sync::Arc;
+
+use crate::models::umapinfo::LumpKind;
+use crate::render::patch;
+use crate::render::tranmap::TranMap;
+use crate::render::translation::{self, TranslationTable};
+use crate::wad::LumpData;
+use crate::wad::legacy::CompositeTexture;
+use crate::wad::util::GraphicFormat;
+
+/// How many bytes of lazily decoded pixels are kept before the least
+/// recently used are dropped.
+const DECODE_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// How many bytes of textures each egui context keeps uploaded before the
+/// least recently used are released.
+const TEXTURE_CACHE_BYTES: usize = 256 * 1024 * 1024;
 
 /// A lightweight, pre-hashed identifier for an asset.
 ///
@@ -32,12 +50,31 @@ impl fmt::LowerHex for AssetId {
     }
 }
 
+/// An image decoded into CPU memory. This is the authoritative copy of every
+/// graphic; GPU textures are only ever derived from it.
+#[derive(Clone)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    /// Unmultiplied RGBA8 pixels, row-major.
+    pub pixels: Vec<u8>,
+    /// Sampler settings to use when this image becomes a texture.
+    pub options: egui::TextureOptions,
+}
+
 /// A centralized registry for textures, raw data, and Doom-specific offsets.
 pub struct AssetStore {
-    /// Texture handles indexed by hashed AssetId.
-    pub textures: HashMap<AssetId, egui::TextureHandle>,
+    /// Decoded pixel data for every loaded image, independent of any egui context.
+    pub images: HashMap<AssetId, DecodedImage>,
+    /// GPU texture handles, created on first draw from `images`. Kept per egui
+    /// context so an offscreen render never hands out the editor's texture ids,
+    /// and released least recently used first.
+    textures: RefCell<Vec<(egui::Context, TextureCache)>>,
     /// The original bytes for images, used when building PK3s.
     pub raw_files: HashMap<AssetId, Vec<u8>>,
+    /// When each raw file was last imported, counting up from 1.
+    import_order: HashMap<AssetId, u64>,
+    imports: u64,
     /// Horizontal and vertical offsets (Doom patch format).
     pub offsets: HashMap<AssetId, (i16, i16)>,
     /// A reverse-lookup to get the original filename (including extension).
@@ -48,21 +85,221 @@ pub struct AssetStore {
     pub base_texture1: Vec<u8>,
     /// Original IWAD TEXTURE2 lump data.
     pub base_texture2: Vec<u8>,
+    /// The composite textures as last baked, to tell which need redoing.
+    baked_composites: Vec<CompositeTexture>,
     /// Original IWAD palette data.
     pub palette: crate::render::palette::DoomPalette,
+    /// Colour translation tables (Boom `CR*` lumps and ID24 translations), by lump name.
+    pub translations: HashMap<String, TranslationTable>,
+    /// Translucency tables (Boom `TRANMAP` and any custom ones), by lump name.
+    pub tranmaps: HashMap<String, TranMap>,
+    /// Lumps the size of a tranmap, left unparsed until a project lump
+    /// references them by name.
+    tranmap_lumps: HashMap<String, LumpData>,
+    /// Each parsed tranmap's estimated opacity, worked out on first use.
+    tranmap_opacity: RefCell<HashMap<String, f32>>,
+    /// Names of the flats found between `F_START`/`F_END` markers or under `flats/`.
+    pub flats: BTreeSet<String>,
+    /// Every lump name seen in the loaded WADs and PK3, for resolving lumps
+    /// that are never decoded, such as music.
+    pub lump_names: BTreeSet<String>,
+    /// Texture names defined by the IWAD's TEXTURE1/2.
+    pub texture_names: BTreeSet<String>,
+    /// Recoloured or translucent copies of images, keyed by their derived id.
+    derived: RefCell<HashMap<AssetId, DerivedImage>>,
+    /// Graphics indexed from a WAD or PK3 that are decoded on first use.
+    lazy: HashMap<AssetId, LazyImage>,
+    /// Pixels decoded from `lazy`, evicted least recently used first.
+    decoded: RefCell<DecodeCache>,
+    /// Graphics the base IWAD filled in, replaced when another is laid under.
+    base_ids: HashSet<AssetId>,
+}
+
+/// A graphic whose bytes are known but not decoded yet.
+struct LazyImage {
+    data: LumpData,
+    format: GraphicFormat,
+    width: u32,
+    height: u32,
+}
+
+impl LazyImage {
+    fn decode(&self, palette: &crate::render::palette::DoomPalette) -> Option<DecodedImage> {
+        let bytes = self.data.bytes();
+        let (width, height, pixels) = match self.format {
+            GraphicFormat::Image => {
+                return decode_image_bytes(&bytes, AssetStore::REPEAT_OPTIONS).ok();
+            }
+            GraphicFormat::Patch => {
+                let (w, h, _, _, pixels) = patch::decode_doom_patch(&bytes, palette)?;
+                (w, h, pixels)
+            }
+            GraphicFormat::Flat => patch::decode_doom_flat(&bytes, palette)?,
+        };
+        Some(DecodedImage {
+            width,
+            height,
+            pixels,
+            options: AssetStore::REPEAT_OPTIONS,
+        })
+    }
+}
+
+/// Decoded lazy images, bounded by their pixel bytes.
+type DecodeCache = LruCache<Arc<DecodedImage>>;
+
+/// The textures uploaded to one egui context, bounded by their texel bytes.
+type TextureCache = LruCache<egui::TextureHandle>;
+
+/// How many bytes a cached value takes up against its cache's budget.
+trait CacheCost {
+    fn cost(&self) -> usize;
+}
+
+impl CacheCost for Arc<DecodedImage> {
+    fn cost(&self) -> usize {
+        self.pixels.len()
+    }
+}
+
+impl CacheCost for egui::TextureHandle {
+    fn cost(&self) -> usize {
+        let [w, h] = self.size();
+        w * h * 4
+    }
+}
+
+/// Values with a byte budget, dropping the least recently used once it's
+/// exceeded. Everything in one can be rebuilt on demand.
+struct LruCache<V> {
+    entries: HashMap<AssetId, (u64, V)>,
+    /// Bumped on every access; an entry's stamp is when it was last used.
+    clock: u64,
+    bytes: usize,
+    budget: usize,
+}
+
+impl<V: CacheCost + Clone> LruCache<V> {
+    fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            clock: 0,
+            bytes: 0,
+            budget,
+        }
+    }
+
+    fn get(&mut self, id: &AssetId) -> Option<V> {
+        self.clock += 1;
+        let (stamp, value) = self.entries.get_mut(id)?;
+        *stamp = self.clock;
+        Some(value.clone())
+    }
+
+    /// Looks a value up without counting it as used.
+    fn peek(&self, id: &AssetId) -> Option<&V> {
+        self.entries.get(id).map(|(_, value)| value)
+    }
+
+    fn insert(&mut self, id: AssetId, value: V) {
+        self.clock += 1;
+        self.bytes += value.cost();
+        if let Some((_, old)) = self.entries.insert(id, (self.clock, value)) {
+            self.bytes -= old.cost();
+        }
+        self.evict();
+    }
+
+    /// Changes a value in place, e.g. to refresh a texture's pixels.
+    fn update(&mut self, id: &AssetId, f: impl FnOnce(&mut V)) {
+        if let Some((_, value)) = self.entries.get_mut(id) {
+            self.bytes -= value.cost();
+            f(value);
+            self.bytes += value.cost();
+        }
+        self.evict();
+    }
+
+    fn remove(&mut self, id: &AssetId) {
+        if let Some((_, value)) = self.entries.remove(id) {
+            self.bytes -= value.cost();
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.bytes > self.budget && self.entries.len() > 1 {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (stamp, _))| *stamp)
+                .map(|(id, _)| *id);
+            if let Some(id) = oldest {
+                self.remove(&id);
+            }
+        }
+    }
+}
+
+/// Decodes a PNG or JPEG into RGBA pixels.
+fn decode_image_bytes(
+    bytes: &[u8],
+    options: egui::TextureOptions,
+) -> image::ImageResult<DecodedImage> {
+    let image_buffer = image::load_from_memory(bytes)?.to_rgba8();
+    Ok(DecodedImage {
+        width: image_buffer.width(),
+        height: image_buffer.height(),
+        pixels: image_buffer.into_raw(),
+        options,
+    })
+}
+
+/// A copy of an image drawn with a translation and/or through a tranmap.
+struct DerivedImage {
+    /// The image it was made from.
+    source: AssetId,
+    /// The translation its pixels were recoloured through, if any.
+    translation: Option<String>,
+    /// The tranmap its texture is blended through, if any.
+    tranmap: Option<String>,
+    image: DecodedImage,
+}
+
+impl DecodedImage {
+    fn to_color_image(&self) -> egui::ColorImage {
+        egui::ColorImage::from_rgba_unmultiplied(
+            [self.width as usize, self.height as usize],
+            &self.pixels,
+        )
+    }
 }
 
 impl Default for AssetStore {
     fn default() -> Self {
         Self {
-            textures: HashMap::new(),
+            images: HashMap::new(),
+            textures: RefCell::new(Vec::new()),
             raw_files: HashMap::new(),
+            import_order: HashMap::new(),
+            imports: 0,
             offsets: HashMap::new(),
             names: HashMap::new(),
             base_pnames: Vec::new(),
             base_texture1: Vec::new(),
             base_texture2: Vec::new(),
+            baked_composites: Vec::new(),
             palette: crate::render::palette::DoomPalette::default(),
+            translations: HashMap::new(),
+            tranmaps: HashMap::new(),
+            tranmap_lumps: HashMap::new(),
+            tranmap_opacity: RefCell::new(HashMap::new()),
+            flats: BTreeSet::new(),
+            lump_names: BTreeSet::new(),
+            texture_names: BTreeSet::new(),
+            derived: RefCell::new(HashMap::new()),
+            lazy: HashMap::new(),
+            decoded: RefCell::new(DecodeCache::new(DECODE_CACHE_BYTES)),
+            base_ids: HashSet::new(),
         }
     }
 }
@@ -88,127 +325,501 @@ impl AssetStore {
     }
 
     /// Loads a standard image file (PNG/JPG) into the store.
-    pub fn load_image(&mut self, ctx: &egui::Context, name: &str, bytes: &[u8]) {
+    pub fn load_image(&mut self, name: &str, bytes: &[u8]) {
         let id = AssetId::new(name);
         self.raw_files.insert(id, bytes.to_vec());
+        self.imports += 1;
+        self.import_order.insert(id, self.imports);
         self.names.insert(id, name.to_string());
-        self.load_texture_only(ctx, name, bytes);
+        self.load_texture_only(name, bytes);
+    }
+
+    /// How recently a raw file was imported; later imports rank higher. Files
+    /// placed in `raw_files` directly rank 0.
+    pub fn import_rank(&self, id: &AssetId) -> u64 {
+        self.import_order.get(id).copied().unwrap_or(0)
     }
 
     /// Loads an image as a texture handle without storing raw file bytes.
-    pub fn load_reference_image(&mut self, ctx: &egui::Context, name: &str, bytes: &[u8]) {
-        self.load_texture_only(ctx, name, bytes);
+    pub fn load_reference_image(&mut self, name: &str, bytes: &[u8]) {
+        self.load_texture_only(name, bytes);
     }
 
-    /// Internal: Decodes image bytes and uploads them to the GPU.
-    fn create_and_store_texture(
-        &mut self,
-        ctx: &egui::Context,
-        name: &str,
-        image: egui::ColorImage,
-        options: egui::TextureOptions,
-    ) {
+    /// Internal: Registers decoded pixels as the new contents of an asset.
+    ///
+    /// If the asset has already been drawn, its texture is refreshed in place so
+    /// animated assets (like fire skies) don't allocate a new texture every step.
+    fn store_decoded(&mut self, name: &str, image: DecodedImage) {
         let id = AssetId::new(name);
-        let handle = ctx.load_texture(name, image, options);
 
-        self.textures.insert(id, handle);
+        for (_, cache) in self.textures.get_mut().iter_mut() {
+            cache.update(&id, |handle| {
+                handle.set(image.to_color_image(), image.options)
+            });
+        }
+        self.forget_derived(|entry| entry.source == id);
+        self.lazy.remove(&id);
+        self.decoded.get_mut().remove(&id);
+        self.base_ids.remove(&id);
+        self.images.insert(id, image);
 
         self.names.entry(id).or_insert_with(|| name.to_string());
     }
 
-    fn load_image_from_bytes(
-        &mut self,
-        ctx: &egui::Context,
-        name: &str,
-        bytes: &[u8],
-        options: egui::TextureOptions,
-    ) {
-        match image::load_from_memory(bytes) {
-            Ok(image) => {
-                let size = [image.width() as _, image.height() as _];
-                let image_buffer = image.to_rgba8();
-                let pixels = image_buffer.as_flat_samples();
-                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
-                self.create_and_store_texture(ctx, name, color_image, options);
-            }
+    fn load_image_from_bytes(&mut self, name: &str, bytes: &[u8], options: egui::TextureOptions) {
+        match decode_image_bytes(bytes, options) {
+            Ok(decoded) => self.store_decoded(name, decoded),
             Err(e) => {
                 eprintln!("!!! FAILED TO LOAD IMAGE '{}': {}", name, e);
             }
         }
     }
 
-    fn load_texture_only(&mut self, ctx: &egui::Context, name: &str, bytes: &[u8]) {
-        self.load_image_from_bytes(ctx, name, bytes, Self::REPEAT_OPTIONS);
+    fn load_texture_only(&mut self, name: &str, bytes: &[u8]) {
+        self.load_image_from_bytes(name, bytes, Self::REPEAT_OPTIONS);
     }
 
     /// Loads an image with linear filtering.
-    pub fn load_smooth_image(&mut self, ctx: &egui::Context, name: &str, bytes: &[u8]) {
-        self.load_image_from_bytes(ctx, name, bytes, egui::TextureOptions::LINEAR);
+    pub fn load_smooth_image(&mut self, name: &str, bytes: &[u8]) {
+        self.load_image_from_bytes(name, bytes, egui::TextureOptions::LINEAR);
     }
 
     /// Directly loads raw RGBA pixels into the texture store.
-    pub fn load_rgba(
-        &mut self,
-        ctx: &egui::Context,
-        name: &str,
-        width: u32,
-        height: u32,
-        pixels: &[u8],
-    ) {
+    pub fn load_rgba(&mut self, name: &str, width: u32, height: u32, pixels: &[u8]) {
         if pixels.len() != (width * height * 4) as usize {
             return;
         }
 
-        let color_image =
-            egui::ColorImage::from_rgba_unmultiplied([width as _, height as _], pixels);
-        self.create_and_store_texture(ctx, name, color_image, Self::REPEAT_OPTIONS);
+        let decoded = DecodedImage {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+            options: Self::REPEAT_OPTIONS,
+        };
+        self.store_decoded(name, decoded);
     }
 
-    /// Loads raw RGBA pixels and registers a Doom patch offset.
-    pub fn load_rgba_with_offset(
+    /// Registers a graphic to be decoded from `data` the first time it's
+    /// used. Replaces any image already stored under the name.
+    pub fn load_lazy(
         &mut self,
-        ctx: &egui::Context,
         name: &str,
-        width: u32,
-        height: u32,
-        left: i16,
-        top: i16,
-        pixels: &[u8],
+        data: LumpData,
+        format: GraphicFormat,
+        size: (u32, u32),
     ) {
         let id = AssetId::new(name);
-        self.offsets.insert(id, (left, top));
-        self.load_rgba(ctx, name, width, height, pixels);
+        for (_, cache) in self.textures.get_mut().iter_mut() {
+            cache.remove(&id);
+        }
+        self.forget_derived(|entry| entry.source == id);
+        self.images.remove(&id);
+        self.decoded.get_mut().remove(&id);
+        self.base_ids.remove(&id);
+        let (width, height) = size;
+        self.lazy.insert(
+            id,
+            LazyImage {
+                data,
+                format,
+                width,
+                height,
+            },
+        );
+        self.names.entry(id).or_insert_with(|| name.to_string());
+    }
+
+    /// Runs `f` on an image's pixels, decoding it first if it was loaded lazily.
+    fn with_image<R>(&self, id: &AssetId, f: impl FnOnce(&DecodedImage) -> R) -> Option<R> {
+        if let Some(img) = self.images.get(id) {
+            return Some(f(img));
+        }
+        let lazy = self.lazy.get(id)?;
+        let cached = self.decoded.borrow_mut().get(id);
+        let img = match cached {
+            Some(img) => img,
+            None => {
+                let img = Arc::new(lazy.decode(&self.palette)?);
+                self.decoded.borrow_mut().insert(*id, img.clone());
+                img
+            }
+        };
+        Some(f(&img))
+    }
+
+    /// Returns true if an image is stored under this id, decoded or not.
+    pub fn has_image(&self, id: &AssetId) -> bool {
+        self.images.contains_key(id) || self.lazy.contains_key(id)
+    }
+
+    /// Returns the pixel dimensions of an image.
+    pub fn image_size(&self, id: &AssetId) -> Option<(u32, u32)> {
+        match self.images.get(id) {
+            Some(img) => Some((img.width, img.height)),
+            None => self.lazy.get(id).map(|img| (img.width, img.height)),
+        }
+    }
+
+    /// Returns the pixel dimensions of a decoded image as an egui vector.
+    pub fn image_size_vec2(&self, id: &AssetId) -> Option<egui::Vec2> {
+        self.image_size(id)
+            .map(|(w, h)| egui::vec2(w as f32, h as f32))
+    }
+
+    /// Returns the GPU texture for an asset, uploading it on first use.
+    pub fn texture(&self, ctx: &egui::Context, id: &AssetId) -> Option<egui::TextureHandle> {
+        if let Some(handle) = self.cached_texture(ctx, id) {
+            return Some(handle);
+        }
+
+        let name = self
+            .names
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string());
+        self.with_image(id, |img| self.upload(ctx, *id, name, img))
     }
 
-    /// Pre-loads built-in application icons and badges.
-    pub fn load_system_assets(&mut self, ctx: &egui::Context) {
+    /// Returns the texture for a patch drawn with an element's translation and
+    /// tranmap.
+    ///
+    /// Recoloured copies are built on first use and cached per (patch,
+    /// translation). Tranmapped patches get a texture of their own so an
+    /// offscreen render can tell which draws to blend through the table (see
+    /// [`Self::tranmapped_textures`]). Unknown translations and tranmaps are
+    /// ignored.
+    pub fn patch_texture(
+        &self,
+        ctx: &egui::Context,
+        id: &AssetId,
+        translation: Option<&str>,
+        tranmap: Option<&str>,
+    ) -> Option<egui::TextureHandle> {
+        let translation = translation
+            .map(Self::stem)
+            .filter(|key| self.translations.contains_key(key));
+        let tranmap = tranmap
+            .map(Self::stem)
+            .filter(|key| self.tranmaps.contains_key(key));
+
+        if translation.is_none() && tranmap.is_none() {
+            return self.texture(ctx, id);
+        }
+
+        let suffix = format!(
+            "{}{}",
+            translation
+                .as_ref()
+                .map(|t| format!("@{}", t))
+                .unwrap_or_default(),
+            tranmap
+                .as_ref()
+                .map(|t| format!("#{}", t))
+                .unwrap_or_default(),
+        );
+        let derived = AssetId::new(&format!("{}{}", id, suffix));
+        if let Some(handle) = self.cached_texture(ctx, &derived) {
+            return Some(handle);
+        }
+
+        let cached = self
+            .derived
+            .borrow()
+            .get(&derived)
+            .map(|entry| entry.image.clone());
+        let image = match cached {
+            Some(image) => image,
+            None => {
+                let image = self.with_image(id, |source| {
+                    let pixels = match &translation {
+                        Some(key) => translation::translate_rgba(
+                            &source.pixels,
+                            &self.palette,
+                            &self.translations[key],
+                        ),
+                        None => source.pixels.clone(),
+                    };
+                    DecodedImage {
+                        pixels,
+                        ..source.clone()
+                    }
+                })?;
+                self.derived.borrow_mut().insert(
+                    derived,
+                    DerivedImage {
+                        source: *id,
+                        translation,
+                        tranmap,
+                        image: image.clone(),
+                    },
+                );
+                image
+            }
+        };
+
+        let name = format!(
+            "{}{}",
+            self.names
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| id.to_string()),
+            suffix
+        );
+        Some(self.upload(ctx, derived, name, &image))
+    }
+
+    /// Maps the textures uploaded to a context that must be blended through a
+    /// tranmap to that tranmap's name.
+    pub fn tranmapped_textures(&self, ctx: &egui::Context) -> HashMap<egui::TextureId, String> {
+        let textures = self.textures.borrow();
+        let Some((_, cache)) = textures.iter().find(|(owner, _)| owner == ctx) else {
+            return HashMap::new();
+        };
+
+        self.derived
+            .borrow()
+            .iter()
+            .filter_map(|(derived, entry)| {
+                let tranmap = entry.tranmap.clone()?;
+                cache.peek(derived).map(|handle| (handle.id(), tranmap))
+            })
+            .collect()
+    }
+
+    /// Registers a TRANMAP-style lump, returning false if the bytes aren't one.
+    pub fn load_tranmap(&mut self, name: &str, bytes: &[u8]) -> bool {
+        let Some(map) = TranMap::from_lump(bytes) else {
+            return false;
+        };
+        let key = Self::stem(name);
+        self.tranmap_opacity.get_mut().remove(&key);
+        self.tranmaps.insert(key, map);
+        true
+    }
+
+    /// The foreground weight a tranmap approximates, for draws that can only
+    /// blend by alpha (see [`TranMap::opacity`]). None if it isn't loaded.
+    pub fn tranmap_opacity(&self, name: &str) -> Option<f32> {
+        let key = Self::stem(name);
+        let map = self.tranmaps.get(&key)?;
+        let mut cache = self.tranmap_opacity.borrow_mut();
+        Some(
+            *cache
+                .entry(key)
+                .or_insert_with(|| map.opacity(&self.palette)),
+        )
+    }
+
+    /// Replaces the palette. Tranmap opacities are estimated against it, so
+    /// they're worked out again.
+    pub fn set_palette(&mut self, palette: crate::render::palette::DoomPalette) {
+        self.palette = palette;
+        self.tranmap_opacity.get_mut().clear();
+    }
+
+    /// Takes note of a lump that may be a tranmap. `TRANMAP` itself, the
+    /// default for translucent elements, is parsed straight away; any other
+    /// waits for [`Self::load_referenced_tranmaps`].
+    pub fn offer_tranmap(&mut self, name: &str, data: LumpData) {
+        let key = Self::stem(name);
+        if key == "TRANMAP" {
+            self.load_tranmap(&key, &data.bytes());
+        } else {
+            self.tranmap_lumps.insert(key, data);
+        }
+    }
+
+    /// Parses the offered tranmaps among `names` that aren't loaded yet.
+    pub fn load_referenced_tranmaps(&mut self, names: &[String]) {
+        for name in names {
+            let key = Self::stem(name);
+            if self.tranmaps.contains_key(&key) {
+                continue;
+            }
+            if let Some(data) = self.tranmap_lumps.remove(&key) {
+                self.load_tranmap(&key, &data.bytes());
+            }
+        }
+    }
+
+    /// Registers a translation lump, returning false if the bytes aren't one.
+    pub fn load_translation(&mut self, name: &str, bytes: &[u8]) -> bool {
+        let Some(table) = translation::parse_translation_lump(name, bytes) else {
+            return false;
+        };
+        let key = Self::stem(name);
+        self.forget_derived(|entry| entry.translation.as_ref() == Some(&key));
+        self.translations.insert(key, table);
+        true
+    }
+
+    fn cached_texture(&self, ctx: &egui::Context, id: &AssetId) -> Option<egui::TextureHandle> {
+        self.textures
+            .borrow_mut()
+            .iter_mut()
+            .find(|(owner, _)| owner == ctx)
+            .and_then(|(_, cache)| cache.get(id))
+    }
+
+    fn upload(
+        &self,
+        ctx: &egui::Context,
+        id: AssetId,
+        name: String,
+        img: &DecodedImage,
+    ) -> egui::TextureHandle {
+        let handle = ctx.load_texture(name, img.to_color_image(), img.options);
+
+        let mut textures = self.textures.borrow_mut();
+        match textures.iter_mut().find(|(owner, _)| owner == ctx) {
+            Some((_, cache)) => {
+                cache.insert(id, handle.clone());
+            }
+            None => {
+                let mut cache = TextureCache::new(TEXTURE_CACHE_BYTES);
+                cache.insert(id, handle.clone());
+                textures.push((ctx.clone(), cache));
+            }
+        }
+        handle
+    }
+
+    /// Drops derived copies (and their textures) that match.
+    fn forget_derived(&mut self, matches: impl Fn(&DerivedImage) -> bool) {
+        let stale: Vec<AssetId> = self
+            .derived
+            .get_mut()
+            .iter()
+            .filter(|(_, entry)| matches(entry))
+            .map(|(derived, _)| *derived)
+            .collect();
+
+        for derived in stale {
+            self.derived.get_mut().remove(&derived);
+            for (_, cache) in self.textures.get_mut().iter_mut() {
+                cache.remove(&derived);
+            }
+        }
+    }
+
+    /// Drops every texture uploaded to the given context, e.g. once an
+    /// offscreen render has finished with it.
+    pub fn release_textures(&self, ctx: &egui::Context) {
+        self.textures.borrow_mut().retain(|(owner, _)| owner != ctx);
+    }
+
+    /// Removes every trace of an asset from the store.
+    pub fn remove(&mut self, id: &AssetId) {
+        for (_, cache) in self.textures.get_mut().iter_mut() {
+            cache.remove(id);
+        }
+        self.forget_derived(|entry| entry.source == *id);
+        self.images.remove(id);
+        self.lazy.remove(id);
+        self.decoded.get_mut().remove(id);
+        self.raw_files.remove(id);
+        self.import_order.remove(id);
+        self.offsets.remove(id);
+        self.names.remove(id);
+    }
+
+    /// Lays a base IWAD's store underneath this one. Its graphics fill in
+    /// the names this store doesn't have, taking over from the previous
+    /// base's. Its palette and texture templates replace the current ones,
+    /// and its lump names, flats, translations and tranmaps are added.
+    pub fn underlay(&mut self, mut base: AssetStore) {
+        for id in std::mem::take(&mut self.base_ids) {
+            self.remove(&id);
+        }
+
+        for (id, image) in base.images.drain() {
+            if !self.has_image(&id) {
+                self.images.insert(id, image);
+                self.base_ids.insert(id);
+            }
+        }
+        for (id, lazy) in base.lazy.drain() {
+            if !self.has_image(&id) {
+                self.lazy.insert(id, lazy);
+                self.base_ids.insert(id);
+            }
+        }
+        for id in &self.base_ids {
+            if let Some(offsets) = base.offsets.remove(id) {
+                self.offsets.insert(*id, offsets);
+            }
+            if let Some(name) = base.names.remove(id) {
+                self.names.insert(*id, name);
+            }
+        }
+
+        if base.lump_names.contains("PLAYPAL") {
+            self.set_palette(base.palette);
+        }
+        if !base.base_pnames.is_empty() {
+            self.base_pnames = base.base_pnames;
+        }
+        if !base.base_texture1.is_empty() {
+            self.base_texture1 = base.base_texture1;
+        }
+        if !base.base_texture2.is_empty() {
+            self.base_texture2 = base.base_texture2;
+        }
+        self.flats.append(&mut base.flats);
+        self.lump_names.append(&mut base.lump_names);
+        self.texture_names.append(&mut base.texture_names);
+        for (key, table) in base.translations {
+            self.translations.entry(key).or_insert(table);
+        }
+        for (key, map) in base.tranmaps {
+            self.tranmaps.entry(key).or_insert(map);
+        }
+        for (key, data) in base.tranmap_lumps {
+            if !self.tranmaps.contains_key(&key) {
+                self.tranmap_lumps.entry(key).or_insert(data);
+            }
+        }
+    }
+
+    /// Pre-loads built-in branding, application icons and badges, and the
+    /// thumbnails of the library's template assets.
+    pub fn load_system_assets(&mut self) {
+        self.load_smooth_image("_BG_MASTER", include_bytes!("../assets/background.png"));
+        self.load_reference_image("HICACOCO", include_bytes!("../assets/HICACOCO.png"));
+        self.load_smooth_image(
+            "_MINIMAP_PLACEHOLDER",
+            include_bytes!("../assets/automap.png"),
+        );
+        self.load_smooth_image(
+            "_MINIMAP_THUMB",
+            include_bytes!("../assets/tinyautomap.png"),
+        );
+        for asset in crate::library::ASSETS {
+            let key = Self::stem(asset.name);
+            self.load_reference_image(&key, asset.bytes);
+        }
+
         self.load_reference_image(
-            ctx,
             "_BADGE_ALLMAP",
             include_bytes!("../assets/badges/Allmap.png"),
         );
         self.load_reference_image(
-            ctx,
             "_BADGE_BERSERK",
             include_bytes!("../assets/badges/Berserk.png"),
         );
         self.load_reference_image(
-            ctx,
             "_BADGE_BLURSPHERE",
             include_bytes!("../assets/badges/BlurSphere.png"),
         );
         self.load_reference_image(
-            ctx,
             "_BADGE_INVULN",
             include_bytes!("../assets/badges/Invuln.png"),
         );
         self.load_reference_image(
-            ctx,
             "_BADGE_LITEAMP",
             include_bytes!("../assets/badges/LiteAmp.png"),
         );
         self.load_reference_image(
-            ctx,
             "_BADGE_RADSUIT",
             include_bytes!("../assets/badges/Radsuit.png"),
         );
@@ -222,7 +833,7 @@ impl AssetStore {
             match c_upper {
                 '-' => {
                     let p1 = AssetId::new(&format!("{}MINUS", stem));
-                    if self.textures.contains_key(&p1) {
+                    if self.has_image(&p1) {
                         return p1;
                     }
                     AssetId::new(&format!("{}-", stem))
@@ -231,7 +842,7 @@ impl AssetStore {
                     let variants = ["PRCNT", "PRCN", "PCNT", "PERCENT", "%"];
                     for v in variants {
                         let p = AssetId::new(&format!("{}{}", stem, v));
-                        if self.textures.contains_key(&p) {
+                        if self.has_image(&p) {
                             return p;
                         }
                     }
@@ -239,7 +850,7 @@ impl AssetStore {
                 }
                 '0'..='9' => {
                     let p1 = AssetId::new(&format!("{}NUM{}", stem, c_upper));
-                    if self.textures.contains_key(&p1) {
+                    if self.has_image(&p1) {
                         return p1;
                     }
                     AssetId::new(&format!("{}{}", stem, c_upper))
@@ -251,11 +862,84 @@ impl AssetStore {
         }
     }
 
-    /// Specialized resolver for Sky textures that handles the SKY/RSKY naming fallback.
+    /// The id a composite texture's baked pixels are stored under. Kept apart
+    /// from the patch ids, since a texture often shares its patch's name.
+    pub fn composite_id(name: &str) -> AssetId {
+        AssetId::new(&format!("_TEXTURE_{}", name))
+    }
+
+    /// Draws a composite texture's patches into one image, so it previews
+    /// like any other graphic. Missing patches are left transparent.
+    fn bake_composite(&mut self, tex: &CompositeTexture) {
+        let (w, h) = (tex.width as usize, tex.height as usize);
+        if w == 0 || h == 0 {
+            return;
+        }
+        let mut pixels = vec![0u8; w * h * 4];
+        for p in &tex.patches {
+            self.with_image(&AssetId::new(&p.patch), |img| {
+                for sy in 0..img.height as usize {
+                    let dy = sy as i64 + p.y as i64;
+                    if !(0..h as i64).contains(&dy) {
+                        continue;
+                    }
+                    for sx in 0..img.width as usize {
+                        let dx = sx as i64 + p.x as i64;
+                        let src = (sy * img.width as usize + sx) * 4;
+                        if (0..w as i64).contains(&dx) && img.pixels[src + 3] > 0 {
+                            let dst = (dy as usize * w + dx as usize) * 4;
+                            pixels[dst..dst + 4].copy_from_slice(&img.pixels[src..src + 4]);
+                        }
+                    }
+                }
+            });
+        }
+        let key = format!("_TEXTURE_{}", tex.name);
+        self.load_rgba(&key, tex.width as u32, tex.height as u32, &pixels);
+    }
+
+    /// Brings the baked composites in line with the document's: textures that
+    /// changed are baked again and ones that are gone are dropped. Cheap when
+    /// nothing changed, so it can run every frame.
+    pub fn sync_composites(&mut self, composites: &[CompositeTexture]) {
+        if self.baked_composites == composites {
+            return;
+        }
+        let previous = std::mem::take(&mut self.baked_composites);
+        for old in previous.iter().filter(|t| !composites.contains(t)) {
+            self.remove(&Self::composite_id(&old.name));
+        }
+        for tex in composites.iter().filter(|t| !previous.contains(t)) {
+            self.bake_composite(tex);
+        }
+        self.baked_composites = composites.to_vec();
+    }
+
+    /// Whether a lump of the given kind is available under `name`.
+    pub fn has_lump(&self, kind: LumpKind, name: &str) -> bool {
+        let upper = name.to_uppercase();
+        let image = self.has_image(&AssetId::new(name));
+        match kind {
+            LumpKind::Graphic => image,
+            LumpKind::Backdrop => image || self.flats.contains(&upper),
+            LumpKind::Music => self.lump_names.contains(&upper),
+            LumpKind::Texture => {
+                self.texture_names.contains(&upper) || self.has_image(&self.resolve_sky_id(&upper))
+            }
+        }
+    }
+
+    /// Specialized resolver for Sky textures that handles the SKY/RSKY naming
+    /// fallback. Composite textures of the same name take priority.
     pub fn resolve_sky_id(&self, name: &str) -> AssetId {
+        let composite = Self::composite_id(name);
+        if self.has_image(&composite) {
+            return composite;
+        }
+
         let mut id = AssetId::new(name);
 
-        if !self.textures.contains_key(&id) {
+        if !self.has_image(&id) {
             if name.starts_with("SKY") {
                 let fallback = format!("R{}", name);
                 id = AssetId::new(&fallback);
@@ -270,7 +954,7 @@ impl AssetStore {
     }
 
     /// Decodes an asset, flips it horizontally, and re-saves the result.
-    pub fn flip_asset_horizontal(&mut self, ctx: &egui::Context, id: AssetId) {
+    pub fn flip_asset_horizontal(&mut self, id: AssetId) {
         if let Some(bytes) = self.raw_files.get(&id) {
             if let Ok(img) = image::load_from_memory(bytes) {
                 let flipped = img.fliph();
@@ -287,9 +971,221 @@ impl AssetStore {
                         .cloned()
                         .unwrap_or_else(|| "unknown".to_string());
 
-                    self.load_image(ctx, &name, &new_bytes);
+                    self.load_image(&name, &new_bytes);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x1 Doom patch with the given offsets.
+    fn tiny_patch(left: i16, top: i16) -> Vec<u8> {
+        let mut patch = vec![2, 0, 1, 0];
+        patch.extend_from_slice(&left.to_le_bytes());
+        patch.extend_from_slice(&top.to_le_bytes());
+        patch.extend_from_slice(&16u32.to_le_bytes());
+        patch.extend_from_slice(&22u32.to_le_bytes());
+        patch.extend_from_slice(&[0, 1, 0, 7, 0, 255, 0, 1, 0, 9, 0, 255]);
+        patch
+    }
+
+    #[test]
+    fn test_decoding_needs_no_context() {
+        let mut assets = AssetStore::default();
+        crate::wad::load_graphic(
+            &mut assets,
+            "STFB0",
+            tiny_patch(-3, 4).into(),
+            crate::wad::Namespace::Global,
+        );
+        assets.load_reference_image(
+            "_BADGE_ALLMAP",
+            include_bytes!("../assets/badges/Allmap.png"),
+        );
+
+        let id = AssetId::new("stfb0");
+        assert!(assets.has_image(&id));
+        assert_eq!(assets.image_size(&id), Some((2, 1)));
+        assert_eq!(assets.offsets.get(&id), Some(&(-3, 4)));
+        assert!(assets.has_image(&AssetId::new("_BADGE_ALLMAP")));
+        assert!(assets.textures.borrow().is_empty());
+
+        fn assert_send<T: Send>() {}
+        assert_send::<AssetStore>();
+    }
+
+    #[test]
+    fn test_lazy_images_decode_on_use_and_evict() {
+        // Room for one decoded 2x1 image.
+        let mut assets = AssetStore {
+            decoded: RefCell::new(DecodeCache::new(8)),
+            ..Default::default()
+        };
+        for name in ["PATCH1", "PATCH2"] {
+            crate::wad::load_graphic(
+                &mut assets,
+                name,
+                tiny_patch(0, 0).into(),
+                crate::wad::Namespace::Patches,
+            );
+        }
+        let (first, second) = (AssetId::new("PATCH1"), AssetId::new("PATCH2"));
+        assert!(assets.decoded.borrow().entries.is_empty());
+
+        let pixel = |id| assets.with_image(id, |img| img.pixels[..4].to_vec());
+        let expected = assets.palette.get(7);
+        assert_eq!(
+            pixel(&first),
+            Some(vec![expected.r(), expected.g(), expected.b(), 255])
+        );
+        assert!(pixel(&second).is_some());
+
+        let decoded = assets.decoded.borrow();
+        assert_eq!(decoded.entries.len(), 1);
+        assert!(decoded.entries.contains_key(&second));
+        drop(decoded);
+
+        // Evicted images decode again on the next use.
+        assert!(pixel(&first).is_some());
+        assert!(assets.decoded.borrow().entries.contains_key(&first));
+    }
+
+    #[test]
+    fn test_textures_evict_least_recently_used() {
+        let ctx = egui::Context::default();
+        let mut assets = AssetStore::default();
+        for name in ["PATCH1", "PATCH2", "PATCH3"] {
+            assets.load_rgba(name, 2, 1, &[0; 8]);
+        }
+        let [first, second, third] = ["PATCH1", "PATCH2", "PATCH3"].map(AssetId::new);
+
+        // Room for two uploaded 2x1 textures.
+        assets
+            .textures
+            .borrow_mut()
+            .push((ctx.clone(), TextureCache::new(16)));
+        let kept = assets.texture(&ctx, &first).unwrap();
+        assets.texture(&ctx, &second);
+        assets.texture(&ctx, &first);
+        assets.texture(&ctx, &third);
+
+        assert_eq!(assets.cached_texture(&ctx, &first).unwrap().id(), kept.id());
+        assert!(assets.cached_texture(&ctx, &third).is_some());
+        assert!(assets.cached_texture(&ctx, &second).is_none());
+
+        // Evicted textures upload again on the next use.
+        assert!(assets.texture(&ctx, &second).is_some());
+    }
+
+    #[test]
+    fn test_texture_created_on_first_use_and_refreshed() {
+        let ctx = egui::Context::default();
+        let mut assets = AssetStore::default();
+        let id = AssetId::new("_FIRE_ANIM_SKY1");
+
+        assert!(assets.texture(&ctx, &id).is_none());
+
+        assets.load_rgba("_FIRE_ANIM_SKY1", 4, 2, &[0; 32]);
+        let first = assets
+            .texture(&ctx, &id)
+            .expect("texture should be uploaded");
+        assert_eq!(first.size(), [4, 2]);
+        assert_eq!(assets.texture(&ctx, &id).unwrap().id(), first.id());
+
+        assets.load_rgba("_FIRE_ANIM_SKY1", 8, 8, &[0; 256]);
+        let refreshed = assets.texture(&ctx, &id).unwrap();
+        assert_eq!(
+            refreshed.id(),
+            first.id(),
+            "animated assets should reuse their handle"
+        );
+        assert_eq!(refreshed.size(), [8, 8]);
+    }
+
+    #[test]
+    fn test_underlay_keeps_project_graphics_and_swaps_bases() {
+        let base = |names: &[&str]| {
+            let mut base = AssetStore::default();
+            for name in names {
+                crate::wad::load_graphic(
+                    &mut base,
+                    name,
+                    tiny_patch(1, 1).into(),
+                    crate::wad::Namespace::Global,
+                );
+            }
+            base
+        };
+        let mut assets = AssetStore::default();
+        assets.load_rgba("STBAR", 4, 2, &[0; 32]);
+
+        assets.underlay(base(&["STBAR", "STFB0"]));
+        let [stbar, stfb0, stfb1] = ["STBAR", "STFB0", "STFB1"].map(AssetId::new);
+        assert_eq!(assets.image_size(&stbar), Some((4, 2)));
+        assert_eq!(assets.offsets.get(&stfb0), Some(&(1, 1)));
+
+        assets.underlay(base(&["STFB1"]));
+        assert_eq!(assets.image_size(&stbar), Some((4, 2)));
+        assert!(!assets.has_image(&stfb0));
+        assert!(assets.has_image(&stfb1));
+    }
+
+    #[test]
+    fn test_only_referenced_tranmaps_are_parsed() {
+        let lump = || LumpData::from(vec![0u8; crate::render::tranmap::TRANMAP_SIZE]);
+        let mut assets = AssetStore::default();
+        for name in ["TRANMAP", "GLASS", "FOG"] {
+            assets.offer_tranmap(name, lump());
+        }
+        assert!(assets.tranmaps.contains_key("TRANMAP"));
+        assert!(!assets.tranmaps.contains_key("GLASS"));
+
+        let mut sbar = crate::models::sbardef::SBarDefFile::new_empty();
+        let mut element = crate::models::sbardef::wrap_graphic("STBAR", 0, 0);
+        element.get_common_mut().tranmap = Some("glass".to_string());
+        sbar.data.status_bars[0].children.push(element);
+        let lump = crate::models::ProjectData::StatusBar(sbar);
+        assert_eq!(lump.tranmap_names(), ["glass"]);
+
+        assets.load_referenced_tranmaps(&lump.tranmap_names());
+        assert!(assets.tranmaps.contains_key("GLASS"));
+        assert!(!assets.tranmaps.contains_key("FOG"));
+    }
+
+    #[test]
+    fn test_replacing_a_translation_keeps_other_recolours() {
+        let ctx = egui::Context::default();
+        let mut assets = AssetStore::default();
+        crate::wad::load_graphic(
+            &mut assets,
+            "STTNUM0",
+            tiny_patch(0, 0).into(),
+            crate::wad::Namespace::Global,
+        );
+        let table: Vec<u8> = (0..=255).collect();
+        assert!(assets.load_translation("CRRED", &table));
+        assert!(assets.load_translation("CRBLUE", &table));
+
+        let id = AssetId::new("STTNUM0");
+        let red = assets
+            .patch_texture(&ctx, &id, Some("CRRED"), None)
+            .unwrap();
+        let blue = assets
+            .patch_texture(&ctx, &id, Some("CRBLUE"), None)
+            .unwrap();
+
+        assert!(assets.load_translation("CRRED", &table));
+        let kept = assets
+            .patch_texture(&ctx, &id, Some("CRBLUE"), None)
+            .unwrap();
+        assert_eq!(kept.id(), blue.id());
+        let rebuilt = assets
+            .patch_texture(&ctx, &id, Some("CRRED"), None)
+            .unwrap();
+        assert_ne!(rebuilt.id(), red.id());
+    }
+}
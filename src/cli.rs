@@ -0,0 +1,608 @@
+//! Headless command-line mode for batch exporting, converting, validating and
+//! rendering projects.
+//!
+//! Nothing in here opens a window, so it can run from build scripts and CI
+//! machines that have no display or GPU. Invoked as `cacoco <command> ...`; any other
+//! argument list falls through to the normal editor window.
+
+use crate::assets::{AssetId, AssetStore};
+use crate::io::{self, LoadedProject};
+use crate::models::ProjectData;
+use crate::models::sbardef::{Element, ElementWrapper, ExportTarget, SBarDefFile};
+use crate::render::offscreen::{self, OffscreenOptions};
+use crate::state::PreviewState;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const USAGE: &str = "\
+Usage:
+  cacoco [FILE]                              Open FILE in the editor
+  cacoco export <INPUT> [--wad OUT] [--pk3 OUT] [--iwad PATH]
+  cacoco convert <INPUT> --target <basic|extended> [--output OUT] [--iwad PATH]
+  cacoco validate <INPUT>... [--iwad PATH]
+  cacoco render <INPUT> --output OUT.png [--bar N] [--scale N] [--widescreen]
+                [--transparent] [--iwad PATH]
+  cacoco help
+
+INPUT may be a WAD, PK3/ZIP or a standalone lump (SBARDEF.json, UMAPINFO.txt, ...).
+`convert` picks its output format from the extension of OUT (.wad, .pk3/.zip,
+anything else is written as JSON). Without --output the JSON goes to stdout.
+`render` draws one status bar of the project's SBARDEF as the viewport shows it;
+--transparent leaves out the world backdrop behind the bar.
+`validate` checks SBARDEF fonts and graphics; other lumps are only parsed and
+listed.
+--iwad defaults to the base IWAD configured in the editor settings.";
+
+/// A parsed headless invocation.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Export {
+        input: String,
+        wad: Option<String>,
+        pk3: Option<String>,
+        iwad: Option<String>,
+    },
+    Convert {
+        input: String,
+        target: ExportTarget,
+        output: Option<String>,
+        iwad: Option<String>,
+    },
+    Validate {
+        inputs: Vec<String>,
+        iwad: Option<String>,
+    },
+    Render {
+        input: String,
+        output: String,
+        bar: usize,
+        flags: RenderFlags,
+        iwad: Option<String>,
+    },
+    Help,
+}
+
+/// Presentation switches for `render`.
+#[derive(Debug, PartialEq, Default)]
+struct RenderFlags {
+    scale: u32,
+    widescreen: bool,
+    transparent: bool,
+}
+
+/// Release builds on Windows use the GUI subsystem and start without a
+/// console, so borrow the one of the shell that launched us; otherwise every
+/// println! from a command goes nowhere.
+#[cfg(windows)]
+pub fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails harmlessly when there is no parent console (e.g. a double-click).
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
+
+/// Returns true if the arguments request a headless command instead of the editor.
+pub fn is_command(args: &[String]) -> bool {
+    matches!(
+        args.first().map(|s| s.as_str()),
+        Some("export" | "convert" | "validate" | "render" | "help" | "--help" | "-h")
+    )
+}
+
+/// Runs a headless command and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let command = match parse(args) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    let result = match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Command::Export {
+            input,
+            wad,
+            pk3,
+            iwad,
+        } => run_export(&input, wad.as_deref(), pk3.as_deref(), iwad.as_deref()),
+        Command::Convert {
+            input,
+            target,
+            output,
+            iwad,
+        } => run_convert(&input, target, output.as_deref(), iwad.as_deref()),
+        Command::Validate { inputs, iwad } => run_validate(&inputs, iwad.as_deref()),
+        Command::Render {
+            input,
+            output,
+            bar,
+            flags,
+            iwad,
+        } => run_render(&input, &output, bar, &flags, iwad.as_deref()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn parse(args: &[String]) -> Result<Command, String> {
+    let (name, rest) = args.split_first().ok_or("no command given")?;
+
+    let mut positional = Vec::new();
+    let mut wad = None;
+    let mut pk3 = None;
+    let mut iwad = None;
+    let mut output = None;
+    let mut target = None;
+    let mut bar = 0;
+    let mut flags = RenderFlags {
+        scale: 1,
+        ..Default::default()
+    };
+
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} expects a value", flag))
+        };
+        match arg.as_str() {
+            "--wad" => wad = Some(value(arg)?),
+            "--pk3" => pk3 = Some(value(arg)?),
+            "--iwad" => iwad = Some(value(arg)?),
+            "--output" | "-o" => output = Some(value(arg)?),
+            "--target" => target = Some(parse_target(&value(arg)?)?),
+            "--bar" => bar = parse_number(arg, &value(arg)?)?,
+            "--scale" => flags.scale = parse_number(arg, &value(arg)?)?.clamp(1, 16) as u32,
+            "--widescreen" => flags.widescreen = true,
+            "--transparent" => flags.transparent = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let single_input = |positional: &mut Vec<String>| match positional.len() {
+        0 => Err(format!("'{}' needs an input file", name)),
+        1 => Ok(positional.remove(0)),
+        _ => Err(format!("'{}' takes exactly one input file", name)),
+    };
+
+    match name.as_str() {
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "export" => {
+            if wad.is_none() && pk3.is_none() {
+                return Err("export needs at least one of --wad or --pk3".to_string());
+            }
+            Ok(Command::Export {
+                input: single_input(&mut positional)?,
+                wad,
+                pk3,
+                iwad,
+            })
+        }
+        "convert" => Ok(Command::Convert {
+            input: single_input(&mut positional)?,
+            target: target.ok_or("convert needs --target basic|extended")?,
+            output,
+            iwad,
+        }),
+        "validate" => {
+            if positional.is_empty() {
+                return Err("validate needs at least one input file".to_string());
+            }
+            Ok(Command::Validate {
+                inputs: positional,
+                iwad,
+            })
+        }
+        "render" => Ok(Command::Render {
+            input: single_input(&mut positional)?,
+            output: output.ok_or("render needs --output OUT.png")?,
+            bar,
+            flags,
+            iwad,
+        }),
+        other => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+fn parse_target(value: &str) -> Result<ExportTarget, String> {
+    match value.to_lowercase().as_str() {
+        "basic" | "kex" | "1.0.0" => Ok(ExportTarget::Basic),
+        "extended" | "1.2.0" => Ok(ExportTarget::Extended),
+        other => Err(format!(
+            "unknown target '{}' (expected basic or extended)",
+            other
+        )),
+    }
+}
+
+/// Loads a project and layers the base IWAD underneath it, mirroring the editor.
+///
+/// The IWAD supplies the PNAMES/TEXTURE1 templates for SKYDEFS exports and the
+/// glyph sizes used when baking text for Basic targets.
+fn load_with_iwad(input: &str, iwad: Option<&str>) -> Result<(LoadedProject, bool), String> {
+    let mut loaded = io::load_project_from_path(input, &io::LoadProgress::default())
+        .map_err(|e| e.to_string())?;
+    let iwad = iwad
+        .map(|s| s.to_string())
+        .or_else(|| crate::config::AppConfig::load().base_wad_path);
+
+    let progress = io::LoadProgress::default();
+    loaded
+        .prepare(iwad.as_deref(), &progress)
+        .map_err(|e| e.to_string())?;
+    let has_iwad = loaded.has_base;
+    for warning in &loaded.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok((loaded, has_iwad))
+}
+
+fn run_export(
+    input: &str,
+    wad: Option<&str>,
+    pk3: Option<&str>,
+    iwad: Option<&str>,
+) -> Result<(), String> {
+    let (loaded, _) = load_with_iwad(input, iwad)?;
+    write_outputs(&loaded, wad, pk3)
+}
+
+fn write_outputs(
+    loaded: &LoadedProject,
+    wad: Option<&str>,
+    pk3: Option<&str>,
+) -> Result<(), String> {
+    if let Some(out) = wad {
+        let skipped = io::save_wad_silent(
+            &loaded.lumps,
+            &loaded.composites,
+            &loaded.assets,
+            &loaded.passthrough_lumps,
+            out,
+        )
+        .map_err(|e| e.to_string())?;
+        println!("Wrote {}", out);
+        for name in skipped {
+            eprintln!(
+                "warning: skipped {}, a newer image has the same lump name",
+                name
+            );
+        }
+    }
+    if let Some(out) = pk3 {
+        let manifest = io::save_pk3_silent(
+            &loaded.lumps,
+            &loaded.composites,
+            &loaded.assets,
+            &loaded.passthrough_lumps,
+            out,
+        )
+        .map_err(|e| e.to_string())?;
+        println!("Wrote {}", out);
+        for line in manifest.to_text().lines() {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+fn run_convert(
+    input: &str,
+    target: ExportTarget,
+    output: Option<&str>,
+    iwad: Option<&str>,
+) -> Result<(), String> {
+    let (mut loaded, _) = load_with_iwad(input, iwad)?;
+
+    for lump in &mut loaded.lumps {
+        lump.set_target(target);
+        lump.normalize_for_target();
+    }
+
+    let ext = output
+        .and_then(|o| Path::new(o).extension())
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match (output, ext.as_str()) {
+        (Some(out), "wad") => write_outputs(&loaded, Some(out), None),
+        (Some(out), "pk3" | "zip") => write_outputs(&loaded, None, Some(out)),
+        _ => {
+            let lump = loaded
+                .lumps
+                .iter()
+                .find(|l| l.as_sbar().is_some())
+                .or(loaded.lumps.first())
+                .ok_or("project contains no lumps")?;
+            let json = lump.to_sanitized_json(&loaded.assets);
+            match output {
+                Some(out) => {
+                    fs::write(out, json)
+                        .map_err(|e| format!("failed to write '{}': {}", out, e))?;
+                    println!("Wrote {}", out);
+                }
+                None => {
+                    let _ = writeln!(std::io::stdout(), "{}", json);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Loads each input and reports it. Only SBARDEF lumps get deeper checks
+/// (fonts and graphics); everything else passes once it parses.
+fn run_validate(inputs: &[String], iwad: Option<&str>) -> Result<(), String> {
+    let mut failed = 0;
+
+    for input in inputs {
+        let (loaded, has_iwad) = match load_with_iwad(input, iwad) {
+            Ok(l) => l,
+            Err(e) => {
+                println!("FAIL {}: {}", input, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        // Lumps that didn't parse only warn in the editor, but fail here.
+        let mut errors: Vec<String> = loaded
+            .warnings
+            .iter()
+            .filter(|w| {
+                matches!(
+                    w,
+                    crate::error::ProjectError::BadJson { .. }
+                        | crate::error::ProjectError::UnsupportedVersion { .. }
+                )
+            })
+            .map(|w| w.to_string())
+            .collect();
+        let mut warnings = Vec::new();
+        for lump in &loaded.lumps {
+            if let ProjectData::StatusBar(sbar) = lump {
+                check_sbardef(sbar, &loaded.assets, has_iwad, &mut errors, &mut warnings);
+            }
+        }
+
+        println!(
+            "{} {}",
+            if errors.is_empty() { "OK  " } else { "FAIL" },
+            input
+        );
+        for lump in &loaded.lumps {
+            println!(
+                "     {} {} ({:?})",
+                lump.standard_lump_name(),
+                lump.version(),
+                lump.target()
+            );
+        }
+        for e in &errors {
+            println!("     error: {}", e);
+        }
+        for w in &warnings {
+            println!("     warning: {}", w);
+        }
+
+        if !errors.is_empty() {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        Err(format!(
+            "{} of {} file(s) failed validation",
+            failed,
+            inputs.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn run_render(
+    input: &str,
+    output: &str,
+    bar: usize,
+    flags: &RenderFlags,
+    iwad: Option<&str>,
+) -> Result<(), String> {
+    let (loaded, _) = load_with_iwad(input, iwad)?;
+
+    let sbar = loaded
+        .lumps
+        .iter()
+        .find_map(|l| l.as_sbar())
+        .ok_or_else(|| format!("'{}' contains no SBARDEF", input))?;
+    if bar >= sbar.data.status_bars.len() {
+        return Err(format!(
+            "status bar {} does not exist ({} defined)",
+            bar,
+            sbar.data.status_bars.len()
+        ));
+    }
+
+    let mut state = PreviewState::default();
+    state.sim.engine.widescreen_mode = flags.widescreen;
+
+    let options = OffscreenOptions {
+        widescreen: flags.widescreen,
+        scale: flags.scale,
+        world: !flags.transparent,
+        ..Default::default()
+    };
+    offscreen::save_statusbar_png(
+        &loaded.assets,
+        sbar,
+        bar,
+        &state,
+        &options,
+        Path::new(output),
+    )
+    .map_err(|e| format!("failed to write '{}': {}", output, e))?;
+    println!("Wrote {}", output);
+    Ok(())
+}
+
+/// Checks an SBARDEF for dangling font references and, when graphics are
+/// available, for patches that don't exist in the project or IWAD.
+fn check_sbardef(
+    file: &SBarDefFile,
+    assets: &AssetStore,
+    check_patches: bool,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    fn walk(
+        file: &SBarDefFile,
+        elements: &[ElementWrapper],
+        bar_idx: usize,
+        patches: &mut Vec<String>,
+        errors: &mut Vec<String>,
+    ) {
+        for el in elements {
+            let data = &file.data;
+            match &el.data {
+                Element::Number(n) | Element::Percent(n)
+                    if !data
+                        .number_fonts
+                        .iter()
+                        .any(|f| f.name.eq_ignore_ascii_case(&n.font)) =>
+                {
+                    errors.push(format!(
+                        "status bar {}: unknown number font '{}'",
+                        bar_idx, n.font
+                    ));
+                }
+                Element::String(s)
+                    if !data
+                        .hud_fonts
+                        .iter()
+                        .any(|f| f.name.eq_ignore_ascii_case(&s.font)) =>
+                {
+                    errors.push(format!(
+                        "status bar {}: unknown hud font '{}'",
+                        bar_idx, s.font
+                    ));
+                }
+                Element::Graphic(g) => patches.push(g.patch.clone()),
+                Element::Animation(a) => patches.extend(a.frames.iter().map(|f| f.lump.clone())),
+                _ => {}
+            }
+            walk(file, &el.get_common().children, bar_idx, patches, errors);
+        }
+    }
+
+    let mut patches = Vec::new();
+    for (idx, bar) in file.data.status_bars.iter().enumerate() {
+        walk(file, &bar.children, idx, &mut patches, errors);
+    }
+
+    if check_patches {
+        patches.sort();
+        patches.dedup();
+        for patch in patches {
+            if !assets.has_image(&AssetId::new(&patch)) {
+                warnings.push(format!("patch '{}' not found in project or IWAD", patch));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert!(!is_command(&args(&["SBARDEF.json"])));
+        assert!(is_command(&args(&["export", "in.pk3", "--wad", "out.wad"])));
+
+        assert_eq!(
+            parse(&args(&["export", "in.pk3", "--wad", "out.wad"])),
+            Ok(Command::Export {
+                input: "in.pk3".to_string(),
+                wad: Some("out.wad".to_string()),
+                pk3: None,
+                iwad: None,
+            })
+        );
+        assert_eq!(
+            parse(&args(&["convert", "in.json", "--target", "basic"])),
+            Ok(Command::Convert {
+                input: "in.json".to_string(),
+                target: ExportTarget::Basic,
+                output: None,
+                iwad: None,
+            })
+        );
+
+        assert!(parse(&args(&["export", "in.pk3"])).is_err());
+        assert!(parse(&args(&["convert", "in.json"])).is_err());
+        assert!(parse(&args(&["validate"])).is_err());
+        assert!(parse(&args(&["validate", "a.json", "--bogus"])).is_err());
+
+        assert_eq!(
+            parse(&args(&[
+                "render",
+                "in.wad",
+                "-o",
+                "bar.png",
+                "--bar",
+                "1",
+                "--scale",
+                "2",
+                "--widescreen",
+            ])),
+            Ok(Command::Render {
+                input: "in.wad".to_string(),
+                output: "bar.png".to_string(),
+                bar: 1,
+                flags: RenderFlags {
+                    scale: 2,
+                    widescreen: true,
+                    transparent: false,
+                },
+                iwad: None,
+            })
+        );
+        assert!(parse(&args(&["render", "in.wad"])).is_err());
+        assert!(parse(&args(&["render", "in.wad", "-o", "x.png", "--bar", "x"])).is_err());
+    }
+}
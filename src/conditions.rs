@@ -1,6 +1,8 @@
 use crate::assets::{AssetId, AssetStore};
+use crate::constants::{DOOM_W, DOOM_W_WIDE};
+use crate::models::interlevel::InterlevelCondition;
 use crate::models::sbardef::ConditionDef;
-use crate::state::PreviewState;
+use crate::state::{IntermissionState, PreviewState};
 
 /// Resolves a set of SBARDEF conditions against the current simulated game state.
 ///
@@ -62,7 +64,7 @@ fn check_single(condition: &ConditionDef, state: &PreviewState, assets: &AssetSt
             let patch_name = condition.param_string.as_deref().unwrap_or("");
 
             let id = AssetId::new(patch_name);
-            let exists = assets.textures.contains_key(&id);
+            let exists = assets.has_image(&id);
 
             if condition.condition == PatchEmpty {
                 !exists
@@ -402,3 +404,101 @@ fn check_powerup_condition(condition: &ConditionDef, state: &PreviewState) -> bo
         _ => true,
     }
 }
+
+/// Resolves a set of INTERLEVEL conditions against the mock intermission state.
+///
+/// `patch_width` is the width of the anim's current frame, for `FitsInFrame`;
+/// layers pass `None` and always fit. Returns `true` if all conditions are met.
+pub fn resolve_interlevel(
+    conditions: &[InterlevelCondition],
+    state: &IntermissionState,
+    patch_width: Option<f32>,
+    widescreen: bool,
+) -> bool {
+    conditions
+        .iter()
+        .all(|c| check_interlevel(c, state, patch_width, widescreen))
+}
+
+/// Evaluates a single INTERLEVEL condition.
+fn check_interlevel(
+    condition: &InterlevelCondition,
+    state: &IntermissionState,
+    patch_width: Option<f32>,
+    widescreen: bool,
+) -> bool {
+    use crate::models::interlevel::InterlevelConditionType::*;
+    let map = state.shown_map();
+    match condition.condition {
+        None => true,
+        CurrMapGreater => map > condition.param,
+        CurrMapEqual => map == condition.param,
+        MapVisited => state.visited.contains(&condition.param),
+        CurrMapNotSecret => !state.secret_maps.contains(&map),
+        AnySecretVisited => state.visited.iter().any(|m| state.secret_maps.contains(m)),
+        OnFinishedScreen => !state.entering,
+        OnEnteringScreen => state.entering,
+        FitsInFrame => {
+            let screen_w = if widescreen { DOOM_W_WIDE } else { DOOM_W };
+            patch_width.is_none_or(|w| w <= screen_w)
+        }
+        // Can't be checked here, so it doesn't hide anything in the preview.
+        Unknown(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::interlevel::InterlevelConditionType;
+
+    fn cond(condition: InterlevelConditionType, param: i32) -> InterlevelCondition {
+        InterlevelCondition { condition, param }
+    }
+
+    #[test]
+    fn test_interlevel_conditions() {
+        use InterlevelConditionType::*;
+        let mut state = IntermissionState::default();
+        state.go_to_map(3);
+        assert_eq!(state.visited.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        let check = |c: &[InterlevelCondition], state: &IntermissionState| {
+            resolve_interlevel(c, state, Option::None, false)
+        };
+        assert!(check(&[], &state));
+        assert!(check(
+            &[cond(CurrMapEqual, 3), cond(OnFinishedScreen, 0)],
+            &state
+        ));
+        assert!(!check(&[cond(CurrMapGreater, 3)], &state));
+        assert!(check(&[cond(MapVisited, 2)], &state));
+        assert!(!check(&[cond(AnySecretVisited, 0)], &state));
+
+        // The entering screen talks about the next map.
+        state.entering = true;
+        assert!(check(
+            &[cond(CurrMapGreater, 3), cond(OnEnteringScreen, 0)],
+            &state
+        ));
+
+        state.go_to_map(9);
+        state.entering = false;
+        assert!(check(&[cond(AnySecretVisited, 0)], &state));
+        assert!(!check(&[cond(CurrMapNotSecret, 0)], &state));
+
+        // Leaving the secret map keeps it visited.
+        state.go_to_map(10);
+        assert_eq!(state.current_map, 9);
+        state.episode_length = 10;
+        state.go_to_map(10);
+        assert!(check(
+            &[cond(AnySecretVisited, 0), cond(CurrMapNotSecret, 0)],
+            &state
+        ));
+
+        let fits = [cond(FitsInFrame, 0)];
+        assert!(!resolve_interlevel(&fits, &state, Some(400.0), false));
+        assert!(resolve_interlevel(&fits, &state, Some(400.0), true));
+    }
+}
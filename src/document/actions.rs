@@ -1,4 +1,5 @@
 use crate::models::sbardef::{ElementWrapper, StatusBarLayout};
+use crate::wad::legacy::CompositeTexture;
 
 #[derive(Debug, Clone)]
 pub enum DocumentAction {
@@ -12,6 +13,12 @@ pub enum DocumentAction {
     Sky(SkyAction),
     /// Specialized UMAPINFO actions.
     Umap(UmapAction),
+    /// Specialized FINALE actions.
+    Finale(FinaleAction),
+    /// Specialized INTERLEVEL actions.
+    Interlevel(InterlevelAction),
+    /// Edits to the project's composite textures, whichever lump is active.
+    Texture(TextureAction),
 }
 
 #[derive(Debug, Clone)]
@@ -65,5 +72,49 @@ pub enum SkyAction {
 #[derive(Debug, Clone)]
 pub enum UmapAction {
     AddMap,
+    Select(usize),
     DeleteMap(usize),
 }
+
+#[derive(Debug, Clone)]
+pub enum FinaleAction {
+    Add,
+    Delete(usize),
+    Move {
+        source: usize,
+        target: usize,
+    },
+    Duplicate(usize),
+    /// Appends a placeholder frame to the death frames of cast member
+    /// `member` if `dead`, otherwise to its alive frames.
+    AddFrame {
+        member: usize,
+        dead: bool,
+    },
+    /// Removes every death or alive frame of cast member `member`.
+    ClearFrames {
+        member: usize,
+        dead: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum InterlevelAction {
+    /// Appends a new node under `parent`: a layer for the root, an anim for
+    /// a layer, or a frame for an anim.
+    Add { parent: Vec<usize> },
+    /// Removes every frame of the anim at `anim`, dropping any of them from
+    /// the selection.
+    ClearFrames { anim: Vec<usize> },
+}
+
+#[derive(Debug, Clone)]
+pub enum TextureAction {
+    Add(CompositeTexture),
+    Remove(usize),
+    /// Replaces the texture at `index` with an edited copy.
+    Update {
+        index: usize,
+        texture: CompositeTexture,
+    },
+}
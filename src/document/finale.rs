@@ -0,0 +1,147 @@
+use crate::document::actions::FinaleAction;
+use crate::models::finale::{CastFrame, CastMember, FinaleDefFile};
+use std::collections::HashSet;
+
+/// Processes cast list mutations for FINALE projects. Cast members are
+/// selected by their index in the roll call.
+pub fn execute_finale_action(
+    file: &mut FinaleDefFile,
+    action: FinaleAction,
+    selection: &mut HashSet<Vec<usize>>,
+) {
+    let members = &mut file
+        .data
+        .cast_roll_call
+        .get_or_insert_with(Default::default)
+        .castmembers;
+
+    match action {
+        FinaleAction::Add => {
+            members.push(CastMember {
+                name: format!("Cast Member {}", members.len() + 1),
+                ..Default::default()
+            });
+            selection.clear();
+            selection.insert(vec![members.len() - 1]);
+        }
+        FinaleAction::Delete(idx) => {
+            if idx < members.len() {
+                members.remove(idx);
+                selection.clear();
+            }
+        }
+        FinaleAction::Move { source, target } => {
+            if source < members.len() {
+                let member = members.remove(source);
+                let mut final_target = target;
+                if source < target {
+                    final_target = final_target.saturating_sub(1);
+                }
+                let safe_target = final_target.min(members.len());
+                members.insert(safe_target, member);
+                selection.clear();
+                selection.insert(vec![safe_target]);
+            }
+        }
+        FinaleAction::Duplicate(idx) => {
+            if let Some(member) = members.get(idx) {
+                let mut new_member = member.clone();
+                new_member.name = format!("{} (Copy)", new_member.name);
+                members.insert(idx + 1, new_member);
+                selection.clear();
+                selection.insert(vec![idx + 1]);
+            }
+        }
+        FinaleAction::AddFrame { member, dead } => {
+            if let Some(member) = members.get_mut(member) {
+                let frames = if dead {
+                    &mut member.dead
+                } else {
+                    &mut member.alive
+                };
+                frames.push(CastFrame::new("HICACOCO".to_string()));
+            }
+        }
+        FinaleAction::ClearFrames { member, dead } => {
+            if let Some(member) = members.get_mut(member) {
+                let frames = if dead {
+                    &mut member.dead
+                } else {
+                    &mut member.alive
+                };
+                frames.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_member_actions() {
+        let mut file = FinaleDefFile::new_empty();
+        let mut selection = HashSet::new();
+
+        execute_finale_action(&mut file, FinaleAction::Add, &mut selection);
+        execute_finale_action(&mut file, FinaleAction::Add, &mut selection);
+        execute_finale_action(&mut file, FinaleAction::Duplicate(0), &mut selection);
+        execute_finale_action(
+            &mut file,
+            FinaleAction::Move {
+                source: 2,
+                target: 0,
+            },
+            &mut selection,
+        );
+
+        let names: Vec<_> = file
+            .data
+            .cast_roll_call
+            .as_ref()
+            .unwrap()
+            .castmembers
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["Cast Member 2", "Cast Member 1", "Cast Member 1 (Copy)"]
+        );
+        assert_eq!(selection, HashSet::from([vec![0]]));
+
+        execute_finale_action(&mut file, FinaleAction::Delete(0), &mut selection);
+        assert_eq!(
+            file.data.cast_roll_call.as_ref().unwrap().castmembers.len(),
+            2
+        );
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_cast_frame_actions() {
+        let mut file = FinaleDefFile::new_empty();
+        let mut selection = HashSet::new();
+        execute_finale_action(&mut file, FinaleAction::Add, &mut selection);
+
+        let add = |dead| FinaleAction::AddFrame { member: 0, dead };
+        execute_finale_action(&mut file, add(false), &mut selection);
+        execute_finale_action(&mut file, add(true), &mut selection);
+        execute_finale_action(&mut file, add(true), &mut selection);
+        execute_finale_action(
+            &mut file,
+            FinaleAction::ClearFrames {
+                member: 0,
+                dead: false,
+            },
+            &mut selection,
+        );
+
+        let member = &file.data.cast_roll_call.unwrap().castmembers[0];
+        assert!(member.alive.is_empty());
+        assert_eq!(member.dead.len(), 2);
+        assert_eq!(member.dead[0].image, "HICACOCO");
+        assert_eq!(selection, HashSet::from([vec![0]]));
+    }
+}
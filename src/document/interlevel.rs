@@ -0,0 +1,417 @@
+use crate::document::actions::{InterlevelAction, TreeAction};
+use crate::models::interlevel::{
+    FrameType, InterlevelAnim, InterlevelDefFile, InterlevelFrame, InterlevelLayer,
+};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// One node of the INTERLEVEL tree, lifted out of its list.
+enum Node {
+    Layer(InterlevelLayer),
+    Anim(InterlevelAnim),
+    Frame(InterlevelFrame),
+}
+
+/// The list of siblings a tree path's parent points at. Paths are
+/// `[layer]`, `[layer, anim]` and `[layer, anim, frame]`.
+enum Siblings<'a> {
+    Layers(&'a mut Vec<InterlevelLayer>),
+    Anims(&'a mut Vec<InterlevelAnim>),
+    Frames(&'a mut Vec<InterlevelFrame>),
+}
+
+impl Siblings<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Siblings::Layers(l) => l.len(),
+            Siblings::Anims(l) => l.len(),
+            Siblings::Frames(l) => l.len(),
+        }
+    }
+
+    fn remove(&mut self, idx: usize) -> Option<Node> {
+        if idx >= self.len() {
+            return None;
+        }
+        Some(match self {
+            Siblings::Layers(l) => Node::Layer(l.remove(idx)),
+            Siblings::Anims(l) => Node::Anim(l.remove(idx)),
+            Siblings::Frames(l) => Node::Frame(l.remove(idx)),
+        })
+    }
+
+    fn clone_at(&self, idx: usize) -> Option<Node> {
+        match self {
+            Siblings::Layers(l) => l.get(idx).cloned().map(Node::Layer),
+            Siblings::Anims(l) => l.get(idx).cloned().map(Node::Anim),
+            Siblings::Frames(l) => l.get(idx).cloned().map(Node::Frame),
+        }
+    }
+
+    /// Inserts a node, clamping the index. Nodes of the wrong depth are
+    /// dropped, so this returns where the node ended up, if anywhere.
+    fn insert(&mut self, idx: usize, node: Node) -> Option<usize> {
+        let idx = idx.min(self.len());
+        match (self, node) {
+            (Siblings::Layers(l), Node::Layer(n)) => l.insert(idx, n),
+            (Siblings::Anims(l), Node::Anim(n)) => l.insert(idx, n),
+            (Siblings::Frames(l), Node::Frame(n)) => l.insert(idx, n),
+            _ => return None,
+        }
+        Some(idx)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        match self {
+            Siblings::Layers(l) => l.swap(a, b),
+            Siblings::Anims(l) => l.swap(a, b),
+            Siblings::Frames(l) => l.swap(a, b),
+        }
+    }
+}
+
+fn siblings_mut<'a>(file: &'a mut InterlevelDefFile, parent: &[usize]) -> Option<Siblings<'a>> {
+    let layers = &mut file.data.layers;
+    match *parent {
+        [] => Some(Siblings::Layers(layers)),
+        [l] => Some(Siblings::Anims(&mut layers.get_mut(l)?.anims)),
+        [l, a] => Some(Siblings::Frames(
+            &mut layers.get_mut(l)?.anims.get_mut(a)?.frames,
+        )),
+        _ => None,
+    }
+}
+
+/// Processes the shared tree actions for INTERLEVEL layers, anims and frames.
+/// Actions that carry SBARDEF elements (add, paste, group) don't apply here.
+pub fn execute_tree_action(
+    file: &mut InterlevelDefFile,
+    action: TreeAction,
+    selection: &mut HashSet<Vec<usize>>,
+) {
+    match action {
+        TreeAction::Delete(paths) => op_delete(file, selection, paths),
+        TreeAction::Duplicate(paths) => op_duplicate(file, selection, paths),
+        TreeAction::MoveUp(path) => op_swap(file, selection, path, -1),
+        TreeAction::MoveDown(path) => op_swap(file, selection, path, 1),
+        TreeAction::MoveSelection {
+            sources,
+            target_parent,
+            insert_idx,
+        } => op_move_selection(file, selection, sources, target_parent, insert_idx),
+        TreeAction::Translate { paths, dx, dy } => op_translate(file, paths, dx, dy),
+        TreeAction::Select(paths) => {
+            selection.clear();
+            selection.extend(paths);
+        }
+        TreeAction::ToggleSelection(paths) => {
+            for path in paths {
+                if !selection.remove(&path) {
+                    selection.insert(path);
+                }
+            }
+        }
+        TreeAction::Add { .. } | TreeAction::Paste { .. } | TreeAction::Group(_) => {}
+    }
+}
+
+/// Processes INTERLEVEL-specific mutations.
+pub fn execute_interlevel_action(
+    file: &mut InterlevelDefFile,
+    action: InterlevelAction,
+    selection: &mut HashSet<Vec<usize>>,
+) {
+    match action {
+        InterlevelAction::Add { parent } => {
+            let node = match parent.len() {
+                0 => Node::Layer(InterlevelLayer::default()),
+                1 => Node::Anim(InterlevelAnim::default()),
+                _ => Node::Frame(InterlevelFrame {
+                    image: "HICACOCO".to_string(),
+                    frame_type: FrameType::FIXED,
+                    duration: 1.0,
+                    ..Default::default()
+                }),
+            };
+            let inserted = siblings_mut(file, &parent).and_then(|mut s| s.insert(usize::MAX, node));
+            if let Some(idx) = inserted {
+                let mut path = parent;
+                path.push(idx);
+                selection.clear();
+                selection.insert(path);
+            }
+        }
+        InterlevelAction::ClearFrames { anim } => {
+            if let Some(Siblings::Frames(frames)) = siblings_mut(file, &anim) {
+                frames.clear();
+                selection.retain(|path| !(path.len() > anim.len() && path.starts_with(&anim)));
+            }
+        }
+    }
+}
+
+fn split(path: &[usize]) -> Option<(&[usize], usize)> {
+    let (last, parent) = path.split_last()?;
+    Some((parent, *last))
+}
+
+/// Sorts deepest-first, then back to front, so removals don't shift the
+/// indices of paths still waiting to be removed.
+fn sort_paths_for_removal(paths: &mut [Vec<usize>]) {
+    paths.sort_by(|a, b| match b.len().cmp(&a.len()) {
+        Ordering::Equal => b.cmp(a),
+        ord => ord,
+    });
+}
+
+fn op_delete(
+    file: &mut InterlevelDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    mut paths: Vec<Vec<usize>>,
+) {
+    sort_paths_for_removal(&mut paths);
+    selection.clear();
+    for path in paths {
+        let list = split(&path).and_then(|(parent, idx)| Some((siblings_mut(file, parent)?, idx)));
+        if let Some((mut list, idx)) = list {
+            list.remove(idx);
+        }
+    }
+}
+
+fn op_duplicate(
+    file: &mut InterlevelDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    mut paths: Vec<Vec<usize>>,
+) {
+    sort_paths_for_removal(&mut paths);
+    selection.clear();
+    for path in paths {
+        let Some((parent, idx)) = split(&path) else {
+            continue;
+        };
+        if let Some(mut list) = siblings_mut(file, parent) {
+            let inserted = list
+                .clone_at(idx)
+                .and_then(|node| list.insert(idx + 1, node));
+            if let Some(new_idx) = inserted {
+                // Copies made earlier sit further down the same list.
+                let depth = parent.len();
+                *selection = selection
+                    .drain()
+                    .map(|mut p| {
+                        if p.starts_with(parent) && p.len() > depth && p[depth] >= new_idx {
+                            p[depth] += 1;
+                        }
+                        p
+                    })
+                    .collect();
+                let mut new_path = parent.to_vec();
+                new_path.push(new_idx);
+                selection.insert(new_path);
+            }
+        }
+    }
+}
+
+fn op_swap(
+    file: &mut InterlevelDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    path: Vec<usize>,
+    direction: isize,
+) {
+    let Some((parent, idx)) = split(&path) else {
+        return;
+    };
+    let Some(mut list) = siblings_mut(file, parent) else {
+        return;
+    };
+    let target = idx
+        .checked_add_signed(direction)
+        .filter(|t| *t < list.len());
+    if let Some(target) = target.filter(|_| idx < list.len()) {
+        list.swap(idx, target);
+        let mut new_path = parent.to_vec();
+        new_path.push(target);
+        selection.clear();
+        selection.insert(new_path);
+    }
+}
+
+/// Moves nodes to `insert_idx` under `target_parent`. Only nodes that fit at
+/// that depth move, so frames can be moved between anims and anims between
+/// layers, but a frame can't become an anim.
+fn op_move_selection(
+    file: &mut InterlevelDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    sources: Vec<Vec<usize>>,
+    mut target_parent: Vec<usize>,
+    mut insert_idx: usize,
+) {
+    let depth = target_parent.len() + 1;
+    let mut to_remove: Vec<_> = sources.into_iter().filter(|p| p.len() == depth).collect();
+    if to_remove.is_empty() {
+        return;
+    }
+    sort_paths_for_removal(&mut to_remove);
+
+    let mut moved = Vec::new();
+    for src in to_remove {
+        let Some((src_parent, src_idx)) = split(&src) else {
+            continue;
+        };
+
+        // Removing an earlier sibling of one of the target's ancestors
+        // shifts the target itself.
+        let shared = src_parent.len();
+        if target_parent.starts_with(src_parent)
+            && shared < target_parent.len()
+            && src_idx < target_parent[shared]
+        {
+            target_parent[shared] -= 1;
+        }
+        if src_parent == target_parent.as_slice() && src_idx < insert_idx {
+            insert_idx -= 1;
+        }
+
+        if let Some(node) = siblings_mut(file, src_parent).and_then(|mut l| l.remove(src_idx)) {
+            moved.push(node);
+        }
+    }
+    moved.reverse();
+
+    if let Some(mut list) = siblings_mut(file, &target_parent) {
+        selection.clear();
+        let mut at = insert_idx;
+        for node in moved {
+            if let Some(idx) = list.insert(at, node) {
+                let mut path = target_parent.clone();
+                path.push(idx);
+                selection.insert(path);
+                at = idx + 1;
+            }
+        }
+    }
+}
+
+/// Moves anims around the screen. Frames carry their anim along.
+fn op_translate(file: &mut InterlevelDefFile, paths: Vec<Vec<usize>>, dx: i32, dy: i32) {
+    let anims: HashSet<(usize, usize)> = paths
+        .iter()
+        .filter_map(|p| match p.as_slice() {
+            [l, a, ..] => Some((*l, *a)),
+            _ => None,
+        })
+        .collect();
+
+    for (l, a) in anims {
+        let anim = file
+            .data
+            .layers
+            .get_mut(l)
+            .and_then(|layer| layer.anims.get_mut(a));
+        if let Some(anim) = anim {
+            anim.x += dx;
+            anim.y += dy;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_names(file: &InterlevelDefFile, l: usize, a: usize) -> Vec<String> {
+        file.data.layers[l].anims[a]
+            .frames
+            .iter()
+            .map(|f| f.image.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_interlevel_tree_actions() {
+        let mut file = InterlevelDefFile::new_empty();
+        let mut selection = HashSet::new();
+        let add = |file: &mut InterlevelDefFile, sel: &mut HashSet<_>, parent: Vec<usize>| {
+            execute_interlevel_action(file, InterlevelAction::Add { parent }, sel);
+        };
+
+        add(&mut file, &mut selection, vec![]);
+        add(&mut file, &mut selection, vec![0]);
+        add(&mut file, &mut selection, vec![0]);
+        for _ in 0..3 {
+            add(&mut file, &mut selection, vec![0, 0]);
+        }
+        assert!(selection.contains(&vec![0, 0, 2]));
+        for (i, frame) in file.data.layers[0].anims[0].frames.iter_mut().enumerate() {
+            frame.image = format!("WIA0000{}", i);
+        }
+
+        // Move the last frame to the front of the same anim.
+        execute_tree_action(
+            &mut file,
+            TreeAction::MoveSelection {
+                sources: vec![vec![0, 0, 2]],
+                target_parent: vec![0, 0],
+                insert_idx: 0,
+            },
+            &mut selection,
+        );
+        assert_eq!(
+            frame_names(&file, 0, 0),
+            ["WIA00002", "WIA00000", "WIA00001"]
+        );
+
+        // Frames can move into another anim, but not up to anim depth.
+        execute_tree_action(
+            &mut file,
+            TreeAction::MoveSelection {
+                sources: vec![vec![0, 0, 0], vec![0, 1]],
+                target_parent: vec![0, 1],
+                insert_idx: 0,
+            },
+            &mut selection,
+        );
+        assert_eq!(frame_names(&file, 0, 1), ["WIA00002"]);
+        assert_eq!(file.data.layers[0].anims.len(), 2);
+        assert!(selection.contains(&vec![0, 1, 0]));
+
+        execute_tree_action(
+            &mut file,
+            TreeAction::Duplicate(vec![vec![0, 1]]),
+            &mut selection,
+        );
+        assert_eq!(file.data.layers[0].anims.len(), 3);
+        assert!(selection.contains(&vec![0, 2]));
+
+        execute_tree_action(
+            &mut file,
+            TreeAction::Translate {
+                paths: vec![vec![0, 2], vec![0, 2, 0]],
+                dx: 5,
+                dy: -3,
+            },
+            &mut selection,
+        );
+        let anim = &file.data.layers[0].anims[2];
+        assert_eq!((anim.x, anim.y), (5, -3));
+
+        execute_tree_action(
+            &mut file,
+            TreeAction::Delete(vec![vec![0, 0], vec![0, 0, 1]]),
+            &mut selection,
+        );
+        assert_eq!(file.data.layers[0].anims.len(), 2);
+        assert_eq!(frame_names(&file, 0, 0), ["WIA00002"]);
+
+        selection.extend([vec![0, 0], vec![0, 0, 0]]);
+        execute_interlevel_action(
+            &mut file,
+            InterlevelAction::ClearFrames { anim: vec![0, 0] },
+            &mut selection,
+        );
+        assert!(frame_names(&file, 0, 0).is_empty());
+        assert_eq!(selection, HashSet::from([vec![0, 0]]));
+    }
+}
@@ -1,12 +1,16 @@
 pub mod actions;
+mod finale;
+mod interlevel;
 mod layout;
 mod sky;
+mod texture;
 mod tree;
 mod umapinfo;
 
 use crate::app::ProjectMode;
 use crate::history::HistoryManager;
 use crate::models::ProjectData;
+use crate::wad::legacy::CompositeTexture;
 use std::collections::HashSet;
 
 /// Manages a collection of ID24 lumps, their selection, and its modification history.
@@ -15,6 +19,8 @@ pub struct ProjectDocument {
     pub lumps: Vec<ProjectData>,
     /// All lumps contained in this project that Cacoco passes through (MAPs and whatnot)
     pub passthrough_lumps: Vec<crate::wad::RawLump>,
+    /// Multi-patch textures the project defines, written into TEXTURE1/2.
+    pub composites: Vec<CompositeTexture>,
     /// The filesystem path where this document is saved.
     pub path: Option<String>,
     /// The set of tree-paths currently selected by the user.
@@ -37,6 +43,7 @@ impl ProjectDocument {
         Self {
             lumps: vec![initial_lump],
             passthrough_lumps: passthrough,
+            composites: Vec::new(),
             path,
             selection: HashSet::new(),
             selection_pivot: None,
@@ -64,7 +71,8 @@ impl ProjectDocument {
         for action in actions {
             match action {
                 DocumentAction::UndoSnapshot => {
-                    self.history.take_snapshot(&self.lumps, &self.selection);
+                    self.history
+                        .take_snapshot(&self.lumps, &self.composites, &self.selection);
                 }
                 _ => {
                     self.dirty = true;
@@ -80,11 +88,15 @@ impl ProjectDocument {
                                 layout::execute_layout_action(sbar, sbar_act, selection_ref);
                             }
                         }
-                        DocumentAction::Tree(tree_act) => {
-                            if let Some(ProjectData::StatusBar(sbar)) = active_lump {
+                        DocumentAction::Tree(tree_act) => match active_lump {
+                            Some(ProjectData::StatusBar(sbar)) => {
                                 tree::execute_tree_action(sbar, tree_act, selection_ref);
                             }
-                        }
+                            Some(ProjectData::Interlevel(inter)) => {
+                                interlevel::execute_tree_action(inter, tree_act, selection_ref);
+                            }
+                            _ => {}
+                        },
                         DocumentAction::Sky(sky_act) => {
                             if let Some(ProjectData::Sky(sky_file)) = active_lump {
                                 sky::execute_sky_action(sky_file, sky_act, selection_ref);
@@ -95,6 +107,23 @@ impl ProjectDocument {
                                 umapinfo::execute_umapinfo_action(info, umap_act, selection_ref);
                             }
                         }
+                        DocumentAction::Finale(finale_act) => {
+                            if let Some(ProjectData::Finale(finale)) = active_lump {
+                                finale::execute_finale_action(finale, finale_act, selection_ref);
+                            }
+                        }
+                        DocumentAction::Interlevel(inter_act) => {
+                            if let Some(ProjectData::Interlevel(inter)) = active_lump {
+                                interlevel::execute_interlevel_action(
+                                    inter,
+                                    inter_act,
+                                    selection_ref,
+                                );
+                            }
+                        }
+                        DocumentAction::Texture(texture_act) => {
+                            texture::execute_texture_action(&mut self.composites, texture_act);
+                        }
                         _ => {}
                     }
                 }
@@ -108,13 +137,15 @@ impl ProjectDocument {
 
     /// Reverts the document to the previous state in history.
     pub fn undo(&mut self) {
-        self.history.undo(&mut self.lumps, &mut self.selection);
+        self.history
+            .undo(&mut self.lumps, &mut self.composites, &mut self.selection);
         self.dirty = true;
     }
 
     /// Re-applies a state that was recently undone.
     pub fn redo(&mut self) {
-        self.history.redo(&mut self.lumps, &mut self.selection);
+        self.history
+            .redo(&mut self.lumps, &mut self.composites, &mut self.selection);
         self.dirty = true;
     }
 }
@@ -0,0 +1,59 @@
+use crate::document::actions::TextureAction;
+use crate::wad::legacy::CompositeTexture;
+
+pub fn execute_texture_action(composites: &mut Vec<CompositeTexture>, action: TextureAction) {
+    match action {
+        TextureAction::Add(texture) => composites.push(texture),
+        TextureAction::Remove(idx) => {
+            if idx < composites.len() {
+                composites.remove(idx);
+            }
+        }
+        TextureAction::Update { index, texture } => {
+            if let Some(slot) = composites.get_mut(index) {
+                *slot = texture;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ProjectMode;
+    use crate::document::{DocumentAction, ProjectDocument};
+    use crate::models::{ProjectData, skydefs::SkyDefsFile};
+
+    #[test]
+    fn test_composite_edits_undo() {
+        let sky = ProjectData::Sky(SkyDefsFile::new_empty());
+        let mut doc = ProjectDocument::new(sky, Vec::new(), None);
+        let texture = |width| CompositeTexture {
+            width,
+            ..CompositeTexture::single("WALL9", 64, 64)
+        };
+
+        let edits = [
+            TextureAction::Add(texture(64)),
+            TextureAction::Update {
+                index: 0,
+                texture: texture(128),
+            },
+        ];
+        for edit in edits {
+            doc.execute_actions(
+                vec![DocumentAction::UndoSnapshot, DocumentAction::Texture(edit)],
+                ProjectMode::SkyDefs,
+            );
+        }
+        assert_eq!(doc.composites, [texture(128)]);
+        assert!(doc.dirty);
+
+        doc.undo();
+        assert_eq!(doc.composites, [texture(64)]);
+        doc.undo();
+        assert!(doc.composites.is_empty());
+        doc.redo();
+        assert_eq!(doc.composites, [texture(64)]);
+    }
+}
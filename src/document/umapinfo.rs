@@ -14,11 +14,16 @@ pub fn execute_umapinfo_action(
             file.data.maps.push(MapEntry {
                 mapname: format!("MAP{:02}", next_num),
                 fields: vec![UmapField::LevelName("New Level".to_string())],
+                ..Default::default()
             });
             let new_idx = file.data.maps.len() - 1;
             selection.clear();
             selection.insert(vec![new_idx]);
         }
+        UmapAction::Select(idx) => {
+            selection.clear();
+            selection.insert(vec![idx]);
+        }
         UmapAction::DeleteMap(idx) => {
             if idx < file.data.maps.len() {
                 file.data.maps.remove(idx);
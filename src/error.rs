@@ -0,0 +1,205 @@
+//! Errors raised while opening or saving a project, with enough detail for
+//! the UI to tell the user what went wrong and where.
+
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ProjectError {
+    NotFound(PathBuf),
+    /// The file exists but couldn't be read or created.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A WAD whose header or directory doesn't hold together.
+    MalformedWad {
+        path: PathBuf,
+        reason: String,
+    },
+    /// A project lump that isn't valid JSON for its type.
+    BadJson {
+        lump: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    Zip {
+        path: PathBuf,
+        source: zip::result::ZipError,
+    },
+    /// A lump written for a newer spec than Cacoco understands.
+    UnsupportedVersion {
+        lump: String,
+        version: String,
+    },
+    /// An archive with nothing Cacoco can edit in it.
+    NoProjectLumps(PathBuf),
+    /// Building the output failed part way through.
+    Write {
+        path: PathBuf,
+        reason: String,
+    },
+    Cancelled,
+}
+
+impl ProjectError {
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            return Self::NotFound(path.into());
+        }
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn bad_json(lump: &str, e: &serde_json::Error) -> Self {
+        // serde_json appends the position, which gets its own fields here.
+        let message = e.to_string();
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        Self::BadJson {
+            lump: lump.to_string(),
+            line: e.line(),
+            column: e.column(),
+            message: message
+                .strip_suffix(&position)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+
+    /// A short heading for the error panel.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "File Not Found",
+            Self::Io { .. } => "Could Not Access File",
+            Self::MalformedWad { .. } => "Malformed WAD",
+            Self::BadJson { .. } => "Invalid JSON",
+            Self::Zip { .. } => "Broken Archive",
+            Self::UnsupportedVersion { .. } => "Unsupported Version",
+            Self::NoProjectLumps(_) => "Nothing To Edit",
+            Self::Write { .. } => "Save Failed",
+            Self::Cancelled => "Cancelled",
+        }
+    }
+
+    /// Labelled facts about the error, for the details section of the panel.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::NotFound(path) | Self::NoProjectLumps(path) => {
+                vec![("Path", path.display().to_string())]
+            }
+            Self::Io { path, source } => vec![
+                ("Path", path.display().to_string()),
+                ("Cause", source.to_string()),
+            ],
+            Self::MalformedWad { path, reason } | Self::Write { path, reason } => vec![
+                ("Path", path.display().to_string()),
+                ("Cause", reason.clone()),
+            ],
+            Self::BadJson {
+                lump,
+                line,
+                column,
+                message,
+            } => vec![
+                ("Lump", lump.clone()),
+                ("Line", line.to_string()),
+                ("Column", column.to_string()),
+                ("Cause", message.clone()),
+            ],
+            Self::Zip { path, source } => vec![
+                ("Path", path.display().to_string()),
+                ("Cause", source.to_string()),
+            ],
+            Self::UnsupportedVersion { lump, version } => {
+                vec![("Lump", lump.clone()), ("Version", version.clone())]
+            }
+            Self::Cancelled => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "File not found: {}", path.display()),
+            Self::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Self::MalformedWad { path, reason } => {
+                write!(f, "{} is not a valid WAD: {}", path.display(), reason)
+            }
+            Self::BadJson {
+                lump,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{} at line {}, column {}: {}",
+                lump, line, column, message
+            ),
+            Self::Zip { path, source } => write!(f, "{}: {}", path.display(), source),
+            Self::UnsupportedVersion { lump, version } => {
+                write!(f, "{} version {} is not supported", lump, version)
+            }
+            Self::NoProjectLumps(path) => write!(
+                f,
+                "{} has no SBARDEF, SKYDEFS, INTERLEVEL, FINALE or UMAPINFO lump",
+                path.display()
+            ),
+            Self::Write { path, reason } => {
+                write!(f, "Could not write {}: {}", path.display(), reason)
+            }
+            Self::Cancelled => write!(f, "Loading cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Zip { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProjectData;
+
+    #[test]
+    fn test_errors_carry_their_location() {
+        let bad = ProjectData::parse_lump("SBARDEF", b"{\n  \"type\": \"statusbar\",\n  oops\n}");
+        match bad {
+            Err(ProjectError::BadJson {
+                lump, line, column, ..
+            }) => {
+                assert_eq!(lump, "SBARDEF");
+                assert_eq!((line, column), (3, 3));
+            }
+            other => panic!("expected bad JSON, got {:?}", other.err()),
+        }
+
+        let newer = br#"{ "type": "statusbar", "version": "2.0.0", "data": {} }"#;
+        assert!(matches!(
+            ProjectData::parse_lump("FINALE", newer),
+            Err(ProjectError::UnsupportedVersion { version, .. }) if version == "2.0.0"
+        ));
+
+        let path = std::env::temp_dir().join(format!("cacoco_broken_{}.wad", std::process::id()));
+        let mut wad = b"PWAD".to_vec();
+        wad.extend(4i32.to_le_bytes());
+        wad.extend(4096i32.to_le_bytes());
+        std::fs::write(&path, &wad).unwrap();
+        let loaded = crate::io::load_project_from_path(
+            &path.to_string_lossy(),
+            &crate::io::LoadProgress::default(),
+        );
+        assert!(matches!(loaded, Err(ProjectError::MalformedWad { .. })));
+        std::fs::remove_file(&path).ok();
+    }
+}
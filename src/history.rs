@@ -1,4 +1,5 @@
 use crate::models::{ProjectData, sbardef::ElementWrapper, sbardef::StatusBarLayout};
+use crate::wad::legacy::CompositeTexture;
 use std::collections::{HashSet, VecDeque};
 
 /// The maximum number of undo steps to keep in memory.
@@ -6,11 +7,14 @@ const MAX_UNDO: usize = 50;
 
 /// A point-in-time capture of the entire project's state.
 ///
-/// Captures all lumps (SBARDEF, SKYDEFS, etc.) and the user's current selection.
+/// Captures all lumps (SBARDEF, SKYDEFS, etc.), the composite textures and the
+/// user's current selection.
 #[derive(Clone)]
 pub struct Snapshot {
     /// All lumps present in the project at the time of the snapshot.
     pub lumps: Vec<ProjectData>,
+    /// The project's composite textures.
+    pub composites: Vec<CompositeTexture>,
     /// The set of paths selected by the user.
     pub selection: HashSet<Vec<usize>>,
 }
@@ -43,10 +47,12 @@ impl HistoryManager {
     pub fn take_snapshot(
         &mut self,
         current_lumps: &Vec<ProjectData>,
+        current_composites: &[CompositeTexture],
         current_selection: &HashSet<Vec<usize>>,
     ) {
         self.undo_stack.push_back(Snapshot {
             lumps: current_lumps.clone(),
+            composites: current_composites.to_vec(),
             selection: current_selection.clone(),
         });
 
@@ -61,15 +67,18 @@ impl HistoryManager {
     pub fn undo(
         &mut self,
         current_lumps: &mut Vec<ProjectData>,
+        current_composites: &mut Vec<CompositeTexture>,
         current_selection: &mut HashSet<Vec<usize>>,
     ) {
         if let Some(prev) = self.undo_stack.pop_back() {
             self.redo_stack.push_back(Snapshot {
                 lumps: current_lumps.clone(),
+                composites: current_composites.clone(),
                 selection: current_selection.clone(),
             });
 
             *current_lumps = prev.lumps;
+            *current_composites = prev.composites;
             *current_selection = prev.selection;
         }
     }
@@ -78,15 +87,18 @@ impl HistoryManager {
     pub fn redo(
         &mut self,
         current_lumps: &mut Vec<ProjectData>,
+        current_composites: &mut Vec<CompositeTexture>,
         current_selection: &mut HashSet<Vec<usize>>,
     ) {
         if let Some(next) = self.redo_stack.pop_back() {
             self.undo_stack.push_back(Snapshot {
                 lumps: current_lumps.clone(),
+                composites: current_composites.clone(),
                 selection: current_selection.clone(),
             });
 
             *current_lumps = next.lumps;
+            *current_composites = next.composites;
             *current_selection = next.selection;
         }
     }
@@ -1,21 +1,79 @@
 use crate::assets::AssetStore;
+use crate::error::ProjectError;
 use crate::models::sbardef::ExportTarget;
+use crate::render::tranmap::TRANMAP_SIZE;
+use crate::render::translation;
 use crate::wad;
-use eframe::egui;
+use crate::wad::legacy::CompositeTexture;
+use crate::wad::pk3::{EntryRole, Pk3Layout, Pk3Manifest};
 use rfd::FileDialog;
 use std::env;
 use std::fs;
 use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
 use zip::write::SimpleFileOptions;
 use zip::{CompressionMethod, ZipWriter};
 
 /// Container for a successfully loaded project and its assets.
+#[derive(Default)]
 pub struct LoadedProject {
     pub lumps: Vec<crate::models::ProjectData>,
     pub assets: AssetStore,
     pub passthrough_lumps: Vec<wad::RawLump>,
+    /// Textures the project's own TEXTURE1/2 define beyond the base IWAD's.
+    pub composites: Vec<CompositeTexture>,
+    /// Problems that didn't stop the load, such as a base IWAD that
+    /// couldn't be read.
+    pub warnings: Vec<ProjectError>,
+    /// Whether [`Self::prepare`] read a base IWAD underneath the project.
+    pub has_base: bool,
+}
+
+impl LoadedProject {
+    /// Adds what the project needs besides its own file: the editor's
+    /// built-in graphics, the base IWAD underneath, the textures the
+    /// project's tables define and the tranmaps its lumps use. If the IWAD
+    /// can't be read, why is in `warnings` and `has_base` stays false.
+    pub fn prepare(
+        &mut self,
+        base_wad: Option<&str>,
+        progress: &LoadProgress,
+    ) -> Result<(), ProjectError> {
+        self.assets.load_system_assets();
+
+        if let Some(path) = base_wad {
+            match read_base_wad(path, progress) {
+                Ok(base) => {
+                    self.assets.underlay(base);
+                    self.has_base = true;
+                }
+                Err(ProjectError::Cancelled) => return Err(ProjectError::Cancelled),
+                Err(e) => self.warnings.push(e),
+            }
+        }
+
+        progress.set_stage("Baking textures");
+        progress.check()?;
+        self.composites = wad::legacy::project_composites(&self.passthrough_lumps, &self.assets);
+        self.assets.sync_composites(&self.composites);
+
+        let names: Vec<String> = self.lumps.iter().flat_map(|l| l.tranmap_names()).collect();
+        self.assets.load_referenced_tranmaps(&names);
+        Ok(())
+    }
+}
+
+/// Indexes a base IWAD into a store of its own, to be laid underneath a
+/// project's with [`AssetStore::underlay`].
+pub fn read_base_wad(path: &str, progress: &LoadProgress) -> Result<AssetStore, ProjectError> {
+    progress.set_stage("Reading base IWAD");
+    let wad = wad::MappedWad::open(Path::new(path)).map_err(|e| ProjectError::io(path, e))?;
+    let mut base = AssetStore::default();
+    wad::load_wad_into_store(&wad, &mut base, progress)?;
+    Ok(base)
 }
 
 /// Opens the system file dialog to pick a project file.
@@ -31,12 +89,164 @@ pub fn open_project_dialog() -> Option<String> {
     None
 }
 
+/// Counters a project load reports while it runs, and the flag that stops it.
+#[derive(Default)]
+pub struct LoadProgress {
+    stage: Mutex<&'static str>,
+    lumps: AtomicUsize,
+    graphics: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl LoadProgress {
+    /// What the load is busy with, for the progress modal.
+    pub fn stage(&self) -> &'static str {
+        self.stage.lock().map_or("", |stage| *stage)
+    }
+
+    pub fn set_stage(&self, stage: &'static str) {
+        if let Ok(mut current) = self.stage.lock() {
+            *current = stage;
+        }
+    }
+
+    /// Lumps or archive entries looked at so far.
+    pub fn lumps(&self) -> usize {
+        self.lumps.load(Ordering::Relaxed)
+    }
+
+    /// Graphics indexed or decoded so far.
+    pub fn graphics(&self) -> usize {
+        self.graphics.load(Ordering::Relaxed)
+    }
+
+    pub fn lump_scanned(&self) {
+        self.lumps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn graphic_indexed(&self) {
+        self.graphics.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Fails once the load has been cancelled, so loops can bail out with `?`.
+    pub fn check(&self) -> Result<(), ProjectError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(ProjectError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Where a background load gets its project from.
+pub enum LoadSource {
+    /// A project file on disk.
+    File(String),
+    /// A project started in the editor, from a library template if one
+    /// was picked. Its lumps are already in memory.
+    New(Option<&'static crate::library::Template>),
+    /// A base IWAD on its own, to lay under whatever is open. Its store is
+    /// the result's `assets`.
+    BaseWad(String),
+}
+
+/// A project being loaded on a worker thread.
+pub struct ProjectLoad {
+    pub source: LoadSource,
+    pub progress: Arc<LoadProgress>,
+    result: mpsc::Receiver<Result<LoadedProject, ProjectError>>,
+}
+
+impl ProjectLoad {
+    /// Starts loading `path` in the background, with `base_wad` layered
+    /// underneath it as in [`LoadedProject::prepare`].
+    pub fn spawn(path: &str, base_wad: Option<String>) -> Self {
+        let worker_path = path.to_string();
+        Self::start(LoadSource::File(path.to_string()), move |progress| {
+            progress.set_stage("Reading project");
+            let mut loaded = load_project_from_path(&worker_path, progress)?;
+            loaded.prepare(base_wad.as_deref(), progress)?;
+            Ok(loaded)
+        })
+    }
+
+    /// Prepares a project whose lumps were made in the editor, like
+    /// [`Self::spawn`] does for one read from disk.
+    pub fn spawn_new(
+        mut seed: LoadedProject,
+        template: Option<&'static crate::library::Template>,
+        base_wad: Option<String>,
+    ) -> Self {
+        Self::start(LoadSource::New(template), move |progress| {
+            seed.prepare(base_wad.as_deref(), progress)?;
+            Ok(seed)
+        })
+    }
+
+    /// Reads a base IWAD in the background, for the editor to lay under its
+    /// current assets once done.
+    pub fn spawn_base_wad(path: &str) -> Self {
+        let worker_path = path.to_string();
+        Self::start(LoadSource::BaseWad(path.to_string()), move |progress| {
+            Ok(LoadedProject {
+                assets: read_base_wad(&worker_path, progress)?,
+                has_base: true,
+                ..Default::default()
+            })
+        })
+    }
+
+    fn start(
+        source: LoadSource,
+        job: impl FnOnce(&LoadProgress) -> Result<LoadedProject, ProjectError> + Send + 'static,
+    ) -> Self {
+        let progress = Arc::new(LoadProgress::default());
+        let (sender, result) = mpsc::channel();
+        let worker_progress = progress.clone();
+        std::thread::spawn(move || {
+            sender.send(job(&worker_progress)).ok();
+        });
+        Self {
+            source,
+            progress,
+            result,
+        }
+    }
+
+    /// What's being loaded, for the progress modal: the file's path or the
+    /// template's name.
+    pub fn title(&self) -> &str {
+        match &self.source {
+            LoadSource::File(path) | LoadSource::BaseWad(path) => path,
+            LoadSource::New(Some(template)) => template.name,
+            LoadSource::New(None) => "New project",
+        }
+    }
+
+    /// The outcome, once the worker has finished.
+    pub fn poll(&self) -> Option<Result<LoadedProject, ProjectError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(ProjectError::io(
+                self.title(),
+                std::io::Error::other("the loader thread stopped unexpectedly"),
+            ))),
+        }
+    }
+}
+
 /// Entry point for loading project data from any supported file format.
-pub fn load_project_from_path(ctx: &egui::Context, path_str: &str) -> Option<LoadedProject> {
+pub fn load_project_from_path(
+    path_str: &str,
+    progress: &LoadProgress,
+) -> Result<LoadedProject, ProjectError> {
     let path = PathBuf::from(path_str);
     if !path.exists() {
-        eprintln!("File not found: {}", path_str);
-        return None;
+        return Err(ProjectError::NotFound(path));
     }
 
     let ext = path
@@ -45,20 +255,13 @@ pub fn load_project_from_path(ctx: &egui::Context, path_str: &str) -> Option<Loa
         .unwrap_or("")
         .to_lowercase();
 
-    if ext == "pk3" || ext == "zip" {
-        load_pk3(ctx, &path)
-    } else if ext == "wad" {
-        match wad::load_wad_project(ctx, &path) {
-            Ok(loaded) => Some(loaded),
-            Err(e) => {
-                eprintln!("Failed to load WAD project: {}", e);
-                None
-            }
-        }
-    } else if ext == "json" || ext == "txt" || ext.is_empty() {
-        load_text_file_with_name(&path).or_else(|| load_extensionless_as_wad(ctx, &path))
-    } else {
-        load_text_file_with_name(&path).or_else(|| load_extensionless_as_wad(ctx, &path))
+    match ext.as_str() {
+        "pk3" | "zip" => load_pk3(&path, progress),
+        "wad" => wad::load_wad_project(&path, progress),
+        _ => match load_extensionless_as_wad(&path, progress)? {
+            Some(loaded) => Ok(loaded),
+            None => load_text_file_with_name(&path),
+        },
     }
 }
 
@@ -66,231 +269,268 @@ pub fn load_project_from_path(ctx: &egui::Context, path_str: &str) -> Option<Loa
 /// This enables proper parsing of `UMAPINFO`, `SBARDEF`, `SKYDEFS`, etc. when passed alone
 /// without extensions as standalone files. If you name a `SBARDEF` file in your project
 /// "`UMAPINFO`" I'll personally come to your house and make a mess of your pots and pans.
-fn load_text_file_with_name(path: &PathBuf) -> Option<LoadedProject> {
+fn load_text_file_with_name(path: &Path) -> Result<LoadedProject, ProjectError> {
     let lump_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let content = fs::read(path).map_err(|e| ProjectError::io(path, e))?;
+    let parsed = crate::models::ProjectData::parse_lump(lump_name, &content)?;
 
-    match fs::read_to_string(path) {
-        Ok(content) => {
-            if let Some(parsed) =
-                crate::models::ProjectData::parse_lump(lump_name, content.as_bytes())
-            {
-                return Some(LoadedProject {
-                    lumps: vec![parsed],
-                    assets: AssetStore::default(),
-                    passthrough_lumps: Vec::new(),
-                });
-            }
-
-            match serde_json::from_str::<crate::models::ProjectData>(&content) {
-                Ok(mut parsed_file) => {
-                    parsed_file.set_target(parsed_file.determine_target());
-                    parsed_file.normalize_for_target();
-
-                    Some(LoadedProject {
-                        lumps: vec![parsed_file],
-                        assets: AssetStore::default(),
-                        passthrough_lumps: Vec::new(),
-                    })
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Failed to parse file {} as {}: {}",
-                        path.display(),
-                        lump_name,
-                        e
-                    );
-                    None
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to read file: {}", e);
-            None
-        }
-    }
+    Ok(LoadedProject {
+        lumps: vec![parsed],
+        ..Default::default()
+    })
 }
 
 /// Attempts to load a file without extension as a WAD or PK3/ZIP file
-/// by checking magic bytes (header sniffing)
-fn load_extensionless_as_wad(ctx: &egui::Context, path: &PathBuf) -> Option<LoadedProject> {
-    if let Ok(mut file) = fs::File::open(path) {
-        let mut header = [0u8; 4];
-        if file.read_exact(&mut header).is_ok() {
-            if &header == b"IWAD" || &header == b"PWAD" {
-                eprintln!("Detected WAD file (no extension), attempting to load...");
-                match wad::load_wad_project(ctx, path) {
-                    Ok(loaded) => return Some(loaded),
-                    Err(e) => eprintln!("Failed to load as WAD: {}", e),
-                }
-            }
-            if &header[0..2] == b"PK" {
-                eprintln!("Detected ZIP/PK3 file (no extension), attempting to load...");
-                return load_pk3(ctx, path);
-            }
-        }
+/// by checking magic bytes (header sniffing). Gives `None` if it is neither.
+fn load_extensionless_as_wad(
+    path: &Path,
+    progress: &LoadProgress,
+) -> Result<Option<LoadedProject>, ProjectError> {
+    let mut header = [0u8; 4];
+    let sniffed = fs::File::open(path).and_then(|mut file| file.read_exact(&mut header));
+    if sniffed.is_err() {
+        return Ok(None);
+    }
+
+    if &header == b"IWAD" || &header == b"PWAD" {
+        wad::load_wad_project(path, progress).map(Some)
+    } else if &header[0..2] == b"PK" {
+        load_pk3(path, progress).map(Some)
+    } else {
+        Ok(None)
     }
-    None
 }
 
-fn load_pk3(ctx: &egui::Context, path: &PathBuf) -> Option<LoadedProject> {
-    let file = fs::File::open(path).ok()?;
-    let mut archive = zip::ZipArchive::new(file).ok()?;
+fn load_pk3(path: &Path, progress: &LoadProgress) -> Result<LoadedProject, ProjectError> {
+    let zip_error = |source| ProjectError::Zip {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = fs::File::open(path).map_err(|e| ProjectError::io(path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
     let mut lumps = Vec::new();
     let mut passthrough_lumps = Vec::new();
+    let mut warnings = Vec::new();
     let mut assets = AssetStore::default();
 
     let valid_lumps = ["SBARDEF", "SKYDEFS", "INTERLEVEL", "FINALE", "UMAPINFO"];
 
     for i in 0..archive.len() {
-        let mut f = archive.by_index(i).unwrap();
+        progress.check()?;
+        let mut f = archive.by_index(i).map_err(zip_error)?;
         let name = f.name().to_string();
 
         if name.ends_with('/') {
             continue;
         }
+        progress.lump_scanned();
 
         let stem = Path::new(&name)
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("");
 
+        assets.lump_names.insert(stem.to_uppercase());
         let is_managed_lump = valid_lumps.iter().any(|&l| l.eq_ignore_ascii_case(stem));
         let is_graphic = name.to_lowercase().starts_with("graphics/");
+        let namespace = name
+            .split_once('/')
+            .and_then(|(dir, _)| wad::Namespace::from_pk3_dir(dir));
 
         if is_managed_lump {
             let mut lump_data = Vec::new();
-            if f.read_to_end(&mut lump_data).is_ok() {
-                if let Some(parsed) = crate::models::ProjectData::parse_lump(&name, &lump_data) {
-                    lumps.push(parsed);
+            f.read_to_end(&mut lump_data)
+                .map_err(|e| zip_error(e.into()))?;
+            match crate::models::ProjectData::parse_lump(stem, &lump_data) {
+                Ok(lump) => lumps.push(lump),
+                Err(e) => {
+                    // Kept as-is so saving doesn't lose it.
+                    warnings.push(e);
+                    let data = wad::LumpData::from(lump_data);
+                    passthrough_lumps.push(wad::RawLump { name, data });
                 }
             }
         } else if is_graphic {
             let mut buffer = Vec::new();
-            if f.read_to_end(&mut buffer).is_ok() {
-                assets.load_image(ctx, &name, &buffer);
+            f.read_to_end(&mut buffer)
+                .map_err(|e| zip_error(e.into()))?;
+            assets.load_image(&name, &buffer);
+            progress.graphic_indexed();
+        } else if let Some(namespace) = namespace {
+            let mut buffer = Vec::new();
+            f.read_to_end(&mut buffer)
+                .map_err(|e| zip_error(e.into()))?;
+            let data = wad::LumpData::from(buffer);
+            if wad::load_graphic(&mut assets, &name, data.clone(), namespace) {
+                progress.graphic_indexed();
             }
+            passthrough_lumps.push(wad::RawLump { name, data });
         } else {
             let mut buffer = Vec::new();
-            if f.read_to_end(&mut buffer).is_ok() {
-                passthrough_lumps.push(wad::RawLump { name, data: buffer });
+            f.read_to_end(&mut buffer)
+                .map_err(|e| zip_error(e.into()))?;
+            let size = buffer.len();
+            let data = wad::LumpData::from(buffer);
+            if size == TRANMAP_SIZE {
+                assets.offer_tranmap(stem, data.clone());
+            } else if translation::may_be_translation(stem, &data.bytes()) {
+                assets.load_translation(stem, &data.bytes());
             }
+            passthrough_lumps.push(wad::RawLump { name, data });
         }
     }
 
     if lumps.is_empty() {
-        return None;
+        return Err(warnings
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| ProjectError::NoProjectLumps(path.to_path_buf())));
     }
 
-    Some(LoadedProject {
+    Ok(LoadedProject {
         lumps,
         assets,
         passthrough_lumps,
+        warnings,
+        ..Default::default()
     })
 }
 
+/// A project image on its way into a PK3.
+struct LooseImage<'a> {
+    path: String,
+    role: EntryRole,
+    name: String,
+    bytes: &'a [u8],
+    /// See [`AssetStore::import_rank`].
+    rank: u64,
+}
+
 /// Internal helper to compress project data into a PK3 structure.
-/// Preserves passthrough data from the original archive while updating managed lumps.
+/// Preserves passthrough data from the original archive while updating managed lumps,
+/// and files loose images into folders by how the project uses them. The manifest of
+/// what went where is returned and stored as the archive comment.
 fn build_pk3<W: Write + Seek>(
     writer: W,
     lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
     assets: &AssetStore,
     passthrough: &[wad::RawLump],
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Pk3Manifest> {
     let mut zip = ZipWriter::new(writer);
     let options = SimpleFileOptions::default()
         .compression_method(CompressionMethod::Deflated)
         .unix_permissions(0o755);
+    let mut manifest = Pk3Manifest::default();
 
     let mut managed_paths = std::collections::HashSet::new();
     for lump in lumps {
         managed_paths.insert(lump.standard_lump_name().to_string());
     }
+    let generated = wad::GeneratedLumps::new(lumps, composites, assets, passthrough)?;
 
-    let has_skydefs = lumps.iter().any(|l| l.standard_lump_name() == "SKYDEFS");
+    let layout = Pk3Layout::new(lumps, composites, assets);
+    let mut images: Vec<LooseImage> = assets
+        .raw_files
+        .iter()
+        .map(|(id, bytes)| {
+            let name = assets
+                .names
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| id.to_string());
+            let (path, role) = layout.place(&name, bytes);
+            LooseImage {
+                path,
+                role,
+                name,
+                bytes,
+                rank: assets.import_rank(id),
+            }
+        })
+        .collect();
+    // Images that land on one path sit together, newest import first.
+    images.sort_by(|a, b| {
+        a.path
+            .to_lowercase()
+            .cmp(&b.path.to_lowercase())
+            .then(b.rank.cmp(&a.rank))
+            .then(a.name.cmp(&b.name))
+    });
 
     for raw in passthrough {
-        if managed_paths.contains(&raw.name) {
+        if managed_paths.contains(&raw.name) || generated.replaces(&raw.name) {
             continue;
         }
-
-        if has_skydefs && (raw.name == "PNAMES" || raw.name == "TEXTURE1" || raw.name == "UMAPINFO")
+        // An imported image replaces the file it was loaded over.
+        if images
+            .iter()
+            .any(|i| i.path.eq_ignore_ascii_case(&raw.name))
         {
+            manifest.record_shadowed(&raw.name, &raw.name, EntryRole::Kept);
             continue;
         }
 
         zip.start_file(&raw.name, options)?;
-        zip.write_all(&raw.data)?;
+        zip.write_all(&raw.data.bytes())?;
+        manifest.record(&raw.name, &raw.name, EntryRole::Kept);
     }
 
     for lump in lumps {
-        zip.start_file(lump.standard_lump_name(), options)?;
+        let name = lump.standard_lump_name();
+        zip.start_file(name, options)?;
         zip.write_all(lump.to_sanitized_json(assets).as_bytes())?;
+        manifest.record(name, name, EntryRole::Definition);
     }
 
-    if has_skydefs {
-        let has_umap =
-            managed_paths.contains("UMAPINFO") || passthrough.iter().any(|r| r.name == "UMAPINFO");
-
-        if !has_umap {
-            let umapinfo_text = wad::generate_simple_umapinfo(lumps);
-            if !umapinfo_text.is_empty() {
-                zip.start_file("UMAPINFO", options)?;
-                zip.write_all(umapinfo_text.as_bytes())?;
-            }
-        }
-
-        let merged_pnames = wad::build_merged_pnames(assets);
-        let merged_texture1 = wad::build_merged_texture1(&merged_pnames, assets);
-        let pnames_data = wad::serialize_pnames(&merged_pnames);
-
-        zip.start_file("PNAMES", options)?;
-        zip.write_all(&pnames_data)?;
-        zip.start_file("TEXTURE1", options)?;
-        zip.write_all(&merged_texture1)?;
-
-        for (id, bytes) in &assets.raw_files {
-            let name = assets
-                .names
-                .get(id)
-                .cloned()
-                .unwrap_or_else(|| format!("{}", id));
-            let mut stem = AssetStore::stem(&name);
-            stem.truncate(8);
+    for (name, data) in &generated.lumps {
+        zip.start_file(*name, options)?;
+        zip.write_all(data)?;
+        manifest.record(name, "generated", EntryRole::Definition);
+    }
 
-            zip.start_file(stem, options)?;
-            zip.write_all(bytes)?;
+    for image in images {
+        // Only the newest of several images bound for one path is written.
+        if manifest.contains(&image.path) {
+            manifest.record_shadowed(&image.path, &image.name, image.role);
+            continue;
         }
-    } else {
-        for (id, bytes) in &assets.raw_files {
-            let original_name = assets
-                .names
-                .get(id)
-                .cloned()
-                .unwrap_or_else(|| format!("{}.png", id));
-
-            if original_name.contains('/') || original_name.contains('\\') {
-                zip.start_file(&original_name, options)?;
-                zip.write_all(bytes)?;
-            } else {
-                let stem = AssetStore::stem(&original_name);
-                let ext = Path::new(&original_name)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("png");
+        zip.start_file(&image.path, options)?;
+        zip.write_all(image.bytes)?;
+        manifest.record(&image.path, &image.name, image.role);
+    }
 
-                zip.start_file(format!("textures/{}.{}", stem, ext), options)?;
-                zip.write_all(bytes)?;
-                zip.start_file(format!("graphics/{}.{}", stem, ext), options)?;
-                zip.write_all(bytes)?;
-            }
-        }
+    let mut comment = manifest.to_text();
+    let mut limit = comment.len().min(u16::MAX as usize);
+    while !comment.is_char_boundary(limit) {
+        limit -= 1;
     }
+    comment.truncate(limit);
+    zip.set_comment(comment);
 
     zip.finish()?;
-    Ok(())
+    Ok(manifest)
 }
 
-pub fn save_json_dialog(json_content: &str, opened_path: Option<String>) -> Option<String> {
+/// Creates `path` and fills it with `write`, after letting go of any WAD
+/// mapped from it.
+fn write_file<T>(
+    path: &Path,
+    write: impl FnOnce(fs::File) -> anyhow::Result<T>,
+) -> Result<T, ProjectError> {
+    wad::source::release(path);
+    let file = fs::File::create(path).map_err(|e| ProjectError::io(path, e))?;
+    write(file).map_err(|e| ProjectError::Write {
+        path: path.to_path_buf(),
+        reason: format!("{:#}", e),
+    })
+}
+
+/// The dialog-driven savers give `Ok(None)` when the dialog is dismissed.
+pub fn save_json_dialog(
+    json_content: &str,
+    opened_path: Option<String>,
+) -> Result<Option<String>, ProjectError> {
     let mut dialog = FileDialog::new()
         .add_filter("SBARDEF JSON", &["json", "txt", "JSON", "TXT"])
         .set_title("Export SBARDEF JSON");
@@ -306,20 +546,20 @@ pub fn save_json_dialog(json_content: &str, opened_path: Option<String>) -> Opti
         dialog = dialog.set_file_name("SBARDEF.json");
     }
 
-    if let Some(path) = dialog.save_file() {
-        if fs::write(&path, json_content).is_ok() {
-            return Some(path.to_string_lossy().into_owned());
-        }
-    }
-    None
+    let Some(path) = dialog.save_file() else {
+        return Ok(None);
+    };
+    fs::write(&path, json_content).map_err(|e| ProjectError::io(&path, e))?;
+    Ok(Some(path.to_string_lossy().into_owned()))
 }
 
 pub fn save_pk3_dialog(
     lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
     assets: &AssetStore,
     passthrough: &[wad::RawLump],
     opened_path: Option<String>,
-) -> Option<String> {
+) -> Result<Option<(String, Pk3Manifest)>, ProjectError> {
     let mut dialog = FileDialog::new()
         .add_filter("Doom Package", &["pk3", "zip", "PK3", "ZIP"])
         .set_title("Save PK3");
@@ -343,27 +583,22 @@ pub fn save_pk3_dialog(
         dialog = dialog.set_file_name("project.pk3");
     }
 
-    if let Some(path) = dialog.save_file() {
-        match fs::File::create(&path) {
-            Ok(fs_file) => {
-                if let Err(e) = build_pk3(fs_file, lumps, assets, passthrough) {
-                    eprintln!("Failed to build PK3: {}", e);
-                } else {
-                    return Some(path.to_string_lossy().into_owned());
-                }
-            }
-            Err(e) => eprintln!("Failed to create file at {:?}: {}", path, e),
-        }
-    }
-    None
+    let Some(path) = dialog.save_file() else {
+        return Ok(None);
+    };
+    let manifest = write_file(&path, |f| {
+        build_pk3(f, lumps, composites, assets, passthrough)
+    })?;
+    Ok(Some((path.to_string_lossy().into_owned(), manifest)))
 }
 
 pub fn save_wad_dialog(
     lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
     assets: &AssetStore,
     passthrough: &[wad::RawLump],
     opened_path: Option<String>,
-) -> Option<String> {
+) -> Result<Option<(String, Vec<String>)>, ProjectError> {
     let mut dialog = FileDialog::new()
         .add_filter("Doom WAD", &["wad", "WAD"])
         .set_title("Export as WAD (KEX Compatible)");
@@ -376,69 +611,62 @@ pub fn save_wad_dialog(
         dialog = dialog.set_file_name("project.wad");
     }
 
-    if let Some(path) = dialog.save_file() {
-        if let Ok(mut f) = fs::File::create(&path) {
-            if wad::write_wad_to_file(&mut f, lumps, assets, passthrough).is_ok() {
-                return Some(path.to_string_lossy().into_owned());
-            }
-        }
-    }
-    None
+    let Some(path) = dialog.save_file() else {
+        return Ok(None);
+    };
+    let skipped = write_file(&path, |mut f| {
+        wad::write_wad_to_file(&mut f, lumps, composites, assets, passthrough)
+    })?;
+    Ok(Some((path.to_string_lossy().into_owned(), skipped)))
 }
 
 pub fn save_pk3_silent(
     lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
     assets: &AssetStore,
     passthrough: &[wad::RawLump],
     path_str: &str,
-) -> anyhow::Result<()> {
-    let path = Path::new(path_str);
-    let fs_file = fs::File::create(path)?;
-    build_pk3(fs_file, lumps, assets, passthrough)?;
-    Ok(())
+) -> Result<Pk3Manifest, ProjectError> {
+    write_file(Path::new(path_str), |f| {
+        build_pk3(f, lumps, composites, assets, passthrough)
+    })
 }
 
-pub fn load_iwad_dialog(ctx: &egui::Context, assets: &mut AssetStore) -> Option<String> {
-    if let Some(path) = FileDialog::new()
+pub fn save_wad_silent(
+    lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
+    assets: &AssetStore,
+    passthrough: &[wad::RawLump],
+    path_str: &str,
+) -> Result<Vec<String>, ProjectError> {
+    write_file(Path::new(path_str), |mut f| {
+        wad::write_wad_to_file(&mut f, lumps, composites, assets, passthrough)
+    })
+}
+
+/// Asks for a base IWAD. It's read by [`ProjectLoad::spawn_base_wad`].
+pub fn pick_iwad_dialog() -> Option<String> {
+    FileDialog::new()
         .add_filter("Doom WAD", &["wad", "WAD"])
         .set_title("Select Base WAD (e.g., DOOM2.WAD)")
         .pick_file()
-    {
-        if let Ok(mut file) = fs::File::open(&path) {
-            if wad::load_wad_into_store(ctx, &mut file, assets).is_ok() {
-                return path.to_str().map(|s| s.to_string());
-            }
-        }
-    }
-    None
-}
-
-pub fn load_wad_from_path(ctx: &egui::Context, path_str: &str, assets: &mut AssetStore) -> bool {
-    let path = Path::new(path_str);
-    if let Ok(mut file) = fs::File::open(path) {
-        if let Err(e) = wad::load_wad_into_store(ctx, &mut file, assets) {
-            eprintln!("Failed to auto-load WAD at {:?}: {}", path, e);
-            false
-        } else {
-            true
-        }
-    } else {
-        false
-    }
+        .map(|path| path.to_string_lossy().into_owned())
 }
 
 /// Launches the game with the current project data.
 ///
 /// If targeting 'Basic', it produces a temporary .WAD for KEX compatibility.
-/// Otherwise, it produces a temporary .PK3.
+/// Otherwise, it produces a temporary .PK3. Fails without launching if that
+/// file can't be written, or if the port can't be started.
 pub fn launch_game(
     assets: &AssetStore,
     source_port: &str,
     iwad: &str,
     target: ExportTarget,
     lumps: &[crate::models::ProjectData],
+    composites: &[CompositeTexture],
     passthrough: &[wad::RawLump],
-) {
+) -> Result<(), ProjectError> {
     let mut temp_path = env::temp_dir();
 
     let extension = if !passthrough.is_empty() || target == ExportTarget::Basic {
@@ -450,19 +678,15 @@ pub fn launch_game(
     temp_path.push(format!("cacotest.{}", extension));
     let temp_path_str = temp_path.to_string_lossy().into_owned();
 
-    match fs::File::create(&temp_path) {
-        Ok(mut fs_file) => {
-            if extension == "wad" {
-                let _ = wad::write_wad_to_file(&mut fs_file, lumps, assets, passthrough);
-            } else {
-                let _ = build_pk3(fs_file, lumps, assets, passthrough);
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to create temp file: {}", e);
-            return;
-        }
-    };
+    if extension == "wad" {
+        write_file(&temp_path, |mut f| {
+            wad::write_wad_to_file(&mut f, lumps, composites, assets, passthrough)
+        })?;
+    } else {
+        write_file(&temp_path, |f| {
+            build_pk3(f, lumps, composites, assets, passthrough)
+        })?;
+    }
 
     let program;
     let mut args = Vec::new();
@@ -472,13 +696,16 @@ pub fn launch_game(
     } else {
         let mut words = shlex::split(source_port).unwrap_or_default();
         if words.is_empty() {
-            return;
+            return Err(ProjectError::io(
+                source_port,
+                std::io::Error::other("the source port command is empty"),
+            ));
         }
         program = words.remove(0);
         args = words;
     }
 
-    let _ = Command::new(&program)
+    Command::new(&program)
         .args(args)
         .arg("-iwad")
         .arg(iwad)
@@ -488,37 +715,35 @@ pub fn launch_game(
         .arg("4")
         .arg("-warp")
         .arg("1")
-        .spawn();
+        .spawn()
+        .map_err(|e| ProjectError::io(&program, e))?;
+    Ok(())
 }
 
-pub fn import_images_dialog(ctx: &egui::Context, assets: &mut AssetStore) -> usize {
+pub fn import_images_dialog(assets: &mut AssetStore) -> usize {
     if let Some(paths) = FileDialog::new()
         .add_filter("Images", &["png", "jpg", "jpeg", "PNG", "JPG", "JPEG"])
         .set_title("Import Graphics")
         .pick_files()
     {
-        return load_images_from_paths(ctx, assets, paths);
+        return load_images_from_paths(assets, paths);
     }
     0
 }
 
-pub fn import_folder_dialog(ctx: &egui::Context, assets: &mut AssetStore) -> usize {
+pub fn import_folder_dialog(assets: &mut AssetStore) -> usize {
     if let Some(path) = FileDialog::new()
         .set_title("Import Folder Recursively")
         .pick_folder()
     {
         let mut paths = Vec::new();
         visit_dirs_for_images(&path, &mut paths);
-        return load_images_from_paths(ctx, assets, paths);
+        return load_images_from_paths(assets, paths);
     }
     0
 }
 
-fn load_images_from_paths(
-    ctx: &egui::Context,
-    assets: &mut AssetStore,
-    paths: Vec<PathBuf>,
-) -> usize {
+fn load_images_from_paths(assets: &mut AssetStore, paths: Vec<PathBuf>) -> usize {
     let mut count = 0;
     for path in paths {
         if let Ok(bytes) = fs::read(&path) {
@@ -526,7 +751,7 @@ fn load_images_from_paths(
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("unknown");
-            assets.load_image(ctx, name, &bytes);
+            assets.load_image(name, &bytes);
             count += 1;
         }
     }
@@ -584,22 +809,169 @@ mod tests {
             .names
             .insert(id_path, "graphics/patch.png".to_string());
 
+        // A file kept from the source PK3 gives way to the image imported over it.
+        let kept = wad::RawLump {
+            name: "graphics/STBAR.png".to_string(),
+            data: vec![1u8; 4].into(),
+        };
+
         let mut buffer = Cursor::new(Vec::new());
-        build_pk3(&mut buffer, &lumps, &assets, &[]).expect("Failed to build PK3");
+        let manifest =
+            build_pk3(&mut buffer, &lumps, &[], &assets, &[kept]).expect("Failed to build PK3");
+        assert_eq!(manifest.shadowed.len(), 1);
+        assert_eq!(manifest.shadowed[0].role, EntryRole::Kept);
 
         let mut zip = zip::ZipArchive::new(buffer).expect("Failed to open built ZIP");
 
         assert!(zip.by_name("SBARDEF").is_ok(), "SBARDEF missing from root");
 
-        assert!(
-            zip.by_name("graphics/STBAR.png").is_ok(),
-            "Loose lump failed to move to graphics/ or gain extension"
-        );
+        let mut stbar = Vec::new();
+        zip.by_name("graphics/STBAR.png")
+            .expect("Loose lump failed to move to graphics/ or gain extension")
+            .read_to_end(&mut stbar)
+            .unwrap();
+        assert_eq!(stbar, dummy_bytes);
 
         assert!(
             zip.by_name("graphics/patch.png").is_ok(),
             "Explicit path failed to preserve correctly"
         );
+
+        assert!(
+            zip.by_name("textures/STBAR.png").is_err(),
+            "Loose lump written to more than one folder"
+        );
+        assert!(zip.comment().starts_with(b"SBARDEF (definition)"));
+    }
+
+    #[test]
+    fn test_background_load_reports_progress_and_cancels() {
+        let path = std::env::temp_dir().join(format!("cacoco_load_{}.pk3", std::process::id()));
+        let lumps = vec![crate::models::ProjectData::StatusBar(
+            SBarDefFile::new_empty(),
+        )];
+        let mut assets = AssetStore::default();
+        let id = AssetId::new("STBAR");
+        assets.raw_files.insert(id, vec![0u8; 10]);
+        assets.names.insert(id, "STBAR".to_string());
+        build_pk3(fs::File::create(&path).unwrap(), &lumps, &[], &assets, &[]).unwrap();
+        let path_str = path.to_string_lossy().into_owned();
+        let base = path.with_extension("wad");
+        wad::write_wad_to_file(
+            &mut fs::File::create(&base).unwrap(),
+            &lumps,
+            &[],
+            &assets,
+            &[],
+        )
+        .unwrap();
+
+        // The base WAD is read on the worker too, after the project.
+        let base_wad = Some(base.to_string_lossy().into_owned());
+        let finish = |load: &ProjectLoad| loop {
+            if let Some(result) = load.poll() {
+                break result;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        };
+        let load = ProjectLoad::spawn(&path_str, base_wad.clone());
+        let loaded = finish(&load).unwrap();
+        assert_eq!(loaded.lumps.len(), 1);
+        assert!(loaded.assets.has_image(&AssetId::new("HICACOCO")));
+        assert!(load.progress.lumps() >= 4);
+        assert_eq!(load.progress.stage(), "Baking textures");
+        assert!(loaded.warnings.is_empty());
+
+        // Projects started in the editor are prepared on the worker as well.
+        let seed = LoadedProject {
+            lumps: vec![crate::models::ProjectData::StatusBar(
+                SBarDefFile::new_empty(),
+            )],
+            ..Default::default()
+        };
+        let new = ProjectLoad::spawn_new(seed, None, base_wad);
+        assert_eq!(new.title(), "New project");
+        let prepared = finish(&new).unwrap();
+        assert_eq!(prepared.lumps.len(), 1);
+        assert!(prepared.assets.has_image(&AssetId::new("HICACOCO")));
+        assert!(new.progress.lumps() >= 1);
+        assert!(prepared.warnings.is_empty());
+
+        // A missing IWAD doesn't stop the project, but it is reported.
+        let mut without_base = LoadedProject::default();
+        without_base
+            .prepare(Some("/nonexistent/doom2.wad"), &LoadProgress::default())
+            .unwrap();
+        assert!(!without_base.has_base);
+        assert!(matches!(
+            without_base.warnings.as_slice(),
+            [ProjectError::NotFound(_)]
+        ));
+
+        let cancelled = LoadProgress::default();
+        cancelled.cancel();
+        assert!(matches!(
+            load_project_from_path(&path_str, &cancelled),
+            Err(ProjectError::Cancelled)
+        ));
+
+        let missing = load_project_from_path("/nonexistent/cacoco.pk3", &cancelled);
+        assert!(matches!(missing, Err(ProjectError::NotFound(_))));
+
+        wad::source::release(&base);
+        fs::remove_file(&base).ok();
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_pk3_keeps_loading_past_a_broken_lump() {
+        let archive = |entries: &[(&str, &str)]| {
+            let path = std::env::temp_dir().join(format!(
+                "cacoco_lumps_{}_{}.pk3",
+                std::process::id(),
+                entries.len()
+            ));
+            let mut zip = ZipWriter::new(fs::File::create(&path).unwrap());
+            for (name, content) in entries {
+                zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+            path
+        };
+        let sbardef = serde_json::to_string(&SBarDefFile::new_empty()).unwrap();
+
+        let path = archive(&[
+            ("SBARDEF.json", &sbardef),
+            ("UMAPINFO.txt", "MAP MAP01 { levelname = \"Entryway\" }"),
+            ("FINALE.json", "{ not json"),
+        ]);
+        let loaded = load_project_from_path(&path.to_string_lossy(), &LoadProgress::default())
+            .expect("the lumps that parse should load");
+        assert_eq!(loaded.lumps.len(), 2);
+        assert!(
+            loaded
+                .lumps
+                .iter()
+                .any(|l| matches!(l, crate::models::ProjectData::UmapInfo(_)))
+        );
+        assert!(matches!(
+            loaded.warnings.as_slice(),
+            [ProjectError::BadJson { lump, .. }] if lump == "FINALE"
+        ));
+        assert!(
+            loaded
+                .passthrough_lumps
+                .iter()
+                .any(|l| l.name == "FINALE.json")
+        );
+        fs::remove_file(&path).ok();
+
+        // With nothing that parses, the lump's own error is the one reported.
+        let path = archive(&[("FINALE.json", "{ not json")]);
+        let broken = load_project_from_path(&path.to_string_lossy(), &LoadProgress::default());
+        assert!(matches!(broken, Err(ProjectError::BadJson { .. })));
+        fs::remove_file(&path).ok();
     }
 }
 
@@ -3,11 +3,13 @@
 mod app;
 mod assets;
 mod cheats;
+mod cli;
 mod conditions;
 mod config;
 mod constants;
 mod discovery;
 mod document;
+mod error;
 mod history;
 mod hotkeys;
 mod io;
@@ -42,12 +44,18 @@ const TITLES: &[&str] = &[
 ///
 /// 𐑦𐑓 𐑢𐑰 𐑛𐑲, 𐑢𐑰 𐑛𐑲 𐑣𐑴𐑤𐑛𐑦𐑙 𐑣𐑨𐑯𐑛𐑟.
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_command(&args) {
+        cli::attach_console();
+        std::process::exit(cli::run(&args));
+    }
+
     let icon_data = load_icon();
 
     let mut rng = rand::rng();
     let title_flavor = TITLES.choose(&mut rng).unwrap_or(&"Cacoco").to_string();
 
-    let open_file_path = std::env::args().nth(1).filter(|arg| !arg.starts_with('-'));
+    let open_file_path = args.first().cloned().filter(|arg| !arg.starts_with('-'));
 
     let native_options = NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -10,6 +10,23 @@ pub enum FinaleType {
     CastRollCall = 2,
 }
 
+impl FinaleType {
+    pub const ALL: [FinaleType; 3] = [
+        FinaleType::ArtScreen,
+        FinaleType::BunnyScroller,
+        FinaleType::CastRollCall,
+    ];
+
+    /// Human-readable name for menus and lists.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            FinaleType::ArtScreen => "Art Screen",
+            FinaleType::BunnyScroller => "Bunny Scroller",
+            FinaleType::CastRollCall => "Cast Roll Call",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct FinaleDefFile {
     pub version: String,
@@ -55,6 +72,21 @@ pub struct BunnyDef {
     pub overlayy: i32,
 }
 
+impl BunnyDef {
+    /// The settings matching Doom II's stock bunny scroller: PFUB1 scrolls in
+    /// against PFUB2, then the seven END patches stamp "THE END" in the middle.
+    pub fn doom2() -> Self {
+        Self {
+            stitchimage: "PFUB1".to_string(),
+            overlay: 0,
+            overlaycount: 7,
+            overlaysound: 0,
+            overlayx: 108,
+            overlayy: 68,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CastRollCallDef {
     pub castmembers: Vec<CastMember>,
@@ -79,3 +111,17 @@ pub struct CastFrame {
     #[serde(default)]
     pub flip: bool,
 }
+
+/// Tics given to newly added frames; most cast frames run 4-10.
+const NEW_FRAME_TICS: f64 = 8.0;
+
+impl CastFrame {
+    /// A frame showing `image` for a typical cast frame's length.
+    pub fn new(image: String) -> Self {
+        CastFrame {
+            image,
+            duration: NEW_FRAME_TICS / crate::constants::DOOM_TICS_PER_SEC,
+            ..Default::default()
+        }
+    }
+}
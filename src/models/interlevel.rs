@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use bitflags::bitflags;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InterlevelDefFile {
@@ -49,15 +50,192 @@ pub struct InterlevelAnim {
 pub struct InterlevelFrame {
     pub image: String,
     #[serde(rename = "type")]
-    /// Bitfield: 1-Infinite, 2-Fixed, 4-Random, 0x1000-RandomFirst, 0x8000000-Widescreen
-    pub frame_type: i32,
+    pub frame_type: FrameType,
     pub duration: f64,
     #[serde(default)]
     pub maxduration: f64,
 }
 
+bitflags! {
+    /// How long an intermission frame stays up, and when it's shown at all.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct FrameType: u32 {
+        /// Stays up forever, ending the anim on this frame.
+        const INFINITE      = 0x0001;
+        /// Stays up for exactly `duration`.
+        const FIXED         = 0x0002;
+        /// Stays up for a random time between `duration` and `maxduration`.
+        const RANDOM        = 0x0004;
+        /// On an anim's first frame: start the anim on a random frame.
+        const RANDOM_FIRST  = 0x1000;
+        /// Only shown when the intermission is drawn widescreen.
+        const WIDESCREEN    = 0x8000000;
+    }
+}
+
+impl Serialize for FrameType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i32(self.bits() as i32)
+    }
+}
+
+impl<'de> Deserialize<'de> for FrameType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Unknown bits, sign bit included, are kept so newer flags survive
+        // a round trip.
+        Ok(FrameType::from_bits_retain(
+            i32::deserialize(deserializer)? as u32
+        ))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InterlevelCondition {
-    pub condition: i32,
+    pub condition: InterlevelConditionType,
     pub param: i32,
 }
+
+/// Checks that decide whether an intermission layer or anim is shown.
+///
+/// "The map" is the one just finished on the tally screen, and the one about
+/// to be entered on the entering screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterlevelConditionType {
+    #[default]
+    None,
+    /// The map number is greater than `param`.
+    CurrMapGreater,
+    /// The map number equals `param`.
+    CurrMapEqual,
+    /// Map `param` has been visited this episode.
+    MapVisited,
+    /// The map is not a secret map.
+    CurrMapNotSecret,
+    /// Any secret map has been visited this episode.
+    AnySecretVisited,
+    /// The tally screen is showing.
+    OnFinishedScreen,
+    /// The "entering" screen is showing.
+    OnEnteringScreen,
+    /// The anim's patch fits inside the screen.
+    FitsInFrame,
+    /// A check this editor doesn't know, kept so it's written back as-is.
+    Unknown(i32),
+}
+
+impl From<i32> for InterlevelConditionType {
+    fn from(value: i32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.value() == value)
+            .unwrap_or(InterlevelConditionType::Unknown(value))
+    }
+}
+
+impl Serialize for InterlevelConditionType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i32(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for InterlevelConditionType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(i32::deserialize(deserializer)?.into())
+    }
+}
+
+impl InterlevelConditionType {
+    pub const ALL: [InterlevelConditionType; 9] = [
+        InterlevelConditionType::None,
+        InterlevelConditionType::CurrMapGreater,
+        InterlevelConditionType::CurrMapEqual,
+        InterlevelConditionType::MapVisited,
+        InterlevelConditionType::CurrMapNotSecret,
+        InterlevelConditionType::AnySecretVisited,
+        InterlevelConditionType::OnFinishedScreen,
+        InterlevelConditionType::OnEnteringScreen,
+        InterlevelConditionType::FitsInFrame,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InterlevelConditionType::None => "Always",
+            InterlevelConditionType::CurrMapGreater => "Map Greater Than",
+            InterlevelConditionType::CurrMapEqual => "Map Is",
+            InterlevelConditionType::MapVisited => "Map Visited",
+            InterlevelConditionType::CurrMapNotSecret => "Map Not Secret",
+            InterlevelConditionType::AnySecretVisited => "Secret Visited",
+            InterlevelConditionType::OnFinishedScreen => "On Tally Screen",
+            InterlevelConditionType::OnEnteringScreen => "On Entering Screen",
+            InterlevelConditionType::FitsInFrame => "Fits On Screen",
+            InterlevelConditionType::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// The number the lump stores for this check.
+    pub fn value(self) -> i32 {
+        match self {
+            InterlevelConditionType::None => 0,
+            InterlevelConditionType::CurrMapGreater => 1,
+            InterlevelConditionType::CurrMapEqual => 2,
+            InterlevelConditionType::MapVisited => 3,
+            InterlevelConditionType::CurrMapNotSecret => 4,
+            InterlevelConditionType::AnySecretVisited => 5,
+            InterlevelConditionType::OnFinishedScreen => 6,
+            InterlevelConditionType::OnEnteringScreen => 7,
+            InterlevelConditionType::FitsInFrame => 8,
+            InterlevelConditionType::Unknown(value) => value,
+        }
+    }
+
+    /// Whether `param` is a map number for this check.
+    pub fn takes_map(self) -> bool {
+        matches!(
+            self,
+            InterlevelConditionType::CurrMapGreater
+                | InterlevelConditionType::CurrMapEqual
+                | InterlevelConditionType::MapVisited
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_values_round_trip() {
+        let json = r#"{"image":"WIA00000","type":-1,"duration":0.0,"maxduration":0.0}"#;
+        let frame: InterlevelFrame = serde_json::from_str(json).unwrap();
+        assert!(
+            frame
+                .frame_type
+                .contains(FrameType::INFINITE | FrameType::WIDESCREEN)
+        );
+        assert_eq!(serde_json::to_string(&frame).unwrap(), json);
+
+        let json = r#"[{"condition":2,"param":5},{"condition":42,"param":-3}]"#;
+        let conditions: Vec<InterlevelCondition> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            conditions[0].condition,
+            InterlevelConditionType::CurrMapEqual
+        );
+        assert_eq!(
+            conditions[1].condition,
+            InterlevelConditionType::Unknown(42)
+        );
+        assert_eq!(serde_json::to_string(&conditions).unwrap(), json);
+    }
+}
@@ -3,9 +3,14 @@ pub mod interlevel;
 pub mod sbardef;
 pub mod skydefs;
 pub mod umapinfo;
+pub mod umapinfo_syntax;
 
+use crate::error::ProjectError;
 use serde::{Deserialize, Serialize};
 
+/// Newest major spec version of the ID24 lumps this editor understands.
+const SUPPORTED_MAJOR_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ProjectData {
@@ -179,11 +184,47 @@ impl ProjectData {
         list
     }
 
+    /// Names of the tranmaps this lump's elements and cast frames blend
+    /// through, other than the default `TRANMAP`.
+    pub fn tranmap_names(&self) -> Vec<String> {
+        fn walk(element: &sbardef::ElementWrapper, names: &mut Vec<String>) {
+            names.extend(element.get_common().tranmap.clone());
+            for child in element.children() {
+                walk(child, names);
+            }
+        }
+
+        let mut names = Vec::new();
+        match self {
+            ProjectData::StatusBar(sbar) => {
+                for bar in &sbar.data.status_bars {
+                    for element in &bar.children {
+                        walk(element, &mut names);
+                    }
+                }
+            }
+            ProjectData::Finale(finale) => {
+                let members = finale
+                    .data
+                    .cast_roll_call
+                    .iter()
+                    .flat_map(|c| &c.castmembers);
+                let frames = members.flat_map(|m| m.alive.iter().chain(&m.dead));
+                names.extend(frames.filter_map(|f| f.tranmap.clone()));
+            }
+            _ => {}
+        }
+        names.retain(|n| !n.is_empty() && !n.eq_ignore_ascii_case("TRANMAP"));
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn draw_properties(
         &mut self,
         ui: &mut eframe::egui::Ui,
         ctx: &crate::ui::properties::editor::PropertyContext,
-    ) -> bool {
+    ) -> (Vec<crate::document::actions::DocumentAction>, bool) {
         use crate::ui::properties::editor::LumpUI;
         match self {
             ProjectData::StatusBar(f) => f.draw_properties(ui, ctx),
@@ -242,6 +283,8 @@ impl ProjectData {
         use crate::ui::properties::editor::LumpUI;
         match self {
             ProjectData::StatusBar(f) => f.get_preview_content(ui, ctx),
+            ProjectData::Finale(f) => f.get_preview_content(ui, ctx),
+            ProjectData::Interlevel(f) => f.get_preview_content(ui, ctx),
             _ => None,
         }
     }
@@ -262,19 +305,37 @@ impl ProjectData {
     }
 
     /// Centralized lump parser for ID24 JSON and UMAPINFO text formats.
-    pub fn parse_lump(name: &str, data: &[u8]) -> Option<Self> {
+    pub fn parse_lump(name: &str, data: &[u8]) -> Result<Self, ProjectError> {
         let content = String::from_utf8_lossy(data);
-        if let Ok(mut parsed) = serde_json::from_str::<Self>(&content) {
-            parsed.set_target(parsed.determine_target());
-            parsed.normalize_for_target();
-            Some(parsed)
-        } else if name.eq_ignore_ascii_case("UMAPINFO") {
-            Some(Self::UmapInfo(umapinfo::UmapInfoFile::from_umapinfo_text(
-                &content,
-            )))
-        } else {
-            None
+        match serde_json::from_str::<Self>(&content) {
+            Ok(mut parsed) => {
+                parsed.check_version(name)?;
+                parsed.set_target(parsed.determine_target());
+                parsed.normalize_for_target();
+                Ok(parsed)
+            }
+            Err(_) if name.eq_ignore_ascii_case("UMAPINFO") => Ok(Self::UmapInfo(
+                umapinfo::UmapInfoFile::from_umapinfo_text(&content),
+            )),
+            Err(e) => Err(ProjectError::bad_json(name, &e)),
+        }
+    }
+
+    /// Rejects lumps from a later major revision of their spec, whose
+    /// fields may mean something this editor would silently get wrong.
+    fn check_version(&self, name: &str) -> Result<(), ProjectError> {
+        let version = self.version();
+        let major = version
+            .split('.')
+            .next()
+            .and_then(|m| m.parse::<u32>().ok());
+        if major.is_some_and(|m| m <= SUPPORTED_MAJOR_VERSION) {
+            return Ok(());
         }
+        Err(ProjectError::UnsupportedVersion {
+            lump: name.to_string(),
+            version: version.to_string(),
+        })
     }
 }
 
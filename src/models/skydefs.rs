@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::{BTreeSet, HashSet};
 
 #[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(i32)]
@@ -97,3 +98,91 @@ pub struct FlatMap {
     pub flat: String,
     pub sky: String,
 }
+
+/// A problem with one flat mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatMapIssue {
+    /// The flat isn't in any loaded WAD or PK3.
+    MissingFlat,
+    /// No sky in this lump has the mapped name.
+    UndefinedSky,
+    /// An earlier mapping already redirects the same flat.
+    DuplicateFlat,
+}
+
+impl FlatMapIssue {
+    pub fn message(self, map: &FlatMap) -> String {
+        match self {
+            FlatMapIssue::MissingFlat => format!("Flat '{}' is not in the loaded WADs.", map.flat),
+            FlatMapIssue::UndefinedSky => format!("Sky '{}' is not defined.", map.sky),
+            FlatMapIssue::DuplicateFlat => format!("Flat '{}' is mapped more than once.", map.flat),
+        }
+    }
+}
+
+impl SkyDefsDefinition {
+    /// Checks every flat mapping for dangling references, by mapping index.
+    /// Flats are only checked once some have been loaded.
+    pub fn flatmapping_issues(&self, flats: &BTreeSet<String>) -> Vec<(usize, FlatMapIssue)> {
+        let mut issues = Vec::new();
+        let mut seen = HashSet::new();
+        let skies: HashSet<String> = self.skies.iter().map(|s| s.name.to_uppercase()).collect();
+
+        for (i, map) in self.flatmapping.iter().flatten().enumerate() {
+            let flat = map.flat.to_uppercase();
+            if !flats.is_empty() && !flats.contains(&flat) {
+                issues.push((i, FlatMapIssue::MissingFlat));
+            }
+            if !skies.contains(&map.sky.to_uppercase()) {
+                issues.push((i, FlatMapIssue::UndefinedSky));
+            }
+            if !seen.insert(flat) {
+                issues.push((i, FlatMapIssue::DuplicateFlat));
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatmapping_issues() {
+        let map = |flat: &str, sky: &str| FlatMap {
+            flat: flat.to_string(),
+            sky: sky.to_string(),
+        };
+        let def = SkyDefsDefinition {
+            skies: vec![SkyDef {
+                name: "SKY1".to_string(),
+                ..Default::default()
+            }],
+            flatmapping: Some(vec![
+                map("F_SKY1", "SKY1"),
+                map("F_SKY2", "SKY9"),
+                map("f_sky1", "sky1"),
+            ]),
+        };
+
+        // Without loaded flats only the skies and duplicates are checked.
+        assert_eq!(
+            def.flatmapping_issues(&BTreeSet::new()),
+            [
+                (1, FlatMapIssue::UndefinedSky),
+                (2, FlatMapIssue::DuplicateFlat)
+            ]
+        );
+
+        let flats = BTreeSet::from(["F_SKY1".to_string()]);
+        assert_eq!(
+            def.flatmapping_issues(&flats),
+            [
+                (1, FlatMapIssue::MissingFlat),
+                (1, FlatMapIssue::UndefinedSky),
+                (2, FlatMapIssue::DuplicateFlat)
+            ]
+        );
+    }
+}
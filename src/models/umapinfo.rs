@@ -1,4 +1,6 @@
+use super::umapinfo_syntax::{self, BlockSyntax, EntrySyntax, UmapSyntax, ValueSyntax};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Represents the different types of keys available in a UMAPINFO map entry.
 /// This modular approach allows the editor to handle any key-value pair
@@ -48,11 +50,44 @@ pub enum UmapField {
         special: i32,
         tag: i32,
     },
+    /// A key the editor doesn't model, such as a port extension, kept
+    /// verbatim so saving doesn't drop it.
+    Unknown {
+        key: String,
+        raw_value: String,
+    },
 }
 
+/// Every key `UmapField` has a variant for.
+const KNOWN_KEYS: &[&str] = &[
+    "levelname",
+    "author",
+    "levelpic",
+    "skytexture",
+    "music",
+    "exitpic",
+    "enterpic",
+    "endpic",
+    "interbackdrop",
+    "intermusic",
+    "next",
+    "nextsecret",
+    "label",
+    "intertextsecret",
+    "partime",
+    "endgame",
+    "endbunny",
+    "endcast",
+    "nointermission",
+    "intertext",
+    "episode",
+    "bossaction",
+    "bossactionednum",
+];
+
 impl UmapField {
     /// Returns the standard UMAPINFO key name for this field.
-    pub fn key_name(&self) -> &'static str {
+    pub fn key_name(&self) -> &str {
         match self {
             UmapField::LevelName(_) => "levelname",
             UmapField::Author(_) => "author",
@@ -77,9 +112,165 @@ impl UmapField {
             UmapField::Episode { .. } => "episode",
             UmapField::BossAction { .. } => "bossaction",
             UmapField::BossActionEdNum { .. } => "bossactionednum",
+            UmapField::Unknown { key, .. } => key,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            UmapField::LevelName(s)
+            | UmapField::Author(s)
+            | UmapField::SkyTexture(s)
+            | UmapField::Music(s)
+            | UmapField::ExitPic(s)
+            | UmapField::EnterPic(s)
+            | UmapField::LevelPic(s)
+            | UmapField::EndPic(s)
+            | UmapField::InterBackdrop(s)
+            | UmapField::InterMusic(s)
+            | UmapField::Next(s)
+            | UmapField::NextSecret(s) => Some(s),
+            _ => None,
         }
     }
 
+    /// The UMAPINFO text of this field's value, as written after the `=`.
+    fn value_text(&self) -> String {
+        let quote = umapinfo_syntax::quote;
+        match self {
+            UmapField::InterText(lines) | UmapField::InterTextSecret(lines) => {
+                if lines.len() == 1 && lines[0] == "clear" {
+                    "clear".to_string()
+                } else {
+                    let quoted: Vec<String> = lines.iter().map(|l| quote(l)).collect();
+                    quoted.join(",\n\t\t")
+                }
+            }
+            UmapField::Episode { patch, name, key } => {
+                if patch == "clear" {
+                    "clear".to_string()
+                } else {
+                    format!("{}, {}, {}", quote(patch), quote(name), quote(key))
+                }
+            }
+            UmapField::Label(v) if v == "clear" => "clear".to_string(),
+            UmapField::ParTime(v) => v.to_string(),
+            UmapField::EndGame(v)
+            | UmapField::EndBunny(v)
+            | UmapField::EndCast(v)
+            | UmapField::NoIntermission(v) => v.to_string(),
+            UmapField::BossAction {
+                thing,
+                special,
+                tag,
+            }
+            | UmapField::BossActionEdNum {
+                ednum: thing,
+                special,
+                tag,
+            } => format!("{}, {}, {}", thing, special, tag),
+            UmapField::Unknown { raw_value, .. } => raw_value.clone(),
+            UmapField::Label(s) => quote(s),
+            _ => quote(self.as_str().unwrap_or_default()),
+        }
+    }
+
+    /// Builds the field a parsed entry describes. Entries with an unknown key,
+    /// or a value this model can't hold, become `Unknown`.
+    fn from_entry(entry: &EntrySyntax) -> Self {
+        Self::from_values(&entry.key.to_lowercase(), &entry.values).unwrap_or_else(|| {
+            UmapField::Unknown {
+                key: entry.key.clone(),
+                raw_value: entry.raw_value().to_string(),
+            }
+        })
+    }
+
+    fn from_values(key: &str, values: &[ValueSyntax]) -> Option<Self> {
+        let texts: Vec<&str> = values.iter().map(ValueSyntax::text).collect();
+        let single = match texts.as_slice() {
+            [v] => Some(v.to_string()),
+            _ => None,
+        };
+        let flag = || match single.as_deref().map(str::to_lowercase).as_deref() {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        };
+        let clear = matches!(values, [v] if v.is_word("clear"));
+        let action = || match texts.as_slice() {
+            [thing, special, tag] => {
+                Some((thing.to_string(), special.parse().ok()?, tag.parse().ok()?))
+            }
+            _ => None,
+        };
+
+        let field = match key {
+            "levelname" => UmapField::LevelName(single?),
+            "author" => UmapField::Author(single?),
+            "skytexture" => UmapField::SkyTexture(single?),
+            "music" => UmapField::Music(single?),
+            "levelpic" => UmapField::LevelPic(single?),
+            "next" => UmapField::Next(single?),
+            "nextsecret" => UmapField::NextSecret(single?),
+            "exitpic" => UmapField::ExitPic(single?),
+            "enterpic" => UmapField::EnterPic(single?),
+            "endpic" => UmapField::EndPic(single?),
+            "interbackdrop" => UmapField::InterBackdrop(single?),
+            "intermusic" => UmapField::InterMusic(single?),
+            "label" if clear => UmapField::Label("clear".to_string()),
+            "label" => UmapField::Label(single?),
+            "partime" => UmapField::ParTime(single?.parse().ok()?),
+            "endgame" => UmapField::EndGame(flag()?),
+            "endbunny" => UmapField::EndBunny(flag()?),
+            "endcast" => UmapField::EndCast(flag()?),
+            "nointermission" => UmapField::NoIntermission(flag()?),
+            "intertext" | "intertextsecret" => {
+                let lines = if clear {
+                    vec!["clear".to_string()]
+                } else {
+                    texts.iter().map(|s| s.to_string()).collect()
+                };
+                if key == "intertext" {
+                    UmapField::InterText(lines)
+                } else {
+                    UmapField::InterTextSecret(lines)
+                }
+            }
+            "episode" if clear => UmapField::Episode {
+                patch: "clear".into(),
+                name: "".into(),
+                key: "".into(),
+            },
+            "episode" => match texts.as_slice() {
+                [patch, name, key] => UmapField::Episode {
+                    patch: patch.to_string(),
+                    name: name.to_string(),
+                    key: key.to_string(),
+                },
+                _ => return None,
+            },
+            "bossaction" => {
+                let (thing, special, tag) = action()?;
+                UmapField::BossAction {
+                    thing,
+                    special,
+                    tag,
+                }
+            }
+            "bossactionednum" => {
+                let (ednum, special, tag) = action()?;
+                UmapField::BossActionEdNum {
+                    ednum,
+                    special,
+                    tag,
+                }
+            }
+            _ => return None,
+        };
+        Some(field)
+    }
+
     /// Returns a mutable reference to the inner string if the field is a simple text type.
     /// Excludes Label and InterTextSecret which have special "Clear" logic.
     pub fn as_string_mut(&mut self) -> Option<&mut String> {
@@ -108,6 +299,10 @@ pub struct MapEntry {
     pub mapname: String,
     /// The collection of Bespoke keys defined for this map.
     pub fields: Vec<UmapField>,
+    /// The block this map was read from, used to write untouched entries
+    /// back exactly as they were.
+    #[serde(skip)]
+    pub syntax: Option<BlockSyntax>,
 }
 
 /// The root structure for a UMAPINFO project lump.
@@ -116,6 +311,9 @@ pub struct UmapInfoFile {
     pub version: String,
     pub metadata: serde_json::Value,
     pub data: UmapInfoDefinition,
+    /// Text after the last map block of the parsed lump.
+    #[serde(skip)]
+    pub trailing: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -133,263 +331,586 @@ impl UmapInfoFile {
                 maps: vec![MapEntry {
                     mapname: "MAP01".to_string(),
                     fields: vec![UmapField::LevelName("Entryway".to_string())],
+                    ..Default::default()
                 }],
             },
+            trailing: String::new(),
         }
     }
 
-    /// Serializes the modular data model into the standard UMAPINFO plaintext format.
+    /// Serializes the modular data model into the standard UMAPINFO plaintext
+    /// format. Maps that were parsed keep their original text wherever their
+    /// fields haven't changed.
     pub fn to_umapinfo_text(&self) -> String {
         let mut out = String::new();
         for map in &self.data.maps {
-            out.push_str(&format!("map {}\n{{\n", map.mapname));
-            for field in &map.fields {
-                match field {
-                    UmapField::InterText(lines) | UmapField::InterTextSecret(lines) => {
-                        if lines.len() == 1 && lines[0] == "clear" {
-                            out.push_str(&format!("\t{} = clear\n", field.key_name()));
-                        } else {
-                            out.push_str(&format!("\t{} = ", field.key_name()));
-                            for (i, line) in lines.iter().enumerate() {
-                                let sep = if i == 0 { "" } else { ",\n\t\t" };
-                                out.push_str(&format!("{}\"{}\"", sep, line));
-                            }
-                            out.push_str("\n");
-                        }
+            match &map.syntax {
+                Some(syntax) => map.write_sourced(&mut out, syntax),
+                None => {
+                    // Keep a blank line after a block that came from the source.
+                    while !out.is_empty() && !out.ends_with("\n\n") {
+                        out.push('\n');
                     }
-                    UmapField::Episode { patch, name, key } => {
-                        if patch == "clear" {
-                            out.push_str("\tepisode = clear\n");
-                        } else {
-                            out.push_str(&format!(
-                                "\tepisode = \"{}\", \"{}\", \"{}\"\n",
-                                patch, name, key
-                            ));
-                        }
-                    }
-                    UmapField::Label(v) => {
-                        if v == "clear" {
-                            out.push_str("\tlabel = clear\n");
-                        } else {
-                            out.push_str(&format!("\tlabel = \"{}\"\n", v));
-                        }
-                    }
-                    UmapField::ParTime(v) => {
-                        out.push_str(&format!("\t{} = {}\n", field.key_name(), v))
-                    }
-                    UmapField::EndGame(v)
-                    | UmapField::EndBunny(v)
-                    | UmapField::EndCast(v)
-                    | UmapField::NoIntermission(v) => {
+                    out.push_str(&format!("map {}\n{{\n", map.mapname));
+                    for field in &map.fields {
                         out.push_str(&format!(
                             "\t{} = {}\n",
                             field.key_name(),
-                            if *v { "true" } else { "false" }
-                        ));
-                    }
-                    UmapField::BossAction {
-                        thing,
-                        special,
-                        tag,
-                    } => {
-                        out.push_str(&format!("\tbossaction = {}, {}, {}\n", thing, special, tag));
-                    }
-                    UmapField::BossActionEdNum {
-                        ednum,
-                        special,
-                        tag,
-                    } => {
-                        out.push_str(&format!(
-                            "\tbossactionednum = {}, {}, {}\n",
-                            ednum, special, tag
+                            field.value_text()
                         ));
                     }
-                    _ => {
-                        if let Some(s) = match field {
-                            UmapField::LevelName(s)
-                            | UmapField::Author(s)
-                            | UmapField::SkyTexture(s)
-                            | UmapField::Music(s)
-                            | UmapField::ExitPic(s)
-                            | UmapField::EnterPic(s)
-                            | UmapField::LevelPic(s)
-                            | UmapField::EndPic(s)
-                            | UmapField::InterBackdrop(s)
-                            | UmapField::InterMusic(s)
-                            | UmapField::Next(s)
-                            | UmapField::NextSecret(s) => Some(s),
-                            _ => None,
-                        } {
-                            out.push_str(&format!("\t{} = \"{}\"\n", field.key_name(), s));
-                        }
-                    }
+                    out.push_str("}\n\n");
                 }
             }
-            out.push_str("}\n\n");
         }
+        out.push_str(&self.trailing);
         out
     }
 
-    /// Attempts to parse a plaintext UMAPINFO lump into the internal modular model.
+    /// Parses a plaintext UMAPINFO lump into the internal modular model,
+    /// remembering its syntax so it can be written back losslessly.
     pub fn from_umapinfo_text(text: &str) -> Self {
-        let mut file = Self {
+        let syntax = UmapSyntax::parse(text);
+        let maps = syntax
+            .blocks
+            .into_iter()
+            .map(|block| MapEntry {
+                mapname: block.name.clone(),
+                fields: block.entries.iter().map(UmapField::from_entry).collect(),
+                syntax: Some(block),
+            })
+            .collect();
+        Self {
             version: "1.0.0".to_string(),
+            data: UmapInfoDefinition { maps },
+            trailing: syntax.tail,
             ..Default::default()
-        };
-        let mut current_map: Option<MapEntry> = None;
-        let mut pending_key: Option<String> = None;
-        let mut pending_values: Vec<String> = Vec::new();
+        }
+    }
+}
 
-        let flush_pending = |map: &mut MapEntry, key: &str, values: &mut Vec<String>| {
-            if values.is_empty() {
-                return;
-            }
+impl MapEntry {
+    /// Writes this map over the block it was parsed from. Unchanged fields
+    /// reuse their source text, edited ones keep their key and leading
+    /// comments, and new ones follow the block's indentation.
+    fn write_sourced(&self, out: &mut String, syntax: &BlockSyntax) {
+        out.push_str(&syntax.leading);
+        out.push_str(&syntax.keyword);
+        if self.mapname == syntax.name {
+            out.push_str(&syntax.name_text);
+        } else {
+            out.push_str(&self.mapname);
+        }
+        out.push_str(&syntax.open);
 
-            let clean_segment = |s: &str| -> String {
-                s.trim()
-                    .trim_matches(',')
-                    .trim()
-                    .trim_matches('"')
-                    .to_string()
+        let mut used = vec![false; syntax.entries.len()];
+        for field in &self.fields {
+            let key = field.key_name();
+            let source = (0..syntax.entries.len())
+                .find(|&i| !used[i] && syntax.entries[i].key.eq_ignore_ascii_case(key));
+            let Some(i) = source else {
+                out.push_str(syntax.indent());
+                out.push_str(&format!("{} = {}", key, field.value_text()));
+                continue;
             };
 
-            let val_joined = values.join(" ");
-            let val_clean = clean_segment(&val_joined);
-
-            let field = match key {
-                "levelname" => Some(UmapField::LevelName(val_clean)),
-                "author" => Some(UmapField::Author(val_clean)),
-                "skytexture" => Some(UmapField::SkyTexture(val_clean)),
-                "music" => Some(UmapField::Music(val_clean)),
-                "levelpic" => Some(UmapField::LevelPic(val_clean)),
-                "next" => Some(UmapField::Next(val_clean)),
-                "nextsecret" => Some(UmapField::NextSecret(val_clean)),
-                "label" => Some(UmapField::Label(val_clean)),
-                "exitpic" => Some(UmapField::ExitPic(val_clean)),
-                "enterpic" => Some(UmapField::EnterPic(val_clean)),
-                "endpic" => Some(UmapField::EndPic(val_clean)),
-                "interbackdrop" => Some(UmapField::InterBackdrop(val_clean)),
-                "intermusic" => Some(UmapField::InterMusic(val_clean)),
-                "partime" => val_clean.parse::<i32>().ok().map(UmapField::ParTime),
-                "endgame" => Some(UmapField::EndGame(val_clean.to_lowercase() == "true")),
-                "endbunny" => Some(UmapField::EndBunny(val_clean.to_lowercase() == "true")),
-                "endcast" => Some(UmapField::EndCast(val_clean.to_lowercase() == "true")),
-                "nointermission" => Some(UmapField::NoIntermission(
-                    val_clean.to_lowercase() == "true",
-                )),
-                "intertext" | "intertextsecret" => {
-                    if val_clean.to_lowercase() == "clear" {
-                        let f = if key == "intertext" {
-                            UmapField::InterText(vec!["clear".into()])
-                        } else {
-                            UmapField::InterTextSecret(vec!["clear".into()])
-                        };
-                        Some(f)
-                    } else {
-                        let lines = values
-                            .iter()
-                            .map(|s| clean_segment(s))
-                            .filter(|s| !s.is_empty())
-                            .collect();
-                        if key == "intertext" {
-                            Some(UmapField::InterText(lines))
-                        } else {
-                            Some(UmapField::InterTextSecret(lines))
-                        }
-                    }
-                }
-                "episode" => {
-                    if val_clean.to_lowercase() == "clear" {
-                        Some(UmapField::Episode {
-                            patch: "clear".into(),
-                            name: "".into(),
-                            key: "".into(),
-                        })
-                    } else {
-                        let parts: Vec<String> =
-                            val_joined.split(',').map(|s| clean_segment(s)).collect();
-                        if parts.len() >= 3 {
-                            Some(UmapField::Episode {
-                                patch: parts[0].clone(),
-                                name: parts[1].clone(),
-                                key: parts[2].clone(),
-                            })
-                        } else {
-                            None
-                        }
-                    }
-                }
-                "bossaction" | "bossactionednum" => {
-                    let parts: Vec<String> =
-                        val_joined.split(',').map(|s| clean_segment(s)).collect();
-                    if parts.len() >= 3 {
-                        if key == "bossaction" {
-                            Some(UmapField::BossAction {
-                                thing: parts[0].clone(),
-                                special: parts[1].parse().unwrap_or(0),
-                                tag: parts[2].parse().unwrap_or(0),
-                            })
-                        } else {
-                            Some(UmapField::BossActionEdNum {
-                                ednum: parts[0].clone(),
-                                special: parts[1].parse().unwrap_or(0),
-                                tag: parts[2].parse().unwrap_or(0),
-                            })
-                        }
-                    } else {
-                        None
-                    }
+            used[i] = true;
+            let entry = &syntax.entries[i];
+            out.push_str(&entry.leading);
+            if *field == UmapField::from_entry(entry) {
+                out.push_str(&entry.text);
+            } else {
+                out.push_str(&entry.text[..entry.value_offset]);
+                if entry.values.is_empty() {
+                    out.push(' ');
                 }
-                _ => None,
-            };
+                out.push_str(&field.value_text());
+            }
+        }
+        out.push_str(&syntax.close);
+    }
+}
+
+/// Actor names a `bossaction` may refer to, as used by ZDoom and the ports
+/// that follow it. Compared case-insensitively.
+pub const THING_MNEMONICS: &[&str] = &[
+    "DoomPlayer",
+    "ZombieMan",
+    "ShotgunGuy",
+    "Archvile",
+    "ArchvileFire",
+    "Revenant",
+    "RevenantTracer",
+    "RevenantTracerSmoke",
+    "Fatso",
+    "FatShot",
+    "ChaingunGuy",
+    "DoomImp",
+    "Demon",
+    "Spectre",
+    "Cacodemon",
+    "BaronOfHell",
+    "BaronBall",
+    "HellKnight",
+    "LostSoul",
+    "SpiderMastermind",
+    "Arachnotron",
+    "Cyberdemon",
+    "PainElemental",
+    "WolfensteinSS",
+    "CommanderKeen",
+    "BossBrain",
+    "BossEye",
+    "BossTarget",
+    "SpawnShot",
+    "SpawnFire",
+    "ExplosiveBarrel",
+    "DoomImpBall",
+    "CacodemonBall",
+    "Rocket",
+    "PlasmaBall",
+    "BFGBall",
+    "ArachnotronPlasma",
+    "BulletPuff",
+    "Blood",
+    "TeleportFog",
+    "ItemFog",
+    "TeleportDest",
+    "BFGExtra",
+    "GreenArmor",
+    "BlueArmor",
+    "HealthBonus",
+    "ArmorBonus",
+    "BlueCard",
+    "RedCard",
+    "YellowCard",
+    "YellowSkull",
+    "RedSkull",
+    "BlueSkull",
+    "Stimpack",
+    "Medikit",
+    "Soulsphere",
+    "InvulnerabilitySphere",
+    "Berserk",
+    "BlurSphere",
+    "RadSuit",
+    "Allmap",
+    "Infrared",
+    "Megasphere",
+    "Clip",
+    "ClipBox",
+    "RocketAmmo",
+    "RocketBox",
+    "Cell",
+    "CellPack",
+    "Shell",
+    "ShellBox",
+    "Backpack",
+    "BFG9000",
+    "Chaingun",
+    "Chainsaw",
+    "RocketLauncher",
+    "PlasmaRifle",
+    "Shotgun",
+    "SuperShotgun",
+    "TechLamp",
+    "TechLamp2",
+    "Column",
+    "TallGreenColumn",
+    "ShortGreenColumn",
+    "TallRedColumn",
+    "ShortRedColumn",
+    "SkullColumn",
+    "HeartColumn",
+    "EvilEye",
+    "FloatingSkull",
+    "TorchTree",
+    "BlueTorch",
+    "GreenTorch",
+    "RedTorch",
+    "ShortBlueTorch",
+    "ShortGreenTorch",
+    "ShortRedTorch",
+    "Stalagtite",
+    "TechPillar",
+    "CandleStick",
+    "Candelabra",
+    "BloodyTwitch",
+    "Meat2",
+    "Meat3",
+    "Meat4",
+    "Meat5",
+    "NonsolidMeat2",
+    "NonsolidMeat4",
+    "NonsolidMeat3",
+    "NonsolidMeat5",
+    "NonsolidTwitch",
+    "DeadCacodemon",
+    "DeadMarine",
+    "DeadZombieMan",
+    "DeadDemon",
+    "DeadLostSoul",
+    "DeadDoomImp",
+    "DeadShotgunGuy",
+    "GibbedMarine",
+    "GibbedMarineExtra",
+    "HeadsOnAStick",
+    "Gibs",
+    "HeadOnAStick",
+    "HeadCandles",
+    "DeadStick",
+    "LiveStick",
+    "BigTree",
+    "BurningBarrel",
+    "HangNoGuts",
+    "HangBNoBrain",
+    "HangTLookingDown",
+    "HangTSkull",
+    "HangTLookingUp",
+    "HangTNoBrain",
+    "ColonGibs",
+    "SmallBloodPool",
+    "BrainStem",
+];
+
+/// What kind of lump a UMAPINFO value names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LumpKind {
+    Graphic,
+    /// A graphic or a flat, as `interbackdrop` accepts either.
+    Backdrop,
+    Music,
+    /// A wall texture from TEXTURE1/2.
+    Texture,
+}
+
+/// A problem found by `UmapInfoFile::issues`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UmapIssue {
+    /// A key no UMAPINFO field knows about.
+    UnknownKey(String),
+    /// `next` or `nextsecret` names a map with no entry.
+    UndefinedMap { key: &'static str, target: String },
+    /// Every route from this map loops without reaching an ending.
+    EndlessLoop,
+    /// A picture, music or texture that isn't loaded.
+    MissingLump { key: String, name: String },
+    /// A `bossaction` thing that isn't a known actor name.
+    UnknownThing(String),
+}
 
-            if let Some(f) = field {
-                map.fields.push(f);
+impl UmapIssue {
+    pub fn message(&self) -> String {
+        match self {
+            Self::UnknownKey(key) => format!("Unknown key '{}'", key),
+            Self::UndefinedMap { key, target } => {
+                format!("'{}' points to {}, which has no entry", key, target)
             }
-            values.clear();
-        };
+            Self::EndlessLoop => "Every exit loops back without reaching an ending".to_string(),
+            Self::MissingLump { key, name } => format!("'{}' lump {} is not loaded", key, name),
+            Self::UnknownThing(thing) => format!("'{}' is not a known thing name", thing),
+        }
+    }
+}
 
-        for line in text.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
-                continue;
+/// An exit from one map to another in `UmapInfoFile::progression`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapExit {
+    pub from: usize,
+    pub to: usize,
+    pub secret: bool,
+}
+
+impl MapEntry {
+    fn field_str(&self, pick: impl Fn(&UmapField) -> Option<&String>) -> Option<&str> {
+        self.fields
+            .iter()
+            .find_map(pick)
+            .map(String::as_str)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// The maps this one exits to: (`next`, `nextsecret`).
+    pub fn exits(&self) -> (Option<&str>, Option<&str>) {
+        let next = self.field_str(|f| match f {
+            UmapField::Next(s) => Some(s),
+            _ => None,
+        });
+        let secret = self.field_str(|f| match f {
+            UmapField::NextSecret(s) => Some(s),
+            _ => None,
+        });
+        (next, secret)
+    }
+
+    /// Whether finishing this map ends the game or leaves the UMAPINFO's
+    /// maps, handing progression back to the game.
+    pub fn ends(&self) -> bool {
+        let ending = self.fields.iter().any(|f| {
+            matches!(
+                f,
+                UmapField::EndGame(true)
+                    | UmapField::EndBunny(true)
+                    | UmapField::EndCast(true)
+                    | UmapField::EndPic(_)
+            )
+        });
+        ending || self.exits().0.is_none()
+    }
+}
+
+impl UmapInfoFile {
+    fn map_index(&self) -> HashMap<String, usize> {
+        self.data
+            .maps
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, m)| (m.mapname.to_uppercase(), i))
+            .collect()
+    }
+
+    /// Every `next`/`nextsecret` exit between defined maps.
+    pub fn progression(&self) -> Vec<MapExit> {
+        let index = self.map_index();
+        let mut exits = Vec::new();
+        for (from, map) in self.data.maps.iter().enumerate() {
+            let (next, secret) = map.exits();
+            for (target, secret) in [(next, false), (secret, true)] {
+                if let Some(&to) = target.and_then(|t| index.get(&t.to_uppercase())) {
+                    exits.push(MapExit { from, to, secret });
+                }
             }
+        }
+        exits
+    }
 
-            if line.to_uppercase().starts_with("MAP") {
-                if let Some(mut m) = current_map.take() {
-                    if let Some(k) = pending_key.take() {
-                        flush_pending(&mut m, &k, &mut pending_values);
-                    }
-                    file.data.maps.push(m);
+    /// Maps from which no route of exits reaches an ending.
+    fn endless_maps(&self, exits: &[MapExit]) -> HashSet<usize> {
+        let maps = &self.data.maps;
+        let mut reaches_end: HashSet<usize> = (0..maps.len()).filter(|&i| maps[i].ends()).collect();
+        // Exits to undefined maps leave the UMAPINFO, which counts as an ending.
+        let index = self.map_index();
+        for (i, map) in maps.iter().enumerate() {
+            let (next, secret) = map.exits();
+            let leaves = [next, secret]
+                .into_iter()
+                .flatten()
+                .any(|t| !index.contains_key(&t.to_uppercase()));
+            if leaves {
+                reaches_end.insert(i);
+            }
+        }
+        loop {
+            let before = reaches_end.len();
+            for e in exits {
+                if reaches_end.contains(&e.to) {
+                    reaches_end.insert(e.from);
                 }
-                let name = line[3..].trim().trim_matches('{').trim().to_string();
-                current_map = Some(MapEntry {
-                    mapname: name,
-                    fields: vec![],
-                });
-                continue;
             }
+            if reaches_end.len() == before {
+                break;
+            }
+        }
+        (0..maps.len())
+            .filter(|i| !reaches_end.contains(i))
+            .collect()
+    }
 
-            if let Some(m) = current_map.as_mut() {
-                if line == "}" {
-                    if let Some(k) = pending_key.take() {
-                        flush_pending(m, &k, &mut pending_values);
-                    }
-                    file.data.maps.push(current_map.take().unwrap());
-                    continue;
+    /// Checks every map for problems a port would trip over or silently
+    /// ignore. `exists` says whether a lump of the given kind is loaded.
+    pub fn issues(&self, exists: impl Fn(LumpKind, &str) -> bool) -> Vec<(usize, UmapIssue)> {
+        let index = self.map_index();
+        let endless = self.endless_maps(&self.progression());
+        let mut issues = Vec::new();
+
+        for (i, map) in self.data.maps.iter().enumerate() {
+            let (next, secret) = map.exits();
+            for (key, target) in [("next", next), ("nextsecret", secret)] {
+                if let Some(t) = target.filter(|t| !index.contains_key(&t.to_uppercase())) {
+                    let target = t.to_string();
+                    issues.push((i, UmapIssue::UndefinedMap { key, target }));
+                }
+            }
+            if endless.contains(&i) {
+                issues.push((i, UmapIssue::EndlessLoop));
+            }
+
+            for field in &map.fields {
+                let lump = match field {
+                    UmapField::LevelPic(s)
+                    | UmapField::ExitPic(s)
+                    | UmapField::EnterPic(s)
+                    | UmapField::EndPic(s) => Some((LumpKind::Graphic, s)),
+                    UmapField::InterBackdrop(s) => Some((LumpKind::Backdrop, s)),
+                    UmapField::Music(s) | UmapField::InterMusic(s) => Some((LumpKind::Music, s)),
+                    UmapField::SkyTexture(s) => Some((LumpKind::Texture, s)),
+                    _ => None,
+                };
+                let missing = lump.filter(|(kind, name)| !name.is_empty() && !exists(*kind, name));
+                if let Some((_, name)) = missing {
+                    let key = field.key_name().to_string();
+                    issues.push((
+                        i,
+                        UmapIssue::MissingLump {
+                            key,
+                            name: name.clone(),
+                        },
+                    ));
                 }
 
-                if let Some((key, val)) = line.split_once('=') {
-                    if let Some(prev_key) = pending_key.take() {
-                        flush_pending(m, &prev_key, &mut pending_values);
+                let unknown_key = match field {
+                    UmapField::Unknown { key, .. } => Some(key),
+                    _ => None,
+                };
+                if let Some(key) =
+                    unknown_key.filter(|k| !KNOWN_KEYS.contains(&k.to_lowercase().as_str()))
+                {
+                    issues.push((i, UmapIssue::UnknownKey(key.clone())));
+                }
+
+                if let UmapField::BossAction { thing, .. } = field {
+                    let known = thing.parse::<u32>().is_ok()
+                        || THING_MNEMONICS
+                            .iter()
+                            .any(|m| m.eq_ignore_ascii_case(thing));
+                    if !known {
+                        issues.push((i, UmapIssue::UnknownThing(thing.clone())));
                     }
-                    pending_key = Some(key.trim().to_lowercase());
-                    pending_values.push(val.trim().to_string());
-                } else if pending_key.is_some() {
-                    pending_values.push(line.to_string());
                 }
             }
         }
-        file
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umapinfo_issues() {
+        let text = r#"
+map MAP01
+{
+    levelname = "Start"
+    next = "MAP02"
+    nextsecret = "MAP03"
+    music = "D_RUNNIN"
+    sparkles = "yes"
+    bossaction = Cyberdemon, 11, 0
+}
+map MAP02
+{
+    next = "MAP05"
+    levelpic = "CWILV01"
+    bossaction = Cyberdude, 11, 0
+}
+map MAP03
+{
+    next = "MAP04"
+}
+map MAP04
+{
+    next = "MAP03"
+    skytexture = "SKY1"
+}
+"#;
+        let file = UmapInfoFile::from_umapinfo_text(text);
+        let exists = |kind: LumpKind, name: &str| match kind {
+            LumpKind::Music => name == "D_RUNNIN",
+            _ => false,
+        };
+        let issues = file.issues(exists);
+        let expected = vec![
+            (0, UmapIssue::UnknownKey("sparkles".into())),
+            (
+                1,
+                UmapIssue::UndefinedMap {
+                    key: "next",
+                    target: "MAP05".into(),
+                },
+            ),
+            (
+                1,
+                UmapIssue::MissingLump {
+                    key: "levelpic".into(),
+                    name: "CWILV01".into(),
+                },
+            ),
+            (1, UmapIssue::UnknownThing("Cyberdude".into())),
+            (2, UmapIssue::EndlessLoop),
+            (3, UmapIssue::EndlessLoop),
+            (
+                3,
+                UmapIssue::MissingLump {
+                    key: "skytexture".into(),
+                    name: "SKY1".into(),
+                },
+            ),
+        ];
+        assert_eq!(issues, expected);
+
+        let secret = MapExit {
+            from: 0,
+            to: 2,
+            secret: true,
+        };
+        assert!(file.progression().contains(&secret));
+    }
+
+    #[test]
+    fn test_umapinfo_round_trip() {
+        let text = r#"// Episode one
+MAP map01 {
+    levelname = "Hangar"   // trailing note
+    /* DSDA extension */
+    author="Someone \"quoted\""
+    intertext = "Line one",
+                "Line two"
+    bossaction = clear
+    next = MAP02
+}
+
+map MAP02
+{
+  levelname = "Plant"
+  partime = 90
+}
+# end
+"#;
+        let syntax = UmapSyntax::parse(text);
+        assert_eq!(syntax.to_text(), text);
+
+        let mut file = UmapInfoFile::from_umapinfo_text(text);
+        assert_eq!(file.to_umapinfo_text(), text);
+
+        let map01 = &file.data.maps[0];
+        assert_eq!(map01.mapname, "map01");
+        assert_eq!(
+            map01.fields[1],
+            UmapField::Author("Someone \"quoted\"".into())
+        );
+        let bossaction = UmapField::Unknown {
+            key: "bossaction".into(),
+            raw_value: "clear".into(),
+        };
+        assert_eq!(map01.fields[3], bossaction);
+
+        let map02 = &mut file.data.maps[1];
+        map02.fields[1] = UmapField::ParTime(120);
+        map02.fields.remove(0);
+        map02.fields.push(UmapField::Next("MAP03".into()));
+        file.data.maps.push(MapEntry {
+            mapname: "MAP03".into(),
+            fields: vec![UmapField::EndGame(true)],
+            ..Default::default()
+        });
+
+        let saved = file.to_umapinfo_text();
+        assert!(saved.starts_with(&text[..text.find("\n\nmap MAP02").unwrap()]));
+        assert!(saved.ends_with(
+            "map MAP02\n{\n  partime = 120\n  next = \"MAP03\"\n}\n\nmap MAP03\n{\n\tendgame = true\n}\n\n\n# end\n"
+        ));
     }
 }
@@ -0,0 +1,336 @@
+//! Concrete syntax of UMAPINFO text. Every byte of the source ends up in
+//! exactly one string of the tree, so untouched parts of a lump can be
+//! written back exactly as they were read: comments, blank lines, key order
+//! and anything the parser didn't understand.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// Whitespace or a comment.
+    Trivia,
+    /// A bare word or number.
+    Word,
+    /// A double-quoted string, quotes included.
+    Quoted,
+    /// One of `=`, `,`, `{` or `}`.
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Range<usize>,
+}
+
+/// A single value on the right of a key's `=`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueSyntax {
+    /// A quoted string, unescaped.
+    Quoted(String),
+    /// A bare word such as `true`, `clear`, a number or an actor name.
+    Bare(String),
+}
+
+impl ValueSyntax {
+    pub fn text(&self) -> &str {
+        match self {
+            ValueSyntax::Quoted(s) | ValueSyntax::Bare(s) => s,
+        }
+    }
+
+    /// Whether this is the bare keyword `word`, ignoring case.
+    pub fn is_word(&self, word: &str) -> bool {
+        matches!(self, ValueSyntax::Bare(s) if s.eq_ignore_ascii_case(word))
+    }
+}
+
+/// A `key = value, value…` line inside a map block.
+#[derive(Debug, Clone, Default)]
+pub struct EntrySyntax {
+    /// Whitespace, comments and stray text before the key.
+    pub leading: String,
+    /// The key as written.
+    pub key: String,
+    /// Source text from the key to the end of the last value.
+    pub text: String,
+    /// Where the values start within `text`.
+    pub value_offset: usize,
+    pub values: Vec<ValueSyntax>,
+}
+
+impl EntrySyntax {
+    /// The source text of the values, commas and line breaks included.
+    pub fn raw_value(&self) -> &str {
+        &self.text[self.value_offset..]
+    }
+}
+
+/// A `map NAME { … }` block.
+#[derive(Debug, Clone, Default)]
+pub struct BlockSyntax {
+    /// Everything between the previous block and this one's `map` keyword.
+    pub leading: String,
+    /// The `map` keyword and the trivia after it.
+    pub keyword: String,
+    /// The map name, unquoted.
+    pub name: String,
+    /// The map name token as written.
+    pub name_text: String,
+    /// Trivia after the name and the opening brace.
+    pub open: String,
+    pub entries: Vec<EntrySyntax>,
+    /// Trivia after the last entry and the closing brace.
+    pub close: String,
+}
+
+impl BlockSyntax {
+    /// The line break and indentation the block's entries start with.
+    pub fn indent(&self) -> &str {
+        self.entries
+            .first()
+            .and_then(|e| e.leading.rfind('\n').map(|i| &e.leading[i..]))
+            .filter(|s| s[1..].chars().all(|c| c == ' ' || c == '\t'))
+            .unwrap_or("\n\t")
+    }
+}
+
+/// A whole UMAPINFO lump.
+#[derive(Debug, Clone, Default)]
+pub struct UmapSyntax {
+    pub blocks: Vec<BlockSyntax>,
+    /// Everything after the last block.
+    pub tail: String,
+}
+
+impl UmapSyntax {
+    pub fn parse(text: &str) -> Self {
+        Parser::new(text).parse()
+    }
+
+    /// Writes the tree back out; gives back the parsed text unchanged.
+    #[cfg(test)]
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for block in &self.blocks {
+            out.push_str(&block.leading);
+            out.push_str(&block.keyword);
+            out.push_str(&block.name_text);
+            out.push_str(&block.open);
+            for entry in &block.entries {
+                out.push_str(&entry.leading);
+                out.push_str(&entry.text);
+            }
+            out.push_str(&block.close);
+        }
+        out.push_str(&self.tail);
+        out
+    }
+}
+
+/// Quotes a string for UMAPINFO, escaping quotes and backslashes.
+pub fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(s: &str) -> String {
+    let inner = s.strip_prefix('"').unwrap_or(s);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            out.extend(chars.next());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let rest = &text[i..];
+        let kind = if bytes[i].is_ascii_whitespace() {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            TokenKind::Trivia
+        } else if rest.starts_with("//") || rest.starts_with('#') {
+            i += rest.find('\n').unwrap_or(rest.len());
+            TokenKind::Trivia
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            i += comment.find("*/").map_or(rest.len(), |end| end + 4);
+            TokenKind::Trivia
+        } else if bytes[i] == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            TokenKind::Quoted
+        } else if b"=,{}".contains(&bytes[i]) {
+            i += 1;
+            TokenKind::Punct
+        } else {
+            while i < bytes.len()
+                && !bytes[i].is_ascii_whitespace()
+                && !b"=,{}\"".contains(&bytes[i])
+                && !bytes[i..].starts_with(b"//")
+                && !bytes[i..].starts_with(b"/*")
+            {
+                i += 1;
+            }
+            TokenKind::Word
+        };
+        // Stay on a character boundary if a multi-byte character was skipped into.
+        while !text.is_char_boundary(i) {
+            i += 1;
+        }
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            tokens: tokenize(text),
+        }
+    }
+
+    /// The first non-trivia token at or after `i`.
+    fn significant(&self, i: usize) -> Option<usize> {
+        (i..self.tokens.len()).find(|&t| self.tokens[t].kind != TokenKind::Trivia)
+    }
+
+    fn src(&self, t: usize) -> &'a str {
+        &self.text[self.tokens[t].span.clone()]
+    }
+
+    fn is_punct(&self, t: usize, c: &str) -> bool {
+        self.tokens[t].kind == TokenKind::Punct && self.src(t) == c
+    }
+
+    fn is_value(&self, t: usize) -> bool {
+        matches!(self.tokens[t].kind, TokenKind::Word | TokenKind::Quoted)
+    }
+
+    fn value(&self, t: usize) -> ValueSyntax {
+        match self.tokens[t].kind {
+            TokenKind::Quoted => ValueSyntax::Quoted(unquote(self.src(t))),
+            _ => ValueSyntax::Bare(self.src(t).to_string()),
+        }
+    }
+
+    fn start(&self, t: usize) -> usize {
+        self.tokens[t].span.start
+    }
+
+    fn end(&self, t: usize) -> usize {
+        self.tokens[t].span.end
+    }
+
+    fn parse(&self) -> UmapSyntax {
+        let mut tree = UmapSyntax::default();
+        // Start of the text not yet claimed by a block.
+        let mut cursor = 0;
+        let mut i = 0;
+
+        while let Some(t) = self.significant(i) {
+            i = t + 1;
+            let is_map =
+                self.tokens[t].kind == TokenKind::Word && self.src(t).eq_ignore_ascii_case("map");
+            let name = self
+                .significant(t + 1)
+                .filter(|&n| is_map && self.is_value(n));
+            let open = name
+                .and_then(|n| self.significant(n + 1))
+                .filter(|&o| self.is_punct(o, "{"));
+            let (Some(name), Some(open)) = (name, open) else {
+                continue;
+            };
+
+            let (entries, close, end) = self.parse_entries(open + 1);
+            tree.blocks.push(BlockSyntax {
+                leading: self.text[cursor..self.start(t)].to_string(),
+                keyword: self.text[self.start(t)..self.start(name)].to_string(),
+                name: self.value(name).text().to_string(),
+                name_text: self.src(name).to_string(),
+                open: self.text[self.end(name)..self.end(open)].to_string(),
+                entries,
+                close: self.text[close..end].to_string(),
+            });
+            cursor = end;
+            i = self.tokens.partition_point(|tok| tok.span.start < end);
+        }
+
+        tree.tail = self.text[cursor..].to_string();
+        tree
+    }
+
+    /// Reads entries up to the block's closing brace. Returns the entries,
+    /// where the closing text starts, and where the block ends.
+    fn parse_entries(&self, mut i: usize) -> (Vec<EntrySyntax>, usize, usize) {
+        let mut entries = Vec::new();
+        let mut cursor = self.tokens.get(i).map_or(self.text.len(), |t| t.span.start);
+
+        while let Some(t) = self.significant(i) {
+            if self.is_punct(t, "}") {
+                return (entries, cursor, self.end(t));
+            }
+            i = t + 1;
+            let eq = self
+                .significant(t + 1)
+                .filter(|&e| self.tokens[t].kind == TokenKind::Word && self.is_punct(e, "="));
+            let Some(eq) = eq else {
+                continue;
+            };
+
+            let mut values = Vec::new();
+            let mut last = eq;
+            let mut next = self.significant(eq + 1).filter(|&v| self.is_value(v));
+            while let Some(v) = next {
+                values.push(self.value(v));
+                last = v;
+                next = self
+                    .significant(v + 1)
+                    .filter(|&c| self.is_punct(c, ","))
+                    .and_then(|c| self.significant(c + 1))
+                    .filter(|&v| self.is_value(v));
+            }
+
+            let value_start = if values.is_empty() {
+                self.end(eq)
+            } else {
+                self.significant(eq + 1)
+                    .map_or(self.end(eq), |v| self.start(v))
+            };
+            entries.push(EntrySyntax {
+                leading: self.text[cursor..self.start(t)].to_string(),
+                key: self.src(t).to_string(),
+                text: self.text[self.start(t)..self.end(last)].to_string(),
+                value_offset: value_start - self.start(t),
+                values,
+            });
+            cursor = self.end(last);
+            i = last + 1;
+        }
+
+        // Unterminated block: it runs to the end of the text.
+        (entries, cursor, self.text.len())
+    }
+}
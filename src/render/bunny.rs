@@ -0,0 +1,160 @@
+use super::projection::ViewportProjection;
+use crate::assets::{AssetId, AssetStore};
+use crate::constants::{DOOM_H, DOOM_TICS_PER_SEC, DOOM_W};
+use crate::models::finale::BunnyDef;
+use eframe::egui;
+
+/// Tic the stitch starts scrolling at, as in `F_BunnyScroll`.
+const SCROLL_START: f64 = 230.0;
+
+/// Tic the first overlay appears at.
+const OVERLAY_START: f64 = 1130.0;
+
+/// Tic the overlay starts counting up, one step every `OVERLAY_STEP` tics.
+const OVERLAY_COUNT_START: f64 = 1180.0;
+const OVERLAY_STEP: f64 = 5.0;
+
+/// How long the last overlay stays up before the timeline ends.
+const END_HOLD: f64 = 2.0 * DOOM_TICS_PER_SEC;
+
+/// Playback position of the bunny scroller preview.
+#[derive(Debug, Clone)]
+pub struct BunnyPlayback {
+    pub tic: f64,
+    pub playing: bool,
+    last_time: Option<f64>,
+}
+
+impl Default for BunnyPlayback {
+    fn default() -> Self {
+        Self {
+            tic: 0.0,
+            playing: true,
+            last_time: None,
+        }
+    }
+}
+
+impl BunnyPlayback {
+    /// Advances playback to `time`, in seconds, looping at the end.
+    pub fn advance(&mut self, def: &BunnyDef, time: f64) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).max(0.0));
+        self.last_time = Some(time);
+        if self.playing {
+            self.tic = (self.tic + dt * DOOM_TICS_PER_SEC) % total_tics(def);
+        }
+    }
+}
+
+/// Length of the whole sequence, up to a short hold on the last overlay.
+pub fn total_tics(def: &BunnyDef) -> f64 {
+    let stages = (def.overlaycount - 1).max(0) as f64;
+    OVERLAY_COUNT_START + stages * OVERLAY_STEP + END_HOLD
+}
+
+/// How many columns of the stitch image are still on screen at `tic`.
+/// Starts at the full width and scrolls one column every two tics.
+pub fn scroll_offset(tic: f64) -> f32 {
+    let scrolled = DOOM_W as f64 - ((tic - SCROLL_START) / 2.0).floor();
+    scrolled.clamp(0.0, DOOM_W as f64) as f32
+}
+
+/// The overlay patch number showing at `tic`, if any.
+pub fn overlay_at(def: &BunnyDef, tic: f64) -> Option<i32> {
+    if tic < OVERLAY_START || def.overlaycount <= 0 {
+        return None;
+    }
+    let stage = ((tic - OVERLAY_COUNT_START) / OVERLAY_STEP)
+        .floor()
+        .max(0.0) as i32;
+    Some(def.overlay + stage.min(def.overlaycount - 1))
+}
+
+/// Draws the scroller at `tic`: the stitch image sliding out to the left
+/// while the background slides in behind it, then the `END` overlays.
+pub fn draw_bunny(
+    painter: &egui::Painter,
+    def: &BunnyDef,
+    background: &str,
+    tic: f64,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) {
+    let scrolled = scroll_offset(tic);
+    let split = DOOM_W - scrolled;
+    let uv_x = scrolled / DOOM_W;
+
+    // Columns left of the split come from the background, offset by the
+    // scroll; the rest are the start of the stitch image.
+    let strips = [
+        (background, 0.0, split, uv_x, 1.0),
+        (def.stitchimage.as_str(), split, DOOM_W, 0.0, uv_x),
+    ];
+    for (lump, x0, x1, u0, u1) in strips {
+        if x1 <= x0 {
+            continue;
+        }
+        if let Some(tex) = assets.texture(painter.ctx(), &AssetId::new(lump)) {
+            let rect = egui::Rect::from_min_max(
+                proj.to_screen(egui::pos2(proj.origin_x + x0, 0.0)),
+                proj.to_screen(egui::pos2(proj.origin_x + x1, DOOM_H)),
+            );
+            let uv = egui::Rect::from_min_max(egui::pos2(u0, 0.0), egui::pos2(u1, 1.0));
+            painter.image(tex.id(), rect, uv, egui::Color32::WHITE);
+        }
+    }
+
+    let Some(number) = overlay_at(def, tic) else {
+        return;
+    };
+    let id = AssetId::new(&format!("END{}", number));
+    if let Some(tex) = assets.texture(painter.ctx(), &id) {
+        let (left, top) = assets
+            .offsets
+            .get(&id)
+            .map(|(x, y)| (*x as f32, *y as f32))
+            .unwrap_or((0.0, 0.0));
+        let pos = egui::pos2(
+            proj.origin_x + def.overlayx as f32 - left,
+            def.overlayy as f32 - top,
+        );
+        let rect = egui::Rect::from_min_size(
+            proj.to_screen(pos),
+            tex.size_vec2() * egui::vec2(proj.final_scale_x, proj.final_scale_y),
+        );
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        painter.image(tex.id(), rect, uv, egui::Color32::WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bunny_timeline() {
+        let def = BunnyDef::doom2();
+
+        assert_eq!(scroll_offset(0.0), 320.0);
+        assert_eq!(scroll_offset(230.0), 320.0);
+        assert_eq!(scroll_offset(330.0), 270.0);
+        assert_eq!(scroll_offset(870.0), 0.0);
+        assert_eq!(scroll_offset(2000.0), 0.0);
+
+        assert_eq!(overlay_at(&def, 1129.0), None);
+        assert_eq!(overlay_at(&def, 1130.0), Some(0));
+        assert_eq!(overlay_at(&def, 1190.0), Some(2));
+        assert_eq!(overlay_at(&def, 1500.0), Some(6));
+
+        let mut playback = BunnyPlayback::default();
+        playback.advance(&def, 0.0);
+        playback.advance(&def, 10.0);
+        assert!((playback.tic - 350.0).abs() < 1e-6);
+
+        // Paused playback holds its position while time moves on.
+        playback.playing = false;
+        playback.advance(&def, 20.0);
+        assert!((playback.tic - 350.0).abs() < 1e-6);
+        assert_eq!(total_tics(&def), 1180.0 + 30.0 + 70.0);
+    }
+}
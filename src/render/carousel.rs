@@ -0,0 +1,216 @@
+use super::graphic::draw_simple_graphic_patch;
+use super::{RenderContext, get_alignment_anchor_offset};
+use crate::assets::AssetId;
+use crate::models::sbardef::*;
+use crate::state::PreviewState;
+use crate::state::simulation::{Inventory, SlotMapping};
+use crate::state::viewer::WEAPON_LOWER_HEIGHT;
+use eframe::egui;
+
+/// Horizontal distance between neighbouring weapon icons.
+const ICON_SPACING: f32 = 40.0;
+/// The frame drawn around the selected weapon.
+const CELL_SIZE: egui::Vec2 = egui::vec2(36.0, 24.0);
+/// How many icons stay visible either side of the selection before fading out.
+const VISIBLE_RADIUS: f32 = 2.5;
+
+/// The area a carousel occupies, used for alignment and hit testing.
+pub(super) const CAROUSEL_SIZE: egui::Vec2 =
+    egui::vec2(ICON_SPACING * VISIBLE_RADIUS * 2.0, CELL_SIZE.y);
+
+/// Every weapon in the order the carousel lists them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Weapon {
+    Fist,
+    Chainsaw,
+    Pistol,
+    Shotgun,
+    SuperShotgun,
+    Chaingun,
+    RocketLauncher,
+    PlasmaGun,
+    Bfg,
+}
+
+impl Weapon {
+    const ALL: [Weapon; 9] = [
+        Weapon::Fist,
+        Weapon::Chainsaw,
+        Weapon::Pistol,
+        Weapon::Shotgun,
+        Weapon::SuperShotgun,
+        Weapon::Chaingun,
+        Weapon::RocketLauncher,
+        Weapon::PlasmaGun,
+        Weapon::Bfg,
+    ];
+
+    /// The pickup sprite used as the icon. Every IWAD ships these, except the
+    /// fist, which borrows the berserk pack.
+    fn sprite(self) -> &'static str {
+        match self {
+            Weapon::Fist => "PSTRA0",
+            Weapon::Chainsaw => "CSAWA0",
+            Weapon::Pistol => "PISTA0",
+            Weapon::Shotgun => "SHOTA0",
+            Weapon::SuperShotgun => "SGN2A0",
+            Weapon::Chaingun => "MGUNA0",
+            Weapon::RocketLauncher => "LAUNA0",
+            Weapon::PlasmaGun => "PLASA0",
+            Weapon::Bfg => "BFUGA0",
+        }
+    }
+
+    fn is_owned(self, inv: &Inventory) -> bool {
+        match self {
+            Weapon::Fist => inv.has_fist,
+            Weapon::Chainsaw => inv.has_chainsaw,
+            Weapon::Pistol => inv.has_pistol,
+            Weapon::Shotgun => inv.has_shotgun,
+            Weapon::SuperShotgun => inv.has_super_shotgun,
+            Weapon::Chaingun => inv.has_chaingun,
+            Weapon::RocketLauncher => inv.has_rocket_launcher,
+            Weapon::PlasmaGun => inv.has_plasma_gun,
+            Weapon::Bfg => inv.has_bfg,
+        }
+    }
+
+    /// Resolves a weapon slot to the weapon in it, following the same slot
+    /// overloading rules as the viewport's weapon sprite.
+    fn from_slot(slot: u8, use_ssg: bool, state: &PreviewState) -> Option<Weapon> {
+        let vanilla = state.sim.engine.slot_mapping == SlotMapping::Vanilla;
+        match slot {
+            1 if vanilla && state.sim.inventory.has_chainsaw => Some(Weapon::Chainsaw),
+            1 => Some(Weapon::Fist),
+            2 => Some(Weapon::Pistol),
+            3 if vanilla && use_ssg && state.sim.inventory.has_super_shotgun => {
+                Some(Weapon::SuperShotgun)
+            }
+            3 => Some(Weapon::Shotgun),
+            4 => Some(Weapon::Chaingun),
+            5 => Some(Weapon::RocketLauncher),
+            6 => Some(Weapon::PlasmaGun),
+            7 => Some(Weapon::Bfg),
+            8 => Some(Weapon::Chainsaw),
+            9 => Some(Weapon::SuperShotgun),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the owned weapons plus the strip's scroll position (as a fractional
+/// index into them) and the index of the highlighted weapon.
+///
+/// While a weapon switch is in progress the strip slides from the weapon
+/// being lowered towards the one being raised, in step with the viewport's
+/// weapon sprite.
+fn carousel_layout(state: &PreviewState) -> (Vec<Weapon>, f32, Option<usize>) {
+    let owned: Vec<Weapon> = Weapon::ALL
+        .into_iter()
+        .filter(|w| w.is_owned(&state.sim.inventory))
+        .collect();
+
+    let index_of = |weapon: Option<Weapon>| owned.iter().position(|w| Some(*w) == weapon);
+
+    let shown = index_of(Weapon::from_slot(
+        state.viewer.display_weapon_slot,
+        state.viewer.display_super_shotgun,
+        state,
+    ));
+    let target = index_of(Weapon::from_slot(
+        state.sim.selected_weapon_slot,
+        state.sim.use_super_shotgun,
+        state,
+    ));
+
+    let scroll = match (shown, target) {
+        (Some(from), Some(to)) if from != to => {
+            let t = (state.viewer.weapon_offset_y / WEAPON_LOWER_HEIGHT).clamp(0.0, 1.0);
+            from as f32 + (to as f32 - from as f32) * t
+        }
+        (_, Some(to)) => to as f32,
+        (Some(from), None) => from as f32,
+        (None, None) => 0.0,
+    };
+
+    (owned, scroll, target.or(shown))
+}
+
+/// Renders the weapon carousel as a strip of owned weapons centred on the
+/// current selection.
+pub(super) fn draw_carousel(ctx: &RenderContext, def: &CarouselDef, pos: egui::Pos2, alpha: f32) {
+    let area = egui::Rect::from_min_size(egui::Pos2::ZERO, CAROUSEL_SIZE);
+    let center =
+        pos + get_alignment_anchor_offset(def.common.alignment, area) + area.center().to_vec2();
+
+    let (owned, scroll, selected) = carousel_layout(ctx.state);
+
+    let icon_align = Alignment::H_CENTER
+        | Alignment::V_CENTER
+        | Alignment::NO_LEFT_OFFSET
+        | Alignment::NO_TOP_OFFSET;
+
+    for (idx, weapon) in owned.iter().enumerate() {
+        let distance = idx as f32 - scroll;
+        let fade = (VISIBLE_RADIUS - distance.abs()).clamp(0.0, 1.0);
+        if fade <= 0.0 {
+            continue;
+        }
+
+        let emphasis = if Some(idx) == selected { 1.0 } else { 0.5 };
+        let icon_pos = egui::pos2((center.x + distance * ICON_SPACING).floor(), center.y);
+
+        draw_simple_graphic_patch(
+            ctx,
+            AssetId::new(weapon.sprite()),
+            icon_pos,
+            icon_align,
+            alpha * fade * emphasis,
+            &None,
+        );
+    }
+
+    if selected.is_some() {
+        let cell = egui::Rect::from_center_size(center, CELL_SIZE);
+        let gold = egui::Color32::from_rgb(255, 200, 0).gamma_multiply(alpha);
+        ctx.painter.rect_stroke(
+            ctx.to_screen_rect(cell),
+            0.0,
+            egui::Stroke::new(ctx.proj.final_scale_x.max(1.0), gold),
+            egui::StrokeKind::Inside,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_follows_weapon_switch() {
+        let mut state = PreviewState::default();
+        state.sim.inventory.has_shotgun = true;
+        state.sim.inventory.has_chaingun = true;
+
+        let (owned, scroll, selected) = carousel_layout(&state);
+        assert_eq!(
+            owned,
+            vec![
+                Weapon::Fist,
+                Weapon::Pistol,
+                Weapon::Shotgun,
+                Weapon::Chaingun
+            ]
+        );
+        assert_eq!((scroll, selected), (1.0, Some(1)));
+
+        state.sim.selected_weapon_slot = 4;
+        state.viewer.weapon_offset_y = WEAPON_LOWER_HEIGHT / 2.0;
+        let (_, scroll, selected) = carousel_layout(&state);
+        assert_eq!((scroll, selected), (2.0, Some(3)));
+
+        state.viewer.display_weapon_slot = 4;
+        let (_, scroll, _) = carousel_layout(&state);
+        assert_eq!(scroll, 3.0);
+    }
+}
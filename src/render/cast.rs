@@ -0,0 +1,332 @@
+use super::projection::ViewportProjection;
+use crate::assets::{AssetId, AssetStore};
+use crate::models::finale::{CastFrame, CastRollCallDef};
+use eframe::egui;
+
+/// Where vanilla places the actor's origin on the cast screen.
+const ACTOR_POS: egui::Pos2 = egui::pos2(160.0, 170.0);
+
+/// Baseline of the actor's name, centred under the sprite.
+const NAME_Y: f32 = 180.0;
+
+/// Width of a space or any character missing from the HUD font.
+const SPACE_W: f32 = 4.0;
+
+/// How long a frame without a positive duration is held before moving on.
+const HOLD_SECONDS: f64 = 1.0;
+
+/// Longest step taken in one update, so a stalled window doesn't skip members.
+const MAX_STEP: f64 = 1.0;
+
+/// The vanilla cast names, keyed by their DeHackEd string mnemonics.
+const CAST_MNEMONICS: &[(&str, &str)] = &[
+    ("CC_ZOMBIE", "ZOMBIEMAN"),
+    ("CC_SHOTGUN", "SHOTGUN GUY"),
+    ("CC_HEAVY", "HEAVY WEAPON DUDE"),
+    ("CC_IMP", "IMP"),
+    ("CC_DEMON", "DEMON"),
+    ("CC_LOST", "LOST SOUL"),
+    ("CC_CACO", "CACODEMON"),
+    ("CC_HELL", "HELL KNIGHT"),
+    ("CC_BARON", "BARON OF HELL"),
+    ("CC_ARACH", "ARACHNOTRON"),
+    ("CC_PAIN", "PAIN ELEMENTAL"),
+    ("CC_REVEN", "REVENANT"),
+    ("CC_MANCU", "MANCUBUS"),
+    ("CC_ARCH", "ARCH-VILE"),
+    ("CC_SPIDER", "THE SPIDER MASTERMIND"),
+    ("CC_CYBER", "THE CYBERDEMON"),
+    ("CC_HERO", "OUR HERO"),
+];
+
+/// Which of a cast member's sequences is playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CastPhase {
+    #[default]
+    Alive,
+    Dying,
+}
+
+/// Playback position of the cast roll call preview.
+#[derive(Debug, Clone, Default)]
+pub struct CastPlayback {
+    pub member: usize,
+    pub phase: CastPhase,
+    pub frame: usize,
+    /// The layer list selection the preview last jumped to.
+    pub followed: Option<usize>,
+    /// The sound most recently started. There's no audio output, so the
+    /// viewport shows it as a caption instead.
+    pub last_sound: Option<String>,
+    elapsed: f64,
+    last_time: Option<f64>,
+}
+
+impl CastPlayback {
+    /// Starts a member's alive sequence, as the cast call does when a new
+    /// actor walks on.
+    pub fn jump_to(&mut self, member: usize, cast: &CastRollCallDef) {
+        let count = cast.castmembers.len().max(1);
+        self.member = member % count;
+        self.phase = CastPhase::Alive;
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.last_sound = cast
+            .castmembers
+            .get(self.member)
+            .map(|m| m.sound.clone())
+            .filter(|s| !s.is_empty());
+        self.start_frame(cast);
+    }
+
+    /// Kills the current member. Members without a death sequence are
+    /// skipped straight to the next one.
+    pub fn kill(&mut self, cast: &CastRollCallDef) {
+        if self.phase == CastPhase::Dying {
+            return;
+        }
+        let has_death = cast
+            .castmembers
+            .get(self.member)
+            .is_some_and(|m| !m.dead.is_empty());
+        if has_death {
+            self.phase = CastPhase::Dying;
+            self.frame = 0;
+            self.elapsed = 0.0;
+            self.start_frame(cast);
+        } else {
+            self.jump_to(self.member + 1, cast);
+        }
+    }
+
+    /// Advances playback to `time`, in seconds.
+    pub fn advance(&mut self, cast: &CastRollCallDef, time: f64) {
+        if self.last_time.is_none() || self.member >= cast.castmembers.len() {
+            self.jump_to(self.member, cast);
+        }
+        let dt = self
+            .last_time
+            .map_or(0.0, |last| (time - last).clamp(0.0, MAX_STEP));
+        self.last_time = Some(time);
+
+        if self.frames(cast).is_empty() {
+            return;
+        }
+
+        self.elapsed += dt;
+        loop {
+            let duration = match self.current_frame(cast) {
+                Some(f) if f.duration > 0.0 => f.duration,
+                Some(_) => HOLD_SECONDS,
+                None => {
+                    self.frame = 0;
+                    return;
+                }
+            };
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            self.step(cast);
+        }
+    }
+
+    /// Returns the frame currently on screen.
+    pub fn current_frame<'a>(&self, cast: &'a CastRollCallDef) -> Option<&'a CastFrame> {
+        self.frames(cast).get(self.frame)
+    }
+
+    fn frames<'a>(&self, cast: &'a CastRollCallDef) -> &'a [CastFrame] {
+        match cast.castmembers.get(self.member) {
+            Some(m) if self.phase == CastPhase::Dying => &m.dead,
+            Some(m) => &m.alive,
+            None => &[],
+        }
+    }
+
+    fn step(&mut self, cast: &CastRollCallDef) {
+        let len = self.frames(cast).len();
+        match self.phase {
+            CastPhase::Alive => self.frame = (self.frame + 1) % len.max(1),
+            CastPhase::Dying if self.frame + 1 < len => self.frame += 1,
+            CastPhase::Dying => {
+                self.jump_to(self.member + 1, cast);
+                return;
+            }
+        }
+        self.start_frame(cast);
+    }
+
+    fn start_frame(&mut self, cast: &CastRollCallDef) {
+        if let Some(sound) = self.current_frame(cast).and_then(|f| f.sound.clone()) {
+            self.last_sound = Some(sound);
+        }
+    }
+}
+
+/// Resolves a cast name, expanding the vanilla DeHackEd mnemonics.
+pub fn display_name(name: &str) -> &str {
+    CAST_MNEMONICS
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, value)| value)
+}
+
+/// Draws the current cast frame and the member's name, like `F_CastDrawer`.
+pub fn draw_cast_call(
+    painter: &egui::Painter,
+    cast: &CastRollCallDef,
+    playback: &CastPlayback,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) {
+    let Some(member) = cast.castmembers.get(playback.member) else {
+        return;
+    };
+
+    if let Some(frame) = playback.current_frame(cast) {
+        draw_cast_frame(painter, frame, assets, proj);
+    }
+
+    let name = display_name(&member.name).to_ascii_uppercase();
+    let glyphs: Vec<_> = name
+        .chars()
+        .map(|c| {
+            let tex = (c != ' ')
+                .then(|| assets.texture(painter.ctx(), &assets.resolve_patch_id("STCFN", c, false)))
+                .flatten();
+            let width = tex.as_ref().map_or(SPACE_W, |t| t.size_vec2().x);
+            (tex, width)
+        })
+        .collect();
+
+    let total: f32 = glyphs.iter().map(|(_, w)| w).sum();
+    let mut x = proj.origin_x + 160.0 - (total / 2.0).floor();
+    for (tex, width) in glyphs {
+        if let Some(tex) = tex {
+            let min = proj.to_screen(egui::pos2(x, NAME_Y));
+            let size = tex.size_vec2() * egui::vec2(proj.final_scale_x, proj.final_scale_y);
+            painter.image(
+                tex.id(),
+                egui::Rect::from_min_size(min, size),
+                egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+        }
+        x += width;
+    }
+}
+
+fn draw_cast_frame(
+    painter: &egui::Painter,
+    frame: &CastFrame,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) {
+    let id = AssetId::new(&frame.image);
+    let tex = assets.patch_texture(
+        painter.ctx(),
+        &id,
+        frame.translation.as_deref(),
+        frame.tranmap.as_deref(),
+    );
+    let Some(tex) = tex else {
+        return;
+    };
+
+    let size = tex.size_vec2();
+    let (left, top) = assets
+        .offsets
+        .get(&id)
+        .map(|(x, y)| (*x as f32, *y as f32))
+        .unwrap_or((0.0, 0.0));
+
+    // Flipped frames mirror around the origin, so the left offset is measured
+    // from the other edge.
+    let x = if frame.flip {
+        ACTOR_POS.x - (size.x - left)
+    } else {
+        ACTOR_POS.x - left
+    };
+    let min = proj.to_screen(egui::pos2(proj.origin_x + x, ACTOR_POS.y - top));
+    let rect = egui::Rect::from_min_size(
+        min,
+        size * egui::vec2(proj.final_scale_x, proj.final_scale_y),
+    );
+
+    let uv = if frame.flip {
+        egui::Rect::from_min_max(egui::pos2(1.0, 0.0), egui::pos2(0.0, 1.0))
+    } else {
+        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0))
+    };
+
+    let alpha = frame
+        .tranmap
+        .as_deref()
+        .and_then(|name| assets.tranmap_opacity(name))
+        .unwrap_or(1.0);
+    let tint = egui::Color32::from_white_alpha((255.0 * alpha) as u8);
+
+    painter.image(tex.id(), rect, uv, tint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::finale::CastMember;
+
+    fn frame(image: &str, tics: f64) -> CastFrame {
+        CastFrame {
+            image: image.to_string(),
+            duration: tics / crate::constants::DOOM_TICS_PER_SEC,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cast_playback_loops_and_dies() {
+        let mut death = frame("POSSH0", 5.0);
+        death.sound = Some("DSPODTH1".to_string());
+        let cast = CastRollCallDef {
+            castmembers: vec![
+                CastMember {
+                    name: "CC_ZOMBIE".to_string(),
+                    sound: "DSPOSIT1".to_string(),
+                    alive: vec![frame("POSSA1", 10.0), frame("POSSB1", 10.0)],
+                    dead: vec![death, frame("POSSI0", 5.0)],
+                },
+                CastMember {
+                    name: "Imp".to_string(),
+                    sound: String::new(),
+                    alive: vec![frame("TROOA1", 10.0)],
+                    dead: Vec::new(),
+                },
+            ],
+        };
+
+        let mut playback = CastPlayback::default();
+        playback.advance(&cast, 0.0);
+        assert_eq!(playback.last_sound.as_deref(), Some("DSPOSIT1"));
+
+        // 25 tics in: the alive loop has wrapped back to the first frame.
+        let t = 25.0 / crate::constants::DOOM_TICS_PER_SEC;
+        playback.advance(&cast, t);
+        assert_eq!(playback.current_frame(&cast).unwrap().image, "POSSA1");
+
+        playback.kill(&cast);
+        assert_eq!(playback.phase, CastPhase::Dying);
+        assert_eq!(playback.last_sound.as_deref(), Some("DSPODTH1"));
+
+        // The death plays once, then the next member walks on.
+        playback.advance(&cast, t + 11.0 / crate::constants::DOOM_TICS_PER_SEC);
+        assert_eq!(playback.member, 1);
+        assert_eq!(playback.phase, CastPhase::Alive);
+        assert_eq!(playback.current_frame(&cast).unwrap().image, "TROOA1");
+
+        // Without a death sequence, killing skips ahead and wraps around.
+        playback.kill(&cast);
+        assert_eq!(playback.member, 0);
+        assert_eq!(display_name("cc_zombie"), "ZOMBIEMAN");
+        assert_eq!(display_name("Imp"), "Imp");
+    }
+}
@@ -1,4 +1,5 @@
-use crate::render::palette::DoomPalette;
+use crate::render::palette::{ColorMatch, DoomPalette};
+use eframe::egui::Color32;
 use rand::RngExt;
 use std::ops::Sub;
 
@@ -70,3 +71,93 @@ impl FireSimulation {
         rgba
     }
 }
+
+/// Spreads `keys` evenly over `steps` colours, blending linearly between
+/// neighbouring keys.
+pub fn gradient(keys: &[Color32], steps: usize) -> Vec<Color32> {
+    let Some(&first) = keys.first() else {
+        return Vec::new();
+    };
+    if keys.len() == 1 || steps < 2 {
+        return vec![first; steps];
+    }
+    (0..steps)
+        .map(|i| {
+            let pos = i as f32 / (steps - 1) as f32 * (keys.len() - 1) as f32;
+            let lo = (pos.floor() as usize).min(keys.len() - 2);
+            let t = pos - lo as f32;
+            let (a, b) = (keys[lo], keys[lo + 1]);
+            let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+            Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+        })
+        .collect()
+}
+
+/// Matches a colour ramp to palette indices for a fire palette. The coolest
+/// slot is always index 0 so burnt-out pixels stay transparent, and the rest
+/// never use it.
+pub fn match_ramp(colors: &[Color32], palette: &DoomPalette, method: ColorMatch) -> Vec<i32> {
+    colors
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| match i {
+            0 => 0,
+            _ => palette.nearest_in(c, method, 1..=255) as i32,
+        })
+        .collect()
+}
+
+/// Reads a gradient strip along the image's longer side, averaging across
+/// the shorter one. Vertical strips are read bottom to top, since fire burns
+/// upwards.
+pub fn gradient_strip(img: &image::RgbaImage) -> Vec<Color32> {
+    let (w, h) = img.dimensions();
+    let vertical = h > w;
+    let (len, across) = if vertical { (h, w) } else { (w, h) };
+    (0..len)
+        .map(|i| {
+            let mut sum = [0u32; 3];
+            for j in 0..across {
+                let (x, y) = if vertical { (j, h - 1 - i) } else { (i, j) };
+                let p = img.get_pixel(x, y);
+                for c in 0..3 {
+                    sum[c] += p[c] as u32;
+                }
+            }
+            let avg = |c: u32| (c / across.max(1)) as u8;
+            Color32::from_rgb(avg(sum[0]), avg(sum[1]), avg(sum[2]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_ramp_matching() {
+        let mut raw = vec![0u8; 768];
+        for (i, c) in raw.chunks_mut(3).enumerate() {
+            c.copy_from_slice(&[i as u8, 0, 0]);
+        }
+        let palette = DoomPalette::from_raw(&raw);
+
+        let keys = [Color32::from_rgb(0, 0, 0), Color32::from_rgb(200, 0, 0)];
+        let ramp = gradient(&keys, 5);
+        assert_eq!(ramp[2], Color32::from_rgb(100, 0, 0));
+
+        for method in ColorMatch::ALL {
+            let indices = match_ramp(&ramp, &palette, method);
+            assert_eq!(indices, vec![0, 50, 100, 150, 200]);
+        }
+        // Black past the first slot must not turn transparent.
+        assert_eq!(
+            match_ramp(&[Color32::BLACK; 2], &palette, ColorMatch::Redmean)[1],
+            1
+        );
+
+        let img = image::RgbaImage::from_fn(1, 3, |_, y| image::Rgba([y as u8 * 10, 0, 0, 255]));
+        let strip = gradient_strip(&img);
+        assert_eq!(strip[0], Color32::from_rgb(20, 0, 0));
+    }
+}
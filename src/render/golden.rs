@@ -0,0 +1,324 @@
+//! Golden-image regression tests for the offscreen renderer.
+//!
+//! Every bundled status bar template is rendered against a synthetic asset set
+//! under a matrix of simulation presets and compared with the reference PNGs in
+//! `tests/golden/`. After an intentional rendering change, rerun the tests with
+//! `CACOCO_BLESS=1` to rewrite the references, and review the new images.
+
+use super::offscreen::{OffscreenOptions, render_statusbar};
+use crate::assets::AssetStore;
+use crate::library::TEMPLATES;
+use crate::models::ProjectData;
+use crate::models::sbardef::{Element, ElementWrapper, SBarDefFile};
+use crate::state::PreviewState;
+use std::path::{Path, PathBuf};
+
+/// How far a single channel may drift before a pixel counts as different.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// How many differing pixels an image may have and still match.
+const MAX_DIFF_PIXELS: usize = 16;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// FNV-1a, so synthetic assets look the same on every machine and run.
+fn name_hash(name: &str) -> u32 {
+    name.bytes()
+        .fold(0x811c9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x01000193))
+}
+
+/// Registers a flat-coloured block with a darker border and a white top-left
+/// texel, so position, size and orientation all show up in a diff.
+fn synthetic_patch(assets: &mut AssetStore, name: &str, width: u32, height: u32) {
+    let hash = name_hash(name);
+    let fill = [
+        64 + (hash & 0x7f) as u8,
+        64 + ((hash >> 8) & 0x7f) as u8,
+        64 + ((hash >> 16) & 0x7f) as u8,
+        255,
+    ];
+    let border = [fill[0] / 2, fill[1] / 2, fill[2] / 2, 255];
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let px = if x == 0 && y == 0 {
+                [255; 4]
+            } else if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                border
+            } else {
+                fill
+            };
+            pixels.extend_from_slice(&px);
+        }
+    }
+    assets.load_rgba(name, width, height, &pixels);
+}
+
+/// Builds a stand-in for the IWAD graphics a layout refers to. Glyphs share a
+/// size per font, like real fonts do; everything else gets a size derived
+/// from its name.
+fn synthetic_assets(file: &SBarDefFile) -> AssetStore {
+    fn walk(elements: &[ElementWrapper], patches: &mut Vec<String>) {
+        for el in elements {
+            match &el.data {
+                Element::Graphic(g) => patches.push(g.patch.clone()),
+                Element::Animation(a) => patches.extend(a.frames.iter().map(|f| f.lump.clone())),
+                _ => {}
+            }
+            walk(&el.get_common().children, patches);
+        }
+    }
+
+    let mut assets = AssetStore::default();
+
+    for font in &file.data.number_fonts {
+        let hash = name_hash(&font.stem);
+        let (w, h) = (5 + hash % 4, 7 + (hash >> 4) % 5);
+        for d in 0..=9 {
+            synthetic_patch(&mut assets, &format!("{}NUM{}", font.stem, d), w, h);
+        }
+        synthetic_patch(&mut assets, &format!("{}MINUS", font.stem), w, h);
+        synthetic_patch(&mut assets, &format!("{}PRCNT", font.stem), w, h);
+    }
+
+    for font in &file.data.hud_fonts {
+        let hash = name_hash(&font.stem);
+        let (w, h) = (4 + hash % 4, 7);
+        for c in 33..=95 {
+            synthetic_patch(&mut assets, &format!("{}{:03}", font.stem, c), w, h);
+        }
+    }
+
+    let mut faces = vec![
+        "STFGOD0".to_string(),
+        "STFDEAD0".to_string(),
+        "STFB0".to_string(),
+    ];
+    for dmg in 0..5 {
+        faces.extend((0..3).map(|look| format!("STFST{}{}", dmg, look)));
+        for prefix in ["STFKILL", "STFEVL", "STFOUCH"] {
+            faces.push(format!("{}{}", prefix, dmg));
+        }
+    }
+    for face in faces {
+        synthetic_patch(&mut assets, &face, 24, 29);
+    }
+
+    let mut patches = Vec::new();
+    for bar in &file.data.status_bars {
+        walk(&bar.children, &mut patches);
+    }
+    for patch in patches {
+        if patch.is_empty() || assets.has_image(&crate::assets::AssetId::new(&patch)) {
+            continue;
+        }
+        let hash = name_hash(&patch);
+        synthetic_patch(&mut assets, &patch, 4 + hash % 28, 4 + (hash >> 8) % 20);
+    }
+
+    assets
+}
+
+/// The simulation states each layout is rendered under.
+fn presets() -> Vec<(&'static str, PreviewState)> {
+    let spawn = PreviewState::default();
+
+    let mut arsenal = PreviewState::default();
+    {
+        let sim = &mut arsenal.sim;
+        sim.player.health = 200;
+        sim.player.armor = 200;
+        sim.player.kills = 27;
+        sim.player.items = 9;
+        sim.player.secrets = 5;
+        let inv = &mut sim.inventory;
+        inv.has_backpack = true;
+        inv.ammo_bullets = 400;
+        inv.ammo_shells = 100;
+        inv.ammo_rockets = 100;
+        inv.ammo_cells = 600;
+        inv.has_chainsaw = true;
+        inv.has_shotgun = true;
+        inv.has_super_shotgun = true;
+        inv.has_chaingun = true;
+        inv.has_rocket_launcher = true;
+        inv.has_plasma_gun = true;
+        inv.has_bfg = true;
+        inv.has_blue_card = true;
+        inv.has_yellow_card = true;
+        inv.has_red_card = true;
+        inv.has_blue_skull = true;
+        inv.has_yellow_skull = true;
+        inv.has_red_skull = true;
+        inv.has_berserk = true;
+        sim.selected_weapon_slot = 6;
+    }
+
+    let mut wounded = PreviewState::default();
+    {
+        let sim = &mut wounded.sim;
+        sim.player.health = 23;
+        sim.player.armor = 7;
+        sim.player.kills = 3;
+        sim.inventory.ammo_bullets = 3;
+        sim.inventory.ammo_shells = 0;
+        sim.inventory.has_shotgun = true;
+        sim.inventory.has_blue_card = true;
+        sim.selected_weapon_slot = 3;
+    }
+
+    let mut dead = PreviewState::default();
+    {
+        let sim = &mut dead.sim;
+        sim.player.health = 0;
+        sim.inventory.ammo_bullets = 0;
+        sim.selected_weapon_slot = 1;
+    }
+
+    let mut presets = vec![
+        ("spawn", spawn),
+        ("arsenal", arsenal),
+        ("wounded", wounded),
+        ("dead", dead),
+    ];
+    for (_, state) in &mut presets {
+        state.viewer.display_weapon_slot = state.sim.selected_weapon_slot;
+    }
+    presets
+}
+
+/// Counts pixels that differ by more than [`CHANNEL_TOLERANCE`] and returns a
+/// visualisation with the differences in red.
+fn diff_images(
+    actual: &image::RgbaImage,
+    expected: &image::RgbaImage,
+) -> (usize, image::RgbaImage) {
+    let mut diff = image::RgbaImage::new(actual.width(), actual.height());
+    let mut count = 0;
+    for ((a, e), d) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let differs =
+            a.0.iter()
+                .zip(e.0)
+                .any(|(x, y)| x.abs_diff(y) > CHANNEL_TOLERANCE);
+        *d = if differs {
+            count += 1;
+            image::Rgba([255, 0, 0, 255])
+        } else {
+            image::Rgba([a.0[0] / 4, a.0[1] / 4, a.0[2] / 4, 255])
+        };
+    }
+    (count, diff)
+}
+
+fn check_golden(name: &str, actual: &image::RgbaImage, bless: bool) -> Result<(), String> {
+    let path = golden_dir().join(format!("{}.png", name));
+
+    if bless {
+        std::fs::create_dir_all(golden_dir()).map_err(|e| e.to_string())?;
+        return actual.save(&path).map_err(|e| e.to_string());
+    }
+
+    let expected = image::open(&path)
+        .map_err(|e| {
+            format!(
+                "{}: missing reference ({}); run with CACOCO_BLESS=1",
+                name, e
+            )
+        })?
+        .to_rgba8();
+
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "{}: size {:?} != reference {:?}",
+            name,
+            actual.dimensions(),
+            expected.dimensions()
+        ));
+    }
+
+    let (count, diff) = diff_images(actual, &expected);
+    if count > MAX_DIFF_PIXELS {
+        let out_dir = std::env::temp_dir().join("cacoco-golden");
+        let _ = std::fs::create_dir_all(&out_dir);
+        let _ = actual.save(out_dir.join(format!("{}.actual.png", name)));
+        let _ = diff.save(out_dir.join(format!("{}.diff.png", name)));
+        return Err(format!(
+            "{}: {} pixels differ (see {})",
+            name,
+            count,
+            out_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_templates_match_golden_images() {
+    let bless = std::env::var_os("CACOCO_BLESS").is_some();
+    let mut failures = Vec::new();
+
+    for template in TEMPLATES {
+        let parsed = serde_json::from_str::<ProjectData>(template.json_content)
+            .unwrap_or_else(|e| panic!("template '{}' failed to parse: {}", template.name, e));
+        // Only status bar templates have golden images.
+        let ProjectData::StatusBar(mut file) = parsed else {
+            continue;
+        };
+        file.normalize_paths();
+        file.target = file.determine_target();
+        file.normalize_for_target();
+
+        let slug = template.name.to_lowercase().replace(' ', "_");
+        let assets = synthetic_assets(&file);
+
+        for bar_idx in 0..file.data.status_bars.len() {
+            for (preset, state) in presets() {
+                for widescreen in [false, true] {
+                    let mut state = state.clone();
+                    state.sim.engine.widescreen_mode = widescreen;
+                    let options = OffscreenOptions {
+                        widescreen,
+                        world: false,
+                        ..Default::default()
+                    };
+
+                    let image = render_statusbar(&assets, &file, bar_idx, &state, &options);
+                    let name = format!(
+                        "{}_bar{}_{}{}",
+                        slug,
+                        bar_idx,
+                        preset,
+                        if widescreen { "_wide" } else { "" }
+                    );
+                    if let Err(e) = check_golden(&name, &image, bless) {
+                        failures.push(e);
+                    }
+                }
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden image mismatches:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_diff_tolerance() {
+    let base = image::RgbaImage::from_pixel(4, 4, image::Rgba([100, 100, 100, 255]));
+    let mut close = base.clone();
+    close.put_pixel(0, 0, image::Rgba([100 + CHANNEL_TOLERANCE, 100, 100, 255]));
+    assert_eq!(diff_images(&close, &base).0, 0);
+
+    let mut moved = base.clone();
+    moved.put_pixel(1, 2, image::Rgba([0, 0, 0, 0]));
+    assert_eq!(diff_images(&moved, &base).0, 1);
+}
@@ -22,7 +22,7 @@ pub(super) fn draw_simple_graphic_patch(
     alpha: f32,
     crop: &Option<CropDef>,
 ) {
-    if let Some(tex) = ctx.assets.textures.get(&patch_id) {
+    if let Some(tex) = ctx.patch_texture(&patch_id) {
         let mut size = tex.size_vec2();
         let (base_scale_x, base_scale_y) = ctx.get_native_scale_factor();
 
@@ -0,0 +1,256 @@
+use super::projection::ViewportProjection;
+use crate::assets::{AssetId, AssetStore};
+use crate::conditions;
+use crate::constants::DOOM_TICS_PER_SEC;
+use crate::models::interlevel::{
+    FrameType, InterlevelAnim, InterlevelDefinition, InterlevelFrame, InterlevelLayer,
+};
+use crate::state::PreviewState;
+use eframe::egui;
+use rand::RngExt;
+use std::collections::HashMap;
+
+/// Playback state of one anim.
+#[derive(Debug, Clone, Default)]
+pub struct AnimPlayback {
+    pub frame: usize,
+    /// Seconds left on the current frame.
+    remaining: f64,
+    started: bool,
+}
+
+impl AnimPlayback {
+    /// Steps the anim forward by `dt` seconds. Widescreen-only frames are
+    /// skipped unless `widescreen` is set.
+    pub fn advance<R: rand::Rng>(
+        &mut self,
+        anim: &InterlevelAnim,
+        dt: f64,
+        widescreen: bool,
+        rng: &mut R,
+    ) {
+        let frames = &anim.frames;
+        if frames.is_empty() {
+            return;
+        }
+        let shown =
+            |f: &InterlevelFrame| widescreen || !f.frame_type.contains(FrameType::WIDESCREEN);
+
+        if !self.started {
+            self.started = true;
+            let random_first = frames[0].frame_type.contains(FrameType::RANDOM_FIRST);
+            self.frame = if random_first {
+                rng.random_range(0..frames.len())
+            } else {
+                0
+            };
+            if !shown(&frames[self.frame]) {
+                self.frame = next_shown(frames, self.frame, &shown);
+            }
+            self.remaining = frame_duration(&frames[self.frame], rng);
+            return;
+        }
+
+        if self.frame >= frames.len() {
+            self.frame = 0;
+        }
+
+        self.remaining -= dt;
+        // Every frame lasts at least a tic, so this can't spin forever.
+        while self.remaining <= 0.0 {
+            self.frame = next_shown(frames, self.frame, &shown);
+            self.remaining += frame_duration(&frames[self.frame], rng);
+        }
+    }
+}
+
+/// Returns the index of the next frame after `from` that is shown, wrapping
+/// around. Returns `from` if nothing else is.
+fn next_shown(
+    frames: &[InterlevelFrame],
+    from: usize,
+    shown: &impl Fn(&InterlevelFrame) -> bool,
+) -> usize {
+    (1..=frames.len())
+        .map(|step| (from + step) % frames.len())
+        .find(|&i| shown(&frames[i]))
+        .unwrap_or(from)
+}
+
+/// Picks how long a frame stays up, in seconds.
+fn frame_duration<R: rand::Rng>(frame: &InterlevelFrame, rng: &mut R) -> f64 {
+    let tic = 1.0 / DOOM_TICS_PER_SEC;
+    if frame.frame_type.contains(FrameType::INFINITE) {
+        return f64::INFINITY;
+    }
+    let duration = frame.duration.max(tic);
+    if frame.frame_type.contains(FrameType::RANDOM) && frame.maxduration > duration {
+        rng.random_range(duration..=frame.maxduration)
+    } else {
+        duration
+    }
+}
+
+/// Playback state of every anim in an INTERLEVEL, keyed by (layer, anim).
+#[derive(Debug, Clone, Default)]
+pub struct InterlevelPlayback {
+    pub anims: HashMap<(usize, usize), AnimPlayback>,
+    last_time: Option<f64>,
+}
+
+impl InterlevelPlayback {
+    /// Advances every anim to `time`, in seconds.
+    pub fn advance<R: rand::Rng>(
+        &mut self,
+        def: &InterlevelDefinition,
+        time: f64,
+        widescreen: bool,
+        rng: &mut R,
+    ) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).max(0.0));
+        self.last_time = Some(time);
+
+        for (l, layer) in def.layers.iter().enumerate() {
+            for (a, anim) in layer.anims.iter().enumerate() {
+                self.anims
+                    .entry((l, a))
+                    .or_default()
+                    .advance(anim, dt, widescreen, rng);
+            }
+        }
+    }
+
+    /// Returns the frame an anim is showing, if any.
+    pub fn current_frame<'a>(
+        &self,
+        layer: usize,
+        anim_idx: usize,
+        anim: &'a InterlevelAnim,
+        widescreen: bool,
+    ) -> Option<&'a InterlevelFrame> {
+        let idx = self.anims.get(&(layer, anim_idx)).map_or(0, |p| p.frame);
+        anim.frames
+            .get(idx)
+            .filter(|f| widescreen || !f.frame_type.contains(FrameType::WIDESCREEN))
+    }
+}
+
+/// Whether a layer's conditions hold in the mock intermission state.
+pub fn layer_shown(layer: &InterlevelLayer, state: &PreviewState) -> bool {
+    let widescreen = state.sim.engine.widescreen_mode;
+    conditions::resolve_interlevel(&layer.conditions, &state.intermission, None, widescreen)
+}
+
+/// Whether an anim's conditions hold while it shows `frame`.
+pub fn anim_shown(
+    ctx: &egui::Context,
+    anim: &InterlevelAnim,
+    frame: Option<&InterlevelFrame>,
+    state: &PreviewState,
+    assets: &AssetStore,
+) -> bool {
+    let width = frame
+        .and_then(|f| assets.texture(ctx, &AssetId::new(&f.image)))
+        .map(|tex| tex.size_vec2().x);
+    let widescreen = state.sim.engine.widescreen_mode;
+    conditions::resolve_interlevel(&anim.conditions, &state.intermission, width, widescreen)
+}
+
+/// The virtual-space rectangle a frame covers when its anim sits at
+/// (`anim.x`, `anim.y`), honouring the patch offsets like `V_DrawPatch`.
+pub fn frame_rect(
+    ctx: &egui::Context,
+    anim: &InterlevelAnim,
+    frame: &InterlevelFrame,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) -> Option<egui::Rect> {
+    let id = AssetId::new(&frame.image);
+    let tex = assets.texture(ctx, &id)?;
+    let (left, top) = assets
+        .offsets
+        .get(&id)
+        .map(|(x, y)| (*x as f32, *y as f32))
+        .unwrap_or((0.0, 0.0));
+    let min = egui::pos2(proj.origin_x + anim.x as f32 - left, anim.y as f32 - top);
+    Some(egui::Rect::from_min_size(min, tex.size_vec2()))
+}
+
+/// Draws one frame of an anim.
+pub fn draw_anim_frame(
+    painter: &egui::Painter,
+    anim: &InterlevelAnim,
+    frame: &InterlevelFrame,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) {
+    let Some(rect) = frame_rect(painter.ctx(), anim, frame, assets, proj) else {
+        return;
+    };
+    let Some(tex) = assets.texture(painter.ctx(), &AssetId::new(&frame.image)) else {
+        return;
+    };
+    let screen = egui::Rect::from_min_max(proj.to_screen(rect.min), proj.to_screen(rect.max));
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    painter.image(tex.id(), screen, uv, egui::Color32::WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(image: &str, tics: f64, frame_type: FrameType) -> InterlevelFrame {
+        InterlevelFrame {
+            image: image.to_string(),
+            frame_type,
+            duration: tics / DOOM_TICS_PER_SEC,
+            maxduration: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_anim_playback_frame_types() {
+        let mut rng = rand::rng();
+        let tic = 1.0 / DOOM_TICS_PER_SEC;
+        let anim = InterlevelAnim {
+            frames: vec![
+                frame("WIA00000", 10.0, FrameType::FIXED),
+                frame("WIA00001", 10.0, FrameType::FIXED | FrameType::WIDESCREEN),
+                frame("WIA00002", 5.0, FrameType::INFINITE),
+            ],
+            ..Default::default()
+        };
+
+        // Widescreen-only frames are skipped in 4:3, and infinite ends the anim.
+        let mut playback = AnimPlayback::default();
+        playback.advance(&anim, 0.0, false, &mut rng);
+        assert_eq!(playback.frame, 0);
+        playback.advance(&anim, 10.0 * tic, false, &mut rng);
+        assert_eq!(playback.frame, 2);
+        playback.advance(&anim, 1000.0, false, &mut rng);
+        assert_eq!(playback.frame, 2);
+
+        let mut wide = AnimPlayback::default();
+        wide.advance(&anim, 0.0, true, &mut rng);
+        wide.advance(&anim, 10.0 * tic, true, &mut rng);
+        assert_eq!(wide.frame, 1);
+
+        let mut random = frame("WIA00100", 4.0, FrameType::RANDOM | FrameType::RANDOM_FIRST);
+        random.maxduration = 8.0 * tic;
+        for _ in 0..50 {
+            let d = frame_duration(&random, &mut rng);
+            assert!((4.0 * tic..=8.0 * tic).contains(&d));
+        }
+        let anim = InterlevelAnim {
+            frames: vec![random.clone(), random.clone(), random],
+            ..Default::default()
+        };
+        let mut starts = std::collections::HashSet::new();
+        for _ in 0..100 {
+            let mut p = AnimPlayback::default();
+            p.advance(&anim, 0.0, false, &mut rng);
+            starts.insert(p.frame);
+        }
+        assert!(starts.len() > 1);
+    }
+}
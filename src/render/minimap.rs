@@ -24,7 +24,7 @@ pub(super) fn draw_minimap(ctx: &RenderContext, def: &MinimapDef, pos: egui::Pos
             }
 
             let id = crate::assets::AssetId::new("_MINIMAP_PLACEHOLDER");
-            if let Some(tex) = ctx.assets.textures.get(&id) {
+            if let Some(tex) = ctx.assets.texture(ctx.painter.ctx(), &id) {
                 let safe_scale = def.scale.max(0.01);
                 let tex_size = tex.size_vec2();
 
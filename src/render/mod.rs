@@ -6,18 +6,29 @@ use eframe::egui;
 use std::collections::HashSet;
 
 pub mod animation;
+pub mod bunny;
 pub mod canvas;
+mod carousel;
+pub mod cast;
 pub mod components;
 pub mod face;
 pub mod fire;
+#[cfg(test)]
+mod golden;
 pub mod graphic;
+pub mod interlevel;
 pub mod list;
 mod minimap;
+pub mod offscreen;
 pub mod palette;
 pub mod patch;
+pub mod progression;
 pub mod projection;
 pub(crate) mod sky;
+pub mod tally;
 pub mod text;
+pub mod tranmap;
+pub mod translation;
 
 /// Defines whether an element is being drawn in the standard background pass
 /// or the specialized foreground pass (used for selection highlighting).
@@ -49,6 +60,10 @@ pub struct RenderContext<'a> {
     /// True if the primary mouse button is currently held down over the viewport.
     pub is_viewport_clicked: bool,
     pub is_native: bool,
+    /// The colour translation of the element being drawn, if any.
+    pub translation: Option<&'a str>,
+    /// The translucency table the element being drawn is blended through, if any.
+    pub tranmap: Option<&'a str>,
 }
 
 impl<'a> RenderContext<'a> {
@@ -65,6 +80,13 @@ impl<'a> RenderContext<'a> {
         egui::Rect::from_min_max(self.to_screen(rect.min), self.to_screen(rect.max))
     }
 
+    /// Returns the texture for a patch, recoloured by the current translation
+    /// and tagged with the current tranmap.
+    pub fn patch_texture(&self, id: &crate::assets::AssetId) -> Option<egui::TextureHandle> {
+        self.assets
+            .patch_texture(self.painter.ctx(), id, self.translation, self.tranmap)
+    }
+
     /// Returns the actual scale to use for drawing pixels (handles Aspect Correction).
     pub fn get_render_scale(&self) -> (f32, f32) {
         (self.proj.final_scale_x, self.proj.final_scale_y)
@@ -76,6 +98,32 @@ impl<'a> RenderContext<'a> {
     }
 }
 
+/// Returns true if any of the elements, or their children, are drawn
+/// through a loaded tranmap.
+pub fn uses_tranmap(elements: &[ElementWrapper], assets: &AssetStore) -> bool {
+    elements.iter().any(|element| {
+        let common = element.get_common();
+        let name = common.tranmap.as_deref().unwrap_or("TRANMAP");
+        (common.translucency && assets.tranmaps.contains_key(&AssetStore::stem(name)))
+            || uses_tranmap(&common.children, assets)
+    })
+}
+
+/// Returns true if any of the elements, or their children, change with time
+/// alone: multi-frame animations and the clock and FPS components.
+pub fn is_animated(elements: &[ElementWrapper]) -> bool {
+    elements.iter().any(|element| {
+        let animated = match &element.data {
+            Element::Animation(def) => def.frames.len() > 1,
+            Element::Component(def) => {
+                matches!(def.type_, ComponentType::Time | ComponentType::FpsCounter)
+            }
+            _ => false,
+        };
+        animated || is_animated(&element.get_common().children)
+    })
+}
+
 /// The main recursive entry point for drawing an SBARDEF element and its children.
 pub fn draw_element_wrapper(
     ctx: &RenderContext,
@@ -113,8 +161,22 @@ pub fn draw_element_wrapper(
     }
 
     let mut alpha = if !visible_in_game { 0.30 } else { 1.0 };
+
+    // Ports blend translucent elements through a TRANMAP. The painter can't
+    // read back what's under an element, so this draws at the table's average
+    // opacity and the status bar viewport covers the result with a software
+    // render that looks the table up per pixel. Without a table, fall back
+    // to 50%.
+    let mut tranmap = None;
     if common.translucency {
-        alpha *= 0.5;
+        let name = common.tranmap.as_deref().unwrap_or("TRANMAP");
+        match ctx.assets.tranmap_opacity(name) {
+            Some(opacity) => {
+                alpha *= opacity;
+                tranmap = Some(name);
+            }
+            None => alpha *= 0.5,
+        }
     }
 
     let is_hovered_branch = ctx
@@ -147,6 +209,8 @@ pub fn draw_element_wrapper(
     let is_native_container = matches!(element.data, Element::Native(_));
     let local_ctx = RenderContext {
         is_native: ctx.is_native || is_native_container,
+        translation: common.translation.as_deref(),
+        tranmap,
         ..*ctx
     };
 
@@ -191,7 +255,7 @@ pub fn draw_element_wrapper(
             Element::Percent(p) => text::draw_number(&local_ctx, p, pos, true, alpha),
             Element::String(s) => text::draw_string(&local_ctx, s, pos, alpha),
             Element::Component(c) => components::draw_component(&local_ctx, c, pos, alpha),
-            Element::Carousel(_) => {}
+            Element::Carousel(c) => carousel::draw_carousel(&local_ctx, c, pos, alpha),
             Element::Minimap(m) => minimap::draw_minimap(&local_ctx, m, pos, alpha),
         }
     } else if let Element::List(l) = &element.data {
@@ -286,9 +350,7 @@ fn get_element_footprint(ctx: &RenderContext, element: &ElementWrapper) -> egui:
         Element::Graphic(g) => {
             let id = crate::assets::AssetId::new(&g.patch);
             ctx.assets
-                .textures
-                .get(&id)
-                .map(|t| t.size_vec2())
+                .image_size_vec2(&id)
                 .unwrap_or(egui::vec2(16.0, 16.0))
         }
         Element::Number(n) | Element::Percent(n) => {
@@ -301,11 +363,12 @@ fn get_element_footprint(ctx: &RenderContext, element: &ElementWrapper) -> egui:
             let lump = a.frames.first().map(|f| &f.lump);
             lump.and_then(|l| {
                 let id = crate::assets::AssetId::new(l);
-                ctx.assets.textures.get(&id).map(|t| t.size_vec2())
+                ctx.assets.image_size_vec2(&id)
             })
             .unwrap_or(egui::vec2(16.0, 16.0))
         }
         Element::Minimap(m) => egui::vec2(m.width as f32, m.height as f32),
+        Element::Carousel(_) => carousel::CAROUSEL_SIZE,
         _ => egui::Vec2::ZERO,
     };
 
@@ -0,0 +1,629 @@
+//! Software rendering of status bar layouts into plain RGBA images.
+//!
+//! The element renderers only know how to paint through an `egui::Painter`, so
+//! instead of duplicating them this runs a single pass of a private, headless
+//! `egui::Context`, tessellates whatever was painted and rasterizes the meshes
+//! on the CPU. No window or GPU is involved.
+
+use crate::assets::AssetStore;
+use crate::constants::{DOOM_H, DOOM_W, DOOM_W_WIDE};
+use crate::models::sbardef::SBarDefFile;
+use crate::render::palette::DoomPalette;
+use crate::render::projection::ViewportProjection;
+use crate::render::tranmap::TranMap;
+use crate::render::{self, RenderContext, RenderPass};
+use crate::state::PreviewState;
+use eframe::egui;
+use eframe::egui::epaint::{self, ClippedPrimitive, Primitive};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Settings for an offscreen render.
+#[derive(Clone, Copy, Debug)]
+pub struct OffscreenOptions {
+    /// Render at 428x200 instead of 320x200. Should normally agree with
+    /// `state.sim.engine.widescreen_mode`, which widescreen conditions read.
+    pub widescreen: bool,
+    /// Integer pixel scale applied to the virtual screen.
+    pub scale: u32,
+    /// Draw the world backdrop, fill flat and weapon sprite behind the bar,
+    /// exactly like the viewport. When false the background stays transparent.
+    pub world: bool,
+    /// Simulation time in seconds, drives animations and blinking elements.
+    pub time: f64,
+}
+
+impl Default for OffscreenOptions {
+    fn default() -> Self {
+        Self {
+            widescreen: false,
+            scale: 1,
+            world: true,
+            time: 0.0,
+        }
+    }
+}
+
+/// Renders one status bar of `file` as the viewport would show it for `state`.
+///
+/// `bar_idx` is clamped to the available layouts.
+pub fn render_statusbar(
+    assets: &AssetStore,
+    file: &SBarDefFile,
+    bar_idx: usize,
+    state: &PreviewState,
+    options: &OffscreenOptions,
+) -> image::RgbaImage {
+    rasterize(assets, file, bar_idx, state, options).into_image()
+}
+
+/// Renders a status bar like [`render_statusbar`], but keeps only the pixels
+/// a tranmap blended, for the editor to lay over its own (alpha-blended)
+/// drawing. None if nothing on the bar went through a tranmap.
+pub fn translucency_overlay(
+    assets: &AssetStore,
+    file: &SBarDefFile,
+    bar_idx: usize,
+    state: &PreviewState,
+    options: &OffscreenOptions,
+) -> Option<egui::ColorImage> {
+    let canvas = rasterize(assets, file, bar_idx, state, options);
+    if !canvas.blended.contains(&true) {
+        return None;
+    }
+    let pixels = canvas
+        .pixels
+        .iter()
+        .zip(&canvas.blended)
+        .map(|(&pixel, &blended)| {
+            if blended {
+                pixel
+            } else {
+                egui::Color32::TRANSPARENT
+            }
+        })
+        .collect();
+    Some(egui::ColorImage::new(
+        [canvas.width as usize, canvas.height as usize],
+        pixels,
+    ))
+}
+
+fn rasterize(
+    assets: &AssetStore,
+    file: &SBarDefFile,
+    bar_idx: usize,
+    state: &PreviewState,
+    options: &OffscreenOptions,
+) -> Canvas {
+    let scale = options.scale.max(1);
+    let base_w = if options.widescreen {
+        DOOM_W_WIDE
+    } else {
+        DOOM_W
+    };
+    let width = base_w as u32 * scale;
+    let height = DOOM_H as u32 * scale;
+
+    let screen_rect =
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32));
+
+    let ctx = egui::Context::default();
+    ctx.tessellation_options_mut(|o| o.feathering = false);
+
+    let raw_input = egui::RawInput {
+        screen_rect: Some(screen_rect),
+        time: Some(options.time),
+        ..Default::default()
+    };
+
+    let output = ctx.run(raw_input, |ctx| {
+        let painter = egui::Painter::new(ctx.clone(), egui::LayerId::background(), screen_rect);
+        let proj = ViewportProjection::new(
+            screen_rect,
+            options.widescreen,
+            false,
+            Some(scale as i32),
+            egui::Vec2::ZERO,
+        );
+
+        let bar_idx = bar_idx.min(file.data.status_bars.len().saturating_sub(1));
+        let Some(bar) = file.data.status_bars.get(bar_idx) else {
+            return;
+        };
+
+        if options.world {
+            painter.rect_filled(screen_rect, 0.0, egui::Color32::BLACK);
+            crate::ui::viewport::render_statusbar_workspace(&painter, bar, assets, state, &proj);
+        }
+
+        let root_y = if bar.fullscreen_render {
+            0.0
+        } else {
+            200.0 - bar.height as f32
+        };
+
+        let selection = HashSet::new();
+        let render_ctx = RenderContext {
+            painter: &painter,
+            assets,
+            file,
+            state,
+            time: options.time,
+            fps: state.viewer.display_fps,
+            mouse_pos: state.interaction.virtual_mouse_pos,
+            selection: &selection,
+            pass: RenderPass::Background,
+            proj: &proj,
+            is_dragging: false,
+            is_viewport_clicked: false,
+            is_native: false,
+            translation: None,
+            tranmap: None,
+        };
+
+        for (idx, child) in bar.children.iter().enumerate() {
+            let mut path = vec![bar_idx, idx];
+            render::draw_element_wrapper(
+                &render_ctx,
+                child,
+                egui::pos2(proj.origin_x, root_y),
+                &mut path,
+                true,
+            );
+        }
+    });
+
+    // Translucent patches were drawn with their own textures; composite those
+    // through their tables by palette index, like ports do.
+    let tranmapped: HashMap<egui::TextureId, PaletteBlend> = assets
+        .tranmapped_textures(&ctx)
+        .into_iter()
+        .filter_map(|(id, name)| {
+            let map = assets.tranmaps.get(&name)?;
+            Some((
+                id,
+                PaletteBlend {
+                    map,
+                    palette: &assets.palette,
+                    opacity: assets.tranmap_opacity(&name)?,
+                },
+            ))
+        })
+        .collect();
+
+    assets.release_textures(&ctx);
+
+    let mut textures = HashMap::new();
+    for (id, delta) in &output.textures_delta.set {
+        apply_texture_delta(&mut textures, *id, delta);
+    }
+
+    let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+    let mut target = Canvas::new(width, height);
+    for ClippedPrimitive {
+        clip_rect,
+        primitive,
+    } in &primitives
+    {
+        if let Primitive::Mesh(mesh) = primitive {
+            target.draw_mesh(
+                mesh,
+                *clip_rect,
+                &textures,
+                tranmapped.get(&mesh.texture_id),
+            );
+        }
+    }
+
+    target
+}
+
+/// Renders a status bar and writes it to disk as a PNG.
+pub fn save_statusbar_png(
+    assets: &AssetStore,
+    file: &SBarDefFile,
+    bar_idx: usize,
+    state: &PreviewState,
+    options: &OffscreenOptions,
+    path: &Path,
+) -> anyhow::Result<()> {
+    render_statusbar(assets, file, bar_idx, state, options)
+        .save_with_format(path, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+/// A CPU copy of an egui texture.
+struct SoftTexture {
+    width: usize,
+    height: usize,
+    /// Premultiplied sRGBA, as egui hands it to real backends.
+    pixels: Vec<egui::Color32>,
+    wrap: egui::TextureWrapMode,
+}
+
+impl SoftTexture {
+    fn sample(&self, uv: egui::Pos2) -> egui::Color32 {
+        let wrap = |coord: f32, size: usize| -> usize {
+            let texel = (coord * size as f32).floor() as i64;
+            let size = size as i64;
+            let idx = match self.wrap {
+                egui::TextureWrapMode::Repeat => texel.rem_euclid(size),
+                egui::TextureWrapMode::MirroredRepeat => {
+                    let period = texel.rem_euclid(size * 2);
+                    if period < size {
+                        period
+                    } else {
+                        size * 2 - 1 - period
+                    }
+                }
+                egui::TextureWrapMode::ClampToEdge => texel.clamp(0, size - 1),
+            };
+            idx as usize
+        };
+        let x = wrap(uv.x, self.width);
+        let y = wrap(uv.y, self.height);
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn apply_texture_delta(
+    textures: &mut HashMap<egui::TextureId, SoftTexture>,
+    id: egui::TextureId,
+    delta: &epaint::ImageDelta,
+) {
+    let epaint::ImageData::Color(image) = &delta.image;
+    let [w, h] = image.size;
+
+    match delta.pos {
+        None => {
+            textures.insert(
+                id,
+                SoftTexture {
+                    width: w,
+                    height: h,
+                    pixels: image.pixels.clone(),
+                    wrap: delta.options.wrap_mode,
+                },
+            );
+        }
+        Some([x0, y0]) => {
+            if let Some(tex) = textures.get_mut(&id) {
+                for row in 0..h {
+                    let dst = (y0 + row) * tex.width + x0;
+                    tex.pixels[dst..dst + w].copy_from_slice(&image.pixels[row * w..(row + 1) * w]);
+                }
+            }
+        }
+    }
+}
+
+/// How to composite a mesh whose texture is blended through a tranmap.
+struct PaletteBlend<'a> {
+    map: &'a TranMap,
+    palette: &'a DoomPalette,
+    /// The alpha the element renderers gave the mesh for full translucency.
+    /// Vertex alpha below it (fades, hidden elements) still mixes linearly.
+    opacity: f32,
+}
+
+/// A premultiplied RGBA framebuffer that egui meshes are rasterized into.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<egui::Color32>,
+    /// Palette indices already matched to colours, for tranmap blending.
+    nearest: HashMap<egui::Color32, u8>,
+    /// Which pixels a tranmap blended.
+    blended: Vec<bool>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![egui::Color32::TRANSPARENT; (width * height) as usize],
+            nearest: HashMap::new(),
+            blended: vec![false; (width * height) as usize],
+        }
+    }
+
+    fn draw_mesh(
+        &mut self,
+        mesh: &epaint::Mesh,
+        clip_rect: egui::Rect,
+        textures: &HashMap<egui::TextureId, SoftTexture>,
+        through: Option<&PaletteBlend>,
+    ) {
+        let texture = textures.get(&mesh.texture_id);
+        let clip = clip_rect.intersect(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(self.width as f32, self.height as f32),
+        ));
+        if !clip.is_positive() {
+            return;
+        }
+
+        for tri in mesh.indices.chunks_exact(3) {
+            let v = [
+                &mesh.vertices[tri[0] as usize],
+                &mesh.vertices[tri[1] as usize],
+                &mesh.vertices[tri[2] as usize],
+            ];
+            self.draw_triangle(v, clip, texture, through);
+        }
+    }
+
+    /// Rasterizes one triangle with pixel-center sampling and a top-left fill
+    /// rule, so quads split along a diagonal never blend a pixel twice.
+    fn draw_triangle(
+        &mut self,
+        v: [&epaint::Vertex; 3],
+        clip: egui::Rect,
+        texture: Option<&SoftTexture>,
+        through: Option<&PaletteBlend>,
+    ) {
+        let edge = |a: egui::Pos2, b: egui::Pos2, p: egui::Pos2| {
+            (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+        };
+
+        let area = edge(v[0].pos, v[1].pos, v[2].pos);
+        if area == 0.0 {
+            return;
+        }
+        // Normalise winding so every inside point has positive edge weights.
+        let v = if area < 0.0 { [v[0], v[2], v[1]] } else { v };
+        let area = area.abs();
+
+        // In screen space (y down) with this winding, an edge is "top-left"
+        // if it runs upward, or is horizontal and runs right-to-left.
+        let is_top_left = |a: egui::Pos2, b: egui::Pos2| {
+            let dy = b.y - a.y;
+            let dx = b.x - a.x;
+            dy < 0.0 || (dy == 0.0 && dx < 0.0)
+        };
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        let top_left = edges.map(|(a, b)| is_top_left(v[a].pos, v[b].pos));
+
+        let min_x = v.iter().map(|p| p.pos.x).fold(f32::MAX, f32::min);
+        let max_x = v.iter().map(|p| p.pos.x).fold(f32::MIN, f32::max);
+        let min_y = v.iter().map(|p| p.pos.y).fold(f32::MAX, f32::min);
+        let max_y = v.iter().map(|p| p.pos.y).fold(f32::MIN, f32::max);
+
+        let x0 = min_x.max(clip.min.x).floor().max(0.0) as u32;
+        let x1 = (max_x.min(clip.max.x).ceil() as u32).min(self.width);
+        let y0 = min_y.max(clip.min.y).floor().max(0.0) as u32;
+        let y1 = (max_y.min(clip.max.y).ceil() as u32).min(self.height);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(p) {
+                    continue;
+                }
+
+                let mut w = [0.0; 3];
+                let mut inside = true;
+                for (i, (a, b)) in edges.iter().enumerate() {
+                    let e = edge(v[*a].pos, v[*b].pos, p);
+                    if e < 0.0 || (e == 0.0 && !top_left[i]) {
+                        inside = false;
+                        break;
+                    }
+                    w[i] = e / area;
+                }
+                if !inside {
+                    continue;
+                }
+
+                let color = lerp_color([v[0].color, v[1].color, v[2].color], w);
+                let texel = match texture {
+                    Some(tex) => {
+                        let uv = egui::pos2(
+                            v[0].uv.x * w[0] + v[1].uv.x * w[1] + v[2].uv.x * w[2],
+                            v[0].uv.y * w[0] + v[1].uv.y * w[1] + v[2].uv.y * w[2],
+                        );
+                        tex.sample(uv)
+                    }
+                    None => egui::Color32::WHITE,
+                };
+
+                let idx = (y * self.width + x) as usize;
+                let src = match through {
+                    // Patches are either opaque or fully transparent per texel.
+                    Some(_) if texel.a() < 128 => continue,
+                    Some(pb) => {
+                        self.blended[idx] = true;
+                        self.tranmap_texel(pb, texel, color.a(), self.pixels[idx])
+                    }
+                    None => multiply(texel, color),
+                };
+                self.pixels[idx] = blend(src, self.pixels[idx]);
+            }
+        }
+    }
+
+    /// Looks up the colour a texel leaves over the framebuffer in a tranmap.
+    /// An empty (transparent) background counts as palette black.
+    fn tranmap_texel(
+        &mut self,
+        pb: &PaletteBlend,
+        texel: egui::Color32,
+        vertex_alpha: u8,
+        dst: egui::Color32,
+    ) -> egui::Color32 {
+        let mut index_of = |c: egui::Color32| {
+            let [r, g, b, _] = c.to_srgba_unmultiplied();
+            let rgb = egui::Color32::from_rgb(r, g, b);
+            *self
+                .nearest
+                .entry(rgb)
+                .or_insert_with(|| pb.palette.nearest_index(rgb))
+        };
+        let fg = index_of(texel);
+        let bg = index_of(if dst.a() == 0 {
+            egui::Color32::BLACK
+        } else {
+            dst
+        });
+
+        // Same truncation the element renderers use to build their tint.
+        let full = (255.0 * pb.opacity) as u8;
+        let coverage = if vertex_alpha >= full {
+            1.0
+        } else {
+            vertex_alpha as f32 / full as f32
+        };
+        pb.palette
+            .get(pb.map.blend(fg, bg))
+            .gamma_multiply(coverage)
+    }
+
+    fn into_image(self) -> image::RgbaImage {
+        let mut out = image::RgbaImage::new(self.width, self.height);
+        for (dst, src) in out.pixels_mut().zip(self.pixels) {
+            *dst = image::Rgba(src.to_srgba_unmultiplied());
+        }
+        out
+    }
+}
+
+fn lerp_color(colors: [egui::Color32; 3], w: [f32; 3]) -> egui::Color32 {
+    if colors[0] == colors[1] && colors[1] == colors[2] {
+        return colors[0];
+    }
+    let channel = |i: usize| {
+        let v: f32 = (0..3).map(|k| colors[k].to_array()[i] as f32 * w[k]).sum();
+        v.round().clamp(0.0, 255.0) as u8
+    };
+    egui::Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+fn multiply(a: egui::Color32, b: egui::Color32) -> egui::Color32 {
+    let mul = |x: u8, y: u8| ((x as u16 * y as u16 + 127) / 255) as u8;
+    egui::Color32::from_rgba_premultiplied(
+        mul(a.r(), b.r()),
+        mul(a.g(), b.g()),
+        mul(a.b(), b.b()),
+        mul(a.a(), b.a()),
+    )
+}
+
+/// Premultiplied "over" blending, matching egui's `ONE, ONE_MINUS_SRC_ALPHA`.
+fn blend(src: egui::Color32, dst: egui::Color32) -> egui::Color32 {
+    let inv = 255 - src.a() as u16;
+    let over = |s: u8, d: u8| (s as u16 + (d as u16 * inv + 127) / 255).min(255) as u8;
+    egui::Color32::from_rgba_premultiplied(
+        over(src.r(), dst.r()),
+        over(src.g(), dst.g()),
+        over(src.b(), dst.b()),
+        over(src.a(), dst.a()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::sbardef::{
+        CommonAttrs, Element, ElementWrapper, GraphicDef, StatusBarLayout,
+    };
+
+    #[test]
+    fn test_renders_graphic_at_virtual_position() {
+        let mut assets = AssetStore::default();
+        assets.load_rgba("TESTPAT", 2, 2, &[255, 0, 0, 255].repeat(4));
+
+        let mut file = SBarDefFile::new_empty();
+        file.data.status_bars = vec![StatusBarLayout {
+            height: 32,
+            fullscreen_render: true,
+            children: vec![ElementWrapper {
+                data: Element::Graphic(GraphicDef {
+                    common: CommonAttrs {
+                        x: 10,
+                        y: 20,
+                        ..Default::default()
+                    },
+                    patch: "TESTPAT".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+
+        let options = OffscreenOptions {
+            world: false,
+            ..Default::default()
+        };
+        let img = render_statusbar(&assets, &file, 0, &PreviewState::default(), &options);
+
+        assert_eq!(img.dimensions(), (320, 200));
+        assert_eq!(img.get_pixel(10, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(11, 21).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(12, 20).0[3], 0);
+        assert_eq!(img.get_pixel(9, 20).0[3], 0);
+
+        let wide = OffscreenOptions {
+            widescreen: true,
+            scale: 2,
+            ..options
+        };
+        let img = render_statusbar(&assets, &file, 0, &PreviewState::default(), &wide);
+        assert_eq!(img.dimensions(), (856, 400));
+    }
+
+    #[test]
+    fn test_translucency_blends_through_tranmap() {
+        let mut assets = AssetStore::default();
+        assets.load_rgba("UNDER", 1, 1, &[100, 100, 100, 255]);
+        assets.load_rgba("OVER", 1, 1, &[200, 200, 200, 255]);
+
+        let graphic = |patch: &str, translucency: bool| ElementWrapper {
+            data: Element::Graphic(GraphicDef {
+                common: CommonAttrs {
+                    translucency,
+                    ..Default::default()
+                },
+                patch: patch.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut file = SBarDefFile::new_empty();
+        file.data.status_bars = vec![StatusBarLayout {
+            fullscreen_render: true,
+            children: vec![graphic("UNDER", false), graphic("OVER", true)],
+            ..Default::default()
+        }];
+        let options = OffscreenOptions {
+            world: false,
+            ..Default::default()
+        };
+
+        // Without a table, the old 50% alpha approximation.
+        let img = render_statusbar(&assets, &file, 0, &PreviewState::default(), &options);
+        assert!(img.get_pixel(0, 0).0[0].abs_diff(150) <= 1);
+        let state = PreviewState::default();
+        assert!(translucency_overlay(&assets, &file, 0, &state, &options).is_none());
+
+        // The default palette is a greyscale ramp; weight the foreground 3:1.
+        let mut lump = vec![0u8; crate::render::tranmap::TRANMAP_SIZE];
+        for bg in 0..256usize {
+            for fg in 0..256usize {
+                lump[(bg << 8) | fg] = ((fg * 3 + bg) / 4) as u8;
+            }
+        }
+        assert!(assets.load_tranmap("TRANMAP", &lump));
+
+        let img = render_statusbar(&assets, &file, 0, &PreviewState::default(), &options);
+        assert_eq!(img.get_pixel(0, 0).0, [175, 175, 175, 255]);
+
+        // The editor's overlay holds just the blended pixel.
+        let overlay = translucency_overlay(&assets, &file, 0, &state, &options).unwrap();
+        assert_eq!(overlay.pixels[0], egui::Color32::from_gray(175));
+        assert_eq!(overlay.pixels[1], egui::Color32::TRANSPARENT);
+    }
+}
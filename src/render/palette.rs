@@ -32,4 +32,60 @@ impl DoomPalette {
     pub fn get(&self, index: u8) -> Color32 {
         self.colors[index as usize]
     }
+
+    /// Finds the palette entry for a colour, preferring an exact match.
+    pub fn nearest_index(&self, color: Color32) -> u8 {
+        self.nearest_in(color, ColorMatch::Euclidean, 0..=255)
+    }
+
+    /// Finds the entry within `range` closest to `color` under `method`.
+    pub fn nearest_in(
+        &self,
+        color: Color32,
+        method: ColorMatch,
+        range: std::ops::RangeInclusive<u8>,
+    ) -> u8 {
+        range
+            .min_by_key(|&i| method.distance(self.colors[i as usize], color))
+            .unwrap_or(0)
+    }
+}
+
+/// How colours are compared when matching them to the palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMatch {
+    /// Straight distance in RGB space.
+    Euclidean,
+    /// RGB distance weighted by the mean red level, which tracks how the eye
+    /// sees warm colours better than plain RGB.
+    Redmean,
+    /// Fixed 2:4:3 channel weights, favouring green like the eye does.
+    Weighted,
+}
+
+impl ColorMatch {
+    pub const ALL: [Self; 3] = [Self::Euclidean, Self::Redmean, Self::Weighted];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Euclidean => "Nearest RGB",
+            Self::Redmean => "Redmean",
+            Self::Weighted => "Weighted",
+        }
+    }
+
+    /// Squared distance between two colours, scaled to stay in integers.
+    pub fn distance(self, a: Color32, b: Color32) -> i64 {
+        let dr = a.r() as i64 - b.r() as i64;
+        let dg = a.g() as i64 - b.g() as i64;
+        let db = a.b() as i64 - b.b() as i64;
+        match self {
+            Self::Euclidean => dr * dr + dg * dg + db * db,
+            Self::Redmean => {
+                let rmean = (a.r() as i64 + b.r() as i64) / 2;
+                (512 + rmean) * dr * dr + 1024 * dg * dg + (767 - rmean) * db * db
+            }
+            Self::Weighted => 2 * dr * dr + 4 * dg * dg + 3 * db * db,
+        }
+    }
 }
@@ -0,0 +1,176 @@
+use super::projection::ViewportProjection;
+use crate::constants::{DOOM_H, DOOM_W};
+use crate::models::umapinfo::{MapExit, UmapInfoFile};
+use eframe::egui;
+use std::collections::{HashSet, VecDeque};
+
+/// Largest cell a map node gets, in virtual pixels.
+const MAX_CELL: egui::Vec2 = egui::vec2(56.0, 24.0);
+const MARGIN: f32 = 8.0;
+
+const EXIT_COLOR: egui::Color32 = egui::Color32::from_gray(170);
+const SECRET_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 180, 60);
+const ISSUE_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 100, 100);
+
+/// Where each map's node sits in the progression graph.
+pub struct GraphLayout {
+    /// Virtual-space rectangle of each map's node, by map index.
+    pub nodes: Vec<egui::Rect>,
+    pub exits: Vec<MapExit>,
+    cell: egui::Vec2,
+}
+
+impl GraphLayout {
+    /// Lays maps out in columns by how many exits they are from the first map
+    /// of their group, shrunk to fit the screen if needed. Maps nothing leads
+    /// to start new groups below the previous one.
+    pub fn new(file: &UmapInfoFile, origin_x: f32) -> Self {
+        let count = file.data.maps.len();
+        let exits = file.progression();
+        let mut cells: Vec<Option<(usize, usize)>> = vec![None; count];
+        let mut row_base = 0;
+
+        for root in 0..count {
+            if cells[root].is_some() {
+                continue;
+            }
+            let mut rows_in_col: Vec<usize> = Vec::new();
+            let mut queue = VecDeque::from([(root, 0)]);
+            let mut seen = HashSet::from([root]);
+            while let Some((map, col)) = queue.pop_front() {
+                if rows_in_col.len() <= col {
+                    rows_in_col.resize(col + 1, 0);
+                }
+                cells[map] = Some((col, row_base + rows_in_col[col]));
+                rows_in_col[col] += 1;
+                for e in exits.iter().filter(|e| e.from == map) {
+                    if cells[e.to].is_none() && seen.insert(e.to) {
+                        queue.push_back((e.to, col + 1));
+                    }
+                }
+            }
+            row_base += rows_in_col.iter().max().copied().unwrap_or(1);
+        }
+
+        let cols = cells.iter().flatten().map(|c| c.0 + 1).max().unwrap_or(1);
+        let rows = row_base.max(1);
+        let cell = egui::vec2(
+            MAX_CELL.x.min((DOOM_W - MARGIN * 2.0) / cols as f32),
+            MAX_CELL.y.min((DOOM_H - MARGIN * 2.0) / rows as f32),
+        );
+        let node_size = cell * egui::vec2(0.78, 0.6);
+        let nodes = cells
+            .iter()
+            .map(|c| {
+                let (col, row) = c.unwrap_or_default();
+                let min = egui::pos2(
+                    origin_x + MARGIN + col as f32 * cell.x,
+                    MARGIN + row as f32 * cell.y + (cell.y - node_size.y) / 2.0,
+                );
+                egui::Rect::from_min_size(min, node_size)
+            })
+            .collect();
+
+        Self { nodes, exits, cell }
+    }
+
+    /// The map whose node covers a virtual-space point.
+    pub fn node_at(&self, pos: egui::Pos2) -> Option<usize> {
+        self.nodes.iter().position(|r| r.contains(pos))
+    }
+}
+
+/// Draws the map progression graph: normal exits as solid arrows, secret
+/// exits as dashed gold ones. Maps with issues are outlined in red.
+pub fn draw_progression(
+    painter: &egui::Painter,
+    file: &UmapInfoFile,
+    layout: &GraphLayout,
+    flagged: &HashSet<usize>,
+    selected: Option<usize>,
+    proj: &ViewportProjection,
+) {
+    painter.rect_filled(proj.screen_rect, 0.0, egui::Color32::from_gray(16));
+    let to_screen = |p: egui::Pos2| proj.to_screen_subpixel(p);
+    let scale = proj.final_scale_y;
+
+    for e in &layout.exits {
+        let (from, to) = (layout.nodes[e.from], layout.nodes[e.to]);
+        let color = if e.secret { SECRET_COLOR } else { EXIT_COLOR };
+        // Secret exits leave from lower down so both exits to one map stay apart.
+        let lift = if e.secret { 0.25 } else { -0.25 } * from.height();
+        let start = from.right_center() + egui::vec2(0.0, lift);
+        let end = to.left_center();
+        let reach = (layout.cell.x * 0.6).max((end.x - start.x).abs() * 0.4);
+        // Exits back to an earlier column loop around underneath.
+        let dip = if end.x <= start.x { layout.cell.y } else { 0.0 };
+        let points = [
+            start,
+            start + egui::vec2(reach, dip),
+            end + egui::vec2(-reach, dip),
+            end,
+        ]
+        .map(to_screen);
+        let curve = egui::epaint::CubicBezierShape::from_points_stroke(
+            points,
+            false,
+            egui::Color32::TRANSPARENT,
+            egui::Stroke::NONE,
+        );
+        let path = curve.flatten(Some(0.5));
+        let stroke = egui::Stroke::new(1.0, color);
+        if e.secret {
+            painter.extend(egui::Shape::dashed_line(&path, stroke, 4.0, 3.0));
+        } else {
+            painter.add(egui::Shape::line(path.clone(), stroke));
+        }
+        draw_arrow_head(painter, points[2], points[3], color, scale);
+    }
+
+    let font = egui::FontId::monospace(
+        (layout.nodes.first().map_or(8.0, |r| r.height()) * 0.45 * scale).max(6.0),
+    );
+    for (i, (map, rect)) in file.data.maps.iter().zip(&layout.nodes).enumerate() {
+        let screen = egui::Rect::from_min_max(to_screen(rect.min), to_screen(rect.max));
+        let fill = if map.ends() {
+            egui::Color32::from_rgb(30, 50, 35)
+        } else {
+            egui::Color32::from_gray(40)
+        };
+        painter.rect_filled(screen, 2.0, fill);
+
+        let stroke = if selected == Some(i) {
+            egui::Stroke::new(2.0, egui::Color32::YELLOW)
+        } else if flagged.contains(&i) {
+            egui::Stroke::new(1.5, ISSUE_COLOR)
+        } else {
+            egui::Stroke::new(1.0, egui::Color32::from_white_alpha(50))
+        };
+        painter.rect_stroke(screen, 2.0, stroke, egui::StrokeKind::Inside);
+        painter.text(
+            screen.center(),
+            egui::Align2::CENTER_CENTER,
+            &map.mapname,
+            font.clone(),
+            egui::Color32::WHITE,
+        );
+    }
+}
+
+fn draw_arrow_head(
+    painter: &egui::Painter,
+    from: egui::Pos2,
+    tip: egui::Pos2,
+    color: egui::Color32,
+    scale: f32,
+) {
+    let dir = (tip - from).normalized();
+    let size = 2.5 * scale;
+    let side = dir.rot90() * size * 0.6;
+    let base = tip - dir * size;
+    painter.add(egui::Shape::convex_polygon(
+        vec![tip, base + side, base - side],
+        color,
+        egui::Stroke::NONE,
+    ));
+}
@@ -1,9 +1,78 @@
 use crate::assets::{AssetId, AssetStore};
-use crate::models::skydefs::{SkyDef, SkyType};
+use crate::constants::DOOM_H;
+use crate::models::skydefs::{FlatMap, SkyDef, SkyType};
 use crate::render::projection::ViewportProjection;
 use crate::state::PreviewState;
 use eframe::egui;
 
+/// How many sky texels one full turn covers, as in vanilla.
+pub const SKY_TEXELS_PER_TURN: f32 = 1024.0;
+
+/// How far freelook ports let the player look up or down, in degrees.
+const MAX_PITCH: f32 = 56.0;
+
+/// Doom's pixels are 20% taller than they are wide.
+const PIXEL_ASPECT: f32 = 1.2;
+
+/// Vertical strips the sky mesh is split into.
+const SKY_STRIPS: usize = 48;
+
+/// Horizontal fields of view the sky preview can simulate, in degrees.
+pub const FOV_PRESETS: [(&str, f32); 3] = [
+    ("Vanilla 90°", 90.0),
+    ("Widescreen 16:9", 106.26),
+    ("Ultrawide 21:9", 120.51),
+];
+
+/// A simulated player view used to preview skies.
+#[derive(Debug, Clone)]
+pub struct SkyCamera {
+    /// View angle in sky texels, wrapping at `SKY_TEXELS_PER_TURN`.
+    pub yaw: f32,
+    /// Degrees above the horizon.
+    pub pitch: f32,
+    /// Horizontal field of view, in degrees.
+    pub fov: f32,
+    /// Whether moving the mouse over the viewport turns the camera.
+    pub mouse_look: bool,
+}
+
+impl Default for SkyCamera {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: FOV_PRESETS[0].1,
+            mouse_look: false,
+        }
+    }
+}
+
+impl SkyCamera {
+    /// Turns by `yaw` texels and tilts by `pitch` degrees.
+    pub fn look(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw).rem_euclid(SKY_TEXELS_PER_TURN);
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Distance to the projection plane for a view `width` virtual pixels wide.
+    fn focal_length(&self, width: f32) -> f32 {
+        (width / 2.0) / (self.fov.clamp(10.0, 170.0).to_radians() / 2.0).tan()
+    }
+
+    /// The sky column seen at virtual `x` in a view `width` wide. Like
+    /// vanilla, columns count down towards the right.
+    pub fn column_at(&self, x: f32, width: f32) -> f32 {
+        let angle = ((x - width / 2.0) / self.focal_length(width)).atan();
+        self.yaw - angle.to_degrees() * SKY_TEXELS_PER_TURN / 360.0
+    }
+
+    /// How many rows the horizon drops by when looking up.
+    pub fn horizon_shift(&self, width: f32) -> f32 {
+        self.focal_length(width) / PIXEL_ASPECT * self.pitch.to_radians().tan()
+    }
+}
+
 pub fn draw_sky_view(
     painter: &egui::Painter,
     sky: &SkyDef,
@@ -17,7 +86,7 @@ pub fn draw_sky_view(
     if sky.sky_type == SkyType::Fire {
         let dynamic_key = format!("_FIRE_ANIM_{}", sky.name);
         let dynamic_id = AssetId::new(&dynamic_key);
-        if assets.textures.contains_key(&dynamic_id) {
+        if assets.has_image(&dynamic_id) {
             main_tex_id = dynamic_id;
         }
     }
@@ -56,6 +125,63 @@ pub fn draw_sky_view(
     }
 }
 
+/// Draws `map`'s sky across the viewport with its flat tiled in a panel on
+/// the left, as it would look on a ceiling meeting the sky.
+pub fn draw_flat_mapping(
+    painter: &egui::Painter,
+    map: &FlatMap,
+    sky: Option<&SkyDef>,
+    assets: &AssetStore,
+    state: &PreviewState,
+    proj: &ViewportProjection,
+    time: f64,
+) {
+    match sky {
+        Some(sky) => draw_sky_view(painter, sky, assets, state, proj, time),
+        None => {
+            painter.rect_filled(proj.screen_rect, 0.0, egui::Color32::BLACK);
+        }
+    }
+
+    let rect = proj.screen_rect;
+    let panel = egui::Rect::from_min_max(
+        rect.min,
+        egui::pos2(rect.left() + rect.width() / 3.0, rect.bottom()),
+    );
+    painter.rect_filled(panel, 0.0, egui::Color32::from_gray(20));
+
+    if let Some(tex) = assets.texture(painter.ctx(), &AssetId::new(&map.flat)) {
+        // Flats tile at their native size, scaled like everything else.
+        let tile = tex.size_vec2() * egui::vec2(proj.final_scale_x, proj.final_scale_y);
+        let uv = egui::Rect::from_min_max(
+            egui::pos2(0.0, 0.0),
+            egui::pos2(panel.width() / tile.x, panel.height() / tile.y),
+        );
+        let mut mesh = egui::Mesh::with_texture(tex.id());
+        mesh.add_rect_with_uv(panel, uv, egui::Color32::WHITE);
+        painter.add(mesh);
+    }
+    painter.line_segment(
+        [panel.right_top(), panel.right_bottom()],
+        egui::Stroke::new(2.0, egui::Color32::from_gray(10)),
+    );
+
+    let sky_label = sky.map_or(format!("{} (undefined)", map.sky), |s| s.name.clone());
+    let caption = format!("{}  ->  {}", map.flat, sky_label);
+    let galley = painter.layout_no_wrap(
+        caption,
+        egui::FontId::proportional(13.0),
+        egui::Color32::WHITE,
+    );
+    let pos = rect.left_bottom() + egui::vec2(8.0, -8.0 - galley.size().y);
+    painter.rect_filled(
+        egui::Rect::from_min_size(pos, galley.size()).expand(4.0),
+        2.0,
+        egui::Color32::from_black_alpha(180),
+    );
+    painter.galley(pos, galley, egui::Color32::WHITE);
+}
+
 fn draw_single_sky_layer(
     painter: &egui::Painter,
     id: AssetId,
@@ -69,7 +195,7 @@ fn draw_single_sky_layer(
     proj: &ViewportProjection,
     time: f64,
 ) {
-    if let Some(tex) = assets.textures.get(&id) {
+    if let Some(tex) = assets.texture(painter.ctx(), &id) {
         let rect = proj.screen_rect;
         let sky_tex_w = tex.size()[0] as f32;
         let sky_tex_h = tex.size()[1] as f32;
@@ -80,29 +206,65 @@ fn draw_single_sky_layer(
 
         let world_scale_x = scale_x.max(0.01);
         let world_scale_y = scale_y.max(0.01);
+        let camera = &state.viewer.sky_camera;
+        let view_w = rect.width() / proj.final_scale_x;
 
-        let view_width_texels = 256.0 / world_scale_x;
         let scroll_x_val = time as f32 * scroll_x;
-        let yaw_offset = (state.viewer.sky_yaw as f32) + scroll_x_val;
-
         let scroll_y_val = time as f32 * scroll_y;
-        let v_center_texel = mid + scroll_y_val + state.viewer.weapon_offset_y;
-        let v_half_extent_texels = 80.0 / world_scale_y;
 
+        // The sky isn't perspective-scaled vertically: one texel per row,
+        // centred on `mid` at the horizon, which looking up pushes down.
+        let horizon = DOOM_H / 2.0 + camera.horizon_shift(view_w);
+        let v_at = |row: f32| (mid + scroll_y_val + (row - horizon) / world_scale_y) / sky_tex_h;
+        let (v_top, v_bottom) = (v_at(0.0), v_at(DOOM_H));
+
+        // Columns follow the view angle, so the mesh is split into strips
+        // to bend the texture like the column-by-column renderer does.
         let mut mesh = egui::Mesh::with_texture(tex.id());
-        let uv_max_x = yaw_offset / sky_tex_w;
-        let uv_min_x = (yaw_offset + view_width_texels) / sky_tex_w;
-        let uv_min_y = (v_center_texel - v_half_extent_texels) / sky_tex_h;
-        let uv_max_y = (v_center_texel + v_half_extent_texels) / sky_tex_h;
-
-        mesh.add_rect_with_uv(
-            rect,
-            egui::Rect::from_min_max(
-                egui::pos2(uv_min_x, uv_min_y),
-                egui::pos2(uv_max_x, uv_max_y),
-            ),
-            egui::Color32::WHITE,
-        );
+        for i in 0..=SKY_STRIPS {
+            let t = i as f32 / SKY_STRIPS as f32;
+            let column = camera.column_at(t * view_w, view_w);
+            let u = (column / world_scale_x + scroll_x_val) / sky_tex_w;
+            let x = rect.left() + t * rect.width();
+            let base = mesh.vertices.len() as u32;
+            for (y, v) in [(rect.top(), v_top), (rect.bottom(), v_bottom)] {
+                mesh.vertices.push(egui::epaint::Vertex {
+                    pos: egui::pos2(x, y),
+                    uv: egui::pos2(u, v),
+                    color: egui::Color32::WHITE,
+                });
+            }
+            if i > 0 {
+                mesh.add_triangle(base - 2, base - 1, base);
+                mesh.add_triangle(base - 1, base, base + 1);
+            }
+        }
         painter.add(mesh);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sky_camera() {
+        let mut camera = SkyCamera::default();
+
+        // A 90° view spans a quarter turn, 256 texels.
+        let span = camera.column_at(0.0, 320.0) - camera.column_at(320.0, 320.0);
+        assert!((span - 256.0).abs() < 1e-3);
+
+        // A wider FOV on a wider screen keeps the same scale at the centre.
+        camera.fov = FOV_PRESETS[1].1;
+        let centre =
+            |c: &SkyCamera, w: f32| c.column_at(w / 2.0, w) - c.column_at(w / 2.0 + 1.0, w);
+        assert!((centre(&camera, 428.0) - centre(&SkyCamera::default(), 320.0)).abs() < 1e-2);
+
+        camera.look(-10.0, 90.0);
+        assert_eq!(camera.yaw, 1014.0);
+        assert_eq!(camera.pitch, MAX_PITCH);
+        assert_eq!(SkyCamera::default().horizon_shift(320.0), 0.0);
+        assert!(camera.horizon_shift(320.0) > 0.0);
+    }
+}
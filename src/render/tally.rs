@@ -0,0 +1,383 @@
+use super::projection::ViewportProjection;
+use crate::assets::{AssetId, AssetStore};
+use crate::constants::{DOOM_TICS_PER_SEC, DOOM_W};
+use crate::state::{IntermissionState, PreviewState};
+use eframe::egui;
+
+/// Top of the level name / "Finished" titles.
+const TITLE_Y: f32 = 2.0;
+
+/// Where the kills/items/secrets rows start.
+const STATS_X: f32 = 50.0;
+const STATS_Y: f32 = 50.0;
+
+/// Where the time and par row sits.
+const TIME_X: f32 = 16.0;
+const TIME_Y: f32 = 200.0 - 32.0;
+
+/// Tics between each counting stage.
+const PAUSE_TICS: u32 = DOOM_TICS_PER_SEC as u32;
+
+/// How long the finished tally stays up before the preview starts over.
+const END_HOLD: f64 = 3.0 * DOOM_TICS_PER_SEC;
+
+/// Times past this are drawn as "sucks".
+const MAX_TIME: i32 = 61 * 59;
+
+/// The values the tally counts up to. Percentages, then times in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TallyTargets {
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+    pub time: i32,
+    pub par: i32,
+}
+
+impl TallyTargets {
+    pub fn from_state(state: &PreviewState) -> Self {
+        let p = &state.sim.player;
+        Self {
+            kills: state.get_stat_percent(p.kills, p.max_kills),
+            items: state.get_stat_percent(p.items, p.max_items),
+            secrets: state.get_stat_percent(p.secrets, p.max_secrets),
+            time: state.intermission.time,
+            par: state.intermission.par,
+        }
+    }
+}
+
+/// What the tally shows at some tic. Negative counts aren't drawn yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TallyCounts {
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+    pub time: i32,
+    pub par: i32,
+    pub done: bool,
+}
+
+/// Steps `count` towards `target`, returning `true` once it's reached.
+fn count_up(count: &mut i32, target: i32, step: i32) -> bool {
+    *count = (*count + step).min(target.max(0));
+    *count >= target
+}
+
+/// Replays the single player count-up of `WI_updateStats` for `tic` tics:
+/// a pause, kills, a pause, items, a pause, secrets, a pause, then time and
+/// par together.
+pub fn counts_at(targets: &TallyTargets, tic: u32) -> TallyCounts {
+    let mut c = TallyCounts {
+        kills: -1,
+        items: -1,
+        secrets: -1,
+        time: -1,
+        par: -1,
+        done: false,
+    };
+    let mut stage = 1;
+    let mut pause = PAUSE_TICS;
+
+    for _ in 0..tic {
+        let finished = match stage {
+            2 => count_up(&mut c.kills, targets.kills, 2),
+            4 => count_up(&mut c.items, targets.items, 2),
+            6 => count_up(&mut c.secrets, targets.secrets, 2),
+            8 => {
+                let time = count_up(&mut c.time, targets.time, 3);
+                count_up(&mut c.par, targets.par, 3) && time
+            }
+            10 => break,
+            _ => {
+                pause -= 1;
+                pause == 0
+            }
+        };
+        if finished {
+            stage += 1;
+            pause = PAUSE_TICS;
+        }
+    }
+
+    c.done = stage >= 10;
+    c
+}
+
+/// The tic the count-up finishes on.
+fn finish_tic(targets: &TallyTargets) -> u32 {
+    // Counts start at -1, and every stage takes at least one tic.
+    let counting = |target: i32, step: u32| (target.max(0) as u32 + 1).div_ceil(step);
+    let time = counting(targets.time, 3).max(counting(targets.par, 3));
+    5 * PAUSE_TICS
+        + counting(targets.kills, 2)
+        + counting(targets.items, 2)
+        + counting(targets.secrets, 2)
+        + time
+}
+
+/// Playback position of the tally overlay, looping once it's finished.
+#[derive(Debug, Clone, Default)]
+pub struct TallyPlayback {
+    pub tic: f64,
+    last_time: Option<f64>,
+}
+
+impl TallyPlayback {
+    /// Advances playback to `time`, in seconds.
+    pub fn advance(&mut self, targets: &TallyTargets, time: f64) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).max(0.0));
+        self.last_time = Some(time);
+        let length = finish_tic(targets) as f64 + END_HOLD;
+        self.tic = (self.tic + dt * DOOM_TICS_PER_SEC) % length;
+    }
+}
+
+/// Size of a patch, if it's loaded.
+fn patch_size(ctx: &egui::Context, assets: &AssetStore, name: &str) -> Option<egui::Vec2> {
+    assets
+        .texture(ctx, &AssetId::new(name))
+        .map(|tex| tex.size_vec2())
+}
+
+/// Draws a patch at (`x`, `y`) honouring its offsets, like `V_DrawPatch`.
+fn draw_patch(
+    painter: &egui::Painter,
+    assets: &AssetStore,
+    name: &str,
+    pos: egui::Pos2,
+    proj: &ViewportProjection,
+) {
+    let id = AssetId::new(name);
+    let Some(tex) = assets.texture(painter.ctx(), &id) else {
+        return;
+    };
+    let (left, top) = assets
+        .offsets
+        .get(&id)
+        .map(|(x, y)| (*x as f32, *y as f32))
+        .unwrap_or((0.0, 0.0));
+    let min = egui::pos2(proj.origin_x + pos.x - left, pos.y - top);
+    let rect = egui::Rect::from_min_size(min, tex.size_vec2());
+    let screen = egui::Rect::from_min_max(proj.to_screen(rect.min), proj.to_screen(rect.max));
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    painter.image(tex.id(), screen, uv, egui::Color32::WHITE);
+}
+
+/// The intermission number font, drawn right-aligned like `WI_drawNum`.
+struct Numbers<'a> {
+    painter: &'a egui::Painter,
+    assets: &'a AssetStore,
+    proj: &'a ViewportProjection,
+    width: f32,
+}
+
+impl Numbers<'_> {
+    /// Draws `n` ending at `x`, zero-padded to `digits` if given. Returns the
+    /// left edge of what was drawn.
+    fn draw(&self, mut x: f32, y: f32, n: i32, digits: Option<u32>) -> f32 {
+        let digits =
+            digits.unwrap_or_else(|| n.unsigned_abs().checked_ilog10().map_or(1, |d| d + 1));
+        let mut n = n.unsigned_abs();
+        for _ in 0..digits {
+            x -= self.width;
+            let patch = format!("WINUM{}", n % 10);
+            draw_patch(
+                self.painter,
+                self.assets,
+                &patch,
+                egui::pos2(x, y),
+                self.proj,
+            );
+            n /= 10;
+        }
+        x
+    }
+
+    /// A percentage ending at `x`, with the `%` sign drawn at `x`.
+    fn draw_percent(&self, x: f32, y: f32, p: i32) {
+        if p < 0 {
+            return;
+        }
+        draw_patch(
+            self.painter,
+            self.assets,
+            "WIPCNT",
+            egui::pos2(x, y),
+            self.proj,
+        );
+        self.draw(x, y, p, None);
+    }
+
+    /// A time in seconds as `h:mm:ss` ending at `x`, like `WI_drawTime`.
+    fn draw_time(&self, mut x: f32, y: f32, t: i32) {
+        if t < 0 {
+            return;
+        }
+        if t > MAX_TIME {
+            let sucks_w =
+                patch_size(self.painter.ctx(), self.assets, "WISUCKS").map_or(0.0, |s| s.x);
+            draw_patch(
+                self.painter,
+                self.assets,
+                "WISUCKS",
+                egui::pos2(x - sucks_w, y),
+                self.proj,
+            );
+            return;
+        }
+        let colon_w = patch_size(self.painter.ctx(), self.assets, "WICOLON").map_or(0.0, |s| s.x);
+        let mut div = 1;
+        loop {
+            let n = (t / div) % 60;
+            x = self.draw(x, y, n, Some(2)) - colon_w;
+            div *= 60;
+            if div == 60 || t / div != 0 {
+                draw_patch(
+                    self.painter,
+                    self.assets,
+                    "WICOLON",
+                    egui::pos2(x, y),
+                    self.proj,
+                );
+            }
+            if t / div == 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// The level name patch for `map`: UMAPINFO's `levelpic` if set, otherwise
+/// `CWILVxx` for Doom 2 style maps or `WILVem` for episodic ones.
+fn level_pic(
+    ctx: &egui::Context,
+    intermission: &IntermissionState,
+    episode: i32,
+    map: i32,
+    assets: &AssetStore,
+) -> Option<String> {
+    let names = [format!("MAP{:02}", map), format!("E{}M{}", episode, map)];
+    if let Some(pic) = names.iter().find_map(|n| intermission.level_pics.get(n)) {
+        return Some(pic.clone());
+    }
+    [
+        format!("CWILV{:02}", map - 1),
+        format!("WILV{}{}", episode - 1, map - 1),
+    ]
+    .into_iter()
+    .find(|name| patch_size(ctx, assets, name).is_some())
+}
+
+/// Draws a patch centred horizontally on the 320-wide screen.
+fn draw_centered(
+    painter: &egui::Painter,
+    assets: &AssetStore,
+    name: &str,
+    y: f32,
+    proj: &ViewportProjection,
+) {
+    if let Some(size) = patch_size(painter.ctx(), assets, name) {
+        let x = ((DOOM_W - size.x) / 2.0).floor();
+        draw_patch(painter, assets, name, egui::pos2(x, y), proj);
+    }
+}
+
+/// Draws the classic single player intermission on top of the background:
+/// "Finished" with the tally, or "Entering" for the next map.
+pub fn draw_tally(
+    painter: &egui::Painter,
+    state: &PreviewState,
+    counts: &TallyCounts,
+    assets: &AssetStore,
+    proj: &ViewportProjection,
+) {
+    let ctx = painter.ctx();
+    let intermission = &state.intermission;
+    let episode = state.sim.world.episode;
+    let height = |name: &str| patch_size(ctx, assets, name).map_or(0.0, |s| s.y);
+    let scaled = |h: f32| (5.0 * h / 4.0).floor();
+
+    if intermission.entering {
+        let level = level_pic(ctx, intermission, episode, intermission.next_map, assets);
+        draw_centered(painter, assets, "WIENTER", TITLE_Y, proj);
+        if let Some(level) = level {
+            let y = TITLE_Y + scaled(height(&level));
+            draw_centered(painter, assets, &level, y, proj);
+        }
+        return;
+    }
+
+    let level = level_pic(ctx, intermission, episode, intermission.current_map, assets);
+    let mut y = TITLE_Y;
+    if let Some(level) = level {
+        draw_centered(painter, assets, &level, y, proj);
+        y += scaled(height(&level));
+    }
+    draw_centered(painter, assets, "WIF", y, proj);
+
+    let digit = patch_size(ctx, assets, "WINUM0").unwrap_or(egui::vec2(14.0, 12.0));
+    let numbers = Numbers {
+        painter,
+        assets,
+        proj,
+        width: digit.x,
+    };
+    let line_h = (3.0 * digit.y / 2.0).floor();
+    let right = DOOM_W - STATS_X;
+
+    let rows = [
+        ("WIOSTK", counts.kills),
+        ("WIOSTI", counts.items),
+        ("WISCRT2", counts.secrets),
+    ];
+    for (i, (label, count)) in rows.into_iter().enumerate() {
+        let row_y = STATS_Y + i as f32 * line_h;
+        draw_patch(painter, assets, label, egui::pos2(STATS_X, row_y), proj);
+        numbers.draw_percent(right, row_y, count);
+    }
+
+    draw_patch(painter, assets, "WITIME", egui::pos2(TIME_X, TIME_Y), proj);
+    numbers.draw_time(DOOM_W / 2.0 - TIME_X, TIME_Y, counts.time);
+    if intermission.par > 0 {
+        let par_pos = egui::pos2(DOOM_W / 2.0 + TIME_X, TIME_Y);
+        draw_patch(painter, assets, "WIPAR", par_pos, proj);
+        numbers.draw_time(DOOM_W - TIME_X, TIME_Y, counts.par);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_count_up() {
+        let targets = TallyTargets {
+            kills: 5,
+            items: 0,
+            secrets: 100,
+            time: 90,
+            par: 30,
+        };
+
+        let start = counts_at(&targets, PAUSE_TICS);
+        assert_eq!((start.kills, start.items), (-1, -1));
+
+        // Kills count in twos from -1, then hold at the target.
+        assert_eq!(counts_at(&targets, PAUSE_TICS + 1).kills, 1);
+        assert_eq!(counts_at(&targets, PAUSE_TICS + 3).kills, 5);
+        let paused = counts_at(&targets, PAUSE_TICS + 3 + PAUSE_TICS - 1);
+        assert_eq!((paused.kills, paused.items), (5, -1));
+
+        let end = finish_tic(&targets);
+        let almost = counts_at(&targets, end - 1);
+        assert!(!almost.done);
+        assert_eq!(almost.par, 30);
+        let done = counts_at(&targets, end + 1);
+        assert!(done.done);
+        assert_eq!(
+            (done.kills, done.items, done.secrets, done.time, done.par),
+            (5, 0, 100, 90, 30)
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use super::{RenderContext, get_alignment_anchor_offset};
+use crate::assets::AssetId;
 use crate::constants::{DEFAULT_GLYPH_H, DEFAULT_GLYPH_W};
 use crate::models::sbardef::*;
 use eframe::egui;
@@ -156,7 +157,7 @@ pub fn draw_text_line(
     let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
 
     for glyph in layout.glyphs {
-        if let Some(tex) = glyph.texture {
+        if let Some(tex) = glyph.texture.and_then(|id| ctx.patch_texture(&id)) {
             let y_adj = glyph.y_offset * scale_adjustment;
             let char_pos = if ctx.is_native {
                 egui::pos2(cur_x, start_y + y_adj)
@@ -188,12 +189,12 @@ pub fn measure_text_size(ctx: &RenderContext, txt: &str, font: &str, is_num: boo
     layout_text_line(ctx, txt, font, is_num).map_or(egui::Vec2::ZERO, |l| l.size)
 }
 
-fn layout_text_line<'a>(
-    ctx: &'a RenderContext,
+fn layout_text_line(
+    ctx: &RenderContext,
     text: &str,
     font: &str,
     is_num: bool,
-) -> Option<TextLayout<'a>> {
+) -> Option<TextLayout> {
     let (stem, font_type) = if is_num {
         ctx.file
             .data
@@ -220,16 +221,15 @@ fn layout_text_line<'a>(
         let zero_id = ctx.assets.resolve_patch_id(&stem, '0', is_num);
         mono_width = ctx
             .assets
-            .textures
-            .get(&zero_id)
-            .map(|t| t.size_vec2().x)
+            .image_size_vec2(&zero_id)
+            .map(|sz| sz.x)
             .unwrap_or(DEFAULT_GLYPH_W);
     } else if font_type == 1 {
         let chars = if is_num { "0123456789" } else { "ABCDEFGHJM" };
         for c in chars.chars() {
             let id = ctx.assets.resolve_patch_id(&stem, c, is_num);
-            if let Some(tex) = ctx.assets.textures.get(&id) {
-                mono_width = mono_width.max(tex.size_vec2().x);
+            if let Some(sz) = ctx.assets.image_size_vec2(&id) {
+                mono_width = mono_width.max(sz.x);
             }
         }
         if mono_width == 0.0 {
@@ -253,8 +253,7 @@ fn layout_text_line<'a>(
 
         let id = ctx.assets.resolve_patch_id(&stem, c, is_num);
 
-        if let Some(tex) = ctx.assets.textures.get(&id) {
-            let sz = tex.size_vec2();
+        if let Some(sz) = ctx.assets.image_size_vec2(&id) {
             let mut y_offset = 0.0;
 
             let mut advance = if font_type == 2 { sz.x } else { mono_width };
@@ -272,7 +271,7 @@ fn layout_text_line<'a>(
             }
 
             glyphs.push(Glyph {
-                texture: Some(tex),
+                texture: Some(id),
                 tex_w: sz.x,
                 advance,
                 h: sz.y,
@@ -309,15 +308,15 @@ fn layout_text_line<'a>(
     })
 }
 
-struct Glyph<'a> {
-    texture: Option<&'a egui::TextureHandle>,
+struct Glyph {
+    texture: Option<AssetId>,
     tex_w: f32,
     advance: f32,
     h: f32,
     y_offset: f32,
 }
 
-struct TextLayout<'a> {
-    glyphs: Vec<Glyph<'a>>,
+struct TextLayout {
+    glyphs: Vec<Glyph>,
     size: egui::Vec2,
 }
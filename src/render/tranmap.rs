@@ -0,0 +1,78 @@
+use super::palette::DoomPalette;
+
+/// Size of a TRANMAP lump: one output index for every (background, foreground) pair.
+pub const TRANMAP_SIZE: usize = 256 * 256;
+
+/// A Boom-style translucency table.
+pub struct TranMap {
+    table: Vec<u8>,
+}
+
+impl TranMap {
+    pub fn from_lump(bytes: &[u8]) -> Option<Self> {
+        (bytes.len() == TRANMAP_SIZE).then(|| Self {
+            table: bytes.to_vec(),
+        })
+    }
+
+    /// Returns the palette index a foreground pixel leaves over a background
+    /// pixel, using Boom's `tranmap[(bg << 8) + fg]` layout.
+    pub fn blend(&self, fg: u8, bg: u8) -> u8 {
+        self.table[((bg as usize) << 8) | fg as usize]
+    }
+
+    /// Estimates the foreground weight the table was built with, so the GPU
+    /// preview (which can't look up palette indices) can approximate it with
+    /// plain alpha blending. Boom's default map comes out at about 0.66.
+    pub fn opacity(&self, palette: &DoomPalette) -> f32 {
+        let mut num = 0.0f64;
+        let mut den = 0.0f64;
+
+        // A sparse sample is plenty for a least-squares fit of one weight.
+        for bg in (0..=255u8).step_by(7) {
+            for fg in (0..=255u8).step_by(5) {
+                let b = palette.get(bg);
+                let f = palette.get(fg);
+                let r = palette.get(self.blend(fg, bg));
+                for (bc, fc, rc) in [
+                    (b.r(), f.r(), r.r()),
+                    (b.g(), f.g(), r.g()),
+                    (b.b(), f.b(), r.b()),
+                ] {
+                    let d = fc as f64 - bc as f64;
+                    num += (rc as f64 - bc as f64) * d;
+                    den += d * d;
+                }
+            }
+        }
+
+        if den > 0.0 {
+            (num / den).clamp(0.0, 1.0) as f32
+        } else {
+            0.5
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend_layout_and_opacity() {
+        // A greyscale palette blended at 3:1 towards the foreground.
+        let palette = DoomPalette::default();
+        let mut lump = vec![0u8; TRANMAP_SIZE];
+        for bg in 0..256usize {
+            for fg in 0..256usize {
+                lump[(bg << 8) | fg] = ((fg * 3 + bg) / 4) as u8;
+            }
+        }
+        let map = TranMap::from_lump(&lump).unwrap();
+
+        assert_eq!(map.blend(200, 0), 150);
+        assert_eq!(map.blend(0, 200), 50);
+        assert!((map.opacity(&palette) - 0.75).abs() < 0.01);
+        assert!(TranMap::from_lump(&lump[..100]).is_none());
+    }
+}
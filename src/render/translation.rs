@@ -0,0 +1,124 @@
+use super::palette::DoomPalette;
+use eframe::egui::Color32;
+use std::collections::HashMap;
+
+/// A palette remapping table: entry `i` is the index colour `i` is drawn as.
+pub type TranslationTable = [u8; 256];
+
+/// Largest lump worth parsing as an ID24 JSON translation.
+const MAX_JSON_SIZE: usize = 16 * 1024;
+
+fn opens_as_json(bytes: &[u8]) -> bool {
+    bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{')
+}
+
+/// Whether a lump could be a translation at all: a 256-byte table under a
+/// `CR` name, or a small lump that opens like JSON. Cheap enough to ask of
+/// every lump in a WAD.
+pub fn may_be_translation(name: &str, bytes: &[u8]) -> bool {
+    if opens_as_json(bytes) {
+        bytes.len() <= MAX_JSON_SIZE
+    } else {
+        bytes.len() == 256 && crate::assets::AssetStore::stem(name).starts_with("CR")
+    }
+}
+
+/// Parses a translation lump in either of the formats ports understand:
+/// a raw 256-byte Boom colour range lump (`CRRED`, `CRGOLD`, ...) or an ID24
+/// JSON lump of type `translation` with a 256-entry `table`.
+///
+/// Raw tables have no header, so they're only recognised by their `CR` name.
+pub fn parse_translation_lump(name: &str, bytes: &[u8]) -> Option<TranslationTable> {
+    if !may_be_translation(name, bytes) {
+        return None;
+    }
+    if !opens_as_json(bytes) {
+        return bytes.try_into().ok();
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    if !json["type"]
+        .as_str()
+        .is_some_and(|t| t.eq_ignore_ascii_case("translation"))
+    {
+        return None;
+    }
+
+    let entries = json["data"]["table"].as_array()?;
+    if entries.len() != 256 {
+        return None;
+    }
+
+    let mut table = [0u8; 256];
+    for (slot, entry) in table.iter_mut().zip(entries) {
+        *slot = u8::try_from(entry.as_u64()?).ok()?;
+    }
+    Some(table)
+}
+
+/// Recolours unmultiplied RGBA pixels through a translation table.
+///
+/// Decoded patches no longer carry their palette indices, so each colour is
+/// mapped back to its palette entry first (exactly, or to the nearest entry
+/// for truecolour PNGs, like ports do on load). Transparent pixels are kept.
+pub fn translate_rgba(pixels: &[u8], palette: &DoomPalette, table: &TranslationTable) -> Vec<u8> {
+    let mut lookup: HashMap<[u8; 3], Color32> = HashMap::new();
+    let mut out = pixels.to_vec();
+
+    for px in out.chunks_exact_mut(4) {
+        if px[3] == 0 {
+            continue;
+        }
+        let rgb = [px[0], px[1], px[2]];
+        let color = *lookup.entry(rgb).or_insert_with(|| {
+            let index = palette.nearest_index(Color32::from_rgb(rgb[0], rgb[1], rgb[2]));
+            palette.get(table[index as usize])
+        });
+        px[0] = color.r();
+        px[1] = color.g();
+        px[2] = color.b();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_apply_translations() {
+        let mut raw = [0u8; 256];
+        for (i, v) in raw.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+        raw[10] = 200;
+        assert_eq!(parse_translation_lump("CRRED", &raw), Some(raw));
+        assert_eq!(parse_translation_lump("DSPISTOL", &raw), None);
+
+        let json = format!(
+            r#"{{"type": "translation", "version": "1.0.0", "data": {{"table": {:?}}}}}"#,
+            raw.to_vec()
+        );
+        assert_eq!(parse_translation_lump("TRRED", json.as_bytes()), Some(raw));
+        assert_eq!(
+            parse_translation_lump("SKYDEFS", br#"{"type": "skydefs"}"#),
+            None
+        );
+        assert_eq!(parse_translation_lump("CRBLUE", b"not a table"), None);
+
+        // Only CR* tables and JSON are worth trying; a demo or a sound the
+        // size of a table isn't.
+        assert!(may_be_translation("TRRED", json.as_bytes()));
+        assert!(!may_be_translation("DEMO1", &raw));
+        let padded = format!("{}{}", json, " ".repeat(16 * 1024));
+        assert!(!may_be_translation("TRRED", padded.as_bytes()));
+
+        // The default palette is a greyscale ramp, so index == channel value.
+        let palette = DoomPalette::default();
+        let pixels = [10, 10, 10, 255, 10, 10, 10, 0, 11, 11, 11, 255];
+        assert_eq!(
+            translate_rgba(&pixels, &palette, &raw),
+            vec![200, 200, 200, 255, 10, 10, 10, 0, 11, 11, 11, 255]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::models::umapinfo::{UmapField, UmapInfoFile};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// A mock of the game's progress through an episode, used to preview which
+/// INTERLEVEL layers and anims would be shown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntermissionState {
+    /// The map just finished.
+    pub current_map: i32,
+    /// The map about to be entered.
+    pub next_map: i32,
+    /// Maps visited so far this episode.
+    pub visited: BTreeSet<i32>,
+    /// Maps that count as secret maps.
+    pub secret_maps: BTreeSet<i32>,
+    /// Whether the "entering" screen is showing rather than the tally.
+    pub entering: bool,
+    /// How many maps the episode has, for stepping through it.
+    pub episode_length: i32,
+    /// Level time and par time shown on the tally, in seconds.
+    pub time: i32,
+    pub par: i32,
+    /// Whether the tally overlay is drawn over the INTERLEVEL preview.
+    pub show_tally: bool,
+    /// UMAPINFO `levelpic` overrides by map name, synced from the project.
+    #[serde(skip)]
+    pub level_pics: HashMap<String, String>,
+    /// Hash of the UMAPINFO `levelpic`s that `level_pics` was built from.
+    #[serde(skip)]
+    level_pics_source: Option<u64>,
+}
+
+impl Default for IntermissionState {
+    fn default() -> Self {
+        let mut state = Self {
+            current_map: 1,
+            next_map: 2,
+            visited: BTreeSet::new(),
+            secret_maps: BTreeSet::from([9]),
+            entering: false,
+            episode_length: 9,
+            time: 95,
+            par: 30,
+            show_tally: false,
+            level_pics: HashMap::new(),
+            level_pics_source: None,
+        };
+        state.go_to_map(1);
+        state
+    }
+}
+
+impl IntermissionState {
+    /// The map the conditions refer to: the finished map on the tally screen,
+    /// the next map on the entering screen.
+    pub fn shown_map(&self) -> i32 {
+        if self.entering {
+            self.next_map
+        } else {
+            self.current_map
+        }
+    }
+
+    /// Jumps to the intermission after `map`, as if every map before it had
+    /// been played in order. Secret maps stay visited only if already marked.
+    pub fn go_to_map(&mut self, map: i32) {
+        let map = map.clamp(1, self.episode_length.max(1));
+        let secret_visited: BTreeSet<i32> = self
+            .visited
+            .intersection(&self.secret_maps)
+            .copied()
+            .filter(|m| *m <= map)
+            .collect();
+
+        self.current_map = map;
+        self.next_map = map + 1;
+        self.visited = (1..=map)
+            .filter(|m| !self.secret_maps.contains(m) || *m == map)
+            .chain(secret_visited)
+            .collect();
+    }
+
+    /// Picks up the `levelpic` of every map in the project's UMAPINFO. The
+    /// table is only rebuilt when those change.
+    pub fn sync_level_pics(&mut self, umapinfo: Option<&UmapInfoFile>) {
+        let maps = umapinfo.map_or(&[][..], |u| u.data.maps.as_slice());
+        let pics: Vec<(&str, &str)> = maps
+            .iter()
+            .filter_map(|map| {
+                map.fields.iter().find_map(|f| match f {
+                    UmapField::LevelPic(pic) if !pic.is_empty() => {
+                        Some((map.mapname.as_str(), pic.as_str()))
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+
+        let mut hasher = DefaultHasher::new();
+        pics.hash(&mut hasher);
+        let source = hasher.finish();
+        if self.level_pics_source == Some(source) {
+            return;
+        }
+        self.level_pics_source = Some(source);
+        self.level_pics = pics
+            .into_iter()
+            .map(|(map, pic)| (map.to_uppercase(), pic.to_uppercase()))
+            .collect();
+    }
+}
@@ -1,8 +1,10 @@
 pub mod interaction;
+pub mod intermission;
 pub mod simulation;
 pub mod viewer;
 
 pub use interaction::InteractionState;
+pub use intermission::IntermissionState;
 pub use simulation::{EngineContext, SimulationState, SlotMapping};
 pub use viewer::ViewerState;
 
@@ -13,6 +15,10 @@ pub struct PreviewState {
     /// The serialized Doom simulation.
     pub sim: SimulationState,
 
+    /// The mock episode progress INTERLEVEL conditions are checked against.
+    #[serde(default)]
+    pub intermission: IntermissionState,
+
     /// Transient interaction state (Editor logic).
     #[serde(skip)]
     pub interaction: InteractionState,
@@ -1,8 +1,16 @@
 use crate::assets::AssetId;
 use crate::constants::DOOM_TICS_PER_SEC;
+use crate::render::bunny::BunnyPlayback;
+use crate::render::cast::CastPlayback;
 use crate::render::fire::FireSimulation;
+use crate::render::interlevel::InterlevelPlayback;
+use crate::render::sky::SkyCamera;
+use crate::render::tally::TallyPlayback;
 use std::collections::HashMap;
 
+/// How far the weapon sprite drops before the newly selected weapon is raised.
+pub const WEAPON_LOWER_HEIGHT: f32 = 150.0;
+
 /// Handles transient visual state for the viewport preview.
 #[derive(Debug, Clone)]
 pub struct ViewerState {
@@ -14,8 +22,12 @@ pub struct ViewerState {
     pub display_weapon_slot: u8,
     pub display_super_shotgun: bool,
     pub weapon_offset_y: f32,
-    pub sky_yaw: i32,
+    pub sky_camera: SkyCamera,
     pub fire_sims: HashMap<AssetId, FireSimulation>,
+    pub cast: CastPlayback,
+    pub bunny: BunnyPlayback,
+    pub interlevel: InterlevelPlayback,
+    pub tally: TallyPlayback,
 }
 
 impl Default for ViewerState {
@@ -29,8 +41,12 @@ impl Default for ViewerState {
             display_weapon_slot: 2,
             display_super_shotgun: true,
             weapon_offset_y: 0.0,
-            sky_yaw: 0,
+            sky_camera: SkyCamera::default(),
             fire_sims: HashMap::new(),
+            cast: CastPlayback::default(),
+            bunny: BunnyPlayback::default(),
+            interlevel: InterlevelPlayback::default(),
+            tally: TallyPlayback::default(),
         }
     }
 }
@@ -53,7 +69,6 @@ impl ViewerState {
         self.pain_timer = (self.pain_timer - dt).max(0.0);
 
         let speed = 600.0 * dt;
-        let clear_height = 150.0;
 
         let distinct_weapons = self.display_weapon_slot != target_slot;
         let distinct_variants = self.display_weapon_slot == 3
@@ -62,7 +77,7 @@ impl ViewerState {
 
         if distinct_weapons || distinct_variants {
             self.weapon_offset_y += speed;
-            if self.weapon_offset_y >= clear_height {
+            if self.weapon_offset_y >= WEAPON_LOWER_HEIGHT {
                 self.display_weapon_slot = target_slot;
                 self.display_super_shotgun = use_ssg;
             }
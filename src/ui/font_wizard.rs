@@ -251,7 +251,7 @@ fn draw_coverage_tiles(ui: &mut egui::Ui, data: &FontWizardState, assets: &Asset
             let is_num = data.font_type == FontTypeWrapper::Number;
 
             let id = assets.resolve_patch_id(&data.detected_stem, *c, is_num);
-            let texture = assets.textures.get(&id);
+            let texture = assets.texture(ui.ctx(), &id);
             let found = texture.is_some();
 
             let (rect, response) =
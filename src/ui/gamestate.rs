@@ -1,5 +1,5 @@
 use crate::assets::{AssetId, AssetStore};
-use crate::state::PreviewState;
+use crate::state::{IntermissionState, PreviewState};
 use crate::ui::messages::{self, EditorEvent, MessageItem};
 use crate::ui::shared;
 use eframe::egui;
@@ -277,6 +277,125 @@ pub fn draw_gamestate_panel(ui: &mut egui::Ui, state: &mut PreviewState, assets:
     });
 }
 
+/// Draws the mock intermission state INTERLEVEL conditions are checked against:
+/// which screen is up, the current and next map, and the visited maps.
+pub fn draw_intermission_panel(ui: &mut egui::Ui, state: &mut IntermissionState) {
+    ui.style_mut().spacing.item_spacing = egui::vec2(4.0, 4.0);
+
+    egui::Grid::new("intermission_grid")
+        .spacing(egui::vec2(6.0, 4.0))
+        .show(ui, |ui| {
+            ui.label("Screen:");
+            ui.horizontal(|ui| {
+                if ui
+                    .add(egui::Button::new("Tally").selected(!state.entering))
+                    .clicked()
+                {
+                    state.entering = false;
+                }
+                if ui
+                    .add(egui::Button::new("Entering").selected(state.entering))
+                    .clicked()
+                {
+                    state.entering = true;
+                }
+            });
+            ui.end_row();
+
+            ui.label("Map:");
+            ui.horizontal(|ui| {
+                let last = state.episode_length;
+                let mut map = state.current_map;
+                if ui
+                    .add_enabled(map > 1, egui::Button::new("<"))
+                    .on_hover_text("Previous map")
+                    .clicked()
+                {
+                    map -= 1;
+                }
+                ui.add(egui::DragValue::new(&mut map).range(1..=last));
+                if ui
+                    .add_enabled(map < last, egui::Button::new(">"))
+                    .on_hover_text("Next map")
+                    .clicked()
+                {
+                    map += 1;
+                }
+                if map != state.current_map {
+                    state.go_to_map(map);
+                }
+            });
+            ui.end_row();
+
+            ui.label("Next Map:");
+            ui.add(egui::DragValue::new(&mut state.next_map).range(1..=99));
+            ui.end_row();
+
+            ui.label("Tally:");
+            ui.checkbox(&mut state.show_tally, "Show overlay")
+                .on_hover_text("Draw the standard tally over the preview, counting up");
+            ui.end_row();
+
+            if state.show_tally {
+                ui.label("Time:");
+                ui.add(
+                    egui::DragValue::new(&mut state.time)
+                        .range(0..=9999)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                ui.label("Par:");
+                ui.add(
+                    egui::DragValue::new(&mut state.par)
+                        .range(0..=9999)
+                        .suffix(" s"),
+                )
+                .on_hover_text("0 hides the par time");
+                ui.end_row();
+            }
+
+            ui.label("Maps:");
+            if ui
+                .add(egui::DragValue::new(&mut state.episode_length).range(1..=99))
+                .on_hover_text("How many maps the episode has")
+                .changed()
+            {
+                state.go_to_map(state.current_map);
+            }
+            ui.end_row();
+        });
+
+    ui.add_space(2.0);
+    ui.label(
+        egui::RichText::new("Visited maps. Right-click to mark a secret map.")
+            .weak()
+            .size(10.0),
+    );
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+        for map in 1..=state.episode_length {
+            let visited = state.visited.contains(&map);
+            let secret = state.secret_maps.contains(&map);
+            let mut text = egui::RichText::new(map.to_string()).size(11.0);
+            if secret {
+                text = text.color(egui::Color32::from_rgb(220, 180, 60));
+            }
+            let response = ui.add(
+                egui::Button::new(text)
+                    .selected(visited)
+                    .min_size(egui::vec2(22.0, 18.0)),
+            );
+            if response.clicked() && !state.visited.remove(&map) {
+                state.visited.insert(map);
+            }
+            if response.secondary_clicked() && !state.secret_maps.remove(&map) {
+                state.secret_maps.insert(map);
+            }
+        }
+    });
+}
+
 /// Draws the "Game Context" panel (Health, Ammo counts, World level, Engine settings).
 pub fn draw_context_panel(ui: &mut egui::Ui, state: &mut PreviewState, assets: &AssetStore) {
     ui.vertical_centered(|ui| {
@@ -850,8 +969,8 @@ fn draw_icon_button(
     };
 
     let id = AssetId::new(patch);
-    if let Some(tex) = assets.textures.get(&id) {
-        shared::draw_scaled_image(ui, rect.shrink(4.0), tex, tint, 4.0);
+    if let Some(tex) = assets.texture(ui.ctx(), &id) {
+        shared::draw_scaled_image(ui, rect.shrink(4.0), &tex, tint, 4.0);
     } else {
         ui.painter().text(
             rect.center(),
@@ -910,8 +1029,8 @@ fn draw_asset_button(
     };
 
     let id = AssetId::new(patch_key);
-    if let Some(tex) = assets.textures.get(&id) {
-        shared::draw_scaled_image(ui, rect.shrink(INNER_MARGIN), tex, tint, 4.0);
+    if let Some(tex) = assets.texture(ui.ctx(), &id) {
+        shared::draw_scaled_image(ui, rect.shrink(INNER_MARGIN), &tex, tint, 4.0);
     } else {
         ui.painter().text(
             rect.center(),
@@ -112,11 +112,11 @@ fn draw_registered_font_row(
     changed_out: &mut bool,
 ) -> bool {
     let patch_id = assets.resolve_patch_id(stem, prev_char, is_num);
-    let texture = assets.textures.get(&patch_id);
+    let texture = assets.texture(ui.ctx(), &patch_id);
 
     let response = ListRow::new(name)
         .subtitle(format!("({})", stem))
-        .texture(texture)
+        .texture(texture.as_ref())
         .fallback("?")
         .show(ui);
 
@@ -308,11 +308,11 @@ fn draw_unified_font_row(
 
     let stem_upper = font.stem.to_uppercase();
     let preview_id = assets.resolve_patch_id(&stem_upper, preview_char, !font.is_hud);
-    let texture = assets.textures.get(&preview_id);
+    let texture = assets.texture(ui.ctx(), &preview_id);
 
     let response = ListRow::new(font.name)
         .subtitle(font.description)
-        .texture(texture)
+        .texture(texture.as_ref())
         .fallback("Aa")
         .active(is_installed)
         .show(ui);
@@ -331,7 +331,7 @@ fn draw_unified_font_row(
                     for asset in library::ASSETS {
                         let asset_stem = AssetStore::stem(asset.name);
                         if asset_stem.starts_with(&stem_upper) {
-                            assets.load_image(ui.ctx(), &asset_stem, asset.bytes);
+                            assets.load_image(&asset_stem, asset.bytes);
                         }
                     }
                 }
@@ -382,7 +382,9 @@ fn draw_asset_grid(
             let id = AssetId::new(name);
             let is_selected = selection.contains(name);
 
-            let texture_data = assets.textures.get(&id).map(|t| (t.id(), t.size_vec2()));
+            let texture_data = assets
+                .texture(ui.ctx(), &id)
+                .map(|t| (t.id(), t.size_vec2()));
 
             let (rect, response) =
                 ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::click_and_drag());
@@ -455,7 +457,7 @@ fn draw_asset_grid(
                             .collect();
                         let count = list.len();
                         for n in list {
-                            assets.flip_asset_horizontal(ui.ctx(), AssetId::new(&n));
+                            assets.flip_asset_horizontal(AssetId::new(&n));
                         }
                         messages::log_event(state, EditorEvent::AssetsFlipped(count));
                         ui.ctx().request_repaint();
@@ -595,7 +597,9 @@ fn draw_library_item(
     let stem = AssetStore::stem(lib_asset.name);
     let id = AssetId::new(&stem);
 
-    let texture_data = assets.textures.get(&id).map(|t| (t.id(), t.size_vec2()));
+    let texture_data = assets
+        .texture(ui.ctx(), &id)
+        .map(|t| (t.id(), t.size_vec2()));
     let is_project = assets.raw_files.contains_key(&id);
 
     let (rect, response) =
@@ -619,14 +623,14 @@ fn draw_library_item(
 
     if response.drag_started() {
         if !is_project {
-            assets.load_image(ui.ctx(), &stem, lib_asset.bytes);
+            assets.load_image(&stem, lib_asset.bytes);
             changed = true;
         }
         egui::DragAndDrop::set_payload(ui.ctx(), vec![stem.clone()]);
     }
 
     if response.clicked() && !is_project {
-        assets.load_image(ui.ctx(), &stem, lib_asset.bytes);
+        assets.load_image(&stem, lib_asset.bytes);
         changed = true;
     }
 
@@ -661,12 +665,12 @@ fn render_asset_drag_ghost(ui: &egui::Ui, assets: &AssetStore) {
         };
 
         let first_id = AssetId::new(&asset_keys[0]);
-        let texture = assets.textures.get(&first_id);
+        let texture = assets.texture(ui.ctx(), &first_id);
 
         shared::draw_drag_ghost(
             ui.ctx(),
             |ui| {
-                thumbnails::draw_thumbnail_widget(ui, texture, Some("?"), false, false);
+                thumbnails::draw_thumbnail_widget(ui, texture.as_ref(), Some("?"), false, false);
             },
             &label,
         );
@@ -0,0 +1,129 @@
+use super::thumbnails::ListRow;
+use crate::assets::{AssetId, AssetStore};
+use crate::document::actions::{DocumentAction, FinaleAction};
+use crate::models::finale::{FinaleDefFile, FinaleType};
+use crate::ui::context_menu::ContextMenu;
+use crate::ui::shared;
+use eframe::egui;
+use std::collections::HashSet;
+
+/// Renders the finale entry and, for cast roll calls, the list of cast members.
+pub fn draw_finale_layers_list(
+    ui: &mut egui::Ui,
+    file: &FinaleDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    current_idx: &mut usize,
+    assets: &AssetStore,
+    actions: &mut Vec<DocumentAction>,
+) {
+    ui.spacing_mut().item_spacing.y = 1.0;
+
+    let background = assets.texture(ui.ctx(), &AssetId::new(&file.data.background));
+
+    let is_cast = file.data.finale_type == FinaleType::CastRollCall;
+    let members = match &file.data.cast_roll_call {
+        Some(cast) if is_cast => cast.castmembers.as_slice(),
+        _ => &[],
+    };
+    let member_selected = selection
+        .iter()
+        .any(|p| p.first().is_some_and(|i| *i < members.len()));
+
+    let response = ListRow::new(&file.data.background)
+        .subtitle(file.data.finale_type.display_name())
+        .texture(background.as_ref())
+        .fallback("F")
+        .selected(!member_selected)
+        .show(ui);
+    if response.clicked() {
+        selection.clear();
+    }
+
+    let count = members.len();
+    let mut move_request = None;
+    let mut duplicate_request = None;
+    let mut delete_request = None;
+
+    for (i, member) in members.iter().enumerate() {
+        let is_selected = selection.contains(&vec![i]);
+        let is_active = *current_idx == i;
+
+        let texture = member
+            .alive
+            .first()
+            .and_then(|f| assets.texture(ui.ctx(), &AssetId::new(&f.image)));
+
+        let response = ListRow::new(&member.name)
+            .subtitle(format!(
+                "{} alive / {} dead frames",
+                member.alive.len(),
+                member.dead.len()
+            ))
+            .texture(texture.as_ref())
+            .fallback("?")
+            .selected(is_selected)
+            .active(is_active)
+            .show(ui);
+
+        if response.clicked() {
+            selection.clear();
+            selection.insert(vec![i]);
+            *current_idx = i;
+        }
+
+        if response.drag_started() {
+            egui::DragAndDrop::set_payload(ui.ctx(), i);
+        }
+
+        if let Some(indices) = shared::check_list_reorder(ui, response.rect, i, count) {
+            move_request = Some(indices);
+        }
+
+        let just_opened = ContextMenu::check(ui, &response);
+        if let Some(menu) = ContextMenu::get(ui, response.id) {
+            if !is_selected {
+                selection.clear();
+                selection.insert(vec![i]);
+                *current_idx = i;
+            }
+
+            ContextMenu::show(ui, menu, just_opened, |ui| {
+                if ContextMenu::button(ui, "Duplicate", true) {
+                    duplicate_request = Some(i);
+                    ContextMenu::close(ui);
+                }
+                ui.separator();
+                if ContextMenu::button(ui, "Delete Cast Member", true) {
+                    delete_request = Some(i);
+                    ContextMenu::close(ui);
+                }
+            });
+        }
+    }
+
+    if let Some((source, target)) = move_request {
+        actions.push(DocumentAction::UndoSnapshot);
+        actions.push(DocumentAction::Finale(FinaleAction::Move {
+            source,
+            target,
+        }));
+    }
+    if let Some(idx) = duplicate_request {
+        actions.push(DocumentAction::UndoSnapshot);
+        actions.push(DocumentAction::Finale(FinaleAction::Duplicate(idx)));
+    }
+    if let Some(idx) = delete_request {
+        actions.push(DocumentAction::UndoSnapshot);
+        actions.push(DocumentAction::Finale(FinaleAction::Delete(idx)));
+    }
+
+    if let Some(source_idx) = egui::DragAndDrop::payload::<usize>(ui.ctx()).filter(|_| is_cast) {
+        shared::draw_drag_ghost(
+            ui.ctx(),
+            |ui| {
+                ui.label(format!("Cast Member #{}", source_idx));
+            },
+            "Reordering",
+        );
+    }
+}
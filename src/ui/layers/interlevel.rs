@@ -0,0 +1,228 @@
+use super::thumbnails::ListRow;
+use crate::assets::{AssetId, AssetStore};
+use crate::constants::DOOM_TICS_PER_SEC;
+use crate::document::actions::{DocumentAction, InterlevelAction, TreeAction};
+use crate::models::interlevel::InterlevelDefFile;
+use crate::render::interlevel as render;
+use crate::state::PreviewState;
+use crate::ui::context_menu::ContextMenu;
+use crate::ui::shared;
+use eframe::egui;
+use std::collections::HashSet;
+
+/// Renders the INTERLEVEL tree: the background, then layers containing anims
+/// containing frames. Rows can be dragged between parents at the same depth.
+/// Layers and anims whose conditions fail in the mock intermission are dimmed.
+pub fn draw_interlevel_layers_list(
+    ui: &mut egui::Ui,
+    file: &InterlevelDefFile,
+    selection: &mut HashSet<Vec<usize>>,
+    assets: &AssetStore,
+    state: &mut PreviewState,
+    actions: &mut Vec<DocumentAction>,
+) {
+    egui::CollapsingHeader::new("Intermission State")
+        .id_salt("intermission_state")
+        .default_open(true)
+        .show(ui, |ui| {
+            crate::ui::gamestate::draw_intermission_panel(ui, &mut state.intermission);
+        });
+    ui.add_space(4.0);
+
+    ui.spacing_mut().item_spacing.y = 1.0;
+
+    let background = assets.texture(ui.ctx(), &AssetId::new(&file.data.backgroundimage));
+    let response = ListRow::new(&file.data.backgroundimage)
+        .subtitle("Background")
+        .texture(background.as_ref())
+        .fallback("B")
+        .selected(selection.is_empty())
+        .show(ui);
+    if response.clicked() {
+        selection.clear();
+    }
+
+    let layers = &file.data.layers;
+    for (l, layer) in layers.iter().enumerate() {
+        let path = vec![l];
+        let mut subtitle = format!("{} anim(s)", layer.anims.len());
+        if !layer.conditions.is_empty() {
+            subtitle.push_str(&format!(", {} condition(s)", layer.conditions.len()));
+        }
+        let layer_shown = render::layer_shown(layer, state);
+        let response = ListRow::new(format!("Layer {}", l + 1))
+            .subtitle(subtitle)
+            .fallback("L")
+            .selected(selection.contains(&path))
+            .dimmed(!layer_shown)
+            .show(ui);
+
+        let mut folder = folder_state(ui, &path);
+        if response.double_clicked() {
+            folder.toggle(ui);
+        }
+        handle_row(
+            ui,
+            &response,
+            &path,
+            layers.len(),
+            selection,
+            actions,
+            Some("Add Anim"),
+        );
+        folder.show_body_indented(&response, ui, |ui| {
+            for (a, anim) in layer.anims.iter().enumerate() {
+                let path = vec![l, a];
+                let first = anim.frames.first();
+                let texture = first.and_then(|f| assets.texture(ui.ctx(), &AssetId::new(&f.image)));
+                let shown = layer_shown && render::anim_shown(ui.ctx(), anim, first, state, assets);
+                let response = ListRow::new(format!("Anim {}", a + 1))
+                    .subtitle(format!(
+                        "({}, {}), {} frame(s)",
+                        anim.x,
+                        anim.y,
+                        anim.frames.len()
+                    ))
+                    .texture(texture.as_ref())
+                    .fallback("A")
+                    .selected(selection.contains(&path))
+                    .dimmed(!shown)
+                    .show(ui);
+
+                let mut folder = folder_state(ui, &path);
+                if response.double_clicked() {
+                    folder.toggle(ui);
+                }
+                let count = layer.anims.len();
+                handle_row(
+                    ui,
+                    &response,
+                    &path,
+                    count,
+                    selection,
+                    actions,
+                    Some("Add Frame"),
+                );
+                folder.show_body_indented(&response, ui, |ui| {
+                    for (f, frame) in anim.frames.iter().enumerate() {
+                        let path = vec![l, a, f];
+                        let texture = assets.texture(ui.ctx(), &AssetId::new(&frame.image));
+                        let response = ListRow::new(&frame.image)
+                            .subtitle(format!("{:.0} tics", frame.duration * DOOM_TICS_PER_SEC))
+                            .texture(texture.as_ref())
+                            .fallback("?")
+                            .selected(selection.contains(&path))
+                            .show(ui);
+                        let count = anim.frames.len();
+                        handle_row(ui, &response, &path, count, selection, actions, None);
+                    }
+                });
+            }
+        });
+    }
+
+    if let Some(path) = egui::DragAndDrop::payload::<Vec<usize>>(ui.ctx()) {
+        let label = match path.len() {
+            1 => "Layer",
+            2 => "Anim",
+            _ => "Frame",
+        };
+        shared::draw_drag_ghost(
+            ui.ctx(),
+            |ui| {
+                ui.label(label);
+            },
+            "Reordering",
+        );
+    }
+}
+
+fn folder_state(ui: &egui::Ui, path: &[usize]) -> egui::collapsing_header::CollapsingState {
+    egui::collapsing_header::CollapsingState::load_with_default_open(
+        ui.ctx(),
+        egui::Id::new("interlevel_tree").with(path),
+        true,
+    )
+}
+
+/// Handles selection, drag reordering and the context menu of one tree row.
+fn handle_row(
+    ui: &mut egui::Ui,
+    response: &egui::Response,
+    path: &[usize],
+    sibling_count: usize,
+    selection: &mut HashSet<Vec<usize>>,
+    actions: &mut Vec<DocumentAction>,
+    add_child: Option<&str>,
+) {
+    let is_selected = selection.contains(path);
+    let (parent, idx) = (&path[..path.len() - 1], path[path.len() - 1]);
+
+    if response.clicked() {
+        let modifiers = ui.input(|i| i.modifiers);
+        if modifiers.ctrl || modifiers.command {
+            if !selection.remove(path) {
+                selection.insert(path.to_vec());
+            }
+        } else {
+            selection.clear();
+            selection.insert(path.to_vec());
+        }
+    }
+
+    if response.drag_started() {
+        if !is_selected {
+            selection.clear();
+            selection.insert(path.to_vec());
+        }
+        egui::DragAndDrop::set_payload(ui.ctx(), path.to_vec());
+    }
+
+    let dragged = egui::DragAndDrop::payload::<Vec<usize>>(ui.ctx())
+        .filter(|d| d.len() == path.len() && d.as_slice() != path);
+    if dragged.is_some() {
+        let target = shared::handle_list_drag_zone(ui, response.rect, idx, sibling_count);
+        if let Some(insert_idx) = target {
+            actions.push(DocumentAction::UndoSnapshot);
+            actions.push(DocumentAction::Tree(TreeAction::MoveSelection {
+                sources: selection.iter().cloned().collect(),
+                target_parent: parent.to_vec(),
+                insert_idx,
+            }));
+            egui::DragAndDrop::clear_payload(ui.ctx());
+        }
+    }
+
+    let just_opened = ContextMenu::check(ui, response);
+    if let Some(menu) = ContextMenu::get(ui, response.id) {
+        if !is_selected {
+            selection.clear();
+            selection.insert(path.to_vec());
+        }
+
+        ContextMenu::show(ui, menu, just_opened, |ui| {
+            if add_child.is_some_and(|label| ContextMenu::button(ui, label, true)) {
+                actions.push(DocumentAction::UndoSnapshot);
+                actions.push(DocumentAction::Interlevel(InterlevelAction::Add {
+                    parent: path.to_vec(),
+                }));
+                ContextMenu::close(ui);
+            }
+            if ContextMenu::button(ui, "Duplicate", true) {
+                actions.push(DocumentAction::UndoSnapshot);
+                actions.push(DocumentAction::Tree(TreeAction::Duplicate(
+                    selection.iter().cloned().collect(),
+                )));
+                ContextMenu::close(ui);
+            }
+            ui.separator();
+            if ContextMenu::button(ui, "Delete", true) {
+                actions.push(DocumentAction::UndoSnapshot);
+                actions.push(DocumentAction::Tree(TreeAction::Delete(
+                    selection.iter().cloned().collect(),
+                )));
+                ContextMenu::close(ui);
+            }
+        });
+    }
+}
@@ -10,6 +10,8 @@ use std::collections::HashSet;
 
 mod browser;
 pub(crate) mod colors;
+pub(crate) mod finale;
+pub(crate) mod interlevel;
 mod layouts;
 pub(crate) mod sky;
 pub mod thumbnails;
@@ -223,8 +225,7 @@ pub fn draw_layers_panel(
                             if active_tab == BrowserTab::Graphics {
                                 ui.menu_button("Import...", |ui| {
                                     if ui.button("Files").clicked() {
-                                        let count =
-                                            crate::io::import_images_dialog(ui.ctx(), assets);
+                                        let count = crate::io::import_images_dialog(assets);
                                         if count > 0 {
                                             changed = true;
                                             messages::log_event(
@@ -235,8 +236,7 @@ pub fn draw_layers_panel(
                                         ui.close();
                                     }
                                     if ui.button("Folder").clicked() {
-                                        let count =
-                                            crate::io::import_folder_dialog(ui.ctx(), assets);
+                                        let count = crate::io::import_folder_dialog(assets);
                                         if count > 0 {
                                             changed = true;
                                             messages::log_event(
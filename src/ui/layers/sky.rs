@@ -27,11 +27,11 @@ pub fn draw_sky_layers_list(
         let is_active = *current_idx == i;
 
         let id = assets.resolve_sky_id(&def.name);
-        let texture = assets.textures.get(&id);
+        let texture = assets.texture(ui.ctx(), &id);
 
         let response = ListRow::new(&def.name)
             .subtitle(format!("Type: {:?}", def.sky_type))
-            .texture(texture)
+            .texture(texture.as_ref())
             .fallback("?")
             .selected(is_selected)
             .active(is_active)
@@ -78,8 +78,8 @@ pub fn draw_thumbnail(
             }
         }
         _ => {
-            let texture = get_preview_texture(element, assets, file, state, false);
-            draw_static_texture_content(ui, rect, texture, None, !is_visible);
+            let texture = get_preview_texture(ui.ctx(), element, assets, file, state, false);
+            draw_static_texture_content(ui, rect, texture.as_ref(), None, !is_visible);
         }
     }
 
@@ -257,7 +257,7 @@ pub fn draw_live_patches(
     let mut textures = Vec::new();
     for char in text.chars() {
         let id = assets.resolve_patch_id(stem, char, is_number_font);
-        if let Some(tex) = assets.textures.get(&id) {
+        if let Some(tex) = assets.texture(ui.ctx(), &id) {
             textures.push(tex);
         }
     }
@@ -308,13 +308,14 @@ pub fn draw_live_patches(
 }
 
 /// Resolves a preview texture handle for an element based on current state.
-pub fn get_preview_texture<'a>(
-    element: &'a ElementWrapper,
-    assets: &'a AssetStore,
-    file: &'a SBarDefFile,
+pub fn get_preview_texture(
+    ctx: &egui::Context,
+    element: &ElementWrapper,
+    assets: &AssetStore,
+    file: &SBarDefFile,
     state: &PreviewState,
     ouch: bool,
-) -> Option<&'a egui::TextureHandle> {
+) -> Option<egui::TextureHandle> {
     let patch_id = match &element.data {
         Element::Graphic(g) => Some(AssetId::new(&g.patch)),
         Element::Animation(a) => a.frames.first().map(|f| AssetId::new(&f.lump)),
@@ -338,12 +339,7 @@ pub fn get_preview_texture<'a>(
         _ => None,
     };
 
-    if let Some(id) = patch_id {
-        if let Some(tex) = assets.textures.get(&id) {
-            return Some(tex);
-        }
-    }
-    None
+    patch_id.and_then(|id| assets.texture(ctx, &id))
 }
 
 /// A stylized row used in the browser lists (Fonts, Assets).
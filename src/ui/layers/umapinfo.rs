@@ -12,11 +12,12 @@ pub fn draw_umapinfo_layers_list(
     file: &mut UmapInfoFile,
     selection: &mut HashSet<Vec<usize>>,
     current_idx: &mut usize,
-    _assets: &AssetStore,
+    assets: &AssetStore,
     actions: &mut Vec<DocumentAction>,
     _confirmation_modal: &mut Option<crate::app::ConfirmationRequest>,
 ) {
     ui.spacing_mut().item_spacing.y = 1.0;
+    let issues = crate::ui::properties::umapinfo_issues(ui.ctx(), file, assets);
 
     for (i, map) in file.data.maps.iter().enumerate() {
         let is_selected = selection.contains(&vec![i]);
@@ -33,13 +34,26 @@ pub fn draw_umapinfo_layers_list(
                 }
             })
             .unwrap_or_else(|| "Untitled".to_string());
+        let map_issues: Vec<String> = issues
+            .iter()
+            .filter(|(m, _)| *m == i)
+            .map(|(_, issue)| issue.message())
+            .collect();
+        let subtitle = match map_issues.len() {
+            0 => subtitle,
+            1 => format!("⚠ {}", subtitle),
+            n => format!("⚠{} {}", n, subtitle),
+        };
 
-        let response = ListRow::new(&map.mapname)
+        let mut response = ListRow::new(&map.mapname)
             .subtitle(subtitle)
             .fallback("M")
             .selected(is_selected)
             .active(is_active)
             .show(ui);
+        if !map_issues.is_empty() {
+            response = response.on_hover_text(map_issues.join("\n"));
+        }
 
         if response.clicked() {
             selection.clear();
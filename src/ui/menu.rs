@@ -1,21 +1,27 @@
 use crate::app::{CreationModal, PendingAction, ProjectMode};
 use crate::assets::AssetStore;
 use crate::config::{AppConfig, SourcePortConfig};
-use crate::io::{self, LoadedProject};
+use crate::io;
 use crate::ui::context_menu::ContextMenu;
 use crate::ui::shared;
+use crate::wad::pk3::Pk3Manifest;
 use eframe::egui;
 
 /// Result of a menu interaction that requires application-level handling.
 pub enum MenuAction {
     None,
-    LoadProject(LoadedProject, String),
+    LoadProject(String),
     Open,
     RequestDiscard(PendingAction),
-    SaveDone(String),
-    ExportDone(String),
+    /// A finished save, with the manifest when the menu wrote the PK3 itself.
+    SaveDone(String, Option<Pk3Manifest>),
+    /// A finished export, with any images left out of a WAD for sharing a
+    /// lump name with a newer one.
+    ExportDone(String, Vec<String>),
     SetTarget(crate::models::sbardef::ExportTarget),
     NewProject,
+    /// A save or export that went wrong, for the error panel.
+    Failed(crate::error::ProjectError),
 }
 
 /// Draws the primary application menu bar (File, Run, Target).
@@ -126,8 +132,8 @@ pub fn draw_menu_bar(
                 if let Some(path) = file_to_load {
                     if dirty {
                         action = MenuAction::RequestDiscard(PendingAction::Load(path));
-                    } else if let Some(loaded) = io::load_project_from_path(ctx, &path) {
-                        action = MenuAction::LoadProject(loaded, path);
+                    } else {
+                        action = MenuAction::LoadProject(path);
                     }
                     ContextMenu::close(ui);
                 }
@@ -135,16 +141,22 @@ pub fn draw_menu_bar(
 
             ui.separator();
             if ContextMenu::button(ui, "Save", doc.is_some()) {
-                action = MenuAction::SaveDone("SILENT".to_string());
+                action = MenuAction::SaveDone("SILENT".to_string(), None);
                 ContextMenu::close(ui);
             }
             if ContextMenu::button(ui, "Save As...", doc.is_some()) {
                 if let Some(d) = doc {
-                    if let Some(path) =
-                        io::save_pk3_dialog(&d.lumps, assets, &d.passthrough_lumps, d.path.clone())
-                    {
-                        action = MenuAction::SaveDone(path);
-                    }
+                    action = match io::save_pk3_dialog(
+                        &d.lumps,
+                        &d.composites,
+                        assets,
+                        &d.passthrough_lumps,
+                        d.path.clone(),
+                    ) {
+                        Ok(Some((path, manifest))) => MenuAction::SaveDone(path, Some(manifest)),
+                        Ok(None) => MenuAction::None,
+                        Err(e) => MenuAction::Failed(e),
+                    };
                 }
                 ContextMenu::close(ui);
             }
@@ -152,25 +164,42 @@ pub fn draw_menu_bar(
                 if let Some(d) = doc {
                     if let Some(lump) = d.get_lump(active_mode) {
                         let sanitized = lump.to_sanitized_json(assets);
-                        if let Some(path) = io::save_json_dialog(&sanitized, d.path.clone()) {
-                            action = MenuAction::ExportDone(path);
-                        }
+                        action = match io::save_json_dialog(&sanitized, d.path.clone()) {
+                            Ok(Some(path)) => MenuAction::ExportDone(path, Vec::new()),
+                            Ok(None) => MenuAction::None,
+                            Err(e) => MenuAction::Failed(e),
+                        };
                     }
                 }
                 ContextMenu::close(ui);
             }
             if ContextMenu::button(ui, "Export WAD...", doc.is_some()) {
                 if let Some(d) = doc {
-                    if let Some(path) =
-                        io::save_wad_dialog(&d.lumps, assets, &d.passthrough_lumps, d.path.clone())
-                    {
-                        action = MenuAction::ExportDone(path);
-                    }
+                    action = match io::save_wad_dialog(
+                        &d.lumps,
+                        &d.composites,
+                        assets,
+                        &d.passthrough_lumps,
+                        d.path.clone(),
+                    ) {
+                        Ok(Some((path, skipped))) => MenuAction::ExportDone(path, skipped),
+                        Ok(None) => MenuAction::None,
+                        Err(e) => MenuAction::Failed(e),
+                    };
                 }
                 ContextMenu::close(ui);
             }
 
             ui.separator();
+            if ContextMenu::button(ui, "Texture Composer...", doc.is_some()) {
+                ctx.data_mut(|d| {
+                    d.insert_temp(
+                        egui::Id::new(crate::ui::texture_composer::OPEN_ID),
+                        String::new(),
+                    )
+                });
+                ContextMenu::close(ui);
+            }
             if ContextMenu::button(ui, "Settings...", true) {
                 *settings_open = true;
                 ContextMenu::close(ui);
@@ -200,14 +229,17 @@ pub fn draw_menu_bar(
                     if ContextMenu::button(ui, &format!("Launch in {}", port.name), has_file) {
                         if let (Some(d), Some(iwad)) = (doc.as_ref(), config.base_wad_path.as_ref())
                         {
-                            io::launch_game(
+                            if let Err(e) = io::launch_game(
                                 assets,
                                 &port.command,
                                 iwad,
                                 d.lumps[0].target(),
                                 &d.lumps,
+                                &d.composites,
                                 &d.passthrough_lumps,
-                            );
+                            ) {
+                                action = MenuAction::Failed(e);
+                            }
                         }
                         ContextMenu::close(ui);
                     }
@@ -252,12 +284,13 @@ pub fn draw_menu_bar(
 }
 
 /// Renders the specialized Settings window for application-wide configuration.
+/// Returns the base IWAD the user picked, for the caller to read.
 pub fn draw_settings_window(
     ctx: &egui::Context,
     settings_open: &mut bool,
     config: &mut AppConfig,
-    assets: &mut AssetStore,
-) {
+) -> Option<String> {
+    let mut picked = None;
     let mut is_open = *settings_open;
     egui::Window::new("Settings")
         .open(&mut is_open)
@@ -274,9 +307,7 @@ pub fn draw_settings_window(
                 .as_deref()
                 .unwrap_or("Click to browse for DOOM2.WAD...");
             if draw_menu_card(ui, "Base IWAD", iwad_desc) {
-                if let Some(new_path) = io::load_iwad_dialog(ctx, assets) {
-                    config.base_wad_path = Some(new_path);
-                }
+                picked = io::pick_iwad_dialog();
             }
 
             ui.add_space(16.0);
@@ -443,6 +474,7 @@ pub fn draw_settings_window(
     if !is_open {
         *settings_open = false;
     }
+    picked
 }
 
 /// Renders a card-style button used in settings and project selection.
@@ -578,22 +610,20 @@ pub fn draw_creation_wizard(ctx: &egui::Context, app: &mut crate::app::CacocoApp
                             ) {
                                 app.creation_modal = CreationModal::SkyDefs;
                             }
-                            /* TODO: Not quite ready yet...
                             if draw_menu_card(
                                 ui,
-                                "Interlevel Animations",
+                                "Interlevel Animations (INTERLEVEL)",
                                 "Exiting that last map. Here's your statistics. Entering the next one.",
                             ) {
                                 app.creation_modal = CreationModal::Interlevel;
                             }
                             if draw_menu_card(
                                 ui,
-                                "Finale Definitions",
+                                "Finale Definitions (FINALE)",
                                 "Art screens, the bunny scroller thing, and monster cast call.",
                             ) {
                                 app.creation_modal = CreationModal::Finale;
                             }
-                            */
                             if draw_menu_card(
                                 ui,
                                 "Map Information (UMAPINFO)",
@@ -636,7 +666,7 @@ pub fn draw_creation_wizard(ctx: &egui::Context, app: &mut crate::app::CacocoApp
                                 if app.doc.is_some() {
                                     app.add_lump_to_project(new_data);
                                 } else {
-                                    app.new_project(ctx, new_data);
+                                    app.new_project(new_data);
                                 }
                                 app.creation_modal = CreationModal::None;
                             }
@@ -704,13 +734,13 @@ pub fn draw_creation_wizard(ctx: &egui::Context, app: &mut crate::app::CacocoApp
                                                     for lib_asset in crate::library::ASSETS {
                                                         if lib_asset.name.to_lowercase().starts_with(prefix) {
                                                             let key = AssetStore::stem(lib_asset.name);
-                                                            app.assets.load_image(ctx, &key, lib_asset.bytes);
+                                                            app.assets.load_image(&key, lib_asset.bytes);
                                                         }
                                                     }
                                                 }
                                             }
                                         } else {
-                                            app.apply_template(ctx, template);
+                                            app.apply_template(template);
                                         }
                                         app.creation_modal = CreationModal::None;
                                     }
@@ -45,7 +45,13 @@ pub enum MessageItem {
 pub enum EditorEvent {
     ProjectNew,
     ProjectLoaded(String),
+    Failed(String),
+    ProjectLoadCancelled,
     ProjectSaved(String),
+    /// Files written to a PK3 and files left out of it.
+    Pk3Written(usize, usize),
+    /// An image left out of a WAD for a newer one with the same lump name.
+    ImageSkipped(String),
     ProjectExported(String),
     TemplateApplied(String),
     Undo,
@@ -67,7 +73,17 @@ pub fn log_event(state: &mut PreviewState, event: EditorEvent) {
     let msg = match event {
         EditorEvent::ProjectNew => "Created new empty project.".to_string(),
         EditorEvent::ProjectLoaded(path) => format!("Project Loaded: {}", path),
+        EditorEvent::Failed(what) => format!("Error: {}", what),
+        EditorEvent::ProjectLoadCancelled => "Loading cancelled.".to_string(),
         EditorEvent::ProjectSaved(path) => format!("Saved: {}", path),
+        EditorEvent::Pk3Written(files, 0) => format!("PK3: Wrote {} files.", files),
+        EditorEvent::Pk3Written(files, skipped) => format!(
+            "PK3: Wrote {} files, skipped {} (see the archive comment).",
+            files, skipped
+        ),
+        EditorEvent::ImageSkipped(name) => {
+            format!("WAD: Skipped {}, a newer image has its lump name.", name)
+        }
         EditorEvent::ProjectExported(path) => format!("Exported: {}", path),
         EditorEvent::TemplateApplied(name) => format!("Template: {}", name),
         EditorEvent::Undo => "Undo performed.".to_string(),
@@ -8,6 +8,7 @@ pub mod modals;
 pub(crate) mod properties;
 pub mod root;
 pub mod shared;
+pub mod texture_composer;
 pub mod viewport;
 pub mod viewport_controller;
 
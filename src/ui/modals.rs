@@ -8,7 +8,7 @@ use eframe::egui;
 pub fn draw_onboarding_screen(ctx: &egui::Context, app: &mut CacocoApp) {
     egui::CentralPanel::default().show(ctx, |ui| {
         let id = AssetId::new("HICACOCO");
-        if let Some(tex) = app.assets.textures.get(&id) {
+        if let Some(tex) = app.assets.texture(ui.ctx(), &id) {
             let size = tex.size_vec2() * 2.0;
             let rect =
                 egui::Rect::from_center_size(ui.max_rect().center() - egui::vec2(0.0, 150.0), size);
@@ -37,12 +37,10 @@ pub fn draw_onboarding_screen(ctx: &egui::Context, app: &mut CacocoApp) {
                         .as_deref()
                         .unwrap_or("Click to browse for DOOM2.WAD...");
 
-                    if crate::ui::menu::draw_menu_card(ui, "Select Base DOOM II IWAD", desc) {
-                        if let Some(p) = crate::io::load_iwad_dialog(ctx, &mut app.assets) {
-                            app.config.base_wad_path = Some(p);
-                            app.config.save();
-                            app.iwad_verified = true;
-                        }
+                    if crate::ui::menu::draw_menu_card(ui, "Select Base DOOM II IWAD", desc)
+                        && let Some(path) = crate::io::pick_iwad_dialog()
+                    {
+                        app.begin_base_load(&path);
                     }
                     ui.add_space(16.0);
                     ui.label(
@@ -58,6 +56,97 @@ pub fn draw_onboarding_screen(ctx: &egui::Context, app: &mut CacocoApp) {
     });
 }
 
+/// Shows how far a background project or base IWAD load has got, blocking
+/// the editor behind it until the load ends. Returns true if the user asked
+/// to cancel it.
+pub fn draw_loading_modal(ctx: &egui::Context, load: &crate::io::ProjectLoad) -> bool {
+    let heading = match load.source {
+        crate::io::LoadSource::BaseWad(_) => "Loading Base IWAD",
+        _ => "Loading Project",
+    };
+    let response = egui::Modal::new(egui::Id::new("CACOCO_LOAD_PROGRESS")).show(ctx, |ui| {
+        ui.set_width(320.0);
+        ui.vertical_centered(|ui| {
+            ui.heading(heading);
+            ui.add_space(4.0);
+            ui.label(crate::ui::shared::truncate_path(load.title(), 40));
+            ui.label(egui::RichText::new(load.progress.stage()).weak());
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!(
+                    "{} lumps scanned, {} graphics decoded",
+                    load.progress.lumps(),
+                    load.progress.graphics()
+                ));
+            });
+            ui.add_space(8.0);
+            ui.button("Cancel").clicked()
+        })
+        .inner
+    });
+    // Keep the counters moving; the worker can't wake the UI itself.
+    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    response.inner
+}
+
+/// Shows why a load or save failed, and how many more failures are waiting
+/// behind it. Returns true once dismissed.
+pub fn draw_error_modal(
+    ctx: &egui::Context,
+    error: &crate::error::ProjectError,
+    queued: usize,
+) -> bool {
+    let mut close = false;
+    egui::Window::new(error.title())
+        .id(egui::Id::new("CACOCO_ERROR_REPORT"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            ui.set_width(400.0);
+            ui.label(
+                egui::RichText::new(error.to_string())
+                    .color(egui::Color32::from_rgb(200, 100, 100))
+                    .strong(),
+            );
+
+            let details = error.details();
+            if !details.is_empty() {
+                ui.add_space(8.0);
+                ui.collapsing("Details", |ui| {
+                    egui::Grid::new("error_details")
+                        .num_columns(2)
+                        .spacing([12.0, 4.0])
+                        .show(ui, |ui| {
+                            for (label, value) in &details {
+                                ui.label(egui::RichText::new(*label).weak());
+                                ui.label(egui::RichText::new(value).monospace());
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Copy").clicked() {
+                    let mut report = error.to_string();
+                    for (label, value) in &details {
+                        report.push_str(&format!("\n{}: {}", label, value));
+                    }
+                    ui.ctx().copy_text(report);
+                }
+                let label = if queued > 0 { "Next" } else { "Close" };
+                close = ui.button(label).clicked();
+                if queued > 0 {
+                    ui.label(egui::RichText::new(format!("{} more", queued)).weak());
+                }
+            });
+        });
+    close
+}
+
 /// Renders various confirmation dialogs for destructive actions.
 pub fn draw_confirmation_modal(
     ctx: &egui::Context,
@@ -159,10 +248,7 @@ pub fn draw_confirmation_modal(
             ConfirmationRequest::DeleteAssets(items) => {
                 for key in items {
                     let id = AssetId::new(key);
-                    app.assets.textures.remove(&id);
-                    app.assets.raw_files.remove(&id);
-                    app.assets.offsets.remove(&id);
-                    app.assets.names.remove(&id);
+                    app.assets.remove(&id);
                 }
                 if let Some(doc) = &mut app.doc {
                     doc.dirty = true;
@@ -180,9 +266,9 @@ pub fn draw_confirmation_modal(
                     }
                     PendingAction::Load(path) => {
                         if path.is_empty() {
-                            app.open_project_ui(ctx);
-                        } else if let Some(loaded) = crate::io::load_project_from_path(ctx, &path) {
-                            app.load_project(ctx, loaded, &path);
+                            app.open_project_ui();
+                        } else {
+                            app.begin_load(path);
                         }
                     }
                     PendingAction::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
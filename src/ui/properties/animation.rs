@@ -11,6 +11,18 @@ use super::editor::PropertiesUI;
 use super::font_cache::FontCache;
 use super::preview::PreviewContent;
 
+/// Drag payload for frames being reordered, tagged with the list they belong
+/// to so frames can't be dropped into a different sequence.
+#[derive(Clone, Copy, PartialEq)]
+struct FrameDrag(egui::Id);
+
+/// The selected frames of one list and the anchor for shift-click ranges.
+struct FrameSelection {
+    list: FrameDrag,
+    indices: HashSet<usize>,
+    pivot: Option<usize>,
+}
+
 enum FrameAction {
     MoveSelection(Vec<usize>, usize),
     Add(usize, String),
@@ -68,84 +80,7 @@ impl PropertiesUI for AnimationDef {
             }
         }
 
-        let sel_id = ui.make_persistent_id("anim_frame_selection");
-        let pivot_id = ui.make_persistent_id("anim_frame_pivot");
-        let mut selection: HashSet<usize> = ui.data(|d| d.get_temp(sel_id).unwrap_or_default());
-        let mut pivot: Option<usize> = ui.data(|d| d.get_temp(pivot_id));
-
-        let mut actions = Vec::new();
-        ui.spacing_mut().item_spacing.y = 1.0;
-
-        if self.frames.is_empty() {
-            changed |= draw_empty_frame_dropzone(ui, &mut actions);
-        } else {
-            for (idx, frame) in self.frames.iter_mut().enumerate() {
-                let is_active = active_idx == Some(idx);
-                ui.push_id(idx, |ui| {
-                    changed |= draw_frame_row(
-                        ui,
-                        idx,
-                        frame,
-                        assets,
-                        &mut actions,
-                        &mut selection,
-                        &mut pivot,
-                        is_active,
-                    );
-                });
-            }
-        }
-
-        for action in actions {
-            changed = true;
-            match action {
-                FrameAction::MoveSelection(sources, mut target_idx) => {
-                    let mut sorted_src = sources.clone();
-                    sorted_src.sort();
-                    let mut src_desc = sorted_src.clone();
-                    src_desc.sort_by(|a, b| b.cmp(a));
-
-                    let mut moved_items = Vec::new();
-                    for src in src_desc {
-                        if src < target_idx {
-                            target_idx -= 1;
-                        }
-                        if src < self.frames.len() {
-                            moved_items.push(self.frames.remove(src));
-                        }
-                    }
-                    moved_items.reverse();
-
-                    let safe_idx = target_idx.min(self.frames.len());
-                    selection.clear();
-                    for (i, item) in moved_items.into_iter().enumerate() {
-                        self.frames.insert(safe_idx + i, item);
-                        selection.insert(safe_idx + i);
-                    }
-                }
-                FrameAction::Add(i, lump) => {
-                    self.frames.insert(
-                        i.min(self.frames.len()),
-                        FrameDef {
-                            lump,
-                            duration: 1.0 / DOOM_TICS_PER_SEC,
-                        },
-                    );
-                    selection.clear();
-                    selection.insert(i.min(self.frames.len() - 1));
-                }
-                FrameAction::Replace(i, lump) => {
-                    if i < self.frames.len() {
-                        self.frames[i].lump = lump;
-                    }
-                }
-            }
-        }
-
-        ui.data_mut(|d| {
-            d.insert_temp(sel_id, selection);
-            d.insert_temp(pivot_id, pivot);
-        });
+        changed |= draw_frame_sequence(ui, &mut self.frames, assets, "anim_frame", active_idx).0;
 
         changed
     }
@@ -165,16 +100,140 @@ impl PropertiesUI for AnimationDef {
     }
 }
 
-fn draw_frame_row(
+/// A frame in a sequence that can be edited with the shared frame rows.
+pub(super) trait SequenceFrame {
+    fn from_lump(lump: String) -> Self;
+    fn lump(&self) -> &str;
+    fn set_lump(&mut self, lump: String);
+    /// Duration in seconds.
+    fn duration_mut(&mut self) -> &mut f64;
+
+    /// Draws any extra per-frame controls next to the duration.
+    fn draw_extras(&mut self, _ui: &mut egui::Ui) -> bool {
+        false
+    }
+}
+
+impl SequenceFrame for FrameDef {
+    fn from_lump(lump: String) -> Self {
+        FrameDef {
+            lump,
+            duration: 1.0 / DOOM_TICS_PER_SEC,
+        }
+    }
+    fn lump(&self) -> &str {
+        &self.lump
+    }
+    fn set_lump(&mut self, lump: String) {
+        self.lump = lump;
+    }
+    fn duration_mut(&mut self) -> &mut f64 {
+        &mut self.duration
+    }
+}
+
+/// Draws a reorderable list of frames that accepts graphics dragged in from
+/// the asset browser. `id_salt` keeps the frame selection of separate lists
+/// apart. Returns whether anything changed, plus the selected frame indices.
+pub(super) fn draw_frame_sequence<F: SequenceFrame>(
+    ui: &mut egui::Ui,
+    frames: &mut Vec<F>,
+    assets: &AssetStore,
+    id_salt: &str,
+    active_idx: Option<usize>,
+) -> (bool, HashSet<usize>) {
+    let mut changed = false;
+    let sel_id = ui.make_persistent_id(format!("{}_selection", id_salt));
+    let pivot_id = ui.make_persistent_id(format!("{}_pivot", id_salt));
+    let mut state = FrameSelection {
+        list: FrameDrag(sel_id),
+        indices: ui.data(|d| d.get_temp(sel_id).unwrap_or_default()),
+        pivot: ui.data(|d| d.get_temp(pivot_id)),
+    };
+
+    let mut actions = Vec::new();
+    ui.spacing_mut().item_spacing.y = 1.0;
+
+    if frames.is_empty() {
+        changed |= draw_empty_frame_dropzone(ui, &mut actions);
+    } else {
+        for (idx, frame) in frames.iter_mut().enumerate() {
+            let is_active = active_idx == Some(idx);
+            ui.push_id(idx, |ui| {
+                changed |=
+                    draw_frame_row(ui, idx, frame, assets, &mut actions, &mut state, is_active);
+            });
+        }
+    }
+
+    let FrameSelection {
+        indices: mut selection,
+        pivot,
+        ..
+    } = state;
+    for action in actions {
+        changed = true;
+        match action {
+            FrameAction::MoveSelection(sources, mut target_idx) => {
+                let mut sorted_src = sources.clone();
+                sorted_src.sort();
+                let mut src_desc = sorted_src.clone();
+                src_desc.sort_by(|a, b| b.cmp(a));
+
+                let mut moved_items = Vec::new();
+                for src in src_desc {
+                    if src < target_idx {
+                        target_idx -= 1;
+                    }
+                    if src < frames.len() {
+                        moved_items.push(frames.remove(src));
+                    }
+                }
+                moved_items.reverse();
+
+                let safe_idx = target_idx.min(frames.len());
+                selection.clear();
+                for (i, item) in moved_items.into_iter().enumerate() {
+                    frames.insert(safe_idx + i, item);
+                    selection.insert(safe_idx + i);
+                }
+            }
+            FrameAction::Add(i, lump) => {
+                frames.insert(i.min(frames.len()), F::from_lump(lump));
+                selection.clear();
+                selection.insert(i.min(frames.len() - 1));
+            }
+            FrameAction::Replace(i, lump) => {
+                if let Some(frame) = frames.get_mut(i) {
+                    frame.set_lump(lump);
+                }
+            }
+        }
+    }
+
+    selection.retain(|i| *i < frames.len());
+    ui.data_mut(|d| {
+        d.insert_temp(sel_id, selection.clone());
+        d.insert_temp(pivot_id, pivot);
+    });
+
+    (changed, selection)
+}
+
+fn draw_frame_row<F: SequenceFrame>(
     ui: &mut egui::Ui,
     idx: usize,
-    frame: &mut FrameDef,
+    frame: &mut F,
     assets: &AssetStore,
     actions: &mut Vec<FrameAction>,
-    selection: &mut HashSet<usize>,
-    pivot: &mut Option<usize>,
+    state: &mut FrameSelection,
     is_active: bool,
 ) -> bool {
+    let FrameSelection {
+        list,
+        indices: selection,
+        pivot,
+    } = state;
     let mut changed = false;
     let row_height = 42.0;
     let is_selected = selection.contains(&idx);
@@ -218,13 +277,11 @@ fn draw_frame_row(
             selection.clear();
             selection.insert(idx);
         }
-        egui::DragAndDrop::set_payload(ui.ctx(), "FRAME_SELECTION");
+        egui::DragAndDrop::set_payload(ui.ctx(), *list);
     }
 
     if ui.rect_contains_pointer(rect) {
-        if egui::DragAndDrop::payload::<&'static str>(ui.ctx())
-            .is_some_and(|p| *p == "FRAME_SELECTION")
-        {
+        if egui::DragAndDrop::payload::<FrameDrag>(ui.ctx()).is_some_and(|p| *p == *list) {
             let pos = ui.input(|i| i.pointer.latest_pos().unwrap_or_default());
             let rel_y = pos.y - rect.top();
             let top_half = rel_y < (row_height / 2.0);
@@ -305,10 +362,10 @@ fn draw_frame_row(
     );
     let mut thumb_ui = ui.new_child(egui::UiBuilder::new().max_rect(thumb_rect));
 
-    let id = AssetId::new(&frame.lump);
+    let id = AssetId::new(frame.lump());
     thumbnails::draw_thumbnail_widget(
         &mut thumb_ui,
-        assets.textures.get(&id),
+        assets.texture(ui.ctx(), &id).as_ref(),
         Some("?"),
         false,
         false,
@@ -317,7 +374,7 @@ fn draw_frame_row(
     ui.painter().text(
         egui::pos2(rect.min.x + 44.0, rect.center().y),
         egui::Align2::LEFT_CENTER,
-        &frame.lump,
+        frame.lump(),
         egui::FontId::proportional(14.0),
         ui.visuals().text_color(),
     );
@@ -325,7 +382,8 @@ fn draw_frame_row(
     ui.scope_builder(egui::UiBuilder::new().max_rect(rect), |ui| {
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             ui.add_space(8.0);
-            let mut tics = (frame.duration * DOOM_TICS_PER_SEC).round() as i32;
+            let duration = frame.duration_mut();
+            let mut tics = (*duration * DOOM_TICS_PER_SEC).round() as i32;
             if ui
                 .add(
                     egui::DragValue::new(&mut tics)
@@ -334,9 +392,10 @@ fn draw_frame_row(
                 )
                 .changed()
             {
-                frame.duration = tics as f64 / DOOM_TICS_PER_SEC;
+                *duration = tics as f64 / DOOM_TICS_PER_SEC;
                 changed = true;
             }
+            changed |= frame.draw_extras(ui);
         });
     });
 
@@ -344,15 +403,15 @@ fn draw_frame_row(
         let label = if selection.len() > 1 {
             format!("{} frames", selection.len())
         } else {
-            frame.lump.clone()
+            frame.lump().to_string()
         };
-        let id = AssetId::new(&frame.lump);
+        let id = AssetId::new(frame.lump());
         shared::draw_drag_ghost(
             ui.ctx(),
             |ui| {
                 thumbnails::draw_thumbnail_widget(
                     ui,
-                    assets.textures.get(&id),
+                    assets.texture(ui.ctx(), &id).as_ref(),
                     Some("?"),
                     false,
                     false,
@@ -256,11 +256,11 @@ pub fn draw_font_selection_row(
     };
 
     let patch_id = stem.map(|s| assets.resolve_patch_id(s, preview_char, is_number_font));
-    let texture = patch_id.and_then(|id| assets.textures.get(&id));
+    let texture = patch_id.and_then(|id| assets.texture(ui.ctx(), &id));
 
     let response = thumbnails::ListRow::new(target_name)
         .subtitle(format!("({})", stem.unwrap_or(&"???".to_string())))
-        .texture(texture)
+        .texture(texture.as_ref())
         .fallback("?")
         .selected(*current_val == target_name)
         .show(ui);
@@ -310,6 +310,56 @@ pub fn draw_lookup_param_dd(
     changed
 }
 
+/// Draws a labelled lump name field that also accepts assets dragged in from
+/// the asset browser. Returns true if the name changed.
+pub fn draw_asset_name_field(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut String,
+    label_w: f32,
+    field_w: f32,
+) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.add_space((ui.available_width() - label_w - field_w).max(0.0) / 2.0);
+        ui.add_sized([label_w, 18.0], egui::Label::new(label));
+        let response = ui.add(egui::TextEdit::singleline(value).desired_width(field_w));
+        if response.changed() {
+            changed = true;
+        }
+        // Normalise once editing ends, so the caret isn't fought mid-typing.
+        if response.lost_focus() {
+            let stem = AssetStore::stem(value);
+            if stem != *value {
+                *value = stem;
+                changed = true;
+            }
+        }
+
+        let hovered_payload = egui::DragAndDrop::payload::<Vec<String>>(ui.ctx())
+            .filter(|_| ui.rect_contains_pointer(response.rect));
+        if let Some(asset_keys) = hovered_payload {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Copy);
+            ui.painter().rect_stroke(
+                response.rect,
+                2.0,
+                egui::Stroke::new(1.0, egui::Color32::YELLOW),
+                egui::StrokeKind::Inside,
+            );
+            if ui.input(|i| i.pointer.any_released()) {
+                if let Some(key) = asset_keys.first() {
+                    *value = AssetStore::stem(key);
+                    changed = true;
+                }
+                egui::DragAndDrop::clear_payload(ui.ctx());
+            }
+        }
+    });
+
+    changed
+}
+
 /// Helper to draw a selectable list item for use in properties dropdowns.
 pub fn custom_menu_item(ui: &mut egui::Ui, text: &str, selected: bool) -> bool {
     let (rect, response) = ui.allocate_exact_size(
@@ -2,6 +2,7 @@ use super::common;
 use super::common::paint_thumb_content;
 use super::lookups;
 use crate::assets::{AssetId, AssetStore};
+use crate::models::interlevel::{InterlevelCondition, InterlevelConditionType};
 use crate::models::sbardef::{
     ConditionDef, ConditionType, Element, ElementWrapper, ExportTarget, NumberType,
 };
@@ -143,9 +144,9 @@ fn draw_condition_card(
                     .rect_filled(rect, 4.0, egui::Color32::from_gray(45));
 
                 let id = master_icon_name.as_ref().map(|n| AssetId::new(n));
-                let tex = id.and_then(|i| assets.textures.get(&i));
+                let tex = id.and_then(|i| assets.texture(ui.ctx(), &i));
 
-                paint_thumb_content(ui, rect, tex, None);
+                paint_thumb_content(ui, rect, tex.as_ref(), None);
                 if tex.is_none() {
                     ui.painter().text(
                         rect.center(),
@@ -470,3 +471,176 @@ pub fn draw_automap_param(ui: &mut egui::Ui, param: &mut i32) -> bool {
     });
     changed
 }
+
+/// Renders the conditions editor for an INTERLEVEL layer or anim. Cards are
+/// tinted by whether they hold in the mock intermission state.
+pub fn draw_interlevel_conditions_editor(
+    ui: &mut egui::Ui,
+    conditions: &mut Vec<InterlevelCondition>,
+    state: &crate::state::PreviewState,
+    patch_width: Option<f32>,
+) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(format!("Active Rules: {}", conditions.len())).weak());
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.add_space(2.0);
+            if ui.button("Add Condition").clicked() {
+                conditions.push(InterlevelCondition {
+                    condition: InterlevelConditionType::CurrMapEqual,
+                    param: state.intermission.shown_map(),
+                });
+                changed = true;
+            }
+            if !conditions.is_empty() && ui.button("Clear All").clicked() {
+                conditions.clear();
+                changed = true;
+            }
+        });
+    });
+
+    ui.separator();
+    ui.add_space(4.0);
+
+    let mut remove_idx = None;
+    for (i, cond) in conditions.iter_mut().enumerate() {
+        let id = ui.make_persistent_id(format!("il_cond_card_{}", i));
+        ui.push_id(id, |ui| {
+            changed |=
+                draw_interlevel_condition_card(ui, cond, &mut remove_idx, i, state, patch_width);
+        });
+        ui.add_space(4.0);
+    }
+
+    if let Some(i) = remove_idx {
+        conditions.remove(i);
+        changed = true;
+    }
+
+    changed
+}
+
+/// A short label for the icon box of an INTERLEVEL condition card.
+fn interlevel_condition_glyph(cond: &InterlevelCondition) -> String {
+    use InterlevelConditionType::*;
+    match cond.condition {
+        None => "-".to_string(),
+        CurrMapGreater => format!(">{}", cond.param),
+        CurrMapEqual => format!("={}", cond.param),
+        MapVisited => format!("V{}", cond.param),
+        CurrMapNotSecret | AnySecretVisited => "S".to_string(),
+        OnFinishedScreen => "T".to_string(),
+        OnEnteringScreen => "E".to_string(),
+        FitsInFrame => "W".to_string(),
+        Unknown(value) => format!("?{}", value),
+    }
+}
+
+fn draw_interlevel_condition_card(
+    ui: &mut egui::Ui,
+    cond: &mut InterlevelCondition,
+    remove_idx: &mut Option<usize>,
+    my_idx: usize,
+    state: &crate::state::PreviewState,
+    patch_width: Option<f32>,
+) -> bool {
+    let mut changed = false;
+    let is_true = crate::conditions::resolve_interlevel(
+        std::slice::from_ref(cond),
+        &state.intermission,
+        patch_width,
+        state.sim.engine.widescreen_mode,
+    );
+
+    let frame = egui::Frame::new()
+        .inner_margin(4.0)
+        .corner_radius(4.0)
+        .fill(egui::Color32::from_white_alpha(5))
+        .stroke(egui::Stroke::new(1.0, egui::Color32::from_white_alpha(15)));
+
+    let response = frame.show(ui, |ui| {
+        ui.horizontal(|ui| {
+            let box_size = 44.0;
+            let (rect, _) =
+                ui.allocate_exact_size(egui::vec2(box_size, box_size), egui::Sense::hover());
+            ui.painter()
+                .rect_filled(rect, 4.0, egui::Color32::from_gray(45));
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                interlevel_condition_glyph(cond),
+                egui::FontId::proportional(16.0),
+                egui::Color32::from_gray(160),
+            );
+
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add(egui::Button::new("X").min_size(egui::vec2(18.0, 18.0)))
+                            .on_hover_text("Remove Condition")
+                            .clicked()
+                        {
+                            *remove_idx = Some(my_idx);
+                        }
+
+                        ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                            let id = ui.make_persistent_id(format!("il_type_dd_{}", my_idx));
+                            let button_res = shared::combobox_button(
+                                ui,
+                                cond.condition.name(),
+                                ui.available_width(),
+                            );
+                            if button_res.clicked() {
+                                ContextMenu::open(ui, id, button_res.rect.left_bottom());
+                            }
+                            if let Some(menu) = ContextMenu::get(ui, id) {
+                                ContextMenu::show(ui, menu, button_res.clicked(), |ui| {
+                                    ui.set_min_width(140.0);
+                                    for kind in InterlevelConditionType::ALL {
+                                        let current = cond.condition == kind;
+                                        if common::custom_menu_item(ui, kind.name(), current) {
+                                            if !current {
+                                                cond.condition = kind;
+                                                if !kind.takes_map() {
+                                                    cond.param = 0;
+                                                }
+                                                changed = true;
+                                            }
+                                            ContextMenu::close(ui);
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                    });
+                });
+
+                shared::draw_separator_line(ui);
+
+                ui.horizontal(|ui| {
+                    if cond.condition.takes_map() {
+                        ui.label("Map");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut cond.param).range(0..=99))
+                            .changed();
+                    } else {
+                        ui.label(egui::RichText::new("(No Params)").weak().size(11.0));
+                    }
+                });
+            });
+        });
+    });
+
+    let tint_color = if is_true {
+        egui::Color32::from_rgba_unmultiplied(40, 140, 40, 30)
+    } else {
+        egui::Color32::from_rgba_unmultiplied(140, 40, 40, 30)
+    };
+
+    ui.painter()
+        .rect_filled(response.response.rect, 4.0, tint_color);
+
+    changed
+}
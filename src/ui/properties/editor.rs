@@ -39,8 +39,12 @@ pub struct LayerContext<'a> {
 
 /// A trait for any Cacoco-handled lump that provides a user interface for editing its properties.
 pub trait LumpUI {
-    /// Draws the property editor for this lump. Returns true if data was modified.
-    fn draw_properties(&mut self, ui: &mut egui::Ui, ctx: &PropertyContext) -> bool;
+    /// Draws the property editor for this lump. Returns (Actions to execute, has data changed).
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool);
 
     /// Optional: Per-frame logic (animations, simulations).
     fn tick(&self, _ctx: &mut TickContext) {}
@@ -26,9 +26,8 @@ impl PropertiesUI for FaceDef {
 
         let id = AssetId::new("STFST01");
         let (dw, dh) = assets
-            .textures
-            .get(&id)
-            .map(|t| (t.size()[0] as i32, t.size()[1] as i32))
+            .image_size(&id)
+            .map(|(w, h)| (w as i32, h as i32))
             .unwrap_or((24, 29));
 
         draw_crop_editor(ui, &mut self.crop, dw, dh, target)
@@ -1,39 +1,587 @@
-use crate::document::actions::DocumentAction;
-use crate::models::finale::FinaleDefFile;
-use crate::ui::properties::editor::{
-    LayerContext, LumpUI, PropertyContext, TickContext, ViewportContext,
+use super::animation::{SequenceFrame, draw_frame_sequence};
+use super::editor::{LayerContext, LumpUI, PropertyContext, ViewportContext};
+use super::preview::PreviewContent;
+use crate::assets::AssetStore;
+use crate::document::actions::{DocumentAction, FinaleAction};
+use crate::models::finale::{
+    BunnyDef, CastFrame, CastMember, CastRollCallDef, FinaleDefFile, FinaleType,
 };
+use crate::render::bunny;
+use crate::render::cast::{self, CastPhase, CastPlayback};
+use crate::ui::context_menu::ContextMenu;
+use crate::ui::properties::common;
+use crate::ui::shared;
 use eframe::egui;
 use std::collections::HashSet;
 
+/// Titles for the music lumps the stock IWAD endings use.
+const STOCK_MUSIC: &[(&str, &str)] = &[
+    ("D_VICTOR", "Doom Victory"),
+    ("D_READ_M", "Doom II Text Screen"),
+    ("D_EVIL", "Doom II Cast Call"),
+    ("D_BUNNY", "Doom Bunny"),
+];
+
+const LABEL_W: f32 = 80.0;
+const FIELD_W: f32 = 100.0;
+
+impl SequenceFrame for CastFrame {
+    fn from_lump(lump: String) -> Self {
+        CastFrame::new(lump)
+    }
+    fn lump(&self) -> &str {
+        &self.image
+    }
+    fn set_lump(&mut self, lump: String) {
+        self.image = lump;
+    }
+    fn duration_mut(&mut self) -> &mut f64 {
+        &mut self.duration
+    }
+
+    fn draw_extras(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.toggle_value(&mut self.flip, "Flip")
+            .on_hover_text("Mirror the frame horizontally.")
+            .changed()
+    }
+}
+
+impl FinaleDefFile {
+    /// Returns the cast member a selection path points at, if any.
+    fn selected_member(&self, selection: &HashSet<Vec<usize>>) -> Option<usize> {
+        if self.data.finale_type != FinaleType::CastRollCall {
+            return None;
+        }
+        let idx = *selection.iter().next()?.first()?;
+        let cast = self.data.cast_roll_call.as_ref()?;
+        (idx < cast.castmembers.len()).then_some(idx)
+    }
+}
+
+/// Renders the editor for the finale itself: type, music, background and the
+/// fields specific to the chosen type. The music menu lists the loaded `D_`
+/// lumps.
+fn draw_finale_editor(ui: &mut egui::Ui, file: &mut FinaleDefFile, assets: &AssetStore) -> bool {
+    let mut changed = false;
+    let def = &mut file.data;
+
+    ui.vertical_centered(|ui| {
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - 130.0).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Finale Type:"));
+            let type_id = ui.make_persistent_id("finale_type_dd");
+            let btn = shared::combobox_button(ui, def.finale_type.display_name(), 130.0);
+            if btn.clicked() {
+                ContextMenu::open(ui, type_id, btn.rect.left_bottom());
+            }
+
+            if let Some(menu) = ContextMenu::get(ui, type_id) {
+                ContextMenu::show(ui, menu, btn.clicked(), |ui| {
+                    for t in FinaleType::ALL {
+                        if common::custom_menu_item(ui, t.display_name(), def.finale_type == t) {
+                            def.finale_type = t;
+                            match t {
+                                FinaleType::BunnyScroller if def.bunny.is_none() => {
+                                    def.bunny = Some(BunnyDef::doom2());
+                                }
+                                FinaleType::CastRollCall if def.cast_roll_call.is_none() => {
+                                    def.cast_roll_call = Some(Default::default());
+                                }
+                                _ => {}
+                            }
+                            changed = true;
+                            ContextMenu::close(ui);
+                        }
+                    }
+                });
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        ui.heading("Presentation");
+        changed |=
+            common::draw_asset_name_field(ui, "Background:", &mut def.background, LABEL_W, FIELD_W);
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - FIELD_W - 24.0).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Music:"));
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut def.music).desired_width(FIELD_W));
+            if response.changed() {
+                def.music = def.music.to_uppercase();
+                changed = true;
+            }
+
+            let menu_id = ui.make_persistent_id("finale_music_dd");
+            let btn = ui.add_sized([20.0, 18.0], egui::Button::new("..."));
+            if btn.clicked() {
+                ContextMenu::open(ui, menu_id, btn.rect.left_bottom());
+            }
+            if let Some(menu) = ContextMenu::get(ui, menu_id) {
+                ContextMenu::show(ui, menu, btn.clicked(), |ui| {
+                    let mut music = assets
+                        .lump_names
+                        .iter()
+                        .filter(|n| n.starts_with("D_"))
+                        .peekable();
+                    if music.peek().is_none() {
+                        ui.label(egui::RichText::new("No music loaded.").weak().size(11.0));
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(220.0)
+                        .show(ui, |ui| {
+                            for lump in music {
+                                let title = STOCK_MUSIC.iter().find(|(l, _)| l == lump);
+                                let label = match title {
+                                    Some((_, title)) => format!("{} ({})", lump, title),
+                                    None => lump.clone(),
+                                };
+                                if common::custom_menu_item(ui, &label, &def.music == lump) {
+                                    def.music = lump.clone();
+                                    changed = true;
+                                    ContextMenu::close(ui);
+                                }
+                            }
+                        });
+                });
+            }
+        });
+
+        ui.add_space(4.0);
+        changed |= ui
+            .checkbox(&mut def.donextmap, "Continue to next map")
+            .on_hover_text("Proceed to the next map after the finale instead of ending the game.")
+            .changed();
+
+        match def.finale_type {
+            FinaleType::ArtScreen => {}
+            FinaleType::BunnyScroller => {
+                changed |= def.bunny.is_none();
+                let bunny = def.bunny.get_or_insert_with(BunnyDef::doom2);
+                ui.add_space(12.0);
+                ui.separator();
+                ui.heading("Bunny Scroller");
+                ui.label(
+                    egui::RichText::new("Scrolls from the stitch image to the background.")
+                        .weak()
+                        .size(10.0),
+                );
+                ui.add_space(4.0);
+
+                changed |= common::draw_asset_name_field(
+                    ui,
+                    "Stitch Image:",
+                    &mut bunny.stitchimage,
+                    LABEL_W,
+                    FIELD_W,
+                );
+
+                let mut draw_row =
+                    |label: &str, val: &mut i32, range: std::ops::RangeInclusive<i32>| {
+                        ui.horizontal(|ui| {
+                            ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+                            ui.add_sized([LABEL_W, 18.0], egui::Label::new(label));
+                            changed |= ui
+                                .add_sized([FIELD_W, 18.0], egui::DragValue::new(val).range(range))
+                                .changed();
+                        });
+                    };
+
+                draw_row("First Overlay:", &mut bunny.overlay, 0..=99);
+                draw_row("Overlay Count:", &mut bunny.overlaycount, 0..=100);
+                draw_row("Overlay Sound:", &mut bunny.overlaysound, 0..=1000);
+                draw_row("Overlay X:", &mut bunny.overlayx, -320..=640);
+                draw_row("Overlay Y:", &mut bunny.overlayy, -200..=400);
+            }
+            FinaleType::CastRollCall => {
+                let count = def
+                    .cast_roll_call
+                    .as_ref()
+                    .map_or(0, |c| c.castmembers.len());
+                ui.add_space(12.0);
+                ui.separator();
+                ui.heading("Cast Roll Call");
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} cast member(s). Select one in the list to edit it.",
+                        count
+                    ))
+                    .weak()
+                    .size(10.0),
+                );
+            }
+        }
+    });
+
+    changed
+}
+
+/// Renders the editor for a single cast member: its name, sight sound and
+/// the alive and death frame sequences.
+fn draw_cast_member_editor(
+    ui: &mut egui::Ui,
+    member: &mut CastMember,
+    idx: usize,
+    playing: Option<&CastPlayback>,
+    assets: &AssetStore,
+    actions: &mut Vec<DocumentAction>,
+) -> bool {
+    let mut changed = false;
+
+    ui.vertical_centered(|ui| {
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Name:"));
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut member.name).desired_width(FIELD_W))
+                .on_hover_text("Shown under the actor. Can also be a DeHackEd string mnemonic.")
+                .changed();
+        });
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Sight Sound:"));
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut member.sound).desired_width(FIELD_W));
+            if response.changed() {
+                member.sound = member.sound.to_uppercase();
+                changed = true;
+            }
+        });
+    });
+
+    let active = |phase: CastPhase| playing.filter(|p| p.phase == phase).map(|p| p.frame);
+
+    ui.add_space(8.0);
+    changed |= draw_cast_sequence(
+        ui,
+        &mut member.alive,
+        idx,
+        false,
+        assets,
+        active(CastPhase::Alive),
+        actions,
+    );
+    ui.add_space(8.0);
+    changed |= draw_cast_sequence(
+        ui,
+        &mut member.dead,
+        idx,
+        true,
+        assets,
+        active(CastPhase::Dying),
+        actions,
+    );
+
+    changed
+}
+
+/// Renders cast member `member`'s death frames if `dead`, otherwise its
+/// alive frames, plus the extra fields of the frame when exactly one is
+/// selected.
+fn draw_cast_sequence(
+    ui: &mut egui::Ui,
+    frames: &mut Vec<CastFrame>,
+    member: usize,
+    dead: bool,
+    assets: &AssetStore,
+    active_idx: Option<usize>,
+    actions: &mut Vec<DocumentAction>,
+) -> bool {
+    let (title, id_salt) = if dead {
+        ("Death Frames", "cast_dead")
+    } else {
+        ("Alive Frames", "cast_alive")
+    };
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.heading(title);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if !frames.is_empty() && ui.button("Clear").clicked() {
+                actions.push(DocumentAction::Finale(FinaleAction::ClearFrames {
+                    member,
+                    dead,
+                }));
+            }
+            if ui.button("Add").clicked() {
+                actions.push(DocumentAction::Finale(FinaleAction::AddFrame {
+                    member,
+                    dead,
+                }));
+            }
+        });
+    });
+    ui.separator();
+
+    let (sequence_changed, selection) = ui
+        .push_id(id_salt, |ui| {
+            draw_frame_sequence(ui, frames, assets, id_salt, active_idx)
+        })
+        .inner;
+    changed |= sequence_changed;
+
+    let single = (selection.len() == 1)
+        .then(|| selection.iter().next().copied())
+        .flatten();
+    if let Some(frame) = single.and_then(|idx| frames.get_mut(idx)) {
+        ui.add_space(4.0);
+        ui.vertical_centered(|ui| {
+            changed |= draw_optional_lump_field(ui, "Translation:", &mut frame.translation);
+            changed |= draw_optional_lump_field(ui, "Tranmap:", &mut frame.tranmap);
+            changed |= draw_optional_lump_field(ui, "Sound:", &mut frame.sound);
+        });
+    }
+
+    changed
+}
+
+/// A lump name field where an empty string clears the value.
+fn draw_optional_lump_field(ui: &mut egui::Ui, label: &str, value: &mut Option<String>) -> bool {
+    let mut text = value.clone().unwrap_or_default();
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+        ui.add_sized([LABEL_W, 18.0], egui::Label::new(label));
+        if ui
+            .add(egui::TextEdit::singleline(&mut text).desired_width(FIELD_W))
+            .changed()
+        {
+            let text = text.trim().to_uppercase();
+            *value = (!text.is_empty()).then_some(text);
+            changed = true;
+        }
+    });
+    changed
+}
+
 impl LumpUI for FinaleDefFile {
-    fn draw_properties(&mut self, _ui: &mut egui::Ui, _ctx: &PropertyContext) -> bool {
-        false
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool) {
+        let mut actions = Vec::new();
+        let changed = match self.selected_member(ctx.selection) {
+            Some(idx) => {
+                let cast = self
+                    .data
+                    .cast_roll_call
+                    .get_or_insert_with(Default::default);
+                let playback = &ctx.state.viewer.cast;
+                let playing = (playback.member == idx).then_some(playback);
+                draw_cast_member_editor(
+                    ui,
+                    &mut cast.castmembers[idx],
+                    idx,
+                    playing,
+                    ctx.assets,
+                    &mut actions,
+                )
+            }
+            None => draw_finale_editor(ui, self, ctx.assets),
+        };
+        (actions, changed)
     }
-    fn tick(&self, _ctx: &mut TickContext) {}
+
     fn draw_layer_list(
         &mut self,
-        _ui: &mut egui::Ui,
-        _ctx: &mut LayerContext,
+        ui: &mut egui::Ui,
+        ctx: &mut LayerContext,
     ) -> (Vec<DocumentAction>, bool) {
-        (Vec::new(), false)
+        let mut actions = Vec::new();
+        let is_cast = self.data.finale_type == FinaleType::CastRollCall;
+
+        let add_label = is_cast.then_some("Add Cast Member");
+        if shared::heading_action_button(ui, "Finale", add_label, false).clicked() && is_cast {
+            actions.push(DocumentAction::UndoSnapshot);
+            actions.push(DocumentAction::Finale(FinaleAction::Add));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("finale_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                crate::ui::layers::finale::draw_finale_layers_list(
+                    ui,
+                    self,
+                    ctx.selection,
+                    ctx.current_item_idx,
+                    ctx.assets,
+                    &mut actions,
+                );
+            });
+        (actions, false)
     }
 
-    fn header_info(&self, _sel: &HashSet<Vec<usize>>) -> (String, String, egui::Color32) {
+    fn header_info(&self, selection: &HashSet<Vec<usize>>) -> (String, String, egui::Color32) {
+        let member = self
+            .selected_member(selection)
+            .and_then(|idx| self.data.cast_roll_call.as_ref()?.castmembers.get(idx));
+        if let Some(member) = member {
+            return (
+                format!("Cast: {}", member.name),
+                "One actor in the cast roll call.".into(),
+                egui::Color32::from_rgb(60, 40, 60),
+            );
+        }
         (
-            "Finale Definition".into(),
-            "Art screens and cast calls.".into(),
+            format!("Finale: {}", self.data.finale_type.display_name()),
+            "Art screens, the bunny scroller and cast calls.".into(),
             egui::Color32::from_rgb(60, 40, 60),
         )
     }
 
+    fn get_preview_content(&self, _: &egui::Ui, ctx: &PropertyContext) -> Option<PreviewContent> {
+        if let Some(idx) = self.selected_member(ctx.selection) {
+            let member = &self.data.cast_roll_call.as_ref()?.castmembers[idx];
+            return member
+                .alive
+                .first()
+                .map(|f| PreviewContent::Image(f.image.clone()));
+        }
+        (!self.data.background.is_empty())
+            .then(|| PreviewContent::Image(self.data.background.clone()))
+    }
+
     fn render_viewport(&self, ui: &mut egui::Ui, ctx: &mut ViewportContext) -> Vec<DocumentAction> {
-        crate::ui::viewport::render_id24_background(
-            ui,
-            &self.data.background,
-            ctx.assets,
-            ctx.proj,
-        );
+        match (&self.data.bunny, &self.data.cast_roll_call) {
+            (Some(bunny), _) if self.data.finale_type == FinaleType::BunnyScroller => {
+                render_bunny_scroller(ui, ctx, bunny, &self.data.background);
+            }
+            (_, Some(cast)) if self.data.finale_type == FinaleType::CastRollCall => {
+                crate::ui::viewport::render_id24_background(
+                    ui,
+                    &self.data.background,
+                    ctx.assets,
+                    ctx.proj,
+                );
+                self.render_cast_call(ui, ctx, cast);
+            }
+            _ => crate::ui::viewport::render_id24_background(
+                ui,
+                &self.data.background,
+                ctx.assets,
+                ctx.proj,
+            ),
+        }
         Vec::new()
     }
 }
+
+impl FinaleDefFile {
+    /// Plays the cast call: clicking the viewport (or Space/Enter) kills the
+    /// current actor, and the arrow keys step between members.
+    fn render_cast_call(
+        &self,
+        ui: &mut egui::Ui,
+        ctx: &mut ViewportContext,
+        cast: &CastRollCallDef,
+    ) {
+        let playback = &mut ctx.state.viewer.cast;
+
+        // Follow the layer list, so picking a member shows it walking on.
+        let selected = self.selected_member(ctx.selection);
+        if selected != playback.followed {
+            playback.followed = selected;
+            if let Some(idx) = selected {
+                playback.jump_to(idx, cast);
+            }
+        }
+
+        let keys_free = ctx.viewport_res.hovered() && !ui.ctx().wants_keyboard_input();
+        let (kill, prev, next) = ui.input(|i| {
+            (
+                i.key_pressed(egui::Key::Space) || i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight),
+            )
+        });
+        if ctx.viewport_res.clicked() || (keys_free && kill) {
+            playback.kill(cast);
+        }
+        if keys_free && (prev || next) {
+            let count = cast.castmembers.len().max(1);
+            let target = if prev {
+                playback.member + count - 1
+            } else {
+                playback.member + 1
+            };
+            playback.jump_to(target, cast);
+        }
+
+        playback.advance(cast, ui.input(|i| i.time));
+        cast::draw_cast_call(ui.painter(), cast, playback, ctx.assets, ctx.proj);
+
+        if let Some(sound) = &playback.last_sound {
+            ui.painter().text(
+                ctx.proj.screen_rect.left_top() + egui::vec2(6.0, 6.0),
+                egui::Align2::LEFT_TOP,
+                format!("Sound: {}", sound),
+                egui::FontId::monospace(11.0),
+                egui::Color32::from_white_alpha(180),
+            );
+        }
+
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Plays the bunny scroller with a timeline strip along the bottom of the
+/// screen. Dragging the timeline pauses playback.
+fn render_bunny_scroller(
+    ui: &mut egui::Ui,
+    ctx: &mut ViewportContext,
+    bunny: &BunnyDef,
+    background: &str,
+) {
+    let playback = &mut ctx.state.viewer.bunny;
+    playback.advance(bunny, ui.input(|i| i.time));
+    bunny::draw_bunny(
+        ui.painter(),
+        bunny,
+        background,
+        playback.tic,
+        ctx.assets,
+        ctx.proj,
+    );
+
+    let screen = ctx.proj.screen_rect.intersect(ui.clip_rect());
+    let bar_rect = egui::Rect::from_min_max(
+        egui::pos2(screen.left(), screen.bottom() - 28.0),
+        screen.right_bottom(),
+    );
+    ui.painter()
+        .rect_filled(bar_rect, 0.0, egui::Color32::from_black_alpha(160));
+
+    let total = bunny::total_tics(bunny);
+    ui.scope_builder(
+        egui::UiBuilder::new().max_rect(bar_rect.shrink(4.0)),
+        |ui| {
+            ui.horizontal_centered(|ui| {
+                let label = if playback.playing { "Pause" } else { "Play" };
+                if ui.button(label).clicked() {
+                    playback.playing = !playback.playing;
+                }
+                ui.spacing_mut().slider_width = (ui.available_width() - 80.0).max(40.0);
+                let slider = egui::Slider::new(&mut playback.tic, 0.0..=total)
+                    .show_value(false)
+                    .trailing_fill(true);
+                if ui.add(slider).dragged() {
+                    playback.playing = false;
+                }
+                ui.label(
+                    egui::RichText::new(format!("tic {:.0}", playback.tic))
+                        .monospace()
+                        .color(egui::Color32::WHITE),
+                );
+            });
+        },
+    );
+
+    if playback.playing {
+        ui.ctx().request_repaint();
+    }
+}
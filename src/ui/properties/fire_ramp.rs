@@ -0,0 +1,225 @@
+use crate::models::skydefs::FireSkyDef;
+use crate::render::fire::{self, FireSimulation};
+use crate::render::palette::{ColorMatch, DoomPalette};
+use eframe::egui;
+
+/// Temp-data key holding the generator state while its window is open,
+/// combined with the index of the sky it's generating for.
+const FIRE_RAMP_ID: &str = "FIRE_RAMP_TOOL";
+
+/// Size of the fire burning in each candidate preview.
+const PREVIEW_W: u32 = 96;
+const PREVIEW_H: u32 = 48;
+
+/// Builds fire palettes from key colours or an imported gradient strip.
+#[derive(Clone)]
+struct FireRampTool {
+    /// Key colours from cooling to ignition.
+    keys: Vec<egui::Color32>,
+    /// An imported gradient, used instead of the keys when present.
+    strip: Option<(String, Vec<egui::Color32>)>,
+    steps: usize,
+    /// One simulation drives every candidate so they burn in step.
+    sim: FireSimulation,
+    previews: Vec<egui::TextureHandle>,
+}
+
+impl FireRampTool {
+    fn new(fire: &FireSkyDef, time: f64) -> Self {
+        Self {
+            keys: vec![
+                egui::Color32::from_rgb(71, 0, 0),
+                egui::Color32::from_rgb(239, 111, 0),
+                egui::Color32::from_rgb(255, 255, 167),
+            ],
+            strip: None,
+            steps: fire.palette.len().max(2),
+            sim: FireSimulation::new(PREVIEW_W, PREVIEW_H, time),
+            previews: Vec::new(),
+        }
+    }
+
+    fn candidates(&self, palette: &DoomPalette) -> Vec<(ColorMatch, Vec<i32>)> {
+        let source = self.strip.as_ref().map_or(&self.keys, |(_, strip)| strip);
+        let colors = fire::gradient(source, self.steps);
+        ColorMatch::ALL
+            .into_iter()
+            .map(|method| (method, fire::match_ramp(&colors, palette, method)))
+            .collect()
+    }
+}
+
+/// Toggles the palette generator window for the fire sky at index `sky`.
+pub fn toggle(ctx: &egui::Context, sky: usize, fire: &FireSkyDef) {
+    let id = egui::Id::new(FIRE_RAMP_ID).with(sky);
+    let time = ctx.input(|i| i.time);
+    ctx.data_mut(|d| {
+        if d.get_temp::<FireRampTool>(id).is_some() {
+            d.remove::<FireRampTool>(id);
+        } else {
+            d.insert_temp(id, FireRampTool::new(fire, time));
+        }
+    });
+}
+
+/// Shows the palette generator window if it's open for the fire sky at
+/// index `sky`. Returns true if a candidate was applied to `fire`.
+///
+/// Each sky keeps its own generator, so selecting another sky hides the
+/// window rather than applying one sky's keys to the other.
+pub fn draw_fire_ramp_window(
+    ctx: &egui::Context,
+    sky: usize,
+    fire: &mut FireSkyDef,
+    palette: &DoomPalette,
+) -> bool {
+    let id = egui::Id::new(FIRE_RAMP_ID).with(sky);
+    let Some(mut tool) = ctx.data(|d| d.get_temp::<FireRampTool>(id)) else {
+        return false;
+    };
+
+    let time = ctx.input(|i| i.time);
+    if time - tool.sim.last_step_time >= fire.updatetime as f64 {
+        tool.sim.step();
+        tool.sim.last_step_time = time;
+    }
+    ctx.request_repaint();
+
+    let candidates = tool.candidates(palette);
+    for (i, (method, ramp)) in candidates.iter().enumerate() {
+        let rgba = tool.sim.generate_rgba(ramp, palette);
+        let image = egui::ColorImage::from_rgba_unmultiplied(
+            [PREVIEW_W as usize, PREVIEW_H as usize],
+            &rgba,
+        );
+        match tool.previews.get_mut(i) {
+            Some(tex) => tex.set(image, egui::TextureOptions::NEAREST),
+            None => tool.previews.push(ctx.load_texture(
+                format!("_FIRE_RAMP_{}", method.name()),
+                image,
+                egui::TextureOptions::NEAREST,
+            )),
+        }
+    }
+
+    let mut applied = false;
+    let mut is_open = true;
+    egui::Window::new("Generate Fire Palette")
+        .id(egui::Id::new("CACOCO_FIRE_RAMP_WINDOW").with(sky))
+        .resizable(false)
+        .collapsible(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            draw_source(ui, &mut tool);
+
+            ui.horizontal(|ui| {
+                ui.label("Steps:");
+                ui.add(egui::DragValue::new(&mut tool.steps).range(2..=64));
+            });
+
+            ui.add_space(8.0);
+            ui.separator();
+            for (i, (method, ramp)) in candidates.iter().enumerate() {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if let Some(tex) = tool.previews.get(i) {
+                        let size = egui::vec2(PREVIEW_W as f32, PREVIEW_H as f32) * 1.5;
+                        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                        ui.painter().rect_filled(rect, 0.0, egui::Color32::BLACK);
+                        ui.painter().image(
+                            tex.id(),
+                            rect,
+                            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                            egui::Color32::WHITE,
+                        );
+                    }
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(method.name()).strong());
+                        draw_ramp_swatches(ui, ramp, palette);
+                        if ui.button("Apply").clicked() {
+                            fire.palette = ramp.clone();
+                            applied = true;
+                        }
+                    });
+                });
+            }
+        });
+
+    ctx.data_mut(|d| {
+        if is_open {
+            d.insert_temp(id, tool);
+        } else {
+            d.remove::<FireRampTool>(id);
+        }
+    });
+    applied
+}
+
+/// The key colour row, or the imported strip with a button to drop it.
+fn draw_source(ui: &mut egui::Ui, tool: &mut FireRampTool) {
+    ui.label(
+        egui::RichText::new("Cooling (left) to Ignition (right)")
+            .weak()
+            .size(10.0),
+    );
+    let mut use_keys = false;
+    ui.horizontal(|ui| {
+        if let Some((name, strip)) = &tool.strip {
+            ui.label(format!("Image: {}", name));
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 18.0), egui::Sense::hover());
+            let step_w = rect.width() / strip.len().max(1) as f32;
+            for (i, &c) in strip.iter().enumerate() {
+                let x = rect.left() + i as f32 * step_w;
+                let r = egui::Rect::from_x_y_ranges(x..=x + step_w, rect.y_range());
+                ui.painter().rect_filled(r, 0.0, c);
+            }
+            use_keys = ui.button("Use Keys").clicked();
+            return;
+        }
+
+        for key in &mut tool.keys {
+            egui::color_picker::color_edit_button_srgba(ui, key, egui::color_picker::Alpha::Opaque);
+        }
+        if tool.keys.len() < 3 && ui.button("+").on_hover_text("Add a key colour").clicked() {
+            let last = *tool.keys.last().unwrap_or(&egui::Color32::WHITE);
+            tool.keys.push(last);
+        }
+        if tool.keys.len() > 2
+            && ui
+                .button("-")
+                .on_hover_text("Remove a key colour")
+                .clicked()
+        {
+            tool.keys.pop();
+        }
+        ui.separator();
+        if ui.button("Import Image…").clicked() {
+            tool.strip = pick_gradient_image();
+        }
+    });
+    if use_keys {
+        tool.strip = None;
+    }
+}
+
+/// Asks for a gradient image and reads its strip.
+fn pick_gradient_image() -> Option<(String, Vec<egui::Color32>)> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Images", &["png", "jpg", "jpeg"])
+        .set_title("Import Gradient Strip")
+        .pick_file()?;
+    let img = image::open(&path).ok()?.to_rgba8();
+    let strip = fire::gradient_strip(&img);
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    (!strip.is_empty()).then_some((name, strip))
+}
+
+fn draw_ramp_swatches(ui: &mut egui::Ui, ramp: &[i32], palette: &DoomPalette) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 14.0), egui::Sense::hover());
+    let step_w = rect.width() / ramp.len().max(1) as f32;
+    for (i, &idx) in ramp.iter().enumerate() {
+        let x = rect.left() + i as f32 * step_w;
+        let r = egui::Rect::from_x_y_ranges(x..=x + step_w, rect.y_range());
+        ui.painter().rect_filled(r, 0.0, palette.get(idx as u8));
+    }
+}
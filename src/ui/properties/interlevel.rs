@@ -1,39 +1,486 @@
-use crate::document::actions::DocumentAction;
-use crate::models::interlevel::InterlevelDefFile;
+use super::animation::{SequenceFrame, draw_frame_sequence};
+use super::common;
+use super::conditions;
+use super::preview::PreviewContent;
+use crate::assets::{AssetId, AssetStore};
+use crate::constants::DOOM_TICS_PER_SEC;
+use crate::document::actions::{DocumentAction, InterlevelAction, TreeAction};
+use crate::models::interlevel::{
+    FrameType, InterlevelAnim, InterlevelDefFile, InterlevelFrame, InterlevelLayer,
+};
+use crate::render::interlevel as render;
+use crate::render::tally::{self, TallyTargets};
+use crate::state::PreviewState;
+use crate::ui::context_menu::ContextMenu;
 use crate::ui::properties::editor::{
     LayerContext, LumpUI, PropertyContext, TickContext, ViewportContext,
 };
+use crate::ui::shared;
 use eframe::egui;
 use std::collections::HashSet;
 
+const LABEL_W: f32 = 80.0;
+const FIELD_W: f32 = 100.0;
+
+impl SequenceFrame for InterlevelFrame {
+    fn from_lump(lump: String) -> Self {
+        InterlevelFrame {
+            image: lump,
+            frame_type: FrameType::FIXED,
+            duration: 1.0,
+            ..Default::default()
+        }
+    }
+    fn lump(&self) -> &str {
+        &self.image
+    }
+    fn set_lump(&mut self, lump: String) {
+        self.image = lump;
+    }
+    fn duration_mut(&mut self) -> &mut f64 {
+        &mut self.duration
+    }
+}
+
+impl InterlevelDefFile {
+    /// Returns the single selected tree path, if exactly one node is selected.
+    fn single_selection(selection: &HashSet<Vec<usize>>) -> Option<&[usize]> {
+        match selection.len() {
+            1 => selection.iter().next().map(|p| p.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a centred label and drag value pair.
+fn draw_value_row<N: egui::emath::Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut N,
+    drag: impl FnOnce(egui::DragValue) -> egui::DragValue,
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+        ui.add_sized([LABEL_W, 18.0], egui::Label::new(label));
+        ui.add_sized([FIELD_W, 18.0], drag(egui::DragValue::new(value)))
+            .changed()
+    })
+    .inner
+}
+
+/// A duration field edited in tics but stored in seconds.
+fn draw_tics_row(ui: &mut egui::Ui, label: &str, seconds: &mut f64) -> bool {
+    let mut tics = (*seconds * DOOM_TICS_PER_SEC).round() as i32;
+    let changed = draw_value_row(ui, label, &mut tics, |d| d.range(0..=35000).suffix(" tics"));
+    if changed {
+        *seconds = tics as f64 / DOOM_TICS_PER_SEC;
+    }
+    changed
+}
+
+fn draw_interlevel_editor(ui: &mut egui::Ui, file: &mut InterlevelDefFile) -> bool {
+    let mut changed = false;
+    let def = &mut file.data;
+
+    ui.vertical_centered(|ui| {
+        ui.heading("Presentation");
+        changed |= common::draw_asset_name_field(
+            ui,
+            "Background:",
+            &mut def.backgroundimage,
+            LABEL_W,
+            FIELD_W,
+        );
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Music:"));
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut def.music).desired_width(FIELD_W));
+            if response.changed() {
+                def.music = def.music.to_uppercase();
+                changed = true;
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.label(
+            egui::RichText::new(format!(
+                "{} layer(s). Select a layer, anim or frame in the list to edit it.",
+                def.layers.len()
+            ))
+            .weak()
+            .size(10.0),
+        );
+    });
+
+    changed
+}
+
+fn draw_layer_editor(
+    ui: &mut egui::Ui,
+    layer: &mut InterlevelLayer,
+    path: &[usize],
+    state: &PreviewState,
+    actions: &mut Vec<DocumentAction>,
+) -> bool {
+    let mut changed = false;
+    ui.vertical_centered(|ui| {
+        ui.heading("Layer");
+        ui.label(
+            egui::RichText::new(format!("{} anim(s)", layer.anims.len()))
+                .weak()
+                .size(10.0),
+        );
+        ui.add_space(4.0);
+        if ui.button("Add Anim").clicked() {
+            actions.push(DocumentAction::Interlevel(InterlevelAction::Add {
+                parent: path.to_vec(),
+            }));
+        }
+    });
+
+    ui.add_space(8.0);
+    ui.heading("Conditions");
+    changed |=
+        conditions::draw_interlevel_conditions_editor(ui, &mut layer.conditions, state, None);
+    changed
+}
+
+fn draw_anim_editor(
+    ui: &mut egui::Ui,
+    anim: &mut InterlevelAnim,
+    path: &[usize],
+    assets: &AssetStore,
+    state: &PreviewState,
+    active_frame: Option<usize>,
+    actions: &mut Vec<DocumentAction>,
+) -> bool {
+    let mut changed = false;
+    ui.vertical_centered(|ui| {
+        ui.heading("Position");
+        changed |= draw_value_row(ui, "X:", &mut anim.x, |d| d.range(-640..=640));
+        changed |= draw_value_row(ui, "Y:", &mut anim.y, |d| d.range(-400..=400));
+    });
+
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.heading("Frames");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if !anim.frames.is_empty() && ui.button("Clear").clicked() {
+                actions.push(DocumentAction::Interlevel(InterlevelAction::ClearFrames {
+                    anim: path.to_vec(),
+                }));
+            }
+            if ui.button("Add").clicked() {
+                actions.push(DocumentAction::Interlevel(InterlevelAction::Add {
+                    parent: path.to_vec(),
+                }));
+            }
+        });
+    });
+    ui.separator();
+    changed |= draw_frame_sequence(
+        ui,
+        &mut anim.frames,
+        assets,
+        "interlevel_frame",
+        active_frame,
+    )
+    .0;
+
+    ui.add_space(8.0);
+    ui.heading("Conditions");
+    let width = anim
+        .frames
+        .get(active_frame.unwrap_or(0))
+        .and_then(|f| assets.texture(ui.ctx(), &AssetId::new(&f.image)))
+        .map(|tex| tex.size_vec2().x);
+    changed |=
+        conditions::draw_interlevel_conditions_editor(ui, &mut anim.conditions, state, width);
+    changed
+}
+
+/// The mutually exclusive ways a frame's duration is decided.
+const DURATION_KINDS: [(FrameType, &str); 3] = [
+    (FrameType::FIXED, "Fixed"),
+    (FrameType::RANDOM, "Random"),
+    (FrameType::INFINITE, "Infinite"),
+];
+
+fn draw_frame_editor(ui: &mut egui::Ui, frame: &mut InterlevelFrame, is_first: bool) -> bool {
+    let mut changed = false;
+    ui.vertical_centered(|ui| {
+        ui.heading("Frame");
+        changed |= common::draw_asset_name_field(ui, "Image:", &mut frame.image, LABEL_W, FIELD_W);
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - LABEL_W - FIELD_W).max(0.0) / 2.0);
+            ui.add_sized([LABEL_W, 18.0], egui::Label::new("Duration:"));
+            let current = DURATION_KINDS
+                .iter()
+                .find(|(flag, _)| frame.frame_type.contains(*flag))
+                .map_or("None", |(_, name)| name);
+            let menu_id = ui.make_persistent_id("interlevel_duration_kind");
+            let btn = shared::combobox_button(ui, current, FIELD_W);
+            if btn.clicked() {
+                ContextMenu::open(ui, menu_id, btn.rect.left_bottom());
+            }
+            if let Some(menu) = ContextMenu::get(ui, menu_id) {
+                ContextMenu::show(ui, menu, btn.clicked(), |ui| {
+                    for (flag, name) in DURATION_KINDS {
+                        if common::custom_menu_item(ui, name, frame.frame_type.contains(flag)) {
+                            for (other, _) in DURATION_KINDS {
+                                frame.frame_type.remove(other);
+                            }
+                            frame.frame_type.insert(flag);
+                            changed = true;
+                            ContextMenu::close(ui);
+                        }
+                    }
+                });
+            }
+        });
+
+        if !frame.frame_type.contains(FrameType::INFINITE) {
+            let label = if frame.frame_type.contains(FrameType::RANDOM) {
+                "Min Tics:"
+            } else {
+                "Tics:"
+            };
+            changed |= draw_tics_row(ui, label, &mut frame.duration);
+        }
+        if frame.frame_type.contains(FrameType::RANDOM) {
+            changed |= draw_tics_row(ui, "Max Tics:", &mut frame.maxduration);
+        }
+
+        ui.add_space(4.0);
+        let mut flag_box = |ui: &mut egui::Ui, flag: FrameType, label: &str, hint: &str| {
+            let mut on = frame.frame_type.contains(flag);
+            if ui.checkbox(&mut on, label).on_hover_text(hint).changed() {
+                frame.frame_type.set(flag, on);
+                changed = true;
+            }
+        };
+        if is_first {
+            flag_box(
+                ui,
+                FrameType::RANDOM_FIRST,
+                "Random first frame",
+                "Start the anim on a random frame instead of this one.",
+            );
+        }
+        flag_box(
+            ui,
+            FrameType::WIDESCREEN,
+            "Widescreen only",
+            "Only show this frame when the intermission is drawn widescreen.",
+        );
+    });
+    changed
+}
+
 impl LumpUI for InterlevelDefFile {
-    fn draw_properties(&mut self, _ui: &mut egui::Ui, _ctx: &PropertyContext) -> bool {
-        false
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool) {
+        let mut actions = Vec::new();
+        let layers = &mut self.data.layers;
+        let changed = match Self::single_selection(ctx.selection) {
+            Some(&[l]) if l < layers.len() => {
+                draw_layer_editor(ui, &mut layers[l], &[l], ctx.state, &mut actions)
+            }
+            Some(&[l, a]) => match layers.get_mut(l).and_then(|x| x.anims.get_mut(a)) {
+                Some(anim) => {
+                    let playback = ctx.state.viewer.interlevel.anims.get(&(l, a));
+                    let frame = playback.map(|p| p.frame);
+                    draw_anim_editor(
+                        ui,
+                        anim,
+                        &[l, a],
+                        ctx.assets,
+                        ctx.state,
+                        frame,
+                        &mut actions,
+                    )
+                }
+                None => false,
+            },
+            Some(&[l, a, f]) => {
+                let frame = layers
+                    .get_mut(l)
+                    .and_then(|x| x.anims.get_mut(a))
+                    .and_then(|x| x.frames.get_mut(f));
+                match frame {
+                    Some(frame) => draw_frame_editor(ui, frame, f == 0),
+                    None => false,
+                }
+            }
+            _ => draw_interlevel_editor(ui, self),
+        };
+        (actions, changed)
+    }
+
+    fn tick(&self, ctx: &mut TickContext) {
+        let widescreen = ctx.state.sim.engine.widescreen_mode;
+        ctx.state
+            .viewer
+            .interlevel
+            .advance(&self.data, ctx.time, widescreen, &mut rand::rng());
+
+        let show_tally = ctx.state.intermission.show_tally;
+        if show_tally {
+            let targets = TallyTargets::from_state(ctx.state);
+            ctx.state.viewer.tally.advance(&targets, ctx.time);
+        }
+
+        if show_tally || self.data.layers.iter().any(|l| !l.anims.is_empty()) {
+            ctx.ctx.request_repaint();
+        }
     }
-    fn tick(&self, _ctx: &mut TickContext) {}
+
     fn draw_layer_list(
         &mut self,
-        _ui: &mut egui::Ui,
-        _ctx: &mut LayerContext,
+        ui: &mut egui::Ui,
+        ctx: &mut LayerContext,
     ) -> (Vec<DocumentAction>, bool) {
-        (Vec::new(), false)
+        let mut actions = Vec::new();
+
+        if shared::heading_action_button(ui, "Interlevel", Some("Add Layer"), false).clicked() {
+            actions.push(DocumentAction::UndoSnapshot);
+            actions.push(DocumentAction::Interlevel(InterlevelAction::Add {
+                parent: Vec::new(),
+            }));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("interlevel_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                crate::ui::layers::interlevel::draw_interlevel_layers_list(
+                    ui,
+                    self,
+                    ctx.selection,
+                    ctx.assets,
+                    ctx.state,
+                    &mut actions,
+                );
+            });
+        (actions, false)
     }
 
-    fn header_info(&self, _sel: &HashSet<Vec<usize>>) -> (String, String, egui::Color32) {
+    fn header_info(&self, sel: &HashSet<Vec<usize>>) -> (String, String, egui::Color32) {
+        let (title, description) = match Self::single_selection(sel) {
+            Some(&[l]) => (
+                format!("Layer {}", l + 1),
+                "A group of anims shown together.",
+            ),
+            Some(&[l, a]) => (
+                format!("Layer {} / Anim {}", l + 1, a + 1),
+                "An animated patch placed on the background.",
+            ),
+            Some(&[_, _, f]) => (
+                format!("Frame {}", f + 1),
+                "One image in an anim's sequence.",
+            ),
+            _ => (
+                "Interlevel Animation".to_string(),
+                "Victory screens and tallies.",
+            ),
+        };
         (
-            "Interlevel Animation".into(),
-            "Victory screens and tallies.".into(),
+            title,
+            description.into(),
             egui::Color32::from_rgb(40, 60, 40),
         )
     }
 
+    fn get_preview_content(&self, _: &egui::Ui, ctx: &PropertyContext) -> Option<PreviewContent> {
+        let layers = &self.data.layers;
+        let image = match Self::single_selection(ctx.selection) {
+            Some(&[l, a]) => layers.get(l)?.anims.get(a)?.frames.first()?.image.clone(),
+            Some(&[l, a, f]) => layers.get(l)?.anims.get(a)?.frames.get(f)?.image.clone(),
+            _ => self.data.backgroundimage.clone(),
+        };
+        (!image.is_empty()).then_some(PreviewContent::Image(image))
+    }
+
     fn render_viewport(&self, ui: &mut egui::Ui, ctx: &mut ViewportContext) -> Vec<DocumentAction> {
+        let mut actions = Vec::new();
         crate::ui::viewport::render_id24_background(
             ui,
             &self.data.backgroundimage,
             ctx.assets,
             ctx.proj,
         );
-        Vec::new()
+
+        let widescreen = ctx.state.sim.engine.widescreen_mode;
+        let mouse = ctx.state.interaction.virtual_mouse_pos;
+        let mut hovered = None;
+        let mut outlines = Vec::new();
+
+        for (l, layer) in self.data.layers.iter().enumerate() {
+            let layer_shown = render::layer_shown(layer, ctx.state);
+            for (a, anim) in layer.anims.iter().enumerate() {
+                let playback = &ctx.state.viewer.interlevel;
+                let shown = playback.current_frame(l, a, anim, widescreen);
+                let visible =
+                    layer_shown && render::anim_shown(ui.ctx(), anim, shown, ctx.state, ctx.assets);
+                if let Some(frame) = shown.filter(|_| visible) {
+                    render::draw_anim_frame(ui.painter(), anim, frame, ctx.assets, ctx.proj);
+                }
+
+                // Hidden anims stay grabbable by their first frame.
+                let Some(frame) = shown.or(anim.frames.first()) else {
+                    continue;
+                };
+
+                let selected = ctx.selection.iter().any(|p| p.starts_with(&[l, a]));
+                let rect = render::frame_rect(ui.ctx(), anim, frame, ctx.assets, ctx.proj);
+                if let Some(rect) = rect {
+                    // Later anims draw on top, so the last hit wins. Anims
+                    // whose conditions fail only react once selected.
+                    if rect.contains(mouse) && (visible || selected) {
+                        hovered = Some(vec![l, a]);
+                    }
+                    if selected {
+                        outlines.push(rect);
+                    }
+                }
+            }
+        }
+
+        if ctx.state.intermission.show_tally {
+            let targets = TallyTargets::from_state(ctx.state);
+            let counts = tally::counts_at(&targets, ctx.state.viewer.tally.tic as u32);
+            tally::draw_tally(ui.painter(), ctx.state, &counts, ctx.assets, ctx.proj);
+        }
+
+        let hovered = hovered.filter(|_| ctx.viewport_res.hovered() && !ctx.is_panning);
+        ctx.state.interaction.hovered_path = hovered.clone();
+        if let Some(path) = hovered.filter(|_| ctx.primary_pressed) {
+            ctx.state.interaction.grabbed_path = Some(path.clone());
+            if ui.input(|i| i.modifiers.shift) {
+                actions.push(DocumentAction::Tree(TreeAction::ToggleSelection(vec![
+                    path,
+                ])));
+            } else if !ctx.selection.contains(&path) {
+                actions.push(DocumentAction::Tree(TreeAction::Select(vec![path])));
+            }
+        }
+
+        let stroke = egui::Stroke::new(1.0, ui.visuals().selection.stroke.color);
+        for rect in outlines {
+            let screen = egui::Rect::from_min_max(
+                ctx.proj.to_screen(rect.min),
+                ctx.proj.to_screen(rect.max),
+            );
+            ui.painter()
+                .rect_stroke(screen, 0.0, stroke, egui::StrokeKind::Outside);
+        }
+
+        actions
     }
 }
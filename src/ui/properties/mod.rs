@@ -1,4 +1,5 @@
 use crate::assets::AssetStore;
+use crate::document::DocumentAction;
 use crate::models::ProjectData;
 use crate::models::sbardef::{Element, ElementWrapper, ExportTarget};
 use crate::state::PreviewState;
@@ -15,6 +16,7 @@ mod descriptions;
 pub mod editor;
 mod face;
 mod finale;
+mod fire_ramp;
 pub(crate) mod font_cache;
 mod graphics;
 mod interlevel;
@@ -32,6 +34,7 @@ mod umapinfo;
 use editor::PropertiesUI;
 use font_cache::FontCache;
 use preview::PreviewContent;
+pub(crate) use umapinfo::frame_issues as umapinfo_issues;
 
 impl PropertiesUI for ElementWrapper {
     fn draw_specific_fields(
@@ -106,14 +109,16 @@ impl PropertiesUI for ElementWrapper {
     }
 }
 
-/// Renders the entire properties sidebar panel.
+/// Renders the entire properties sidebar panel. Returns (Actions to execute, has
+/// data changed).
 pub fn draw_properties_panel(
     ui: &mut egui::Ui,
     file: &mut Option<ProjectData>,
     selection: &HashSet<Vec<usize>>,
     assets: &AssetStore,
     state: &PreviewState,
-) -> bool {
+) -> (Vec<DocumentAction>, bool) {
+    let mut actions = Vec::new();
     let mut changed = false;
     let target = file.as_ref().map_or(ExportTarget::Basic, |f| f.target());
     ui.data_mut(|d| d.insert_temp(egui::Id::new("cacoco_current_target"), target));
@@ -156,7 +161,9 @@ pub fn draw_properties_panel(
         egui::ScrollArea::vertical()
             .id_salt("prop_scroll")
             .show(ui, |ui| {
-                changed |= f.draw_properties(ui, &prop_ctx);
+                let (lump_actions, lump_changed) = f.draw_properties(ui, &prop_ctx);
+                actions = lump_actions;
+                changed |= lump_changed;
             });
     } else {
         draw_static_header(
@@ -167,7 +174,7 @@ pub fn draw_properties_panel(
         );
     }
 
-    changed
+    (actions, changed)
 }
 
 fn draw_static_header(ui: &mut egui::Ui, title: &str, desc: &str, color: egui::Color32) {
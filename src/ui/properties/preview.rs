@@ -33,12 +33,12 @@ pub fn draw_preview_panel(ui: &mut egui::Ui, assets: &AssetStore, content: Previ
         egui::StrokeKind::Inside,
     );
 
-    let mut textures: Vec<&egui::TextureHandle> = Vec::new();
+    let mut textures: Vec<egui::TextureHandle> = Vec::new();
 
     match content {
         PreviewContent::Image(name) => {
             let id = AssetId::new(&name);
-            if let Some(tex) = assets.textures.get(&id) {
+            if let Some(tex) = assets.texture(ui.ctx(), &id) {
                 textures.push(tex);
             }
         }
@@ -50,7 +50,7 @@ pub fn draw_preview_panel(ui: &mut egui::Ui, assets: &AssetStore, content: Previ
             if let Some(s) = stem {
                 for char in text.chars() {
                     let id = assets.resolve_patch_id(&s, char, is_number_font);
-                    if let Some(tex) = assets.textures.get(&id) {
+                    if let Some(tex) = assets.texture(ui.ctx(), &id) {
                         textures.push(tex);
                     }
                 }
@@ -1,9 +1,14 @@
+use crate::constants::DOOM_TICS_PER_SEC;
 use crate::document::actions::TreeAction;
 use crate::document::{DocumentAction, determine_insertion_point};
 use crate::models::sbardef::{
     AnimationDef, CanvasDef, ComponentDef, ComponentType, Element, ElementWrapper, ExportTarget,
     FaceDef, GraphicDef, ListDef, NumberDef, NumberType, SBarDefFile, StringDef, TextHelperDef,
 };
+use crate::render::{
+    self,
+    offscreen::{self, OffscreenOptions},
+};
 use crate::ui::context_menu::ContextMenu;
 use crate::ui::properties::editor::{LayerContext, PropertiesUI, TickContext, ViewportContext};
 use crate::ui::properties::{
@@ -14,8 +19,11 @@ use crate::ui::properties::{
 };
 use eframe::egui;
 use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 const PROP_TAB_KEY: &str = "cacoco_sbar_tab_state";
+const TRANSLUCENCY_OVERLAY_KEY: &str = "cacoco_tranmap_overlay";
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
 enum PropertyTab {
@@ -23,8 +31,123 @@ enum PropertyTab {
     Conditions,
 }
 
+/// What the translucency overlay was last rendered from. `tic` is only set
+/// while something on the bar moves by itself, so a still bar is rendered
+/// once and animated ones at most once per Doom tic.
+#[derive(Clone, PartialEq, Eq)]
+struct OverlayKey {
+    bar_idx: usize,
+    widescreen: bool,
+    content: u64,
+    sim: u64,
+    tic: Option<u64>,
+}
+
+#[derive(Clone)]
+struct OverlayCache {
+    key: OverlayKey,
+    texture: Option<egui::TextureHandle>,
+}
+
+fn fingerprint(value: &impl serde::Serialize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(value)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Covers the pixels blended through a tranmap with a software render, which
+/// looks each one up in the table like ports do. Everything else stays as the
+/// painter drew it, hover and selection feedback included.
+///
+/// The render is cached and only redone when the bar, the simulation or the
+/// screen mode changes, or on a new tic while the bar is animated.
+fn draw_translucency_overlay(
+    ui: &egui::Ui,
+    file: &SBarDefFile,
+    bar_idx: usize,
+    ctx: &ViewportContext,
+) {
+    let time = ui.input(|i| i.time);
+    let tic = (time * DOOM_TICS_PER_SEC) as u64;
+    let state = &*ctx.state;
+    let moving = file
+        .data
+        .status_bars
+        .get(bar_idx)
+        .is_some_and(|bar| render::is_animated(&bar.children))
+        || state.viewer.pain_timer > 0.0
+        || state.viewer.evil_timer > 0.0
+        || state.viewer.weapon_offset_y > 0.0
+        || state.interaction.strobe_timer > 0.0
+        || state.interaction.hovered_path.is_some()
+        || state.interaction.grabbed_path.is_some();
+
+    let key = OverlayKey {
+        bar_idx,
+        widescreen: state.sim.engine.widescreen_mode,
+        content: fingerprint(file),
+        sim: fingerprint(&state.sim),
+        tic: moving.then_some(tic),
+    };
+
+    let id = egui::Id::new(TRANSLUCENCY_OVERLAY_KEY);
+    let cached = ui.data(|d| d.get_temp::<OverlayCache>(id));
+    let texture = match cached {
+        Some(cache) if cache.key == key => cache.texture,
+        cached => {
+            let options = OffscreenOptions {
+                widescreen: key.widescreen,
+                time,
+                ..Default::default()
+            };
+            let texture = offscreen::translucency_overlay(
+                ctx.assets, file, bar_idx, state, &options,
+            )
+            .map(|image| match cached.and_then(|cache| cache.texture) {
+                Some(mut texture) => {
+                    texture.set(image, egui::TextureOptions::NEAREST);
+                    texture
+                }
+                None => ui.ctx().load_texture(
+                    TRANSLUCENCY_OVERLAY_KEY,
+                    image,
+                    egui::TextureOptions::NEAREST,
+                ),
+            });
+            ui.data_mut(|d| {
+                d.insert_temp(
+                    id,
+                    OverlayCache {
+                        key,
+                        texture: texture.clone(),
+                    },
+                )
+            });
+            texture
+        }
+    };
+    let Some(texture) = texture else {
+        return;
+    };
+    let size = texture.size_vec2();
+
+    let rect = egui::Rect::from_min_max(
+        ctx.proj.to_screen(egui::Pos2::ZERO),
+        ctx.proj.to_screen(size.to_pos2()),
+    );
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    ui.painter()
+        .image(texture.id(), rect, uv, egui::Color32::WHITE);
+}
+
 impl LumpUI for SBarDefFile {
-    fn draw_properties(&mut self, ui: &mut egui::Ui, ctx: &PropertyContext) -> bool {
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool) {
         let mut changed = false;
 
         let mut current_tab = ui.data(|d| {
@@ -138,7 +261,7 @@ impl LumpUI for SBarDefFile {
         }
 
         ui.data_mut(|d| d.insert_temp(egui::Id::new(PROP_TAB_KEY), current_tab));
-        changed
+        (Vec::new(), changed)
     }
 
     fn tick(&self, _ctx: &mut TickContext) {}
@@ -412,7 +535,7 @@ impl LumpUI for SBarDefFile {
     }
 
     fn render_viewport(&self, ui: &mut egui::Ui, ctx: &mut ViewportContext) -> Vec<DocumentAction> {
-        use crate::render::{self, RenderPass};
+        use crate::render::RenderPass;
         let mut actions = Vec::new();
 
         let bar_idx = ctx
@@ -420,7 +543,13 @@ impl LumpUI for SBarDefFile {
             .min(self.data.status_bars.len().saturating_sub(1));
         let bar = &self.data.status_bars[bar_idx];
 
-        crate::ui::viewport::render_statusbar_workspace(ui, bar, ctx.assets, ctx.state, ctx.proj);
+        crate::ui::viewport::render_statusbar_workspace(
+            ui.painter(),
+            bar,
+            ctx.assets,
+            ctx.state,
+            ctx.proj,
+        );
 
         let root_y = if bar.fullscreen_render {
             0.0
@@ -444,6 +573,8 @@ impl LumpUI for SBarDefFile {
                 is_dragging: ctx.viewport_res.dragged_by(egui::PointerButton::Primary),
                 is_viewport_clicked: ctx.viewport_res.contains_pointer() && ctx.primary_down,
                 is_native: false,
+                translation: None,
+                tranmap: None,
             };
 
             if ctx.viewport_res.hovered() {
@@ -496,6 +627,8 @@ impl LumpUI for SBarDefFile {
                 is_dragging: ctx.viewport_res.dragged_by(egui::PointerButton::Primary),
                 is_viewport_clicked: ctx.viewport_res.contains_pointer() && ctx.primary_down,
                 is_native: false,
+                translation: None,
+                tranmap: None,
             };
 
             for (idx, child) in bar.children.iter().enumerate() {
@@ -512,6 +645,10 @@ impl LumpUI for SBarDefFile {
 
         draw_pass(ui.painter(), RenderPass::Background);
 
+        if render::uses_tranmap(&bar.children, ctx.assets) {
+            draw_translucency_overlay(ui, self, bar_idx, ctx);
+        }
+
         if !ctx.selection.is_empty() && ctx.state.interaction.strobe_timer > 0.0 {
             draw_pass(ui.painter(), RenderPass::Foreground);
         }
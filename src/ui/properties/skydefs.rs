@@ -1,11 +1,13 @@
 use super::editor::{LayerContext, LumpUI, PropertyContext, TickContext, ViewportContext};
-use crate::assets::AssetStore;
+use crate::assets::{AssetId, AssetStore};
 use crate::document::DocumentAction;
-use crate::models::skydefs::{SkyDefsFile, SkyType};
+use crate::models::skydefs::{FlatMapIssue, SkyDefsDefinition, SkyDefsFile, SkyType};
+use crate::render::sky::FOV_PRESETS;
 use crate::state::PreviewState;
 use crate::ui::context_menu::ContextMenu;
 use crate::ui::properties::common;
 use crate::ui::shared;
+use crate::ui::viewport_controller::{SKY_LOOK_SENSITIVITY, SKY_PITCH_SENSITIVITY};
 use eframe::egui;
 use std::collections::HashSet;
 
@@ -77,7 +79,7 @@ pub fn draw_skydefs_editor(
     ui: &mut egui::Ui,
     file: &mut SkyDefsFile,
     selection_path: &[usize],
-    _assets: &AssetStore,
+    assets: &AssetStore,
     _state: &PreviewState,
 ) -> bool {
     let mut changed = false;
@@ -156,6 +158,17 @@ pub fn draw_skydefs_editor(
                 &mut s.scalex,
                 &mut s.scaley,
             );
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 130.0).max(0.0) / 2.0);
+                if ui
+                    .button("Compose Texture…")
+                    .on_hover_text("Build this texture from several patches")
+                    .clicked()
+                {
+                    let id = egui::Id::new(crate::ui::texture_composer::OPEN_ID);
+                    ui.ctx().data_mut(|d| d.insert_temp(id, s.name.clone()));
+                }
+            });
 
             match s.sky_type {
                 SkyType::Fire => {
@@ -215,7 +228,7 @@ pub fn draw_skydefs_editor(
                             }
 
                             for (i, &idx) in fire.palette.iter().enumerate() {
-                                let color = _assets.palette.get(idx as u8);
+                                let color = assets.palette.get(idx as u8);
                                 let step_rect = egui::Rect::from_min_size(
                                     rect.min + egui::vec2(i as f32 * step_w, 0.0),
                                     egui::vec2(step_w, ramp_h),
@@ -236,7 +249,14 @@ pub fn draw_skydefs_editor(
 
                         ui.add_space(8.0);
                         ui.horizontal(|ui| {
-                            ui.add_space((ui.available_width() - 155.0).max(0.0) / 2.0);
+                            ui.add_space((ui.available_width() - 240.0).max(0.0) / 2.0);
+                            if ui
+                                .button("Generate…")
+                                .on_hover_text("Match a gradient to the palette")
+                                .clicked()
+                            {
+                                super::fire_ramp::toggle(ui.ctx(), selection_path[0], fire);
+                            }
                             if ui.button("+ Add").clicked() {
                                 fire.palette.push(0);
                                 changed = true;
@@ -301,50 +321,17 @@ pub fn draw_skydefs_editor(
                 });
                 changed = true;
             }
-
-            if let Some(mappings) = &mut file.data.flatmapping {
-                let mut to_remove = None;
-                ui.add_space(4.0);
-                let row_width = ui.available_width();
-                ui.vertical(|ui| {
-                    ui.spacing_mut().item_spacing.y = 4.0;
-                    for (idx, map) in mappings.iter_mut().enumerate() {
-                        ui.horizontal(|ui| {
-                            ui.add_space((row_width - 265.0).max(0.0) / 2.0);
-
-                            if ui.add_sized([20.0, 20.0], egui::Button::new("X")).clicked() {
-                                to_remove = Some(idx);
-                            }
-                            ui.label("Flat:");
-                            if ui
-                                .add_sized([75.0, 18.0], egui::TextEdit::singleline(&mut map.flat))
-                                .changed()
-                            {
-                                map.flat = map.flat.to_uppercase();
-                                changed = true;
-                            }
-                            ui.label("to Sky:");
-                            if ui
-                                .add_sized([75.0, 18.0], egui::TextEdit::singleline(&mut map.sky))
-                                .changed()
-                            {
-                                map.sky = map.sky.to_uppercase();
-                                changed = true;
-                            }
-                        });
-                    }
-                });
-                if let Some(idx) = to_remove {
-                    mappings.remove(idx);
-                    changed = true;
-                }
-                if mappings.is_empty() {
-                    file.data.flatmapping = None;
-                    changed = true;
-                }
-            }
         });
 
+        if let Some(fire) = &mut s.fire {
+            changed |= super::fire_ramp::draw_fire_ramp_window(
+                ui.ctx(),
+                selection_path[0],
+                fire,
+                &assets.palette,
+            );
+        }
+
         if let Some(slot_idx) = active_slot {
             if let Some(fire) = &mut s.fire {
                 if slot_idx < fire.palette.len() {
@@ -358,7 +345,7 @@ pub fn draw_skydefs_editor(
                             let current = fire.palette[slot_idx] as u8;
                             if let Some(new_color) = super::palette_picker::draw_palette_grid(
                                 ui,
-                                &_assets.palette,
+                                &assets.palette,
                                 current,
                             ) {
                                 fire.palette[slot_idx] = new_color as i32;
@@ -374,15 +361,172 @@ pub fn draw_skydefs_editor(
         }
     }
 
+    if selection_path[0] < file.data.skies.len() {
+        changed |= draw_flat_mappings(ui, &mut file.data, assets);
+    }
+
     changed
 }
 
-impl LumpUI for SkyDefsFile {
-    fn draw_properties(&mut self, ui: &mut egui::Ui, ctx: &PropertyContext) -> bool {
-        if let Some(path) = ctx.selection.iter().next() {
-            return draw_skydefs_editor(ui, self, path, ctx.assets, ctx.state);
+/// Temp-data key holding the index of the flat mapping shown in the viewport.
+pub const FLAT_PREVIEW_ID: &str = "SKY_FLAT_PREVIEW";
+
+/// A name field with a dropdown of `names` and a filter box beside it.
+/// Names that aren't listed can still be typed; the field is outlined in red
+/// when `invalid`.
+fn draw_name_picker<'a>(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    value: &mut String,
+    names: impl Iterator<Item = &'a str>,
+    invalid: bool,
+) -> bool {
+    let mut changed = false;
+    let field = ui.add_sized([75.0, 18.0], egui::TextEdit::singleline(value));
+    if field.changed() {
+        *value = value.to_uppercase();
+        changed = true;
+    }
+    if invalid {
+        ui.painter().rect_stroke(
+            field.rect,
+            2.0,
+            egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 100, 100)),
+            egui::StrokeKind::Inside,
+        );
+    }
+    let btn = shared::combobox_button(ui, "", 20.0);
+    if btn.clicked() {
+        ContextMenu::open(ui, id, field.rect.left_bottom());
+    }
+
+    if let Some(menu) = ContextMenu::get(ui, id) {
+        let filter_id = id.with("filter");
+        let mut filter: String = ui.data(|d| d.get_temp(filter_id).unwrap_or_default());
+        ContextMenu::show(ui, menu, btn.clicked(), |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut filter)
+                    .hint_text("Filter...")
+                    .desired_width(f32::INFINITY),
+            );
+            let needle = filter.to_uppercase();
+            let mut matches = names.filter(|n| n.contains(needle.as_str())).peekable();
+            if matches.peek().is_none() {
+                ui.label(egui::RichText::new("Nothing to pick.").weak().size(11.0));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(220.0)
+                .show(ui, |ui| {
+                    for name in matches {
+                        if common::custom_menu_item(ui, name, name == value.as_str()) {
+                            *value = name.to_string();
+                            changed = true;
+                            ContextMenu::close(ui);
+                        }
+                    }
+                });
+        });
+        ui.data_mut(|d| d.insert_temp(filter_id, filter));
+    }
+    changed
+}
+
+/// Renders the global flat-to-sky mappings, with pickers offering the
+/// loaded flats and the defined skies, and any dangling references listed.
+fn draw_flat_mappings(
+    ui: &mut egui::Ui,
+    data: &mut SkyDefsDefinition,
+    assets: &AssetStore,
+) -> bool {
+    let mut changed = false;
+    let issues = data.flatmapping_issues(&assets.flats);
+    let sky_names: Vec<String> = data.skies.iter().map(|s| s.name.clone()).collect();
+
+    let preview_id = egui::Id::new(FLAT_PREVIEW_ID);
+    let mut preview: Option<usize> = ui.data(|d| d.get_temp(preview_id).unwrap_or_default());
+
+    let Some(mappings) = &mut data.flatmapping else {
+        return false;
+    };
+
+    let mut to_remove = None;
+    ui.add_space(4.0);
+    let row_width = ui.available_width();
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 4.0;
+        for (idx, map) in mappings.iter_mut().enumerate() {
+            let has = |issue| issues.contains(&(idx, issue));
+            ui.horizontal(|ui| {
+                ui.add_space((row_width - 265.0).max(0.0) / 2.0);
+
+                if ui.add_sized([20.0, 20.0], egui::Button::new("X")).clicked() {
+                    to_remove = Some(idx);
+                }
+
+                let (thumb, _) =
+                    ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::hover());
+                let texture = assets.texture(ui.ctx(), &AssetId::new(&map.flat));
+                common::paint_thumb_content(ui, thumb, texture.as_ref(), None);
+
+                let flat_id = ui.make_persistent_id(("flat_pick", idx));
+                let flats = assets.flats.iter().map(|f| f.as_str());
+                let bad_flat = has(FlatMapIssue::MissingFlat) || has(FlatMapIssue::DuplicateFlat);
+                changed |= draw_name_picker(ui, flat_id, &mut map.flat, flats, bad_flat);
+
+                ui.label("to");
+                let sky_id = ui.make_persistent_id(("sky_pick", idx));
+                let skies = sky_names.iter().map(|s| s.as_str());
+                let bad_sky = has(FlatMapIssue::UndefinedSky);
+                changed |= draw_name_picker(ui, sky_id, &mut map.sky, skies, bad_sky);
+
+                let is_previewed = preview == Some(idx);
+                if ui
+                    .add(egui::Button::new("View").selected(is_previewed))
+                    .on_hover_text("Show this flat next to its sky in the viewport")
+                    .clicked()
+                {
+                    preview = if is_previewed { None } else { Some(idx) };
+                }
+            });
         }
-        false
+    });
+
+    if !issues.is_empty() {
+        ui.add_space(4.0);
+        for (idx, issue) in &issues {
+            ui.label(
+                egui::RichText::new(issue.message(&mappings[*idx]))
+                    .color(egui::Color32::from_rgb(200, 100, 100))
+                    .size(10.0),
+            );
+        }
+    }
+
+    if let Some(idx) = to_remove {
+        mappings.remove(idx);
+        preview = None;
+        changed = true;
+    }
+    if mappings.is_empty() {
+        data.flatmapping = None;
+        changed = true;
+    }
+    ui.data_mut(|d| d.insert_temp(preview_id, preview));
+
+    changed
+}
+
+impl LumpUI for SkyDefsFile {
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool) {
+        let changed = match ctx.selection.iter().next() {
+            Some(path) => draw_skydefs_editor(ui, self, path, ctx.assets, ctx.state),
+            None => false,
+        };
+        (Vec::new(), changed)
     }
 
     fn tick(&self, ctx: &mut TickContext) {
@@ -392,11 +536,7 @@ impl LumpUI for SkyDefsFile {
                     let sky_id = ctx.assets.resolve_sky_id(&sky.name);
 
                     let sim = ctx.state.viewer.fire_sims.entry(sky_id).or_insert_with(|| {
-                        let (w, h) = if let Some(tex) = ctx.assets.textures.get(&sky_id) {
-                            (tex.size()[0] as u32, tex.size()[1] as u32)
-                        } else {
-                            (256, 128)
-                        };
+                        let (w, h) = ctx.assets.image_size(&sky_id).unwrap_or((256, 128));
                         crate::render::fire::FireSimulation::new(w, h, ctx.time)
                     });
 
@@ -406,7 +546,7 @@ impl LumpUI for SkyDefsFile {
                         let rgba = sim.generate_rgba(&fire_def.palette, &ctx.assets.palette);
                         let dynamic_key = format!("_FIRE_ANIM_{}", sky.name);
                         ctx.assets
-                            .load_rgba(ctx.ctx, &dynamic_key, sim.width, sim.height, &rgba);
+                            .load_rgba(&dynamic_key, sim.width, sim.height, &rgba);
                         ctx.ctx.request_repaint();
                     }
                 }
@@ -461,27 +601,121 @@ impl LumpUI for SkyDefsFile {
         )
     }
 
-    fn render_viewport(
-        &self,
-        _ui: &mut egui::Ui,
-        ctx: &mut ViewportContext,
-    ) -> Vec<DocumentAction> {
+    fn render_viewport(&self, ui: &mut egui::Ui, ctx: &mut ViewportContext) -> Vec<DocumentAction> {
+        let preview: Option<usize> = ui.ctx().data(|d| {
+            d.get_temp(egui::Id::new(FLAT_PREVIEW_ID))
+                .unwrap_or_default()
+        });
+        let mapping = preview.and_then(|i| self.data.flatmapping.as_ref()?.get(i));
+        if let Some(map) = mapping {
+            let sky = self
+                .data
+                .skies
+                .iter()
+                .find(|s| s.name.eq_ignore_ascii_case(&map.sky));
+            crate::render::sky::draw_flat_mapping(
+                ui.painter(),
+                map,
+                sky,
+                ctx.assets,
+                ctx.state,
+                ctx.proj,
+                ui.input(|i| i.time),
+            );
+            return Vec::new();
+        }
+
         let sky_idx = ctx
             .current_item_idx
             .min(self.data.skies.len().saturating_sub(1));
         if let Some(sky) = self.data.skies.get(sky_idx) {
             crate::render::sky::draw_sky_view(
-                _ui.painter(),
+                ui.painter(),
                 sky,
                 ctx.assets,
                 ctx.state,
                 ctx.proj,
-                _ui.input(|i| i.time),
+                ui.input(|i| i.time),
             );
+            draw_camera_bar(ui, ctx);
         } else {
-            _ui.painter()
+            ui.painter()
                 .rect_filled(ctx.proj.screen_rect, 0.0, egui::Color32::BLACK);
         }
         Vec::new()
     }
 }
+
+/// Draws the camera strip along the bottom of the sky preview and, while
+/// mouse look is on, turns the camera with the pointer. Escape leaves mouse
+/// look.
+fn draw_camera_bar(ui: &mut egui::Ui, ctx: &mut ViewportContext) {
+    let camera = &mut ctx.state.viewer.sky_camera;
+    let screen = ctx.proj.screen_rect.intersect(ui.clip_rect());
+    let bar_rect = egui::Rect::from_min_max(
+        egui::pos2(screen.left(), screen.bottom() - 28.0),
+        screen.right_bottom(),
+    );
+
+    if camera.mouse_look {
+        let (delta, hover, escape) = ui.input(|i| {
+            (
+                i.pointer.delta(),
+                i.pointer.hover_pos(),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        let looking = hover.is_some_and(|p| screen.contains(p) && !bar_rect.contains(p));
+        if escape {
+            camera.mouse_look = false;
+        } else if looking && !ctx.primary_down {
+            camera.look(
+                delta.x * SKY_LOOK_SENSITIVITY,
+                delta.y * SKY_PITCH_SENSITIVITY,
+            );
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+        }
+    }
+
+    ui.painter()
+        .rect_filled(bar_rect, 0.0, egui::Color32::from_black_alpha(160));
+    ui.scope_builder(
+        egui::UiBuilder::new().max_rect(bar_rect.shrink(4.0)),
+        |ui| {
+            ui.horizontal_centered(|ui| {
+                if ui
+                    .add(egui::Button::new("Mouse Look").selected(camera.mouse_look))
+                    .on_hover_text("Turn the view with the mouse. Esc to stop.")
+                    .clicked()
+                {
+                    camera.mouse_look = !camera.mouse_look;
+                }
+
+                let current = FOV_PRESETS
+                    .iter()
+                    .find(|(_, fov)| (fov - camera.fov).abs() < 0.01)
+                    .map_or("Custom", |(name, _)| name);
+                egui::ComboBox::from_id_salt("sky_fov")
+                    .selected_text(current)
+                    .width(120.0)
+                    .show_ui(ui, |ui| {
+                        for (name, fov) in FOV_PRESETS {
+                            ui.selectable_value(&mut camera.fov, fov, name);
+                        }
+                    });
+
+                if ui.button("Reset").clicked() {
+                    camera.look(-camera.yaw, -camera.pitch);
+                }
+                ui.label(
+                    egui::RichText::new(format!(
+                        "yaw {:.0}  pitch {:+.0}°",
+                        camera.yaw, camera.pitch
+                    ))
+                    .monospace()
+                    .color(egui::Color32::WHITE),
+                );
+            });
+        },
+    );
+}
@@ -35,11 +35,7 @@ pub fn rebake_text(element: &mut ElementWrapper, assets: &AssetStore, fonts: &Fo
 
         let id = assets.resolve_patch_id(&stem, c, is_number_font);
 
-        let width = if let Some(tex) = assets.textures.get(&id) {
-            tex.size()[0] as i32
-        } else {
-            8
-        };
+        let width = assets.image_size(&id).map_or(8, |(w, _)| w as i32);
 
         let letter_graphic = ElementWrapper {
             data: Element::Graphic(GraphicDef {
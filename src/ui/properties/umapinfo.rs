@@ -1,13 +1,18 @@
-use super::editor::{LayerContext, LumpUI, PropertyContext};
+use super::editor::{LayerContext, LumpUI, PropertyContext, ViewportContext};
 use crate::assets::AssetStore;
 use crate::document::DocumentAction;
-use crate::models::umapinfo::{MapEntry, UmapField, UmapInfoFile};
+use crate::document::actions::UmapAction;
+use crate::models::umapinfo::{MapEntry, UmapField, UmapInfoFile, UmapIssue};
+use crate::render::progression::{self, GraphLayout};
 use crate::state::PreviewState;
 use crate::ui::context_menu::ContextMenu;
 use crate::ui::properties::common;
 use crate::ui::shared;
 use eframe::egui;
 use std::collections::HashSet;
+use std::sync::Arc;
+
+const ISSUES_KEY: &str = "cacoco_umapinfo_issues";
 
 /// Metadata for a UMAPINFO field type to facilitate UI lookups and creation.
 struct FieldMetadata {
@@ -124,15 +129,39 @@ const FIELD_REGISTRY: &[FieldMetadata] = &[
     },
 ];
 
+/// The problems [`UmapInfoFile::issues`] finds, checked once per frame. The
+/// check walks the whole map graph and looks up every referenced lump, and
+/// the layer list, properties panel and viewport all show its result.
+pub(crate) fn frame_issues(
+    ctx: &egui::Context,
+    file: &UmapInfoFile,
+    assets: &AssetStore,
+) -> Arc<Vec<(usize, UmapIssue)>> {
+    let id = egui::Id::new(ISSUES_KEY);
+    let pass = ctx.cumulative_pass_nr();
+    let cached = ctx.data(|d| d.get_temp::<(u64, Arc<Vec<(usize, UmapIssue)>>)>(id));
+    if let Some((_, issues)) = cached.filter(|(cached_pass, _)| *cached_pass == pass) {
+        return issues;
+    }
+    let issues = Arc::new(file.issues(|kind, name| assets.has_lump(kind, name)));
+    ctx.data_mut(|d| d.insert_temp(id, (pass, issues.clone())));
+    issues
+}
+
 /// Renders the high-level editor for UMAPINFO map entries in the Properties Panel.
 pub fn draw_umapinfo_editor(
     ui: &mut egui::Ui,
     file: &mut UmapInfoFile,
     selection_path: &[usize],
-    _assets: &AssetStore,
+    assets: &AssetStore,
     _state: &PreviewState,
 ) -> bool {
     let mut changed = false;
+    let issues: Vec<UmapIssue> = frame_issues(ui.ctx(), file, assets)
+        .iter()
+        .filter(|(i, _)| *i == selection_path[0])
+        .map(|(_, issue)| issue.clone())
+        .collect();
 
     if let Some(map) = file.data.maps.get_mut(selection_path[0]) {
         ui.vertical_centered(|ui| {
@@ -149,6 +178,17 @@ pub fn draw_umapinfo_editor(
             });
             ui.add_space(8.0);
 
+            for issue in &issues {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", issue.message()))
+                        .color(egui::Color32::from_rgb(200, 100, 100))
+                        .size(11.0),
+                );
+            }
+            if !issues.is_empty() {
+                ui.add_space(8.0);
+            }
+
             ui.horizontal(|ui| {
                 ui.label(egui::RichText::new(format!("Keys: {}", map.fields.len())).weak());
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -346,6 +386,29 @@ fn draw_field_card(
                             ui.add(egui::TextEdit::singleline(key).desired_width(20.0));
                         });
                     }
+                    UmapField::Unknown { key, raw_value } => {
+                        ui.horizontal(|ui| {
+                            ui.label("Key:");
+                            if ui
+                                .add(egui::TextEdit::singleline(key).desired_width(90.0))
+                                .changed()
+                            {
+                                changed = true;
+                            }
+                        });
+                        if ui
+                            .add(
+                                egui::TextEdit::multiline(raw_value)
+                                    .code_editor()
+                                    .desired_width(ui.available_width())
+                                    .desired_rows(1),
+                            )
+                            .on_hover_text("Written to the lump as-is")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -383,7 +446,7 @@ fn draw_add_field_menu(ui: &mut egui::Ui, map: &mut MapEntry) -> bool {
 /// Renders the scrollable dropdown to change an existing field's type.
 fn draw_change_field_menu(ui: &mut egui::Ui, field: &mut UmapField) -> bool {
     let mut changed = false;
-    let current_key = field.key_name();
+    let current_key = field.key_name().to_string();
 
     egui::ScrollArea::vertical()
         .max_height(300.0)
@@ -448,11 +511,16 @@ fn create_default_field(key: &str) -> UmapField {
 }
 
 impl LumpUI for UmapInfoFile {
-    fn draw_properties(&mut self, ui: &mut egui::Ui, ctx: &PropertyContext) -> bool {
-        if let Some(path) = ctx.selection.iter().next() {
-            return draw_umapinfo_editor(ui, self, path, ctx.assets, ctx.state);
-        }
-        false
+    fn draw_properties(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &PropertyContext,
+    ) -> (Vec<DocumentAction>, bool) {
+        let changed = match ctx.selection.iter().next() {
+            Some(path) => draw_umapinfo_editor(ui, self, path, ctx.assets, ctx.state),
+            None => false,
+        };
+        (Vec::new(), changed)
     }
 
     fn draw_layer_list(
@@ -485,6 +553,45 @@ impl LumpUI for UmapInfoFile {
         (actions, false)
     }
 
+    fn render_viewport(&self, ui: &mut egui::Ui, ctx: &mut ViewportContext) -> Vec<DocumentAction> {
+        let layout = GraphLayout::new(self, ctx.proj.origin_x);
+        let issues = frame_issues(ui.ctx(), self, ctx.assets);
+        let flagged = issues.iter().map(|(i, _)| *i).collect();
+        let selected = ctx.selection.iter().next().map(|p| p[0]);
+        progression::draw_progression(ui.painter(), self, &layout, &flagged, selected, ctx.proj);
+
+        let hovered = ctx
+            .viewport_res
+            .hover_pos()
+            .and_then(|pos| layout.node_at(ctx.proj.to_virtual(pos)));
+        let Some(idx) = hovered.filter(|_| !ctx.is_panning) else {
+            return Vec::new();
+        };
+
+        let map = &self.data.maps[idx];
+        ctx.viewport_res.clone().on_hover_ui_at_pointer(|ui| {
+            ui.label(egui::RichText::new(&map.mapname).strong());
+            if let Some(UmapField::LevelName(name)) = map
+                .fields
+                .iter()
+                .find(|f| matches!(f, UmapField::LevelName(_)))
+            {
+                ui.label(name);
+            }
+            for (_, issue) in issues.iter().filter(|(i, _)| *i == idx) {
+                ui.label(
+                    egui::RichText::new(issue.message())
+                        .color(egui::Color32::from_rgb(200, 100, 100)),
+                );
+            }
+        });
+
+        if ctx.primary_pressed {
+            return vec![DocumentAction::Umap(UmapAction::Select(idx))];
+        }
+        Vec::new()
+    }
+
     fn header_info(&self, selection: &HashSet<Vec<usize>>) -> (String, String, egui::Color32) {
         if let Some(path) = selection.iter().next() {
             if let Some(map) = self.data.maps.get(path[0]) {
@@ -1,6 +1,8 @@
 use crate::app::{CacocoApp, ConfirmationRequest, PendingAction, ProjectMode};
 use crate::ui::font_wizard;
 use crate::ui::messages::{self, EditorEvent};
+use crate::ui::texture_composer::{self, TextureComposerState};
+use crate::wad::pk3::Pk3Manifest;
 use crate::{document, ui};
 use eframe::egui;
 use std::collections::HashSet;
@@ -12,6 +14,7 @@ use std::path::Path;
 pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
     if !app.iwad_verified {
         ui::modals::draw_onboarding_screen(ctx, app);
+        draw_background_modals(ctx, app);
         return;
     }
 
@@ -25,11 +28,15 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
 
     update_window_title(ctx, app);
 
-    if let Some(action) = app.hotkeys.check(ctx) {
-        handle_action(app, action, ctx);
-    }
+    // The loading modal blocks the pointer; keep shortcuts off the old
+    // document too until the load ends.
+    if app.project_load.is_none() {
+        if let Some(action) = app.hotkeys.check(ctx) {
+            handle_action(app, action);
+        }
 
-    handle_arrow_key_movement(ctx, app);
+        handle_arrow_key_movement(ctx, app);
+    }
 
     app.cheat_engine.process_input(ctx, &mut app.preview_state);
     app.preview_state.update(ctx.input(|i| i.stable_dt));
@@ -41,6 +48,15 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
         }
     }
 
+    // Level pics only show on the tally over the INTERLEVEL preview.
+    if app.active_mode == ProjectMode::Interlevel && app.preview_state.intermission.show_tally {
+        let umapinfo = app
+            .doc
+            .as_ref()
+            .and_then(|d| d.lumps.iter().find_map(|l| l.as_umapinfo()));
+        app.preview_state.intermission.sync_level_pics(umapinfo);
+    }
+
     let last_mode = ctx.data(|d| d.get_temp::<ProjectMode>(egui::Id::new("active_mode")));
     if let Some(lm) = last_mode {
         if lm != app.active_mode {
@@ -73,7 +89,7 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
                 &mut app.assets,
                 &mut app.settings_open,
             );
-            handle_menu_action(app, menu_action, ctx);
+            handle_menu_action(app, menu_action);
 
             ui.add_space(2.0);
             ui.separator();
@@ -97,20 +113,26 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
                     if let Some(doc) = &mut app.doc {
                         let mut active_lump = doc.get_lump(app.active_mode).cloned();
 
-                        if ui::draw_properties_panel(
+                        let (actions, changed) = ui::draw_properties_panel(
                             ui,
                             &mut active_lump,
                             &doc.selection,
                             &app.assets,
                             &app.preview_state,
-                        ) {
+                        );
+                        if changed {
                             if let Some(updated) = active_lump {
+                                // A newly typed tranmap name may be one
+                                // the loaded WADs hold but nothing used yet.
+                                app.assets
+                                    .load_referenced_tranmaps(&updated.tranmap_names());
                                 if let Some(lump_ref) = doc.get_lump_mut(app.active_mode) {
                                     *lump_ref = updated;
                                     doc.dirty = true;
                                 }
                             }
                         }
+                        app.execute_actions(actions);
                     } else {
                         ui::draw_properties_panel(
                             ui,
@@ -189,12 +211,9 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
     });
 
     if app.settings_open {
-        ui::draw_settings_window(
-            ctx,
-            &mut app.settings_open,
-            &mut app.config,
-            &mut app.assets,
-        );
+        if let Some(path) = ui::draw_settings_window(ctx, &mut app.settings_open, &mut app.config) {
+            app.begin_base_load(&path);
+        }
     }
 
     if let Some(doc) = &mut app.doc {
@@ -206,10 +225,33 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
         }
     }
 
+    if let Some(doc) = &mut app.doc {
+        let mut actions = Vec::new();
+        let composer_id = egui::Id::new(texture_composer::OPEN_ID);
+        if let Some(name) = ctx.data_mut(|d| d.remove_temp::<String>(composer_id)) {
+            let composer = app
+                .texture_composer
+                .get_or_insert_with(|| TextureComposerState::new(&app.assets));
+            actions.extend(composer.focus(&name, &doc.composites, &app.assets));
+        }
+        actions.extend(texture_composer::draw_texture_composer(
+            ctx,
+            &mut app.texture_composer,
+            &doc.composites,
+            &app.assets,
+        ));
+        if !actions.is_empty() {
+            doc.execute_actions(actions, app.active_mode);
+        }
+        app.assets.sync_composites(&doc.composites);
+    }
+
     if let Some(request) = app.confirmation_modal.clone() {
         ui::modals::draw_confirmation_modal(ctx, app, &request);
     }
 
+    draw_background_modals(ctx, app);
+
     if let Some(target) =
         ctx.data(|d| d.get_temp::<crate::app::CreationModal>(egui::Id::new("creation_modal_type")))
     {
@@ -224,6 +266,25 @@ pub fn draw_root_ui(ctx: &egui::Context, app: &mut CacocoApp) {
     }
 }
 
+/// Shows the oldest reported error and the progress of a background load.
+/// Drawn over the onboarding screen too, which starts base IWAD loads.
+fn draw_background_modals(ctx: &egui::Context, app: &mut CacocoApp) {
+    if let Some(error) = app.error_reports.front() {
+        let queued = app.error_reports.len() - 1;
+        if ui::modals::draw_error_modal(ctx, error, queued) {
+            app.error_reports.pop_front();
+        }
+    }
+
+    let cancel_load = app
+        .project_load
+        .as_ref()
+        .is_some_and(|load| ui::modals::draw_loading_modal(ctx, load));
+    if cancel_load {
+        app.cancel_project_load();
+    }
+}
+
 /// Helper to update the OS window title based on current document state.
 fn update_window_title(ctx: &egui::Context, app: &CacocoApp) {
     let (is_dirty, path_opt) = app
@@ -338,7 +399,7 @@ fn draw_left_sidebar_drawer(ui: &mut egui::Ui, app: &mut CacocoApp) {
 }
 
 /// Dispatches keyboard shortcuts to application actions.
-fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui::Context) {
+fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action) {
     use crate::document::actions::{DocumentAction, SBarAction, TreeAction};
     use crate::hotkeys::Action;
     use crate::models::ProjectData;
@@ -363,7 +424,7 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
                     PendingAction::Load("".to_string()),
                 ));
             } else {
-                app.open_project_ui(ctx);
+                app.open_project_ui();
             }
         }
         Action::Save => {
@@ -373,32 +434,44 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
                     None => true,
                 };
                 if needs_dialog {
-                    if let Some(p) = crate::io::save_pk3_dialog(
+                    match crate::io::save_pk3_dialog(
                         &doc.lumps,
+                        &doc.composites,
                         &app.assets,
                         &doc.passthrough_lumps,
                         doc.path.clone(),
                     ) {
-                        doc.path = Some(p.clone());
-                        doc.dirty = false;
-                        app.add_to_recent(&p);
-                        messages::log_event(&mut app.preview_state, EditorEvent::ProjectSaved(p));
+                        Ok(Some((p, manifest))) => {
+                            doc.path = Some(p.clone());
+                            doc.dirty = false;
+                            app.add_to_recent(&p);
+                            messages::log_event(
+                                &mut app.preview_state,
+                                EditorEvent::ProjectSaved(p),
+                            );
+                            log_manifest(&mut app.preview_state, &manifest);
+                        }
+                        Ok(None) => {}
+                        Err(e) => app.report_error(e),
                     }
                 } else {
                     let p = doc.path.as_ref().unwrap();
-                    if crate::io::save_pk3_silent(
+                    match crate::io::save_pk3_silent(
                         &doc.lumps,
+                        &doc.composites,
                         &app.assets,
                         &doc.passthrough_lumps,
                         p,
-                    )
-                    .is_ok()
-                    {
-                        doc.dirty = false;
-                        messages::log_event(
-                            &mut app.preview_state,
-                            EditorEvent::ProjectSaved(p.clone()),
-                        );
+                    ) {
+                        Ok(manifest) => {
+                            doc.dirty = false;
+                            messages::log_event(
+                                &mut app.preview_state,
+                                EditorEvent::ProjectSaved(p.clone()),
+                            );
+                            log_manifest(&mut app.preview_state, &manifest);
+                        }
+                        Err(e) => app.report_error(e),
                     }
                 }
             }
@@ -407,12 +480,16 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
             if let Some(doc) = &app.doc {
                 if let Some(lump) = doc.get_lump(app.active_mode) {
                     let sanitized = lump.to_sanitized_json(&app.assets);
-                    if let Some(p) = crate::io::save_json_dialog(&sanitized, doc.path.clone()) {
-                        app.add_to_recent(&p);
-                        messages::log_event(
-                            &mut app.preview_state,
-                            EditorEvent::ProjectExported(p),
-                        );
+                    match crate::io::save_json_dialog(&sanitized, doc.path.clone()) {
+                        Ok(Some(p)) => {
+                            app.add_to_recent(&p);
+                            messages::log_event(
+                                &mut app.preview_state,
+                                EditorEvent::ProjectExported(p),
+                            );
+                        }
+                        Ok(None) => {}
+                        Err(e) => app.report_error(e),
                     }
                 }
             }
@@ -496,11 +573,12 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
         Action::Duplicate => {
             if let Some(doc) = &mut app.doc {
                 if !doc.selection.is_empty() {
-                    doc.history.take_snapshot(&doc.lumps, &doc.selection);
+                    doc.history
+                        .take_snapshot(&doc.lumps, &doc.composites, &doc.selection);
                     let paths: Vec<Vec<usize>> = doc.selection.iter().cloned().collect();
                     let mut actions = Vec::new();
                     for path in paths {
-                        if path.len() == 1 {
+                        if path.len() == 1 && app.active_mode == ProjectMode::SBarDef {
                             actions.push(DocumentAction::SBar(SBarAction::DuplicateStatusBar(
                                 path[0],
                             )));
@@ -561,6 +639,21 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
                         }
                     }
                 }
+                let is_interlevel = matches!(
+                    doc.get_lump(app.active_mode),
+                    Some(ProjectData::Interlevel(_))
+                );
+                if is_interlevel && !doc.selection.is_empty() {
+                    let paths: Vec<Vec<usize>> = doc.selection.iter().cloned().collect();
+                    doc.execute_actions(
+                        vec![
+                            DocumentAction::UndoSnapshot,
+                            DocumentAction::Tree(TreeAction::Delete(paths)),
+                        ],
+                        app.active_mode,
+                    );
+                    messages::log_event(&mut app.preview_state, EditorEvent::Delete);
+                }
             }
         }
         Action::Deselect => {
@@ -573,14 +666,14 @@ fn handle_action(app: &mut CacocoApp, action: crate::hotkeys::Action, ctx: &egui
 }
 
 /// Dispatches menu actions to application logic.
-fn handle_menu_action(app: &mut CacocoApp, action: ui::MenuAction, ctx: &egui::Context) {
+fn handle_menu_action(app: &mut CacocoApp, action: ui::MenuAction) {
     match action {
         ui::MenuAction::NewProject => {
             app.doc = None;
             app.creation_modal = crate::app::CreationModal::LumpSelector;
         }
-        ui::MenuAction::LoadProject(loaded, path) => app.load_project(ctx, loaded, &path),
-        ui::MenuAction::Open => app.open_project_ui(ctx),
+        ui::MenuAction::LoadProject(path) => app.begin_load(&path),
+        ui::MenuAction::Open => app.open_project_ui(),
         ui::MenuAction::RequestDiscard(pending) => {
             app.confirmation_modal = Some(ConfirmationRequest::DiscardChanges(pending));
         }
@@ -612,24 +705,39 @@ fn handle_menu_action(app: &mut CacocoApp, action: ui::MenuAction, ctx: &egui::C
                 doc.dirty = true;
             }
         }
-        ui::MenuAction::SaveDone(path) => {
+        ui::MenuAction::SaveDone(path, manifest) => {
             if path == "SILENT" {
-                handle_action(app, crate::hotkeys::Action::Save, ctx);
+                handle_action(app, crate::hotkeys::Action::Save);
             } else if let Some(doc) = &mut app.doc {
                 doc.path = Some(path.clone());
                 doc.dirty = false;
                 app.add_to_recent(&path);
                 messages::log_event(&mut app.preview_state, EditorEvent::ProjectSaved(path));
+                if let Some(manifest) = manifest {
+                    log_manifest(&mut app.preview_state, &manifest);
+                }
             }
         }
-        ui::MenuAction::ExportDone(path) => {
+        ui::MenuAction::ExportDone(path, skipped) => {
             app.add_to_recent(&path);
             messages::log_event(&mut app.preview_state, EditorEvent::ProjectExported(path));
+            for name in skipped {
+                messages::log_event(&mut app.preview_state, EditorEvent::ImageSkipped(name));
+            }
         }
+        ui::MenuAction::Failed(e) => app.report_error(e),
         _ => {}
     }
 }
 
+/// Logs what a PK3 save wrote, so skipped files don't go unnoticed.
+fn log_manifest(state: &mut crate::state::PreviewState, manifest: &Pk3Manifest) {
+    messages::log_event(
+        state,
+        EditorEvent::Pk3Written(manifest.entries.len(), manifest.shadowed.len()),
+    );
+}
+
 /// Handles arrow key movement for selected elements in the viewport.
 /// Moves selected layers by 1 pixel (or 10 pixels with Shift held).
 /// First press moves immediately and waits, then after a delay
@@ -746,7 +854,6 @@ mod tests {
 
     #[test]
     fn test_downgrade_trigger_modal() {
-        let ctx = egui::Context::default();
         let mut app = CacocoApp::default();
 
         let list_element = ElementWrapper {
@@ -764,7 +871,7 @@ mod tests {
         ));
         app.active_mode = ProjectMode::SBarDef;
 
-        handle_menu_action(&mut app, MenuAction::SetTarget(ExportTarget::Basic), &ctx);
+        handle_menu_action(&mut app, MenuAction::SetTarget(ExportTarget::Basic));
 
         assert!(app.confirmation_modal.is_some());
         if let Some(ConfirmationRequest::DowngradeTarget(target)) = app.confirmation_modal {
@@ -776,7 +883,6 @@ mod tests {
 
     #[test]
     fn test_discard_changes_on_open() {
-        let ctx = egui::Context::default();
         let mut app = CacocoApp::default();
 
         app.doc = Some(document::ProjectDocument::new(
@@ -786,7 +892,7 @@ mod tests {
         ));
         app.doc.as_mut().unwrap().dirty = true;
 
-        handle_action(&mut app, Action::Open, &ctx);
+        handle_action(&mut app, Action::Open);
 
         assert!(app.confirmation_modal.is_some());
         if let Some(ConfirmationRequest::DiscardChanges(PendingAction::Load(path))) =
@@ -800,7 +906,6 @@ mod tests {
 
     #[test]
     fn test_delete_status_bar_confirmation_and_execution() {
-        let ctx = egui::Context::default();
         let mut app = CacocoApp::default();
 
         let mut sbar = SBarDefFile::new_empty();
@@ -818,7 +923,7 @@ mod tests {
 
         app.doc.as_mut().unwrap().selection.insert(vec![0]);
 
-        handle_action(&mut app, Action::Delete, &ctx);
+        handle_action(&mut app, Action::Delete);
 
         let idx_to_delete = match app.confirmation_modal {
             Some(ConfirmationRequest::DeleteStatusBar(idx)) => idx,
@@ -847,7 +952,6 @@ mod tests {
 
     #[test]
     fn test_delete_layer_with_children_confirmation() {
-        let ctx = egui::Context::default();
         let mut app = CacocoApp::default();
 
         let mut parent = ElementWrapper {
@@ -871,7 +975,7 @@ mod tests {
 
         app.doc.as_mut().unwrap().selection.insert(vec![0, 0]);
 
-        handle_action(&mut app, Action::Delete, &ctx);
+        handle_action(&mut app, Action::Delete);
 
         assert!(app.confirmation_modal.is_some());
         if let Some(ConfirmationRequest::DeleteLayers(paths)) = &app.confirmation_modal {
@@ -920,10 +1024,13 @@ mod tests {
 
     #[test]
     fn test_add_lump_preserves_existing_data() {
-        let ctx = egui::Context::default();
         let mut app = CacocoApp::default();
 
-        app.new_project(&ctx, ProjectData::StatusBar(SBarDefFile::new_empty()));
+        app.new_project(ProjectData::StatusBar(SBarDefFile::new_empty()));
+        while app.project_load.is_some() {
+            app.poll_project_load();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
 
         let sky_lump = ProjectData::Sky(crate::models::skydefs::SkyDefsFile::new_empty());
         app.add_lump_to_project(sky_lump);
@@ -937,4 +1044,17 @@ mod tests {
         assert!(doc.get_lump(ProjectMode::SBarDef).is_some());
         assert!(doc.get_lump(ProjectMode::SkyDefs).is_some());
     }
+
+    #[test]
+    fn test_reported_errors_queue_until_dismissed() {
+        let mut app = CacocoApp::default();
+        app.report_error(crate::error::ProjectError::NotFound("doom2.wad".into()));
+        app.report_error(crate::error::ProjectError::Cancelled);
+
+        assert_eq!(app.error_reports.len(), 2);
+        assert!(matches!(
+            app.error_reports.front(),
+            Some(crate::error::ProjectError::NotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,407 @@
+use crate::assets::{AssetId, AssetStore};
+use crate::document::DocumentAction;
+use crate::document::actions::TextureAction;
+use crate::wad::legacy::{self, CompositeTexture, TexturePatch};
+use eframe::egui;
+
+/// Temp-data key a texture name is put under to open the composer on it.
+/// An empty name just opens the window.
+pub const OPEN_ID: &str = "texture_composer_open";
+
+/// Internal state for the composite texture editor.
+pub struct TextureComposerState {
+    pub selected: Option<usize>,
+    pub selected_patch: Option<usize>,
+    /// The IWAD's own textures, offered as starting points.
+    base: Vec<CompositeTexture>,
+    import_filter: String,
+}
+
+impl TextureComposerState {
+    pub fn new(assets: &AssetStore) -> Self {
+        let mut base = legacy::parse_texture_lump(&assets.base_texture1, &assets.base_pnames);
+        base.extend(legacy::parse_texture_lump(
+            &assets.base_texture2,
+            &assets.base_pnames,
+        ));
+        Self {
+            selected: None,
+            selected_patch: None,
+            base,
+            import_filter: String::new(),
+        }
+    }
+
+    /// Selects the composite called `name`, creating it first if needed from
+    /// the IWAD's definition or the project image of that name.
+    pub fn focus(
+        &mut self,
+        name: &str,
+        composites: &[CompositeTexture],
+        assets: &AssetStore,
+    ) -> Vec<DocumentAction> {
+        let mut actions = Vec::new();
+        if name.is_empty() {
+            return actions;
+        }
+        let existing = composites
+            .iter()
+            .position(|t| t.name.eq_ignore_ascii_case(name));
+        let idx = existing.unwrap_or_else(|| {
+            let tex = self
+                .base
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+                .cloned()
+                .unwrap_or_else(|| {
+                    let (w, h) = assets.image_size(&AssetId::new(name)).unwrap_or((256, 128));
+                    CompositeTexture::single(name, w as u16, h as u16)
+                });
+            actions.push(DocumentAction::Texture(TextureAction::Add(tex)));
+            composites.len()
+        });
+        self.selected = Some(idx);
+        self.selected_patch = None;
+        actions
+    }
+}
+
+/// Renders the texture composer window over the document's composites and
+/// returns the edits made to them.
+pub fn draw_texture_composer(
+    ctx: &egui::Context,
+    state: &mut Option<TextureComposerState>,
+    composites: &[CompositeTexture],
+    assets: &AssetStore,
+) -> Vec<DocumentAction> {
+    let mut actions = Vec::new();
+    let Some(data) = state else {
+        return actions;
+    };
+
+    let mut is_open = true;
+    egui::Window::new("Texture Composer")
+        .open(&mut is_open)
+        .collapsible(false)
+        .default_size([640.0, 420.0])
+        .show(ctx, |ui| {
+            // As in the main panels, a press anywhere in the window marks an
+            // undo step, so drags and typing undo as one edit.
+            if ui.input(|i| i.pointer.any_pressed()) && ui.ui_contains_pointer() {
+                actions.push(DocumentAction::UndoSnapshot);
+            }
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(150.0);
+                    actions.extend(draw_texture_list(ui, data, composites, assets));
+                });
+                ui.separator();
+                ui.vertical(|ui| {
+                    let selected = data
+                        .selected
+                        .and_then(|i| Some((i, composites.get(i)?.clone())));
+                    let Some((index, mut tex)) = selected else {
+                        ui.label(
+                            egui::RichText::new("Select or create a texture.")
+                                .weak()
+                                .italics(),
+                        );
+                        return;
+                    };
+                    if draw_texture_fields(ui, data, &mut tex, assets) {
+                        actions.push(DocumentAction::Texture(TextureAction::Update {
+                            index,
+                            texture: tex,
+                        }));
+                    }
+                });
+            });
+        });
+
+    if !is_open {
+        *state = None;
+    }
+    actions
+}
+
+/// The project's composites, plus buttons to add and remove them.
+fn draw_texture_list(
+    ui: &mut egui::Ui,
+    data: &mut TextureComposerState,
+    composites: &[CompositeTexture],
+    assets: &AssetStore,
+) -> Vec<DocumentAction> {
+    let mut actions = Vec::new();
+    ui.horizontal(|ui| {
+        if ui.button("+ New").clicked() {
+            let name = (1..)
+                .map(|n| format!("TEXTURE{}", n))
+                .find(|n| !composites.iter().any(|t| &t.name == n))
+                .unwrap_or_default();
+            actions.push(DocumentAction::Texture(TextureAction::Add(
+                CompositeTexture {
+                    name,
+                    width: 256,
+                    height: 128,
+                    masked: false,
+                    patches: Vec::new(),
+                },
+            )));
+            data.selected = Some(composites.len());
+            data.selected_patch = None;
+        }
+        let removable = data.selected.filter(|&i| i < composites.len());
+        if ui
+            .add_enabled(removable.is_some(), egui::Button::new("Remove"))
+            .clicked()
+        {
+            actions.push(DocumentAction::Texture(TextureAction::Remove(
+                removable.unwrap_or_default(),
+            )));
+            data.selected = None;
+        }
+    });
+
+    ui.add_space(4.0);
+    egui::ScrollArea::vertical()
+        .id_salt("composite_list")
+        .max_height(240.0)
+        .show(ui, |ui| {
+            for (i, tex) in composites.iter().enumerate() {
+                let label = format!("{}  ({} patches)", tex.name, tex.patches.len());
+                if ui
+                    .add(egui::Button::new(label).selected(data.selected == Some(i)))
+                    .clicked()
+                {
+                    data.selected = Some(i);
+                    data.selected_patch = None;
+                }
+            }
+        });
+
+    ui.add_space(8.0);
+    ui.label(egui::RichText::new("Copy from IWAD").weak().size(10.0));
+    ui.add(
+        egui::TextEdit::singleline(&mut data.import_filter)
+            .hint_text("Texture name")
+            .desired_width(f32::INFINITY),
+    );
+    let filter = data.import_filter.to_uppercase();
+    if !filter.is_empty() {
+        let mut pick = None;
+        for tex in data
+            .base
+            .iter()
+            .filter(|t| t.name.contains(&filter))
+            .take(8)
+        {
+            if ui.button(&tex.name).clicked() {
+                pick = Some(tex.name.clone());
+            }
+        }
+        if let Some(name) = pick {
+            actions.extend(data.focus(&name, composites, assets));
+            data.import_filter.clear();
+        }
+    }
+    actions
+}
+
+/// Name, size, patch rows and a preview of one composite texture.
+fn draw_texture_fields(
+    ui: &mut egui::Ui,
+    data: &mut TextureComposerState,
+    tex: &mut CompositeTexture,
+    assets: &AssetStore,
+) -> bool {
+    let mut changed = false;
+    let size_of = |name: &str| assets.image_size(&AssetId::new(name));
+
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        let mut name = tex.name.clone();
+        if ui
+            .add(
+                egui::TextEdit::singleline(&mut name)
+                    .char_limit(8)
+                    .desired_width(80.0),
+            )
+            .changed()
+        {
+            tex.name = name.to_uppercase();
+            changed = true;
+        }
+        ui.label("Size:");
+        changed |= ui
+            .add(egui::DragValue::new(&mut tex.width).range(1..=4096))
+            .changed();
+        ui.label("x");
+        changed |= ui
+            .add(egui::DragValue::new(&mut tex.height).range(1..=4096))
+            .changed();
+        if ui
+            .button("Fit")
+            .on_hover_text("Size the texture to its patches")
+            .clicked()
+        {
+            tex.fit_to_patches(size_of);
+            changed = true;
+        }
+    });
+
+    ui.add_space(6.0);
+    changed |= draw_preview(ui, data, tex, assets);
+    ui.add_space(6.0);
+
+    let mut remove = None;
+    let mut swap = None;
+    let count = tex.patches.len();
+    for (i, p) in tex.patches.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let selected = data.selected_patch == Some(i);
+            if ui
+                .add(egui::Button::new(format!("{}", i + 1)).selected(selected))
+                .clicked()
+            {
+                data.selected_patch = Some(i);
+            }
+            let missing = size_of(&p.patch).is_none();
+            let mut name = p.patch.clone();
+            let edit = ui.add(
+                egui::TextEdit::singleline(&mut name)
+                    .char_limit(8)
+                    .desired_width(80.0)
+                    .text_color_opt(missing.then_some(egui::Color32::from_rgb(200, 100, 100))),
+            );
+            if edit.changed() {
+                p.patch = name.to_uppercase();
+                changed = true;
+            }
+            if missing {
+                edit.on_hover_text("No patch of this name is loaded.");
+            }
+            ui.label("X:");
+            changed |= ui.add(egui::DragValue::new(&mut p.x)).changed();
+            ui.label("Y:");
+            changed |= ui.add(egui::DragValue::new(&mut p.y)).changed();
+            if ui.add_enabled(i > 0, egui::Button::new("⏶")).clicked() {
+                swap = Some((i - 1, i));
+            }
+            if ui
+                .add_enabled(i + 1 < count, egui::Button::new("⏷"))
+                .clicked()
+            {
+                swap = Some((i, i + 1));
+            }
+            if ui.button("X").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some((a, b)) = swap {
+        tex.patches.swap(a, b);
+        data.selected_patch = None;
+        changed = true;
+    }
+    if let Some(i) = remove {
+        tex.patches.remove(i);
+        data.selected_patch = None;
+        changed = true;
+    }
+
+    if ui
+        .button("+ Add Patch")
+        .on_hover_text("Places the patch to the right of the last one")
+        .clicked()
+    {
+        let (patch, x, y) = tex.patches.last().map_or((String::new(), 0, 0), |last| {
+            let w = size_of(&last.patch).map_or(0, |(w, _)| w as i16);
+            (last.patch.clone(), last.x.saturating_add(w), last.y)
+        });
+        tex.patches.push(TexturePatch { patch, x, y });
+        data.selected_patch = Some(tex.patches.len() - 1);
+        changed = true;
+    }
+    changed
+}
+
+/// Draws the texture with its patch outlines. The selected patch can be
+/// dragged into place.
+fn draw_preview(
+    ui: &mut egui::Ui,
+    data: &mut TextureComposerState,
+    tex: &mut CompositeTexture,
+    assets: &AssetStore,
+) -> bool {
+    let tex_size = egui::vec2(tex.width.max(1) as f32, tex.height.max(1) as f32);
+    let avail = egui::vec2(ui.available_width(), 220.0);
+    let scale = (avail.x / tex_size.x)
+        .min(avail.y / tex_size.y)
+        .clamp(0.1, 4.0);
+    let (rect, response) = ui.allocate_exact_size(tex_size * scale, egui::Sense::drag());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(24));
+
+    let patch_rect = |p: &TexturePatch| {
+        let size = assets
+            .image_size_vec2(&AssetId::new(&p.patch))
+            .unwrap_or(egui::Vec2::ZERO);
+        let min = rect.min + egui::vec2(p.x as f32, p.y as f32) * scale;
+        egui::Rect::from_min_size(min, size * scale)
+    };
+
+    let baked = assets.texture(ui.ctx(), &AssetStore::composite_id(&tex.name));
+    if let Some(handle) = baked.filter(|h| h.size_vec2() == tex_size) {
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        painter.image(handle.id(), rect, uv, egui::Color32::WHITE);
+    }
+
+    for (i, p) in tex.patches.iter().enumerate() {
+        let color = if data.selected_patch == Some(i) {
+            egui::Color32::YELLOW
+        } else {
+            egui::Color32::from_white_alpha(60)
+        };
+        painter.rect_stroke(
+            patch_rect(p),
+            0.0,
+            egui::Stroke::new(1.0, color),
+            egui::StrokeKind::Inside,
+        );
+    }
+    painter.rect_stroke(
+        rect,
+        0.0,
+        egui::Stroke::new(1.0, egui::Color32::from_white_alpha(120)),
+        egui::StrokeKind::Inside,
+    );
+
+    if response.drag_started() {
+        let pos = response.interact_pointer_pos();
+        data.selected_patch = tex
+            .patches
+            .iter()
+            .rposition(|p| pos.is_some_and(|pos| patch_rect(p).contains(pos)));
+    }
+
+    let mut changed = false;
+    if response.dragged() {
+        let patch = data.selected_patch.and_then(|i| tex.patches.get_mut(i));
+        if let Some(p) = patch {
+            let moved = response.total_drag_delta().unwrap_or_default() / scale;
+            let start = ui
+                .ctx()
+                .data_mut(|d| *d.get_temp_mut_or_insert_with(response.id, || (p.x, p.y)));
+            let (x, y) = (
+                start.0.saturating_add(moved.x.round() as i16),
+                start.1.saturating_add(moved.y.round() as i16),
+            );
+            changed = (x, y) != (p.x, p.y);
+            (p.x, p.y) = (x, y);
+        }
+    }
+    if response.drag_stopped() {
+        ui.ctx().data_mut(|d| d.remove::<(i16, i16)>(response.id));
+    }
+    changed
+}
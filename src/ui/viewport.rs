@@ -339,6 +339,8 @@ pub fn draw_viewport(
                         is_dragging: controller.is_dragging,
                         is_viewport_clicked: true,
                         is_native: false,
+                        translation: None,
+                        tranmap: None,
                     };
 
                     for (i, key) in asset_keys.iter().enumerate() {
@@ -382,7 +384,7 @@ pub(crate) fn render_id24_background(
     proj: &ViewportProjection,
 ) {
     let id = AssetId::new(lump);
-    if let Some(tex) = assets.textures.get(&id) {
+    if let Some(tex) = assets.texture(ui.ctx(), &id) {
         let uv_rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
 
         ui.painter()
@@ -392,7 +394,7 @@ pub(crate) fn render_id24_background(
 
 /// Renders the complex workspace for SBARDEF, including world view and status bar flats.
 pub(crate) fn render_statusbar_workspace(
-    ui: &mut egui::Ui,
+    painter: &egui::Painter,
     bar: &StatusBarLayout,
     assets: &AssetStore,
     state: &PreviewState,
@@ -407,7 +409,7 @@ pub(crate) fn render_statusbar_workspace(
     let y_center = h_view / 2.0;
     let y_offset_from_top = y_center - 100.0;
 
-    if let Some(tex) = assets.textures.get(&AssetId::new("_BG_MASTER")) {
+    if let Some(tex) = assets.texture(painter.ctx(), &AssetId::new("_BG_MASTER")) {
         let mut uv_rect = if state.sim.engine.widescreen_mode {
             egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0))
         } else {
@@ -421,8 +423,7 @@ pub(crate) fn render_statusbar_workspace(
         let mut draw_rect = proj.screen_rect;
         draw_rect.max.y -= (200.0 - h_view) * proj.final_scale_y;
 
-        ui.painter()
-            .image(tex.id(), draw_rect, uv_rect, egui::Color32::WHITE);
+        painter.image(tex.id(), draw_rect, uv_rect, egui::Color32::WHITE);
     }
 
     if !bar.fullscreen_render && bar_height > 0.0 {
@@ -430,7 +431,7 @@ pub(crate) fn render_statusbar_workspace(
             .fill_flat
             .clone()
             .unwrap_or_else(|| "GRNROCK".to_string());
-        if let Some(tex) = assets.textures.get(&AssetId::new(&flat_key)) {
+        if let Some(tex) = assets.texture(painter.ctx(), &AssetId::new(&flat_key)) {
             let tile_size_px = 64.0 * proj.final_scale_x;
             let bar_area_rect = egui::Rect::from_min_max(
                 egui::pos2(
@@ -449,7 +450,7 @@ pub(crate) fn render_statusbar_workspace(
                     )
                     .intersect(bar_area_rect);
 
-                    ui.painter().image(
+                    painter.image(
                         tex.id(),
                         r,
                         egui::Rect::from_min_max(
@@ -466,13 +467,17 @@ pub(crate) fn render_statusbar_workspace(
     let mut world_clip_rect = proj.screen_rect;
     world_clip_rect.max.y -= (200.0 - h_view) * proj.final_scale_y;
 
-    ui.scope_builder(egui::UiBuilder::new().max_rect(world_clip_rect), |ui| {
-        render_player_weapon(ui, state, assets, proj, y_offset_from_top);
-    });
+    render_player_weapon(
+        &painter.with_clip_rect(world_clip_rect),
+        state,
+        assets,
+        proj,
+        y_offset_from_top,
+    );
 }
 
 fn render_player_weapon(
-    ui: &egui::Ui,
+    painter: &egui::Painter,
     state: &PreviewState,
     assets: &AssetStore,
     proj: &ViewportProjection,
@@ -515,7 +520,7 @@ fn render_player_weapon(
 
     if let Some(lump) = weapon_lump_name {
         let id = AssetId::new(lump);
-        if let Some(tex) = assets.textures.get(&id) {
+        if let Some(tex) = assets.texture(painter.ctx(), &id) {
             let tex_size = tex.size_vec2();
             let scaled_size = egui::vec2(
                 tex_size.x * proj.final_scale_x,
@@ -526,7 +531,7 @@ fn render_player_weapon(
                 (state.viewer.weapon_offset_y + constant_offset + v_shift) * proj.final_scale_y;
             let draw_y = (proj.screen_rect.max.y - scaled_size.y) + total_offset_y;
 
-            ui.painter().image(
+            painter.image(
                 tex.id(),
                 egui::Rect::from_min_size(egui::pos2(draw_x, draw_y), scaled_size),
                 egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
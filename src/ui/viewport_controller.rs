@@ -3,6 +3,13 @@ use crate::render::projection::ViewportProjection;
 use eframe::egui;
 use std::collections::HashSet;
 
+/// Sky texels turned per pixel of mouse movement.
+pub const SKY_LOOK_SENSITIVITY: f32 = 2.0;
+
+/// Degrees of pitch per pixel of mouse movement. Moving down looks up, like
+/// dragging the sky.
+pub const SKY_PITCH_SENSITIVITY: f32 = 0.25;
+
 /// Manages the persistent interaction state of the HUD viewport.
 ///
 /// The controller handles coordinate mapping, element translation (dragging),
@@ -42,18 +49,10 @@ impl ViewportController {
         if viewport_res.dragged_by(egui::PointerButton::Primary) {
             if active_mode == crate::app::ProjectMode::SkyDefs {
                 let delta = ui.input(|i| i.pointer.delta());
-                let sensitivity = 2.0;
-
-                self.move_accumulator.x += delta.x * sensitivity;
-
-                let shift_x = self.move_accumulator.x.trunc() as i32;
-                if shift_x != 0 {
-                    state.viewer.sky_yaw = (state.viewer.sky_yaw + shift_x).rem_euclid(1024);
-                    self.move_accumulator.x -= shift_x as f32;
-                }
-
-                state.viewer.weapon_offset_y -= delta.y * sensitivity;
-
+                state.viewer.sky_camera.look(
+                    delta.x * SKY_LOOK_SENSITIVITY,
+                    delta.y * SKY_PITCH_SENSITIVITY,
+                );
                 self.is_dragging = true;
             } else if !selection.is_empty() {
                 ui.ctx().set_cursor_icon(egui::CursorIcon::None);
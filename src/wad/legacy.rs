@@ -7,20 +7,101 @@
 //! the only viable option in the short term to just import from a currently
 //! loaded IWAD/PWAD"*.
 
+use super::RawLump;
 use super::util::{get_image_dimensions, parse_lump_name};
-use crate::assets::{AssetId, AssetStore};
-use std::collections::HashSet;
+use crate::assets::AssetStore;
+use std::collections::HashMap;
 
-/// Merges current project assets into the existing PNAMES list from the loaded IWAD.
+/// One patch placed within a texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TexturePatch {
+    pub patch: String,
+    pub x: i16,
+    pub y: i16,
+}
+
+/// A TEXTURE1/TEXTURE2 entry: a named texture stitched from one or more patches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeTexture {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    pub masked: bool,
+    pub patches: Vec<TexturePatch>,
+}
+
+impl CompositeTexture {
+    /// A texture made of the patch of the same name at the origin, as
+    /// generated for every image in the project.
+    pub fn single(name: &str, width: u16, height: u16) -> Self {
+        Self {
+            name: name.to_uppercase(),
+            width,
+            height,
+            masked: false,
+            patches: vec![TexturePatch {
+                patch: name.to_uppercase(),
+                x: 0,
+                y: 0,
+            }],
+        }
+    }
+
+    /// Whether this is anything more than what `single` would generate.
+    pub fn is_composite(&self) -> bool {
+        match self.patches.as_slice() {
+            [p] => !p.patch.eq_ignore_ascii_case(&self.name) || p.x != 0 || p.y != 0,
+            _ => true,
+        }
+    }
+
+    /// Grows or shrinks the texture to the bounds of its patches, measured
+    /// from the origin.
+    pub fn fit_to_patches(&mut self, size_of: impl Fn(&str) -> Option<(u32, u32)>) {
+        let (mut w, mut h) = (0i32, 0i32);
+        for p in &self.patches {
+            let (pw, ph) = size_of(&p.patch).unwrap_or((0, 0));
+            w = w.max(p.x as i32 + pw as i32);
+            h = h.max(p.y as i32 + ph as i32);
+        }
+        if w > 0 && h > 0 {
+            self.width = w.min(u16::MAX as i32) as u16;
+            self.height = h.min(u16::MAX as i32) as u16;
+        }
+    }
+}
+
+/// Parses a binary PNAMES lump.
+pub fn parse_pnames(data: &[u8]) -> Vec<String> {
+    let Some(count) = data
+        .get(0..4)
+        .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+    else {
+        return Vec::new();
+    };
+    (0..count.max(0) as usize)
+        .map_while(|j| data.get(4 + j * 8..12 + j * 8))
+        .map(parse_lump_name)
+        .collect()
+}
+
+/// Merges current project assets and the patches of composite textures into
+/// the existing PNAMES list from the loaded IWAD.
 /// Returns a complete list of patch names, ensuring no duplicates are added.
-pub fn build_merged_pnames(assets: &AssetStore) -> Vec<String> {
+pub fn build_merged_pnames(composites: &[CompositeTexture], assets: &AssetStore) -> Vec<String> {
     let mut merged = assets.base_pnames.clone();
-    for (id, name) in &assets.names {
-        if assets.raw_files.contains_key(id) {
-            let stem = AssetStore::stem(name);
-            if !merged.iter().any(|n| n.eq_ignore_ascii_case(&stem)) {
-                merged.push(stem);
-            }
+    let images = assets
+        .names
+        .iter()
+        .filter(|(id, _)| assets.raw_files.contains_key(id))
+        .map(|(_, name)| AssetStore::stem(name));
+    let patches = composites
+        .iter()
+        .flat_map(|t| &t.patches)
+        .map(|p| p.patch.to_uppercase());
+    for stem in images.chain(patches) {
+        if !stem.is_empty() && !merged.iter().any(|n| n.eq_ignore_ascii_case(&stem)) {
+            merged.push(stem);
         }
     }
     merged
@@ -41,188 +122,311 @@ pub fn serialize_pnames(names: &[String]) -> Vec<u8> {
     buf
 }
 
-/// Reconstructs the TEXTURE1 table by merging original IWAD definitions with
-/// new definitions from the project. Existing textures are overwritten if
-/// a replacement image is found in the project.
-pub fn build_merged_texture1(new_pnames: &[String], assets: &AssetStore) -> Vec<u8> {
-    if assets.base_texture1.is_empty() {
-        let mut to_add = Vec::new();
-        for (id, name) in &assets.names {
-            if assets.raw_files.contains_key(id) {
-                to_add.push(AssetStore::stem(name));
-            }
-        }
-        return serialize_texture1(&to_add, assets);
-    }
+/// Parses a binary TEXTURE1/TEXTURE2 lump, naming patches through `pnames`.
+/// Truncated entries are skipped.
+pub fn parse_texture_lump(data: &[u8], pnames: &[String]) -> Vec<CompositeTexture> {
+    let read_i16 = |at: usize| {
+        data.get(at..at + 2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+    };
+    let read_i32 = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+    };
 
-    let data = &assets.base_texture1;
-    let old_num_tex = i32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
-
-    let mut existing_names = Vec::new();
-    for i in 0..old_num_tex {
-        let off_pos = 4 + (i * 4);
-        let offset = i32::from_le_bytes(data[off_pos..off_pos + 4].try_into().unwrap()) as usize;
-        if offset + 8 <= data.len() {
-            existing_names.push(parse_lump_name(&data[offset..offset + 8]));
-        } else {
-            existing_names.push("".to_string());
-        }
+    let count = read_i32(0).unwrap_or(0).max(0) as usize;
+    let mut textures = Vec::new();
+    for i in 0..count {
+        let Some(offset) = read_i32(4 + i * 4).map(|o| o.max(0) as usize) else {
+            break;
+        };
+        let Some(name) = data.get(offset..offset + 8).map(parse_lump_name) else {
+            continue;
+        };
+        let header = (
+            read_i32(offset + 8),
+            read_i16(offset + 12),
+            read_i16(offset + 14),
+            read_i16(offset + 20),
+        );
+        let (Some(masked), Some(width), Some(height), Some(patch_count)) = header else {
+            continue;
+        };
+
+        let patches = (0..patch_count.max(0) as usize)
+            .map_while(|p| {
+                let at = offset + 22 + p * 10;
+                let index = read_i16(at + 4)?.max(0) as usize;
+                Some(TexturePatch {
+                    patch: pnames.get(index).cloned().unwrap_or_default(),
+                    x: read_i16(at)?,
+                    y: read_i16(at + 2)?,
+                })
+            })
+            .collect();
+
+        textures.push(CompositeTexture {
+            name,
+            width: width as u16,
+            height: height as u16,
+            masked: masked != 0,
+            patches,
+        });
     }
+    textures
+}
 
-    let mut to_append = Vec::new();
-    let mut to_overwrite = HashSet::new();
+/// Splits a binary TEXTURE1/TEXTURE2 lump into its entries, each with its
+/// name and its bytes exactly as stored. Truncated entries are skipped.
+fn split_texture_lump(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let read_i32 = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+    };
 
-    for (id, name) in &assets.names {
-        if assets.raw_files.contains_key(id) {
-            let stem = AssetStore::stem(name);
-            if let Some(pos) = existing_names
-                .iter()
-                .position(|n| n.eq_ignore_ascii_case(&stem))
-            {
-                to_overwrite.insert(pos);
-            } else {
-                to_append.push(stem);
-            }
-        }
+    let count = read_i32(0).unwrap_or(0).max(0) as usize;
+    (0..count)
+        .map_while(|i| read_i32(4 + i * 4).map(|o| o.max(0) as usize))
+        .filter_map(|offset| {
+            let patch_count = data
+                .get(offset + 20..offset + 22)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]).max(0) as usize)?;
+            let entry = data.get(offset..offset + 22 + patch_count * 10)?;
+            Some((parse_lump_name(&entry[..8]), entry.to_vec()))
+        })
+        .collect()
+}
+
+/// Encodes one texture entry, naming its patches by their `index` in PNAMES.
+fn encode_texture(
+    tex: &CompositeTexture,
+    index: &HashMap<String, usize>,
+) -> anyhow::Result<Vec<u8>> {
+    let mut entry = Vec::with_capacity(22 + tex.patches.len() * 10);
+    let mut name8 = [0u8; 8];
+    let upper = tex.name.to_uppercase();
+    let b = upper.as_bytes();
+    name8[..b.len().min(8)].copy_from_slice(&b[..b.len().min(8)]);
+
+    entry.extend_from_slice(&name8);
+    entry.extend_from_slice(&(tex.masked as i32).to_le_bytes());
+    entry.extend_from_slice(&tex.width.to_le_bytes());
+    entry.extend_from_slice(&tex.height.to_le_bytes());
+    entry.extend_from_slice(&0i32.to_le_bytes());
+    entry.extend_from_slice(&(tex.patches.len() as u16).to_le_bytes());
+    for p in &tex.patches {
+        let Some(&p_idx) = index.get(&p.patch.to_uppercase()) else {
+            anyhow::bail!(
+                "texture {} uses patch '{}', which is not in PNAMES",
+                tex.name,
+                p.patch
+            );
+        };
+        entry.extend_from_slice(&p.x.to_le_bytes());
+        entry.extend_from_slice(&p.y.to_le_bytes());
+        entry.extend_from_slice(&(p_idx as u16).to_le_bytes());
+        entry.extend_from_slice(&1u16.to_le_bytes());
+        entry.extend_from_slice(&0u16.to_le_bytes());
     }
+    Ok(entry)
+}
 
-    let new_num_tex = old_num_tex + to_append.len();
-    let mut new_lump = Vec::new();
-    new_lump.extend_from_slice(&(new_num_tex as i32).to_le_bytes());
-
-    let mut running_offset = 4 + (new_num_tex * 4);
-    let mut new_offsets = Vec::new();
-    let mut new_data_payload = Vec::new();
-
-    for i in 0..new_num_tex {
-        new_offsets.push(running_offset as i32);
-        let mut entry = Vec::new();
-
-        if i < old_num_tex {
-            if to_overwrite.contains(&i) {
-                let name = &existing_names[i];
-                let mut name8 = [0u8; 8];
-                let upper = name.to_uppercase();
-                let b_bytes = upper.as_bytes();
-                name8[..b_bytes.len().min(8)].copy_from_slice(&b_bytes[..b_bytes.len().min(8)]);
-
-                let id = AssetId::new(name);
-                let (w, h) = assets
-                    .raw_files
-                    .get(&id)
-                    .map(|b| get_image_dimensions(b))
-                    .unwrap_or((256, 128));
-                let p_idx = new_pnames
-                    .iter()
-                    .position(|n| n.eq_ignore_ascii_case(name))
-                    .unwrap_or(0) as u16;
-
-                entry.extend_from_slice(&name8);
-                entry.extend_from_slice(&0i32.to_le_bytes());
-                entry.extend_from_slice(&w.to_le_bytes());
-                entry.extend_from_slice(&h.to_le_bytes());
-                entry.extend_from_slice(&0i32.to_le_bytes());
-                entry.extend_from_slice(&1u16.to_le_bytes());
-                entry.extend_from_slice(&[0, 0, 0, 0]);
-                entry.extend_from_slice(&p_idx.to_le_bytes());
-                entry.extend_from_slice(&[1, 0, 1, 0]);
-            } else {
-                let old_off_pos = 4 + (i * 4);
-                let old_off =
-                    i32::from_le_bytes(data[old_off_pos..old_off_pos + 4].try_into().unwrap())
-                        as usize;
-                let next_off = if i + 1 < old_num_tex {
-                    i32::from_le_bytes(data[old_off_pos + 4..old_off_pos + 8].try_into().unwrap())
-                        as usize
-                } else {
-                    data.len()
-                };
-                entry.extend_from_slice(&data[old_off..next_off]);
-            }
-        } else {
-            let name = &to_append[i - old_num_tex];
-            let mut name8 = [0u8; 8];
-            let upper = name.to_uppercase();
-            let b_bytes = upper.as_bytes();
-            name8[..b_bytes.len().min(8)].copy_from_slice(&b_bytes[..b_bytes.len().min(8)]);
-
-            let id = AssetId::new(name);
-            let (w, h) = assets
-                .raw_files
-                .get(&id)
-                .map(|b| get_image_dimensions(b))
-                .unwrap_or((256, 128));
-            let p_idx = new_pnames
-                .iter()
-                .position(|n| n.eq_ignore_ascii_case(name))
-                .unwrap_or(0) as u16;
-
-            entry.extend_from_slice(&name8);
-            entry.extend_from_slice(&0i32.to_le_bytes());
-            entry.extend_from_slice(&w.to_le_bytes());
-            entry.extend_from_slice(&h.to_le_bytes());
-            entry.extend_from_slice(&0i32.to_le_bytes());
-            entry.extend_from_slice(&1u16.to_le_bytes());
-            entry.extend_from_slice(&[0, 0, 0, 0]);
-            entry.extend_from_slice(&p_idx.to_le_bytes());
-            entry.extend_from_slice(&[1, 0, 1, 0]);
-        }
+/// Looks PNAMES entries up by name. The first of any duplicates wins, as in
+/// the engine.
+fn pnames_index(pnames: &[String]) -> HashMap<String, usize> {
+    pnames
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, n)| (n.to_uppercase(), i))
+        .collect()
+}
 
-        running_offset += entry.len();
-        new_data_payload.extend(entry);
+/// Joins encoded entries into a binary TEXTURE1/TEXTURE2 lump.
+fn join_texture_entries(entries: &[Vec<u8>]) -> Vec<u8> {
+    let header_len = 4 + entries.len() * 4;
+    let mut buf = Vec::with_capacity(header_len + entries.iter().map(Vec::len).sum::<usize>());
+    buf.extend_from_slice(&(entries.len() as i32).to_le_bytes());
+    let mut offset = header_len;
+    for entry in entries {
+        buf.extend_from_slice(&(offset as i32).to_le_bytes());
+        offset += entry.len();
     }
+    for entry in entries {
+        buf.extend_from_slice(entry);
+    }
+    buf
+}
 
-    for off in new_offsets {
-        new_lump.extend_from_slice(&off.to_le_bytes());
+/// Reconstructs the TEXTURE1 and TEXTURE2 tables from the IWAD definitions.
+///
+/// Every project image becomes a single-patch texture and every composite
+/// texture is written as defined. Either replaces a texture of the same name
+/// in whichever table holds it, or is appended to TEXTURE1. The IWAD's other
+/// entries are copied byte for byte, which holds up because `new_pnames`
+/// starts with the IWAD's PNAMES. TEXTURE2 is only returned if the IWAD had
+/// one.
+pub fn build_merged_textures(
+    new_pnames: &[String],
+    composites: &[CompositeTexture],
+    assets: &AssetStore,
+) -> anyhow::Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let mut texture1 = split_texture_lump(&assets.base_texture1);
+    let mut texture2 = split_texture_lump(&assets.base_texture2);
+
+    let mut images: Vec<CompositeTexture> = assets
+        .names
+        .iter()
+        .filter_map(|(id, name)| {
+            let bytes = assets.raw_files.get(id)?;
+            let (w, h) = get_image_dimensions(bytes);
+            Some(CompositeTexture::single(&AssetStore::stem(name), w, h))
+        })
+        .collect();
+    images.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let index = pnames_index(new_pnames);
+    for tex in images.iter().chain(composites) {
+        let entry = encode_texture(tex, &index)?;
+        let existing = texture1
+            .iter_mut()
+            .chain(texture2.iter_mut())
+            .find(|(name, _)| name.eq_ignore_ascii_case(&tex.name));
+        match existing {
+            Some(slot) => slot.1 = entry,
+            None => texture1.push((tex.name.to_uppercase(), entry)),
+        }
     }
-    new_lump.extend(new_data_payload);
-    new_lump
+
+    let join = |table: Vec<(String, Vec<u8>)>| {
+        let entries: Vec<Vec<u8>> = table.into_iter().map(|(_, entry)| entry).collect();
+        join_texture_entries(&entries)
+    };
+    let texture2 = (!assets.base_texture2.is_empty()).then(|| join(texture2));
+    Ok((join(texture1), texture2))
 }
 
-/// Creates a minimal, standalone TEXTURE1 lump for use when no base IWAD data is available.
-fn serialize_texture1(names: &[String], assets: &AssetStore) -> Vec<u8> {
-    let mut buf = Vec::new();
-    let num_tex = names.len() as i32;
-    buf.extend_from_slice(&num_tex.to_le_bytes());
+/// Picks out the textures a project's own PNAMES/TEXTUREx lumps define
+/// beyond the IWAD's and the ones generated for its images, so they can be
+/// edited again after a round-trip through a PK3 or WAD.
+pub fn project_composites(lumps: &[RawLump], assets: &AssetStore) -> Vec<CompositeTexture> {
+    let find = |name: &str| {
+        lumps
+            .iter()
+            .find(|l| AssetStore::stem(&l.name) == name)
+            .map(|l| l.data.bytes())
+    };
+    let Some(pnames) = find("PNAMES").map(|data| parse_pnames(&data)) else {
+        return Vec::new();
+    };
 
-    let mut offset = 4 + (num_tex * 4);
-    let mut offsets = Vec::new();
-    let mut data_block = Vec::new();
+    let base: Vec<CompositeTexture> =
+        parse_texture_lump(&assets.base_texture1, &assets.base_pnames)
+            .into_iter()
+            .chain(parse_texture_lump(
+                &assets.base_texture2,
+                &assets.base_pnames,
+            ))
+            .collect();
 
-    for (idx, name) in names.iter().enumerate() {
-        offsets.push(offset);
-        let mut tex_entry = Vec::new();
-        let mut name8 = [0u8; 8];
-        let upper = name.to_uppercase();
-        let b = upper.as_bytes();
-        name8[..b.len().min(8)].copy_from_slice(&b[..b.len().min(8)]);
-
-        let id = AssetId::new(name);
-        let (w, h) = assets
-            .raw_files
-            .get(&id)
-            .map(|b| get_image_dimensions(b))
-            .unwrap_or((256, 128));
-
-        tex_entry.extend_from_slice(&name8);
-        tex_entry.extend_from_slice(&0i32.to_le_bytes());
-        tex_entry.extend_from_slice(&w.to_le_bytes());
-        tex_entry.extend_from_slice(&h.to_le_bytes());
-        tex_entry.extend_from_slice(&0i32.to_le_bytes());
-        tex_entry.extend_from_slice(&1u16.to_le_bytes());
-        tex_entry.extend_from_slice(&0i16.to_le_bytes());
-        tex_entry.extend_from_slice(&0i16.to_le_bytes());
-        tex_entry.extend_from_slice(&(idx as u16).to_le_bytes());
-        tex_entry.extend_from_slice(&1u16.to_le_bytes());
-        tex_entry.extend_from_slice(&1u16.to_le_bytes());
-
-        offset += tex_entry.len() as i32;
-        data_block.extend(tex_entry);
+    ["TEXTURE1", "TEXTURE2"]
+        .into_iter()
+        .filter_map(find)
+        .flat_map(|data| parse_texture_lump(&data, &pnames))
+        .filter(|t| t.is_composite() && !base.contains(t))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize_texture_lump(
+        textures: &[CompositeTexture],
+        pnames: &[String],
+    ) -> anyhow::Result<Vec<u8>> {
+        let index = pnames_index(pnames);
+        let entries = textures
+            .iter()
+            .map(|tex| encode_texture(tex, &index))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(join_texture_entries(&entries))
     }
 
-    for off in offsets {
-        buf.extend_from_slice(&off.to_le_bytes());
+    fn sky(name: &str, patches: &[(&str, i16)]) -> CompositeTexture {
+        CompositeTexture {
+            name: name.to_string(),
+            width: 512,
+            height: 128,
+            masked: false,
+            patches: patches
+                .iter()
+                .map(|&(patch, x)| TexturePatch {
+                    patch: patch.to_string(),
+                    x,
+                    y: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_texture_tables() {
+        let pnames = vec!["SKY1".to_string(), "SKY1L".to_string(), "SKY1R".to_string()];
+        let textures = vec![
+            sky("SKY1", &[("SKY1", 0)]),
+            sky("WIDESKY", &[("SKY1L", 0), ("SKY1R", 256)]),
+        ];
+        let lump = serialize_texture_lump(&textures, &pnames).unwrap();
+        assert_eq!(parse_texture_lump(&lump, &pnames), textures);
+        assert_eq!(parse_pnames(&serialize_pnames(&pnames)), pnames);
+        assert!(!textures[0].is_composite());
+        assert!(textures[1].is_composite());
+
+        // A composite replaces the IWAD's entry in TEXTURE2 and new names go
+        // to TEXTURE1, with their patches added to PNAMES.
+        let mut assets = AssetStore::default();
+        assets.base_pnames = pnames.clone();
+        assets.base_texture1 = serialize_texture_lump(&textures[..1], &pnames).unwrap();
+        assets.base_texture2 = serialize_texture_lump(&textures[1..], &pnames).unwrap();
+        let wide = sky("WIDESKY", &[("SKY1R", 0), ("SKY1L", 256)]);
+        let mut extra = sky("SKY4", &[("SKY4A", 0), ("SKY4B", 256)]);
+        extra.fit_to_patches(|_| Some((256, 200)));
+        assert_eq!((extra.width, extra.height), (512, 200));
+        let composites = vec![wide.clone(), extra.clone()];
+
+        let new_pnames = build_merged_pnames(&composites, &assets);
+        assert_eq!(&new_pnames[3..], ["SKY4A", "SKY4B"]);
+        // The colormap field is unused by the engine, but an untouched IWAD
+        // entry still comes through with it intact.
+        assets.base_texture1[38] = 7;
+        let (texture1_data, texture2_data) =
+            build_merged_textures(&new_pnames, &composites, &assets).unwrap();
+        let texture2_data = texture2_data.unwrap();
+        assert_eq!(
+            split_texture_lump(&texture1_data)[0],
+            split_texture_lump(&assets.base_texture1)[0]
+        );
+        let texture1 = parse_texture_lump(&texture1_data, &new_pnames);
+        let texture2 = parse_texture_lump(&texture2_data, &new_pnames);
+        assert_eq!(texture1, vec![textures[0].clone(), extra.clone()]);
+        assert_eq!(texture2, vec![wide.clone()]);
+
+        // A patch that resolves to nothing is refused rather than guessed at.
+        let unnamed = sky("SKY5", &[("", 0)]);
+        let err = build_merged_textures(&new_pnames, &[unnamed], &assets).unwrap_err();
+        assert!(err.to_string().contains("SKY5"));
+
+        // Reading the exported tables back only picks up what the project added.
+        let lumps = [
+            ("PNAMES", serialize_pnames(&new_pnames)),
+            ("TEXTURE1", texture1_data),
+            ("TEXTURE2", texture2_data),
+        ]
+        .map(|(name, data)| RawLump {
+            name: name.to_string(),
+            data: data.into(),
+        });
+        assert_eq!(project_composites(&lumps, &assets), vec![extra, wide]);
     }
-    buf.extend(data_block);
-    buf
 }
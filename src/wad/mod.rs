@@ -4,80 +4,137 @@
 //! of compatible WAD structures for export, including legacy texture tables.
 
 pub mod legacy;
+pub mod pk3;
+pub mod source;
 pub mod umapinfo;
 pub mod util;
 
 use crate::assets::AssetStore;
+use crate::error::ProjectError;
+use crate::io::LoadProgress;
 use crate::render::palette::DoomPalette;
-use crate::render::patch;
-use std::fs;
-use std::io::{Read, Seek, Write};
+use crate::render::tranmap::TRANMAP_SIZE;
+use crate::render::translation;
+use std::io::{Seek, Write};
+use std::ops::Range;
+use std::sync::Arc;
 
 use crate::models::ProjectData;
-pub use legacy::{build_merged_pnames, build_merged_texture1, serialize_pnames};
+pub use legacy::{build_merged_pnames, build_merged_textures, serialize_pnames};
+pub use source::{LumpData, MappedWad};
 pub use umapinfo::generate_simple_umapinfo;
-pub use util::{is_graphic_lump, parse_lump_name};
+pub use util::{Namespace, parse_lump_name};
 
 /// Represents a lump Cacoco doesn't interpret, but preserves.
 #[derive(Clone)]
 pub struct RawLump {
     pub name: String,
-    pub data: Vec<u8>,
+    pub data: LumpData,
 }
 
-/// Scans a WAD for both assets and ID24 project lumps.
+/// One entry of a WAD's lump directory.
+struct DirEntry {
+    name: String,
+    range: Range<usize>,
+}
+
+/// Reads a WAD's directory. Returns whether it's an IWAD along with its
+/// entries, or `None` if the bytes don't start with a WAD header. Errors
+/// describe what's wrong with a directory that doesn't fit the file.
+fn read_directory(bytes: &[u8]) -> Result<Option<(bool, Vec<DirEntry>)>, String> {
+    let sig = bytes.get(0..4).unwrap_or_default();
+    if sig != b"IWAD" && sig != b"PWAD" {
+        return Ok(None);
+    }
+
+    let header_field = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| "the header is truncated".to_string())
+    };
+    let num_lumps =
+        usize::try_from(header_field(4)?).map_err(|_| "the lump count is negative".to_string())?;
+    let dir_offset = usize::try_from(header_field(8)?)
+        .map_err(|_| "the directory offset is negative".to_string())?;
+    let dir = num_lumps
+        .checked_mul(16)
+        .and_then(|len| bytes.get(dir_offset..dir_offset.checked_add(len)?))
+        .ok_or_else(|| {
+            format!(
+                "the directory of {} lumps at offset {} runs past the end of the file",
+                num_lumps, dir_offset
+            )
+        })?;
+
+    let entries = dir
+        .chunks_exact(16)
+        .map(|entry| {
+            let file_pos = i32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let size = i32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            let name = parse_lump_name(&entry[8..16]);
+            match (usize::try_from(file_pos), usize::try_from(size)) {
+                (Ok(start), Ok(size)) => Ok(DirEntry {
+                    name,
+                    range: start..start.saturating_add(size),
+                }),
+                _ => Err(format!("lump {} has a negative offset or size", name)),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Some((sig == b"IWAD", entries)))
+}
+
+/// Scans a WAD for both assets and ID24 project lumps. Lumps are kept as
+/// ranges of the mapped file rather than copied.
 pub fn load_wad_project(
-    ctx: &eframe::egui::Context,
-    path: &std::path::PathBuf,
-) -> anyhow::Result<crate::io::LoadedProject> {
-    let mut file = fs::File::open(path)?;
+    path: &std::path::Path,
+    progress: &LoadProgress,
+) -> Result<crate::io::LoadedProject, ProjectError> {
+    let wad = MappedWad::open(path).map_err(|e| ProjectError::io(path, e))?;
     let mut assets = AssetStore::default();
 
-    load_wad_into_store(ctx, &mut file, &mut assets)?;
+    load_wad_into_store(&wad, &mut assets, progress)?;
 
-    file.seek(std::io::SeekFrom::Start(0))?;
-    let mut header = [0u8; 12];
-    file.read_exact(&mut header)?;
-    let num_lumps = i32::from_le_bytes(header[4..8].try_into()?) as usize;
-    let dir_offset = i32::from_le_bytes(header[8..12].try_into()?) as u64;
-
-    file.seek(std::io::SeekFrom::Start(dir_offset))?;
-    let mut dir_buffer = vec![0u8; num_lumps * 16];
-    file.read_exact(&mut dir_buffer)?;
+    let (_, entries) = read_directory(&wad.bytes())
+        .and_then(|dir| dir.ok_or_else(|| "it has no IWAD or PWAD header".to_string()))
+        .map_err(|reason| ProjectError::MalformedWad {
+            path: path.to_path_buf(),
+            reason,
+        })?;
 
     let mut lumps = Vec::new();
     let mut passthrough_lumps = Vec::new();
-    let _managed_names = ["SBARDEF", "SKYDEFS", "INTERLEVEL", "FINALE", "UMAPINFO"];
-
-    for i in 0..num_lumps {
-        let entry = &dir_buffer[i * 16..(i + 1) * 16];
-        let name = parse_lump_name(&entry[8..16]);
-        let size = i32::from_le_bytes(entry[4..8].try_into()?) as usize;
-        let file_pos = i32::from_le_bytes(entry[0..4].try_into()?) as u64;
-
-        let mut lump_data = vec![0u8; size];
-        if size > 0 {
-            let current_pos = file.stream_position()?;
-            file.seek(std::io::SeekFrom::Start(file_pos))?;
-            file.read_exact(&mut lump_data)?;
-            file.seek(std::io::SeekFrom::Start(current_pos))?;
+    let mut warnings = Vec::new();
+    let managed_names = ["SBARDEF", "SKYDEFS", "INTERLEVEL", "FINALE", "UMAPINFO"];
+
+    for entry in entries {
+        let Some(data) = wad.lump(entry.range) else {
+            continue;
+        };
+
+        if managed_names
+            .iter()
+            .any(|&m| m.eq_ignore_ascii_case(&entry.name))
+        {
+            match ProjectData::parse_lump(&entry.name, &data.bytes()) {
+                Ok(lump) => lumps.push(lump),
+                Err(e) => warnings.push(e),
+            }
         }
 
         passthrough_lumps.push(RawLump {
-            name: name.clone(),
-            data: lump_data.clone(),
+            name: entry.name,
+            data,
         });
-
-        let managed_names = ["SBARDEF", "SKYDEFS", "INTERLEVEL", "FINALE", "UMAPINFO"];
-
-        if managed_names.iter().any(|&m| m.eq_ignore_ascii_case(&name)) {
-            if let Some(parsed) = ProjectData::parse_lump(&name, &lump_data) {
-                lumps.push(parsed);
-            }
-        }
     }
 
     if lumps.is_empty() {
+        // A WAD without project lumps starts a new status bar, but one whose
+        // project lumps are all broken has nothing to edit.
+        if !warnings.is_empty() {
+            return Err(warnings.remove(0));
+        }
         lumps.push(ProjectData::StatusBar(
             crate::models::sbardef::SBarDefFile::new_empty(),
         ));
@@ -87,129 +144,230 @@ pub fn load_wad_project(
         lumps,
         assets,
         passthrough_lumps,
+        warnings,
+        ..Default::default()
     })
 }
 
-/// Scans a WAD file and populates the AssetStore with its contents.
+/// Indexes a mapped WAD into the AssetStore. Graphics are only sniffed here
+/// and decoded the first time they're drawn.
 ///
 /// If the WAD is an IWAD, this function also captures PNAMES and TEXTUREx
 /// tables to be used as a template for later exports.
 pub fn load_wad_into_store(
-    _ctx: &eframe::egui::Context,
-    file: &mut fs::File,
+    wad: &Arc<MappedWad>,
     assets: &mut AssetStore,
-) -> anyhow::Result<()> {
-    let mut header = [0u8; 12];
-    file.read_exact(&mut header).ok();
-
-    let sig = &header[0..4];
-    let is_iwad = sig == b"IWAD";
-    if sig != b"IWAD" && sig != b"PWAD" {
+    progress: &LoadProgress,
+) -> Result<(), ProjectError> {
+    let directory = read_directory(&wad.bytes()).map_err(|reason| ProjectError::MalformedWad {
+        path: wad.path().to_path_buf(),
+        reason,
+    })?;
+    let Some((is_iwad, entries)) = directory else {
         return Ok(());
+    };
+    let lumps: Vec<(String, LumpData)> = entries
+        .into_iter()
+        .filter_map(|e| Some((e.name, wad.lump(e.range)?)))
+        .collect();
+
+    for (name, data) in &lumps {
+        let bytes = data.bytes();
+        match name.as_str() {
+            "PLAYPAL" if bytes.len() >= 768 => {
+                assets.set_palette(DoomPalette::from_raw(&bytes[..768]));
+            }
+            "PNAMES" if is_iwad && bytes.len() >= 4 => {
+                assets.base_pnames = legacy::parse_pnames(&bytes);
+            }
+            "TEXTURE1" if is_iwad => {
+                assets.base_texture1 = bytes.to_vec();
+                let textures = legacy::parse_texture_lump(&bytes, &[]);
+                assets
+                    .texture_names
+                    .extend(textures.into_iter().map(|t| t.name));
+            }
+            "TEXTURE2" if is_iwad => {
+                assets.base_texture2 = bytes.to_vec();
+                let textures = legacy::parse_texture_lump(&bytes, &[]);
+                assets
+                    .texture_names
+                    .extend(textures.into_iter().map(|t| t.name));
+            }
+            _ => {}
+        }
     }
 
-    let num_lumps = i32::from_le_bytes(header[4..8].try_into()?) as usize;
-    let dir_offset = i32::from_le_bytes(header[8..12].try_into()?) as u64;
-
-    file.seek(std::io::SeekFrom::Start(dir_offset))?;
-    let mut dir_buffer = vec![0u8; num_lumps * 16];
-    file.read_exact(&mut dir_buffer)?;
+    let mut namespace = Namespace::Global;
+    for (name, data) in lumps {
+        progress.check()?;
+        progress.lump_scanned();
+        assets.lump_names.insert(name.clone());
 
-    for i in 0..num_lumps {
-        let entry = &dir_buffer[i * 16..(i + 1) * 16];
-        let name = parse_lump_name(&entry[8..16]);
-        let size = i32::from_le_bytes(entry[4..8].try_into()?) as usize;
-        let file_pos = i32::from_le_bytes(entry[0..4].try_into()?) as u64;
+        if let Some((marked, opens)) = Namespace::from_marker(&name) {
+            namespace = if opens { marked } else { Namespace::Global };
+            continue;
+        }
+        let size = data.bytes().len();
+        if size == 0 {
+            continue;
+        }
 
-        if name == "PLAYPAL" {
-            file.seek(std::io::SeekFrom::Start(file_pos))?;
-            let mut pal_bytes = vec![0u8; 768];
-            if file.read_exact(&mut pal_bytes).is_ok() {
-                assets.palette = DoomPalette::from_raw(&pal_bytes);
-            }
+        if load_graphic(assets, &name, data.clone(), namespace) {
+            progress.graphic_indexed();
+            continue;
+        }
+        if namespace != Namespace::Global {
+            continue;
+        }
+        if translation::may_be_translation(&name, &data.bytes()) {
+            assets.load_translation(&name, &data.bytes());
+        } else if size == TRANMAP_SIZE {
+            assets.offer_tranmap(&name, data);
         }
+    }
+    Ok(())
+}
 
-        if is_iwad {
-            match name.as_str() {
-                "PNAMES" => {
-                    file.seek(std::io::SeekFrom::Start(file_pos))?;
-                    let mut data = vec![0u8; size];
-                    file.read_exact(&mut data)?;
-                    if data.len() >= 4 {
-                        let count = i32::from_le_bytes(data[0..4].try_into()?) as usize;
-                        assets.base_pnames.clear();
-                        for j in 0..count {
-                            let start = 4 + (j * 8);
-                            if start + 8 <= data.len() {
-                                assets
-                                    .base_pnames
-                                    .push(parse_lump_name(&data[start..start + 8]));
-                            }
-                        }
-                    }
-                }
-                "TEXTURE1" => {
-                    file.seek(std::io::SeekFrom::Start(file_pos))?;
-                    assets.base_texture1 = vec![0u8; size];
-                    file.read_exact(&mut assets.base_texture1)?;
-                }
-                "TEXTURE2" => {
-                    file.seek(std::io::SeekFrom::Start(file_pos))?;
-                    assets.base_texture2 = vec![0u8; size];
-                    file.read_exact(&mut assets.base_texture2)?;
-                }
-                _ => {}
-            }
+/// Indexes a lump as a graphic if its bytes and namespace say it is one. Its
+/// size and offsets are read from the header now; the pixels are decoded
+/// when first drawn. Flats and `TX_START` textures are also registered under
+/// those names, and high-resolution replacements never override an existing
+/// graphic.
+pub fn load_graphic(
+    assets: &mut AssetStore,
+    name: &str,
+    data: LumpData,
+    namespace: Namespace,
+) -> bool {
+    let bytes = data.bytes();
+    let Some(format) = util::sniff_graphic(&bytes, namespace) else {
+        return false;
+    };
+    let name = AssetStore::stem(name);
+    let id = crate::assets::AssetId::new(&name);
+    if namespace == Namespace::HiRes && assets.has_image(&id) {
+        return true;
+    }
+
+    let size = match format {
+        util::GraphicFormat::Image => {
+            let reader = image::ImageReader::new(std::io::Cursor::new(&*bytes))
+                .with_guessed_format()
+                .ok()
+                .and_then(|r| r.into_dimensions().ok());
+            let Some(size) = reader else {
+                return false;
+            };
+            size
+        }
+        util::GraphicFormat::Patch => {
+            let (width, height) = util::get_image_dimensions(&bytes);
+            let left = i16::from_le_bytes([bytes[4], bytes[5]]);
+            let top = i16::from_le_bytes([bytes[6], bytes[7]]);
+            assets.offsets.insert(id, (left, top));
+            (width as u32, height as u32)
+        }
+        util::GraphicFormat::Flat => (64, 64),
+    };
+    drop(bytes);
+    assets.load_lazy(&name, data, format, size);
+
+    match namespace {
+        Namespace::Flats => {
+            assets.flats.insert(name);
         }
+        Namespace::Textures => {
+            assets.texture_names.insert(name);
+        }
+        _ => {}
     }
+    true
+}
 
-    for i in 0..num_lumps {
-        let entry = &dir_buffer[i * 16..(i + 1) * 16];
-        let name = parse_lump_name(&entry[8..16]);
-        let size = i32::from_le_bytes(entry[4..8].try_into()?) as usize;
-        let file_pos = i32::from_le_bytes(entry[0..4].try_into()?) as u64;
+/// The lumps an export adds so ports that read textures the old way still
+/// see the project's: PNAMES and TEXTUREx rebuilt around its images and
+/// composite textures, and for SKYDEFS projects without one, a UMAPINFO that
+/// puts the sky on MAP01.
+pub struct GeneratedLumps {
+    pub lumps: Vec<(&'static str, Vec<u8>)>,
+}
 
-        if size == 0 {
-            continue;
+impl GeneratedLumps {
+    pub fn new(
+        lumps: &[ProjectData],
+        composites: &[legacy::CompositeTexture],
+        assets: &AssetStore,
+        passthrough: &[RawLump],
+    ) -> anyhow::Result<Self> {
+        let has_skydefs = lumps.iter().any(|l| l.as_sky().is_some());
+        let mut generated = Vec::new();
+
+        if has_skydefs || !composites.is_empty() {
+            let pnames = build_merged_pnames(composites, assets);
+            let (texture1, texture2) = build_merged_textures(&pnames, composites, assets)?;
+            generated.push(("PNAMES", serialize_pnames(&pnames)));
+            generated.push(("TEXTURE1", texture1));
+            generated.extend(texture2.map(|data| ("TEXTURE2", data)));
         }
 
-        if is_graphic_lump(&name) {
-            let mut lump_data = vec![0u8; size];
-            file.seek(std::io::SeekFrom::Start(file_pos))?;
-            file.read_exact(&mut lump_data)?;
-
-            if let Some((width, height, left, top, pixels)) =
-                patch::decode_doom_patch(&lump_data, &assets.palette)
-            {
-                assets.load_rgba_with_offset(_ctx, &name, width, height, left, top, &pixels);
-            } else if size == 4096 {
-                if let Some((w, h, pixels)) = patch::decode_doom_flat(&lump_data, &assets.palette) {
-                    assets.load_rgba(_ctx, &name, w, h, &pixels);
-                }
-            } else {
-                assets.load_reference_image(_ctx, &name, &lump_data);
+        let has_umapinfo = lumps.iter().any(|l| l.as_umapinfo().is_some())
+            || passthrough
+                .iter()
+                .any(|r| AssetStore::stem(&r.name) == "UMAPINFO");
+        if has_skydefs && !has_umapinfo {
+            let text = generate_simple_umapinfo(lumps);
+            if !text.is_empty() {
+                generated.push(("UMAPINFO", text.into_bytes()));
             }
         }
+
+        Ok(Self { lumps: generated })
+    }
+
+    /// Whether a carried-over lump called `name` is superseded. The texture
+    /// tables go as a set, even when the IWAD has no TEXTURE2 to rebuild.
+    pub fn replaces(&self, name: &str) -> bool {
+        let stem = AssetStore::stem(name);
+        let tables = ["PNAMES", "TEXTURE1", "TEXTURE2"];
+        self.lumps.iter().any(|(generated, _)| {
+            *generated == stem || (*generated == "PNAMES" && tables.contains(&stem.as_str()))
+        })
     }
-    Ok(())
 }
 
 /// Writes a collection of ID24 project lumps and associated assets into a new PWAD.
 ///
-/// If the project contains a SKYDEFS lump, this function automatically generates
-/// legacy PNAMES, TEXTURE1, and UMAPINFO lumps to ensure cross-port compatibility.
+/// Carried-over lumps come first, then the project's own lumps, the
+/// [`GeneratedLumps`] and the project's images. Flats go between `FF_START`
+/// and `FF_END`, and patches and sky textures between `PP_START` and
+/// `PP_END`, so the rebuilt texture tables find them. When several images
+/// would get the same lump name, the most recently imported one is written;
+/// the names of the others are returned.
 pub fn write_wad_to_file<W: Write + Seek>(
     writer: &mut W,
     lumps: &[ProjectData],
+    composites: &[legacy::CompositeTexture],
     assets: &AssetStore,
     passthrough: &[RawLump],
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Vec<String>> {
     writer.write_all(b"PWAD")?;
     writer.write_all(&0i32.to_le_bytes())?;
     writer.write_all(&0i32.to_le_bytes())?;
 
     let mut records = Vec::new();
+    let mut put = |writer: &mut W, name: &str, data: &[u8]| -> anyhow::Result<()> {
+        let pos = writer.stream_position()? as u32;
+        writer.write_all(data)?;
+        records.push(Record {
+            pos,
+            size: data.len() as u32,
+            name: name.to_uppercase(),
+        });
+        Ok(())
+    };
 
+    let generated = GeneratedLumps::new(lumps, composites, assets, passthrough)?;
     let mut managed_map = std::collections::HashMap::new();
     for l in lumps {
         managed_map.insert(l.standard_lump_name().to_string(), l);
@@ -217,33 +375,88 @@ pub fn write_wad_to_file<W: Write + Seek>(
 
     for raw in passthrough {
         let name_upper = raw.name.to_uppercase();
-        let pos = writer.stream_position()? as u32;
-        let mut size = raw.data.len() as u32;
-
         if let Some(managed) = managed_map.remove(&name_upper) {
-            let new_data = managed.to_sanitized_json(assets);
-            writer.write_all(new_data.as_bytes())?;
-            size = new_data.len() as u32;
-        } else {
-            writer.write_all(&raw.data)?;
+            put(
+                writer,
+                &name_upper,
+                managed.to_sanitized_json(assets).as_bytes(),
+            )?;
+        } else if !generated.replaces(&name_upper) {
+            put(writer, &name_upper, &raw.data.bytes())?;
         }
+    }
 
-        records.push(Record {
-            pos,
-            size,
-            name: name_upper,
-        });
+    let mut leftovers: Vec<_> = managed_map.into_iter().collect();
+    leftovers.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, managed) in leftovers {
+        put(writer, &name, managed.to_sanitized_json(assets).as_bytes())?;
     }
 
-    for (name, managed) in managed_map {
-        let pos = writer.stream_position()? as u32;
-        let new_data = managed.to_sanitized_json(assets);
-        writer.write_all(new_data.as_bytes())?;
-        records.push(Record {
-            pos,
-            size: new_data.len() as u32,
-            name,
-        });
+    for (name, data) in &generated.lumps {
+        put(writer, name, data)?;
+    }
+
+    let layout = pk3::Pk3Layout::new(lumps, composites, assets);
+    let mut images: Vec<WadImage> = assets
+        .raw_files
+        .iter()
+        .filter_map(|(id, bytes)| {
+            let name = assets.names.get(id)?;
+            let stem = AssetStore::stem(name);
+            // The directory only holds eight characters of a name.
+            let lump: String = stem.chars().take(8).collect();
+            Some(WadImage {
+                role: layout.role(&stem),
+                lump,
+                name: name.clone(),
+                bytes,
+                rank: assets.import_rank(id),
+            })
+        })
+        .filter(|image| !image.lump.is_empty())
+        .collect();
+    // Images that share a lump name sit together, newest import first.
+    images.sort_by(|a, b| {
+        a.lump
+            .cmp(&b.lump)
+            .then(b.rank.cmp(&a.rank))
+            .then(a.name.cmp(&b.name))
+    });
+    let mut skipped = Vec::new();
+    images.dedup_by(|later, kept| {
+        let shadowed = later.lump == kept.lump;
+        if shadowed {
+            skipped.push(later.name.clone());
+        }
+        shadowed
+    });
+
+    let markers_of = |role| match role {
+        pk3::EntryRole::Flat => Some(("FF_START", "FF_END")),
+        pk3::EntryRole::Patch | pk3::EntryRole::Sky => Some(("PP_START", "PP_END")),
+        _ => None,
+    };
+    for markers in [
+        None,
+        markers_of(pk3::EntryRole::Flat),
+        markers_of(pk3::EntryRole::Patch),
+    ] {
+        let members: Vec<_> = images
+            .iter()
+            .filter(|i| markers_of(i.role) == markers)
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        if let Some((open, _)) = markers {
+            put(writer, open, &[])?;
+        }
+        for image in members {
+            put(writer, &image.lump, image.bytes)?;
+        }
+        if let Some((_, close)) = markers {
+            put(writer, close, &[])?;
+        }
     }
 
     let num_lumps = records.len() as i32;
@@ -261,7 +474,18 @@ pub fn write_wad_to_file<W: Write + Seek>(
     writer.seek(std::io::SeekFrom::Start(4))?;
     writer.write_all(&num_lumps.to_le_bytes())?;
     writer.write_all(&directory_pos.to_le_bytes())?;
-    Ok(())
+    Ok(skipped)
+}
+
+/// A project image on its way into a WAD.
+struct WadImage<'a> {
+    lump: String,
+    role: pk3::EntryRole,
+    /// The name it was imported under.
+    name: String,
+    bytes: &'a [u8],
+    /// See [`AssetStore::import_rank`].
+    rank: u64,
 }
 
 struct Record {
@@ -269,3 +493,114 @@ struct Record {
     size: u32,
     name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::legacy::{CompositeTexture, TexturePatch};
+
+    #[test]
+    fn test_wad_export_keeps_composites() {
+        let mut assets = AssetStore::default();
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbaImage::new(4, 4)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        assets.load_image("BRICK9.png", png.get_ref());
+
+        let mut wall = CompositeTexture::single("WALL9", 8, 4);
+        wall.patches = [0, 4]
+            .map(|x| TexturePatch {
+                patch: "BRICK9".to_string(),
+                x,
+                y: 0,
+            })
+            .to_vec();
+        let lumps = [ProjectData::StatusBar(
+            crate::models::sbardef::SBarDefFile::new_empty(),
+        )];
+
+        let path = std::env::temp_dir().join(format!("cacoco_wall_{}.wad", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        write_wad_to_file(&mut file, &lumps, &[wall.clone()], &assets, &[]).unwrap();
+        drop(file);
+
+        let loaded = load_wad_project(&path, &LoadProgress::default()).unwrap();
+        let names: Vec<&str> = loaded
+            .passthrough_lumps
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "SBARDEF", "PNAMES", "TEXTURE1", "PP_START", "BRICK9", "PP_END"
+            ]
+        );
+        assert_eq!(
+            legacy::project_composites(&loaded.passthrough_lumps, &assets),
+            [wall]
+        );
+        assert!(
+            loaded
+                .assets
+                .has_image(&crate::assets::AssetId::new("BRICK9"))
+        );
+        source::release(&path);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_newest_import_wins_a_lump_name_clash() {
+        let mut assets = AssetStore::default();
+        for (name, size) in [("STATUSBG1.png", 2), ("STATUSBG2.png", 3)] {
+            let mut png = std::io::Cursor::new(Vec::new());
+            image::RgbaImage::new(size, size)
+                .write_to(&mut png, image::ImageFormat::Png)
+                .unwrap();
+            assets.load_image(name, png.get_ref());
+        }
+
+        let mut wad = std::io::Cursor::new(Vec::new());
+        let skipped = write_wad_to_file(&mut wad, &[], &[], &assets, &[]).unwrap();
+        assert_eq!(skipped, ["STATUSBG1.png"]);
+
+        let bytes = wad.into_inner();
+        let count = i32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        assert_eq!(count, 1);
+        let dir = i32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let pos = i32::from_le_bytes(bytes[dir..dir + 4].try_into().unwrap()) as usize;
+        let size = i32::from_le_bytes(bytes[dir + 4..dir + 8].try_into().unwrap()) as usize;
+        assert_eq!(&bytes[dir + 8..dir + 16], b"STATUSBG");
+        let image = image::load_from_memory(&bytes[pos..pos + size]).unwrap();
+        assert_eq!(image.width(), 3);
+    }
+
+    #[test]
+    fn test_broken_lump_becomes_a_warning() {
+        let lumps = [ProjectData::StatusBar(
+            crate::models::sbardef::SBarDefFile::new_empty(),
+        )];
+        let broken = RawLump {
+            name: "FINALE".to_string(),
+            data: b"{ not json".to_vec().into(),
+        };
+
+        let path = std::env::temp_dir().join(format!("cacoco_broken_{}.wad", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        write_wad_to_file(&mut file, &lumps, &[], &AssetStore::default(), &[broken]).unwrap();
+        drop(file);
+
+        let loaded = load_wad_project(&path, &LoadProgress::default()).unwrap();
+        assert!(matches!(
+            loaded.lumps.as_slice(),
+            [ProjectData::StatusBar(_)]
+        ));
+        assert!(matches!(
+            loaded.warnings.as_slice(),
+            [ProjectError::BadJson { lump, .. }] if lump == "FINALE"
+        ));
+        source::release(&path);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,248 @@
+//! Folder layout for exported PK3s, and the manifest of what went where.
+
+use crate::assets::AssetStore;
+use crate::models::ProjectData;
+use crate::wad::legacy::CompositeTexture;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// What a file in an exported PK3 is for, which decides the folder it goes in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryRole {
+    /// A lump Cacoco edits, or a table or UMAPINFO generated for one.
+    Definition,
+    /// Carried over unchanged from the file the project was loaded from.
+    Kept,
+    /// A status bar, intermission or finale graphic.
+    Graphic,
+    /// A glyph of a status bar number font or HUD font.
+    Font,
+    Sky,
+    Flat,
+    /// A patch stitched into a multi-patch texture.
+    Patch,
+}
+
+impl EntryRole {
+    /// The folder a loose image with this role is written to. Fonts share
+    /// `graphics/`, where SBARDEF looks their glyphs up by name.
+    fn folder(self) -> &'static str {
+        match self {
+            EntryRole::Graphic | EntryRole::Font => "graphics",
+            EntryRole::Sky => "textures",
+            EntryRole::Flat => "flats",
+            EntryRole::Patch => "patches",
+            EntryRole::Definition | EntryRole::Kept => "",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            EntryRole::Definition => "definition",
+            EntryRole::Kept => "kept from source",
+            EntryRole::Graphic => "graphic",
+            EntryRole::Font => "status bar font",
+            EntryRole::Sky => "sky texture",
+            EntryRole::Flat => "flat",
+            EntryRole::Patch => "texture patch",
+        }
+    }
+}
+
+/// One file written to the archive.
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    pub path: String,
+    /// The asset or lump it was written from.
+    pub source: String,
+    pub role: EntryRole,
+}
+
+/// Everything written to a PK3, in the order it was written.
+#[derive(Debug, Default)]
+pub struct Pk3Manifest {
+    pub entries: Vec<ManifestEntry>,
+    /// Files left out because their path was taken: older imports of an
+    /// image, or kept files a newer import replaces.
+    pub shadowed: Vec<ManifestEntry>,
+}
+
+impl Pk3Manifest {
+    pub fn record(&mut self, path: &str, source: &str, role: EntryRole) {
+        self.entries.push(ManifestEntry {
+            path: path.to_string(),
+            source: source.to_string(),
+            role,
+        });
+    }
+
+    pub fn record_shadowed(&mut self, path: &str, source: &str, role: EntryRole) {
+        self.shadowed.push(ManifestEntry {
+            path: path.to_string(),
+            source: source.to_string(),
+            role,
+        });
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.path.eq_ignore_ascii_case(path))
+    }
+
+    /// One line per entry: the archive path, then where it came from and why.
+    /// Shadowed images follow, so a missing one can be traced.
+    pub fn to_text(&self) -> String {
+        let written = self.entries.iter().map(|e| {
+            if e.source == e.path {
+                format!("{} ({})\n", e.path, e.role.describe())
+            } else {
+                format!("{} <- {} ({})\n", e.path, e.source, e.role.describe())
+            }
+        });
+        let shadowed = self.shadowed.iter().map(|e| {
+            format!(
+                "{} <- {} ({}, skipped: path already taken)\n",
+                e.path,
+                e.source,
+                e.role.describe()
+            )
+        });
+        written.chain(shadowed).collect()
+    }
+}
+
+/// Decides where each project image goes from how the project's lumps use it.
+pub struct Pk3Layout {
+    skies: HashSet<String>,
+    flats: HashSet<String>,
+    patches: HashSet<String>,
+    font_stems: Vec<String>,
+}
+
+impl Pk3Layout {
+    pub fn new(
+        lumps: &[ProjectData],
+        composites: &[CompositeTexture],
+        assets: &AssetStore,
+    ) -> Self {
+        let mut skies = HashSet::new();
+        let mut flats: HashSet<String> = assets.flats.iter().map(|f| f.to_uppercase()).collect();
+        let mut font_stems = Vec::new();
+
+        for lump in lumps {
+            if let Some(sky) = lump.as_sky() {
+                for def in &sky.data.skies {
+                    skies.insert(def.name.to_uppercase());
+                    if let Some(fg) = &def.foregroundtex {
+                        skies.insert(fg.name.to_uppercase());
+                    }
+                }
+                for map in sky.data.flatmapping.iter().flatten() {
+                    flats.insert(map.flat.to_uppercase());
+                }
+            }
+            if let Some(sbar) = lump.as_sbar() {
+                let stems = sbar.data.number_fonts.iter().map(|f| &f.stem);
+                let stems = stems.chain(sbar.data.hud_fonts.iter().map(|f| &f.stem));
+                font_stems.extend(stems.filter(|s| !s.is_empty()).map(|s| s.to_uppercase()));
+            }
+        }
+
+        let patches = composites
+            .iter()
+            .filter(|t| t.is_composite())
+            .flat_map(|t| &t.patches)
+            .map(|p| p.patch.to_uppercase())
+            .collect();
+
+        Self {
+            skies,
+            flats,
+            patches,
+            font_stems,
+        }
+    }
+
+    /// How the project uses the image named `stem`.
+    pub fn role(&self, stem: &str) -> EntryRole {
+        let stem = stem.to_uppercase();
+        if self.skies.contains(&stem) {
+            EntryRole::Sky
+        } else if self.flats.contains(&stem) {
+            EntryRole::Flat
+        } else if self.patches.contains(&stem) {
+            EntryRole::Patch
+        } else if self.font_stems.iter().any(|f| stem.starts_with(f.as_str())) {
+            EntryRole::Font
+        } else {
+            EntryRole::Graphic
+        }
+    }
+
+    /// Where an image goes in the archive. Names that already carry a
+    /// folder, such as entries read from a PK3, keep their path.
+    pub fn place(&self, name: &str, bytes: &[u8]) -> (String, EntryRole) {
+        let stem = AssetStore::stem(name);
+        let role = self.role(&stem);
+        if name.contains('/') || name.contains('\\') {
+            return (name.replace('\\', "/"), role);
+        }
+
+        let ext = Path::new(name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_string)
+            .or_else(|| {
+                let format = image::guess_format(bytes).ok()?;
+                format.extensions_str().first().map(|e| e.to_string())
+            })
+            .unwrap_or_else(|| "png".to_string());
+        (format!("{}/{}.{}", role.folder(), stem, ext), role)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::sbardef::{NumberFontDef, SBarDefFile};
+    use crate::models::skydefs::{SkyDef, SkyDefsFile};
+    use crate::wad::legacy::TexturePatch;
+
+    #[test]
+    fn test_assets_placed_by_usage() {
+        let mut sky = SkyDefsFile::new_empty();
+        sky.data.skies.push(SkyDef {
+            name: "SKY9".to_string(),
+            ..Default::default()
+        });
+        let mut sbar = SBarDefFile::new_empty();
+        sbar.data.number_fonts.push(NumberFontDef {
+            name: "BigNum".to_string(),
+            type_: 0,
+            stem: "STT".to_string(),
+        });
+        let lumps = vec![ProjectData::Sky(sky), ProjectData::StatusBar(sbar)];
+
+        let mut assets = AssetStore::default();
+        assets.flats.insert("FLOOR9".to_string());
+        let mut wall = CompositeTexture::single("WALL9", 64, 64);
+        wall.patches.push(TexturePatch {
+            patch: "BRICK9".to_string(),
+            x: 32,
+            y: 0,
+        });
+
+        let layout = Pk3Layout::new(&lumps, &[wall], &assets);
+        let png = b"\x89PNG\r\n\x1a\n";
+        assert_eq!(layout.place("sky9.png", png).0, "textures/SKY9.png");
+        assert_eq!(layout.place("FLOOR9", png).0, "flats/FLOOR9.png");
+        assert_eq!(layout.place("BRICK9", png).0, "patches/BRICK9.png");
+        assert_eq!(layout.place("STTNUM3", png).1, EntryRole::Font);
+        assert_eq!(layout.place("STBAR.jpg", png).0, "graphics/STBAR.jpg");
+        assert_eq!(
+            layout.place("hud/extra/SKY9.png", png),
+            ("hud/extra/SKY9.png".to_string(), EntryRole::Sky)
+        );
+    }
+}
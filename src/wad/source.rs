@@ -0,0 +1,169 @@
+//! Memory-mapped WAD files, so lumps can be indexed and kept around without
+//! reading them into memory.
+
+use memmap2::Mmap;
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, Weak};
+
+/// Every WAD currently mapped, so one can be let go of before it's overwritten.
+static OPEN_WADS: Mutex<Vec<Weak<MappedWad>>> = Mutex::new(Vec::new());
+
+/// Where a mapped WAD's bytes live.
+enum Backing {
+    Mapped(Mmap),
+    /// Copied into memory, once the file was about to be overwritten.
+    Owned(Vec<u8>),
+}
+
+impl Backing {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Backing::Mapped(map) => map,
+            Backing::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/// A WAD file mapped into memory, shared by every lump read from it.
+pub struct MappedWad {
+    path: PathBuf,
+    backing: RwLock<Backing>,
+}
+
+impl MappedWad {
+    pub fn open(path: &Path) -> std::io::Result<Arc<Self>> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is only read, and `release` copies it out before
+        // Cacoco overwrites the file itself.
+        let map = unsafe { Mmap::map(&file)? };
+        let wad = Arc::new(Self {
+            path: path.canonicalize()?,
+            backing: RwLock::new(Backing::Mapped(map)),
+        });
+
+        let mut open = OPEN_WADS.lock().unwrap_or_else(|e| e.into_inner());
+        open.retain(|w| w.strong_count() > 0);
+        open.push(Arc::downgrade(&wad));
+        Ok(wad)
+    }
+
+    /// Where the WAD was mapped from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn size(&self) -> usize {
+        self.read().as_slice().len()
+    }
+
+    /// The whole file.
+    pub fn bytes(&self) -> LumpBytes<'_> {
+        let backing = self.read();
+        let size = backing.as_slice().len();
+        LumpBytes(BytesRef::Mapped(backing, 0..size))
+    }
+
+    /// A lump's bytes, or `None` if the range runs past the end of the file.
+    pub fn lump(self: &Arc<Self>, range: Range<usize>) -> Option<LumpData> {
+        (range.start <= range.end && range.end <= self.size()).then(|| LumpData::Mapped {
+            wad: self.clone(),
+            range,
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Backing> {
+        self.backing.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn detach(&self) {
+        let mut backing = self.backing.write().unwrap_or_else(|e| e.into_inner());
+        if let Backing::Mapped(map) = &*backing {
+            *backing = Backing::Owned(map.to_vec());
+        }
+    }
+}
+
+/// Copies any WAD mapped from `path` into memory, so the file can be
+/// overwritten without pulling the bytes out from under its lumps.
+pub fn release(path: &Path) {
+    let Ok(path) = path.canonicalize() else {
+        return;
+    };
+    let open = OPEN_WADS.lock().unwrap_or_else(|e| e.into_inner());
+    for wad in open.iter().filter_map(Weak::upgrade) {
+        if wad.path == path {
+            wad.detach();
+        }
+    }
+}
+
+/// The bytes of a lump: owned, or a range of a mapped WAD that is only
+/// paged in when read.
+#[derive(Clone)]
+pub enum LumpData {
+    Owned(Arc<[u8]>),
+    Mapped {
+        wad: Arc<MappedWad>,
+        range: Range<usize>,
+    },
+}
+
+impl LumpData {
+    pub fn bytes(&self) -> LumpBytes<'_> {
+        match self {
+            LumpData::Owned(bytes) => LumpBytes(BytesRef::Owned(bytes)),
+            LumpData::Mapped { wad, range } => {
+                LumpBytes(BytesRef::Mapped(wad.read(), range.clone()))
+            }
+        }
+    }
+}
+
+impl From<Vec<u8>> for LumpData {
+    fn from(bytes: Vec<u8>) -> Self {
+        LumpData::Owned(bytes.into())
+    }
+}
+
+/// Borrowed access to a lump's bytes.
+pub struct LumpBytes<'a>(BytesRef<'a>);
+
+enum BytesRef<'a> {
+    Owned(&'a [u8]),
+    Mapped(RwLockReadGuard<'a, Backing>, Range<usize>),
+}
+
+impl Deref for LumpBytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            BytesRef::Owned(bytes) => bytes,
+            BytesRef::Mapped(backing, range) => &backing.as_slice()[range.clone()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_before_overwrite() {
+        let path = std::env::temp_dir().join(format!("cacoco_mapped_{}.wad", std::process::id()));
+        std::fs::write(&path, b"PWADlump").unwrap();
+
+        let wad = MappedWad::open(&path).unwrap();
+        let lump = wad.lump(4..8).unwrap();
+        assert!(wad.lump(4..9).is_none());
+        assert_eq!(&*lump.bytes(), b"lump");
+
+        release(&path);
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(&*lump.bytes(), b"lump");
+        assert_eq!(wad.bytes().len(), 8);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
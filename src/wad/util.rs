@@ -1,19 +1,116 @@
 //! Low-level utility functions for parsing Doom WAD data structures.
 
-use once_cell::sync::Lazy;
-use std::collections::HashSet;
-
-/// A collection of standard Doom lump prefixes used to identify graphical data
-/// such as Patches, Flats, and Sprites during the WAD scanning phase.
-pub static GRAPHIC_PREFIXES: Lazy<HashSet<&'static str>> = Lazy::new(|| {
-    let prefixes = [
-        "ST", "WI", "M_", "BRDR", "DGT", "NUM", "PRCN", "MINUS", "PUNG", "SAWG", "PISG", "SHTG",
-        "SHT2", "CHGG", "MISG", "PLSG", "BFGG", "BKEY", "YKEY", "RKEY", "BSKU", "YSKU", "RSKU",
-        "PINV", "PSTR", "PINS", "SUIT", "PMAP", "PVIS", "ARM", "MEDI", "BPAK", "AMMO", "SHEL",
-        "CELL", "ROCK", "INTER", "FINALE", "TITLE", "PAT", "GRN", "SKY", "RSKY", "F_SKY",
-    ];
-    prefixes.into_iter().collect()
-});
+/// A WAD namespace, delimited by `*_START` and `*_END` marker lumps. Lumps
+/// outside any markers are in the global namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Namespace {
+    #[default]
+    Global,
+    Sprites,
+    /// Wall patches referenced by PNAMES.
+    Patches,
+    Flats,
+    /// ZDoom-style `TX_START` textures: whole images used as wall textures.
+    Textures,
+    /// High-resolution replacements for graphics of the same name.
+    HiRes,
+}
+
+impl Namespace {
+    /// The namespace a marker lump opens or closes, and whether it opens it.
+    /// Numbered markers like `P1_START` count as their outer namespace.
+    pub fn from_marker(name: &str) -> Option<(Self, bool)> {
+        let (prefix, opens) = match name.strip_suffix("_START") {
+            Some(prefix) => (prefix, true),
+            None => (name.strip_suffix("_END")?, false),
+        };
+        let namespace = match prefix {
+            "S" | "SS" => Self::Sprites,
+            "P" | "PP" | "P1" | "P2" | "P3" => Self::Patches,
+            "F" | "FF" | "F1" | "F2" | "F3" => Self::Flats,
+            "TX" => Self::Textures,
+            "HI" => Self::HiRes,
+            _ => return None,
+        };
+        Some((namespace, opens))
+    }
+
+    /// The namespace a PK3's top-level directory stands for. `graphics/` and
+    /// other directories are left to the caller.
+    pub fn from_pk3_dir(dir: &str) -> Option<Self> {
+        match dir.to_lowercase().as_str() {
+            "sprites" => Some(Self::Sprites),
+            "patches" => Some(Self::Patches),
+            "flats" => Some(Self::Flats),
+            "textures" => Some(Self::Textures),
+            "hires" => Some(Self::HiRes),
+            _ => None,
+        }
+    }
+}
+
+/// How a graphic lump is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicFormat {
+    /// A PNG or other image file the `image` crate can read.
+    Image,
+    /// A Doom column-based patch.
+    Patch,
+    /// A raw 64x64 flat.
+    Flat,
+}
+
+/// Works out whether a lump holds a graphic, from its bytes and the
+/// namespace it was found in. Raw flats have no header, so they're only
+/// recognised between flat markers.
+pub fn sniff_graphic(data: &[u8], namespace: Namespace) -> Option<GraphicFormat> {
+    if is_image_file(data) {
+        Some(GraphicFormat::Image)
+    } else if namespace == Namespace::Flats {
+        (data.len() == 4096).then_some(GraphicFormat::Flat)
+    } else {
+        is_doom_patch(data).then_some(GraphicFormat::Patch)
+    }
+}
+
+/// Whether the bytes start with a PNG or JPEG signature.
+pub fn is_image_file(data: &[u8]) -> bool {
+    data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])
+        || data.starts_with(&[0xFF, 0xD8, 0xFF])
+}
+
+/// Whether the bytes are a well-formed Doom patch: a sane header, column
+/// offsets that point past the offset table, and posts that all end inside
+/// the lump.
+pub fn is_doom_patch(data: &[u8]) -> bool {
+    if data.len() < 8 {
+        return false;
+    }
+    let width = u16::from_le_bytes([data[0], data[1]]) as usize;
+    let height = u16::from_le_bytes([data[2], data[3]]) as usize;
+    let table_end = 8 + width * 4;
+    if width == 0 || height == 0 || width > 4096 || height > 4096 || data.len() < table_end {
+        return false;
+    }
+
+    data[8..table_end].chunks_exact(4).all(|offset| {
+        let mut cursor = u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]) as usize;
+        if cursor < table_end {
+            return false;
+        }
+        // Each post is: top delta, length, padding, pixels, padding.
+        while let Some(&top) = data.get(cursor) {
+            if top == 255 {
+                return true;
+            }
+            let Some(&len) = data.get(cursor + 1) else {
+                return false;
+            };
+            cursor += 4 + len as usize;
+        }
+        false
+    })
+}
 
 /// Converts a fixed-length null-terminated or space-padded byte slice
 /// from a WAD directory into a clean, uppercase Rust String.
@@ -24,13 +121,6 @@ pub fn parse_lump_name(bytes: &[u8]) -> String {
         .to_uppercase()
 }
 
-/// Returns true if the given lump name begins with a known graphical prefix.
-pub fn is_graphic_lump(name: &str) -> bool {
-    GRAPHIC_PREFIXES
-        .iter()
-        .any(|prefix| name.starts_with(prefix))
-}
-
 /// Sniffs the dimensions (Width, Height) of an image lump by inspecting its header.
 /// Supports both standard PNG files and Doom's internal Patch format.
 pub fn get_image_dimensions(bytes: &[u8]) -> (u16, u16) {
@@ -46,3 +136,53 @@ pub fn get_image_dimensions(bytes: &[u8]) -> (u16, u16) {
     }
     (256, 128)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaces_and_sniffing() {
+        assert_eq!(
+            Namespace::from_marker("P1_START"),
+            Some((Namespace::Patches, true))
+        );
+        assert_eq!(
+            Namespace::from_marker("FF_END"),
+            Some((Namespace::Flats, false))
+        );
+        assert_eq!(
+            Namespace::from_marker("TX_START"),
+            Some((Namespace::Textures, true))
+        );
+        assert_eq!(Namespace::from_marker("HUDBAR1"), None);
+
+        // A 2x1 patch: one post per column.
+        let mut patch = vec![2, 0, 1, 0, 0, 0, 0, 0];
+        patch.extend_from_slice(&16u32.to_le_bytes());
+        patch.extend_from_slice(&22u32.to_le_bytes());
+        patch.extend_from_slice(&[0, 1, 0, 7, 0, 255]);
+        patch.extend_from_slice(&[0, 1, 0, 9, 0, 255]);
+        assert_eq!(
+            sniff_graphic(&patch, Namespace::Global),
+            Some(GraphicFormat::Patch)
+        );
+
+        let mut truncated = patch.clone();
+        truncated.pop();
+        assert_eq!(sniff_graphic(&truncated, Namespace::Global), None);
+
+        let flat = vec![0u8; 4096];
+        assert_eq!(sniff_graphic(&flat, Namespace::Global), None);
+        assert_eq!(
+            sniff_graphic(&flat, Namespace::Flats),
+            Some(GraphicFormat::Flat)
+        );
+
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+        assert_eq!(
+            sniff_graphic(&png, Namespace::Sprites),
+            Some(GraphicFormat::Image)
+        );
+    }
+}